@@ -1,4 +1,4 @@
-use crate::lexer::token::{Token, TokenType};
+use crate::lexer::token::{NumberSuffix, StringPart, Token, TokenType};
 use std::collections::HashMap;
 use once_cell::sync::Lazy;
 
@@ -19,6 +19,7 @@ static KEYWORDS: Lazy<HashMap<&'static str, TokenType>> = Lazy::new(|| {
     map.insert("for", TokenType::For);
     map.insert("in", TokenType::In);
     map.insert("return", TokenType::Return);
+    map.insert("match", TokenType::Match);
     map.insert("true", TokenType::BooleanLiteral(true));
     map.insert("false", TokenType::BooleanLiteral(false));
     map.insert("async", TokenType::Async);
@@ -54,18 +55,39 @@ impl std::fmt::Display for LexError {
 
 impl std::error::Error for LexError {}
 
+/// A source line of a `"""` string, its indentation kept apart until the
+/// closing quotes say how much of it to strip.
+struct TextLine {
+    line: usize,
+    indent: String,
+    parts: Vec<StringPart>,
+}
+
 pub struct Lexer<'a> {
     input: &'a [char],
     position: usize,
     current_char: Option<char>,
     line: usize,
     column: usize,
+    /// Byte offset of `current_char` in the source text.
+    offset: usize,
+    /// Byte offset where the token being scanned starts.
+    token_start: usize,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a [char]) -> Self {
-        let current_char = input.get(0).copied();
-        Self { input, position: 0, current_char, line: 1, column: 1 }
+        let current_char = input.first().copied();
+        Self { input, position: 0, current_char, line: 1, column: 1, offset: 0, token_start: 0 }
+    }
+
+    /// A token covering the text from `token_start` up to the current character.
+    fn token(&self, token_type: TokenType, lexeme: String, line: usize, column: usize) -> Token {
+        Token {
+            end_line: self.line,
+            end_column: self.column,
+            ..Token::new(token_type, lexeme, line, column).with_offsets(self.token_start, self.offset)
+        }
     }
 
     fn error(&self, message: impl Into<String>) -> LexError {
@@ -73,6 +95,7 @@ impl<'a> Lexer<'a> {
     }
 
     fn advance(&mut self) {
+        self.offset += self.current_char.map_or(0, char::len_utf8);
         if let Some('\n') = self.current_char {
             self.line += 1;
             self.column = 1;
@@ -89,7 +112,7 @@ impl<'a> Lexer<'a> {
     }
 
     fn skip_whitespace(&mut self) {
-        while self.current_char.map_or(false, |c| c.is_whitespace()) {
+        while self.current_char.is_some_and(|c| c.is_whitespace()) {
             self.advance();
         }
     }
@@ -122,7 +145,9 @@ impl<'a> Lexer<'a> {
     }
 
 
-    fn read_string(&mut self) -> Result<String, LexError> {
+    /// A string literal, or an interpolated string when it contains `${...}`.
+    fn read_string(&mut self) -> Result<TokenType, LexError> {
+        let mut parts = Vec::new();
         let mut value = String::new();
         let start_line = self.line;
         let start_column = self.column;
@@ -130,49 +155,23 @@ impl<'a> Lexer<'a> {
 
         while let Some(ch) = self.current_char {
             match ch {
-                '"' => { self.advance(); return Ok(value); }
-                '\\' => {
+                '"' => {
                     self.advance();
-                    match self.current_char {
-                        Some('n') => value.push('\n'),
-                        Some('t') => value.push('\t'),
-                        Some('r') => value.push('\r'),
-                        Some('"') => value.push('"'),
-                        Some('\\') => value.push('\\'),
-                        Some('0') => value.push('\0'),
-                        Some('u') => {
-                            self.advance();
-                            let mut hex = String::new();
-                            for _ in 0..4 {
-                                let ch = self.current_char.ok_or(self.error("Unexpected end in unicode escape"))?;
-                                hex.push(ch);
-                                self.advance();
-                            }
-                            let code = u32::from_str_radix(&hex, 16).map_err(|_| self.error("Invalid unicode hex"))?;
-                            value.push(char::from_u32(code).ok_or(self.error("Invalid unicode code point"))?);
-                            continue;
-                        }
-                        Some('$') if self.peek() == Some('{') => {
-                            value.push_str("${");
-                            self.advance(); // skip $
-                            self.advance(); // skip {
-                            let mut brace_count = 1;
-                            while let Some(c) = self.current_char {
-                                self.advance();
-                                value.push(c);
-                                match c {
-                                    '{' => brace_count += 1,
-                                    '}' => { brace_count -= 1; if brace_count == 0 { break; } }
-                                    _ => {}
-                                }
-                            }
-                            if brace_count > 0 { return Err(self.error("Unterminated string interpolation")); }
-                        }
-                        Some(c) => return Err(self.error(format!("Invalid escape sequence: \\{}", c))),
-                        None => return Err(self.error("Unexpected end in string")),
+                    if parts.is_empty() {
+                        return Ok(TokenType::StringLiteral(value));
                     }
-                    self.advance();
+                    if !value.is_empty() {
+                        parts.push(StringPart::Literal(value));
+                    }
+                    return Ok(TokenType::InterpolatedString(parts));
                 }
+                '$' if self.peek() == Some('{') => {
+                    if !value.is_empty() {
+                        parts.push(StringPart::Literal(std::mem::take(&mut value)));
+                    }
+                    parts.push(StringPart::Expr(self.read_interpolation()?));
+                }
+                '\\' => value.push(self.read_escape()?),
                 _ => { value.push(ch); self.advance(); }
             }
         }
@@ -180,18 +179,401 @@ impl<'a> Lexer<'a> {
         Err(LexError { message: "Unterminated string".to_string(), line: start_line, column: start_column })
     }
 
-    fn read_number(&mut self) -> Result<TokenType, LexError> {
+    /// A raw string such as `r"C:\dir"` or `r#"say "hi""#`: no escapes or
+    /// interpolation, and it ends at the first `"` followed by as many `#` as
+    /// it started with.
+    fn read_raw_string(&mut self) -> Result<String, LexError> {
+        let (line, column) = (self.line, self.column);
+        let hashes = self.raw_string_hashes().expect("raw strings start with r\" or r#");
+        for _ in 0..hashes + 2 {
+            self.advance(); // skip r, the #s and "
+        }
+
         let mut value = String::new();
-        let mut is_float = false;
+        while let Some(ch) = self.current_char {
+            if ch == '"' && (1..=hashes).all(|i| self.input.get(self.position + i) == Some(&'#')) {
+                for _ in 0..=hashes {
+                    self.advance();
+                }
+                return Ok(value);
+            }
+            value.push(ch);
+            self.advance();
+        }
+        Err(LexError { message: "Unterminated raw string".to_string(), line, column })
+    }
+
+    /// How many `#` follow the `r` of a raw string, or `None` if the `r`
+    /// doesn't start one.
+    fn raw_string_hashes(&self) -> Option<usize> {
+        let hashes = self.input[self.position + 1..].iter().take_while(|&&c| c == '#').count();
+        (self.input.get(self.position + 1 + hashes) == Some(&'"')).then_some(hashes)
+    }
+
+    fn at_triple_quote(&self) -> bool {
+        self.input[self.position..].starts_with(&['"', '"', '"'])
+    }
+
+    /// A `"""` string. Its text starts on the line after the opening quotes and
+    /// ends on the line before the closing ones, whose indentation is removed
+    /// from every line. Escapes and `${...}` work as in other strings.
+    fn read_multiline_string(&mut self) -> Result<TokenType, LexError> {
+        let (line, column) = (self.line, self.column);
+        for _ in 0..3 {
+            self.advance();
+        }
+        while matches!(self.current_char, Some(' ' | '\t' | '\r')) {
+            self.advance();
+        }
+        if self.current_char != Some('\n') {
+            return Err(self.error("Multi-line string text must start on the line after \"\"\""));
+        }
+        self.advance();
+
+        let mut lines = vec![self.read_text_line_start()];
+        loop {
+            let parts = &mut lines.last_mut().expect("there is always a current line").parts;
+            match self.current_char {
+                None => return Err(LexError { message: "Unterminated multi-line string".to_string(), line, column }),
+                Some('"') if self.at_triple_quote() => break,
+                Some('\r') if self.peek() == Some('\n') => self.advance(),
+                Some('\n') => {
+                    self.advance();
+                    lines.push(self.read_text_line_start());
+                }
+                Some('$') if self.peek() == Some('{') => parts.push(StringPart::Expr(self.read_interpolation()?)),
+                Some('\\') => {
+                    let ch = self.read_escape()?;
+                    Self::push_text(parts, ch);
+                }
+                Some(ch) => {
+                    Self::push_text(parts, ch);
+                    self.advance();
+                }
+            }
+        }
+
+        let closing = lines.pop().expect("there is always a current line");
+        if !closing.parts.is_empty() {
+            return Err(self.error("Closing \"\"\" of a multi-line string must be on its own line"));
+        }
+        for _ in 0..3 {
+            self.advance();
+        }
+
+        let mut parts = Vec::new();
+        for (i, text_line) in lines.into_iter().enumerate() {
+            if i > 0 {
+                Self::push_text(&mut parts, '\n');
+            }
+            match text_line.indent.strip_prefix(closing.indent.as_str()) {
+                Some(rest) => rest.chars().for_each(|ch| Self::push_text(&mut parts, ch)),
+                // Blank lines may be indented less
+                None if text_line.parts.is_empty() => {}
+                None => {
+                    return Err(LexError {
+                        message: "Line is indented less than the closing \"\"\" of its string".to_string(),
+                        line: text_line.line,
+                        column: 1,
+                    })
+                }
+            }
+            for part in text_line.parts {
+                match part {
+                    StringPart::Literal(text) => text.chars().for_each(|ch| Self::push_text(&mut parts, ch)),
+                    expr => parts.push(expr),
+                }
+            }
+        }
+
+        match &parts[..] {
+            [] => Ok(TokenType::StringLiteral(String::new())),
+            [StringPart::Literal(text)] => Ok(TokenType::StringLiteral(text.clone())),
+            _ => Ok(TokenType::InterpolatedString(parts)),
+        }
+    }
+
+    /// Starts a line of a multi-line string, reading its indentation.
+    fn read_text_line_start(&mut self) -> TextLine {
+        let line = self.line;
+        let mut indent = String::new();
+        while let Some(ch @ (' ' | '\t')) = self.current_char {
+            indent.push(ch);
+            self.advance();
+        }
+        TextLine { line, indent, parts: Vec::new() }
+    }
+
+    fn push_text(parts: &mut Vec<StringPart>, ch: char) {
+        match parts.last_mut() {
+            Some(StringPart::Literal(text)) => text.push(ch),
+            _ => parts.push(StringPart::Literal(ch.to_string())),
+        }
+    }
+
+    /// The character a `\` escape sequence stands for, consuming it. Besides
+    /// the usual escapes, `\u` takes four hex digits or up to six in braces,
+    /// as in `\u{1F600}`.
+    fn read_escape(&mut self) -> Result<char, LexError> {
+        self.advance(); // skip \
+        let escaped = match self.current_char {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('"') => '"',
+            Some('\'') => '\'',
+            Some('\\') => '\\',
+            Some('0') => '\0',
+            Some('$') => '$',
+            Some('u') => {
+                self.advance();
+                let braced = self.current_char == Some('{');
+                if braced {
+                    self.advance();
+                }
+                let mut hex = String::new();
+                while hex.len() < if braced { 6 } else { 4 } {
+                    match self.current_char {
+                        Some(ch) if ch.is_ascii_hexdigit() => { hex.push(ch); self.advance(); }
+                        Some(_) if braced && !hex.is_empty() => break,
+                        Some(_) => return Err(self.error("Invalid unicode escape")),
+                        None => return Err(self.error("Unexpected end in unicode escape")),
+                    }
+                }
+                if braced {
+                    if self.current_char != Some('}') {
+                        return Err(self.error("Invalid unicode escape"));
+                    }
+                    self.advance();
+                }
+                let code = u32::from_str_radix(&hex, 16).map_err(|_| self.error("Invalid unicode escape"))?;
+                return char::from_u32(code).ok_or(self.error("Invalid unicode code point"));
+            }
+            Some('x') => {
+                self.advance();
+                let code = self.read_hex_byte()?;
+                if !code.is_ascii() {
+                    return Err(self.error("\\x escapes above 7F are only allowed in byte literals"));
+                }
+                return Ok(code as char);
+            }
+            Some(c) => return Err(self.error(format!("Invalid escape sequence: \\{}", c))),
+            None => return Err(self.error("Unexpected end in string")),
+        };
+        self.advance();
+        Ok(escaped)
+    }
+
+    /// The two hex digits of a `\x` escape.
+    fn read_hex_byte(&mut self) -> Result<u8, LexError> {
+        let mut value = 0;
+        for _ in 0..2 {
+            let digit = self
+                .current_char
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.error("Expected two hex digits after \\x"))?;
+            value = value * 16 + digit as u8;
+            self.advance();
+        }
+        Ok(value)
+    }
 
+    /// A rune literal such as `'a'` or `'\u{1F600}'`.
+    fn read_rune(&mut self) -> Result<TokenType, LexError> {
+        let (line, column) = (self.line, self.column);
+        self.advance(); // skip '
+        let rune = match self.current_char {
+            Some('\'') => return Err(self.error("Empty rune literal")),
+            Some('\n') | None => return Err(LexError { message: "Unterminated rune literal".to_string(), line, column }),
+            Some('\\') => self.read_escape()?,
+            Some(ch) => { self.advance(); ch }
+        };
+        self.close_char_literal("Rune", line, column)?;
+        Ok(TokenType::RuneLiteral(rune))
+    }
+
+    /// A byte literal such as `b'a'` or `b'\xFF'`.
+    fn read_byte_literal(&mut self) -> Result<TokenType, LexError> {
+        let (line, column) = (self.line, self.column);
+        self.advance(); // skip b
+        self.advance(); // skip '
+        let byte = match self.current_char {
+            Some('\'') => return Err(self.error("Empty byte literal")),
+            Some('\n') | None => return Err(LexError { message: "Unterminated byte literal".to_string(), line, column }),
+            Some(_) => self.read_byte()?,
+        };
+        self.close_char_literal("Byte", line, column)?;
+        Ok(TokenType::ByteLiteral(byte))
+    }
+
+    /// A byte string such as `b"GET \r\n"`; unlike strings it never interpolates.
+    fn read_byte_string(&mut self) -> Result<TokenType, LexError> {
+        let (line, column) = (self.line, self.column);
+        self.advance(); // skip b
+        self.advance(); // skip "
+        let mut bytes = Vec::new();
         while let Some(ch) = self.current_char {
-            if ch.is_ascii_digit() { value.push(ch); self.advance(); }
-            else if ch == '.' && !is_float && self.peek().map_or(false, |c| c.is_ascii_digit()) { value.push(ch); is_float = true; self.advance(); }
-            else { break; }
+            if ch == '"' {
+                self.advance();
+                return Ok(TokenType::ByteStringLiteral(bytes));
+            }
+            bytes.push(self.read_byte()?);
+        }
+        Err(LexError { message: "Unterminated byte string".to_string(), line, column })
+    }
+
+    /// One byte of a byte literal: an ASCII character or an escape, where `\x`
+    /// may go up to `\xFF`.
+    fn read_byte(&mut self) -> Result<u8, LexError> {
+        match (self.current_char, self.peek()) {
+            (Some('\\'), Some('x')) => {
+                self.advance();
+                self.advance();
+                self.read_hex_byte()
+            }
+            (Some('\\'), Some('u')) => Err(self.error("Unicode escapes are not allowed in byte literals")),
+            (Some('\\'), _) => Ok(self.read_escape()? as u8),
+            (Some(ch), _) if ch.is_ascii() => {
+                self.advance();
+                Ok(ch as u8)
+            }
+            (Some(ch), _) => Err(self.error(format!("Non-ASCII character '{}' in byte literal", ch))),
+            (None, _) => Err(self.error("Unexpected end in byte literal")),
+        }
+    }
+
+    /// Consumes the `'` that closes a rune or byte literal.
+    fn close_char_literal(&mut self, kind: &str, line: usize, column: usize) -> Result<(), LexError> {
+        if self.current_char == Some('\'') {
+            self.advance();
+            return Ok(());
+        }
+        let mut rest_of_line = self.input[self.position..].iter().take_while(|&&c| c != '\n');
+        let message = if rest_of_line.any(|&c| c == '\'') {
+            format!("{} literal must contain exactly one character", kind)
+        } else {
+            format!("Unterminated {} literal", kind.to_lowercase())
+        };
+        Err(LexError { message, line, column })
+    }
+
+    /// The tokens of a `${...}` inside a string, lexed where they stand so
+    /// they keep their source positions. Braces nest, and strings inside may
+    /// interpolate in turn.
+    fn read_interpolation(&mut self) -> Result<Vec<Token>, LexError> {
+        let (line, column) = (self.line, self.column);
+        let token_start = self.token_start;
+        self.advance(); // skip $
+        self.advance(); // skip {
+
+        let mut tokens = Vec::new();
+        let mut depth = 0;
+        loop {
+            let token = self.next_token()?;
+            match token.token_type {
+                TokenType::LeftBrace => depth += 1,
+                TokenType::RightBrace if depth == 0 => {
+                    tokens.push(Token::new(TokenType::Eof, String::new(), token.line, token.column).with_offsets(token.start, token.start));
+                    break;
+                }
+                TokenType::RightBrace => depth -= 1,
+                TokenType::Eof => return Err(LexError { message: "Unterminated string interpolation".to_string(), line, column }),
+                _ => {}
+            }
+            tokens.push(token);
+        }
+        self.token_start = token_start;
+
+        if tokens.len() == 1 {
+            return Err(LexError { message: "Empty string interpolation".to_string(), line, column });
+        }
+        Ok(tokens)
+    }
+
+    /// A decimal number, or an integer with a `0x`, `0o` or `0b` prefix.
+    /// Digits may be separated by `_`; decimal numbers may have a fraction
+    /// and an exponent; either may end in a type suffix such as `u8` or `f32`.
+    fn read_number(&mut self) -> Result<TokenType, LexError> {
+        let radix = match (self.current_char, self.peek()) {
+            (Some('0'), Some('x' | 'X')) => 16,
+            (Some('0'), Some('o' | 'O')) => 8,
+            (Some('0'), Some('b' | 'B')) => 2,
+            _ => 10,
+        };
+        if radix != 10 {
+            self.advance(); // skip 0
+            self.advance(); // skip the radix letter
+        }
+
+        let mut value = self.read_digits(radix);
+        if value.is_empty() {
+            return Err(self.error("Expected digits after the number's prefix"));
+        }
+        if let Some(digit) = self.current_char.filter(|c| c.is_ascii_digit()) {
+            return Err(self.error(format!("Invalid digit '{}' in base {} literal", digit, radix)));
+        }
+
+        let mut is_float = false;
+        if radix == 10 {
+            if self.current_char == Some('.') && self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.advance();
+                value.push('.');
+                value.push_str(&self.read_digits(10));
+                is_float = true;
+            }
+            if matches!(self.current_char, Some('e' | 'E')) {
+                let sign = matches!(self.peek(), Some('+' | '-'));
+                let first_digit = self.input.get(self.position + 1 + sign as usize);
+                if !first_digit.is_some_and(|c| c.is_ascii_digit()) {
+                    return Err(self.error("Expected digits in the exponent"));
+                }
+                value.push('e');
+                self.advance();
+                if sign {
+                    value.extend(self.current_char);
+                    self.advance();
+                }
+                value.push_str(&self.read_digits(10));
+                is_float = true;
+            }
         }
 
-        if is_float { value.parse().map(TokenType::FloatLiteral).map_err(|_| self.error("Invalid float literal")) }
-        else { value.parse().map(TokenType::IntLiteral).map_err(|_| self.error("Invalid integer literal")) }
+        let suffix = if self.current_char.is_some_and(|c| c.is_alphabetic() || c == '_') {
+            let text = self.read_identifier();
+            let suffix = NumberSuffix::parse(&text)
+                .ok_or_else(|| self.error(format!("Invalid suffix '{}' for a number literal", text)))?;
+            if is_float && !suffix.is_float() {
+                return Err(self.error(format!("Integer suffix '{}' on a float literal", text)));
+            }
+            if radix != 10 && suffix.is_float() {
+                return Err(self.error(format!("Float suffix '{}' on a base {} literal", text, radix)));
+            }
+            Some(suffix)
+        } else {
+            None
+        };
+
+        if is_float || suffix.is_some_and(NumberSuffix::is_float) {
+            match value.parse::<f64>() {
+                Ok(f) if f.is_finite() => Ok(TokenType::FloatLiteral(f, suffix)),
+                _ => Err(self.error("Float literal is out of range")),
+            }
+        } else {
+            u64::from_str_radix(&value, radix)
+                .map(|n| TokenType::IntLiteral(n, suffix))
+                .map_err(|_| self.error("Integer literal is too large"))
+        }
+    }
+
+    /// Digits of `radix` with any `_` separators dropped.
+    fn read_digits(&mut self, radix: u32) -> String {
+        let mut digits = String::new();
+        while let Some(ch) = self.current_char {
+            if ch.is_digit(radix) { digits.push(ch); }
+            else if ch != '_' || digits.is_empty() { break; }
+            self.advance();
+        }
+        digits
     }
 
     fn read_identifier(&mut self) -> String {
@@ -234,66 +616,164 @@ impl<'a> Lexer<'a> {
 
             let line = self.line;
             let column = self.column;
+            self.token_start = self.offset;
 
             match self.current_char {
-                None => return Ok(Token::new(TokenType::Eof, "".to_string(), line, column)),
-                Some('"') => { let val = self.read_string()?; return Ok(Token::new(TokenType::StringLiteral(val.clone()), val, line, column)); }
-                Some(ch) if ch.is_ascii_digit() => { let token_type = self.read_number()?; let lexeme = match &token_type { TokenType::IntLiteral(n) => n.to_string(), TokenType::FloatLiteral(f) => f.to_string(), _ => unreachable!() }; return Ok(Token::new(token_type, lexeme, line, column)); }
+                None => return Ok(self.token(TokenType::Eof, "".to_string(), line, column)),
+                Some('"') => {
+                    let first = self.position;
+                    let token_type = if self.at_triple_quote() { self.read_multiline_string()? } else { self.read_string()? };
+                    let lexeme = match &token_type {
+                        TokenType::StringLiteral(value) => value.clone(),
+                        _ => self.input[first..self.position].iter().collect(),
+                    };
+                    return Ok(self.token(token_type, lexeme, line, column));
+                }
+                Some('\'') => {
+                    let first = self.position;
+                    let token_type = self.read_rune()?;
+                    let lexeme = self.input[first..self.position].iter().collect();
+                    return Ok(self.token(token_type, lexeme, line, column));
+                }
+                Some('r') if self.raw_string_hashes().is_some() => {
+                    let value = self.read_raw_string()?;
+                    return Ok(self.token(TokenType::StringLiteral(value.clone()), value, line, column));
+                }
+                Some('b') if matches!(self.peek(), Some('\'' | '"')) => {
+                    let first = self.position;
+                    let token_type = if self.peek() == Some('\'') { self.read_byte_literal()? } else { self.read_byte_string()? };
+                    let lexeme = self.input[first..self.position].iter().collect();
+                    return Ok(self.token(token_type, lexeme, line, column));
+                }
+                Some(ch) if ch.is_ascii_digit() => {
+                    let first = self.position;
+                    let token_type = self.read_number()?;
+                    let lexeme = self.input[first..self.position].iter().collect();
+                    return Ok(self.token(token_type, lexeme, line, column));
+                }
                 Some(ch) if ch.is_alphabetic() || ch == '_' => {
                     let id = self.read_identifier();
                     if let Some(tt) = KEYWORDS.get(id.as_str()) {
-                        match tt {
-                            TokenType::DSL(dsl_name) => {
-                                self.skip_whitespace();
-                                if self.current_char == Some('{') {
-                                    let content = self.read_dsl_content(dsl_name)?;
-                                    return Ok(Token::new(TokenType::DSLContent { dsl_type: dsl_name.clone(), content: content.clone() }, content, line, column));
-                                }
+                        if let TokenType::DSL(dsl_name) = tt {
+                            self.skip_whitespace();
+                            if self.current_char == Some('{') {
+                                let content = self.read_dsl_content(dsl_name)?;
+                                return Ok(self.token(TokenType::DSLContent { dsl_type: dsl_name.clone(), content: content.clone() }, content, line, column));
                             }
-                            _ => {}
                         }
-                        return Ok(Token::new(tt.clone(), id, line, column));
+                        return Ok(self.token(tt.clone(), id, line, column));
                     }
-                    return Ok(Token::new(TokenType::Identifier(id.clone()), id, line, column));
+                    return Ok(self.token(TokenType::Identifier(id.clone()), id, line, column));
                 }
-                Some('+') => { self.advance(); return Ok(Token::new(TokenType::Plus, "+".to_string(), line, column)); }
+                Some('+') => { self.advance(); return Ok(self.token(TokenType::Plus, "+".to_string(), line, column)); }
                 Some('-') => {
-                    if self.peek() == Some('>') { self.advance(); self.advance(); return Ok(Token::new(TokenType::Arrow, "->".to_string(), line, column)); }
-                    self.advance(); return Ok(Token::new(TokenType::Minus, "-".to_string(), line, column));
+                    if self.peek() == Some('>') { self.advance(); self.advance(); return Ok(self.token(TokenType::Arrow, "->".to_string(), line, column)); }
+                    self.advance(); return Ok(self.token(TokenType::Minus, "-".to_string(), line, column));
                 }
-                Some('*') => { self.advance(); return Ok(Token::new(TokenType::Star, "*".to_string(), line, column)); }
-                Some('/') => { self.advance(); return Ok(Token::new(TokenType::Slash, "/".to_string(), line, column)); }
-                Some('%') => { self.advance(); return Ok(Token::new(TokenType::Percent, "%".to_string(), line, column)); }
-                Some('=') => { if self.peek() == Some('=') { self.advance(); self.advance(); return Ok(Token::new(TokenType::EqualEqual, "==".to_string(), line, column)); } self.advance(); return Ok(Token::new(TokenType::Equal, "=".to_string(), line, column)); }
-                Some('!') => { if self.peek() == Some('=') { self.advance(); self.advance(); return Ok(Token::new(TokenType::NotEqual, "!=".to_string(), line, column)); } self.advance(); return Ok(Token::new(TokenType::Not, "!".to_string(), line, column)); }
-                Some('<') => { if self.peek() == Some('=') { self.advance(); self.advance(); return Ok(Token::new(TokenType::LessEqual, "<=".to_string(), line, column)); } self.advance(); return Ok(Token::new(TokenType::Less, "<".to_string(), line, column)); }
-                Some('>') => { if self.peek() == Some('=') { self.advance(); self.advance(); return Ok(Token::new(TokenType::GreaterEqual, ">=".to_string(), line, column)); } self.advance(); return Ok(Token::new(TokenType::Greater, ">".to_string(), line, column)); }
-                Some('&') => { if self.peek() == Some('&') { self.advance(); self.advance(); return Ok(Token::new(TokenType::And, "&&".to_string(), line, column)); } else { return Err(self.error("Unexpected character '&', did you mean '&&'?")); } }
-                Some('|') => { if self.peek() == Some('|') { self.advance(); self.advance(); return Ok(Token::new(TokenType::Or, "||".to_string(), line, column)); } else { return Err(self.error("Unexpected character '|', did you mean '||'?")); } }
-                Some('?') => { self.advance(); return Ok(Token::new(TokenType::Question, "?".to_string(), line, column)); }
-                Some(':') => { self.advance(); return Ok(Token::new(TokenType::Colon, ":".to_string(), line, column)); }
-                Some('(') => { self.advance(); return Ok(Token::new(TokenType::LeftParen, "(".to_string(), line, column)); }
-                Some(')') => { self.advance(); return Ok(Token::new(TokenType::RightParen, ")".to_string(), line, column)); }
-                Some('{') => { self.advance(); return Ok(Token::new(TokenType::LeftBrace, "{".to_string(), line, column)); }
-                Some('}') => { self.advance(); return Ok(Token::new(TokenType::RightBrace, "}".to_string(), line, column)); }
-                Some('[') => { self.advance(); return Ok(Token::new(TokenType::LeftBracket, "[".to_string(), line, column)); }
-                Some(']') => { self.advance(); return Ok(Token::new(TokenType::RightBracket, "]".to_string(), line, column)); }
-                Some(';') => { self.advance(); return Ok(Token::new(TokenType::Semicolon, ";".to_string(), line, column)); }
-                Some(',') => { self.advance(); return Ok(Token::new(TokenType::Comma, ",".to_string(), line, column)); }
-                Some('.') => { self.advance(); return Ok(Token::new(TokenType::Dot, ".".to_string(), line, column)); }
+                Some('*') => { self.advance(); return Ok(self.token(TokenType::Star, "*".to_string(), line, column)); }
+                Some('/') => { self.advance(); return Ok(self.token(TokenType::Slash, "/".to_string(), line, column)); }
+                Some('%') => { self.advance(); return Ok(self.token(TokenType::Percent, "%".to_string(), line, column)); }
+                Some('=') => { if self.peek() == Some('>') { self.advance(); self.advance(); return Ok(self.token(TokenType::FatArrow, "=>".to_string(), line, column)); } else if self.peek() == Some('=') { self.advance(); self.advance(); return Ok(self.token(TokenType::EqualEqual, "==".to_string(), line, column)); } self.advance(); return Ok(self.token(TokenType::Equal, "=".to_string(), line, column)); }
+                Some('!') => { if self.peek() == Some('=') { self.advance(); self.advance(); return Ok(self.token(TokenType::NotEqual, "!=".to_string(), line, column)); } self.advance(); return Ok(self.token(TokenType::Not, "!".to_string(), line, column)); }
+                Some('<') => { if self.peek() == Some('=') { self.advance(); self.advance(); return Ok(self.token(TokenType::LessEqual, "<=".to_string(), line, column)); } self.advance(); return Ok(self.token(TokenType::Less, "<".to_string(), line, column)); }
+                Some('>') => { if self.peek() == Some('=') { self.advance(); self.advance(); return Ok(self.token(TokenType::GreaterEqual, ">=".to_string(), line, column)); } self.advance(); return Ok(self.token(TokenType::Greater, ">".to_string(), line, column)); }
+                Some('&') => { if self.peek() == Some('&') { self.advance(); self.advance(); return Ok(self.token(TokenType::And, "&&".to_string(), line, column)); } else { return Err(self.error("Unexpected character '&', did you mean '&&'?")); } }
+                Some('|') => { if self.peek() == Some('|') { self.advance(); self.advance(); return Ok(self.token(TokenType::Or, "||".to_string(), line, column)); } else { return Err(self.error("Unexpected character '|', did you mean '||'?")); } }
+                Some('?') => { self.advance(); return Ok(self.token(TokenType::Question, "?".to_string(), line, column)); }
+                Some(':') => { self.advance(); return Ok(self.token(TokenType::Colon, ":".to_string(), line, column)); }
+                Some('(') => { self.advance(); return Ok(self.token(TokenType::LeftParen, "(".to_string(), line, column)); }
+                Some(')') => { self.advance(); return Ok(self.token(TokenType::RightParen, ")".to_string(), line, column)); }
+                Some('{') => { self.advance(); return Ok(self.token(TokenType::LeftBrace, "{".to_string(), line, column)); }
+                Some('}') => { self.advance(); return Ok(self.token(TokenType::RightBrace, "}".to_string(), line, column)); }
+                Some('[') => { self.advance(); return Ok(self.token(TokenType::LeftBracket, "[".to_string(), line, column)); }
+                Some(']') => { self.advance(); return Ok(self.token(TokenType::RightBracket, "]".to_string(), line, column)); }
+                Some(';') => { self.advance(); return Ok(self.token(TokenType::Semicolon, ";".to_string(), line, column)); }
+                Some(',') => { self.advance(); return Ok(self.token(TokenType::Comma, ",".to_string(), line, column)); }
+                Some('.') => { self.advance(); return Ok(self.token(TokenType::Dot, ".".to_string(), line, column)); }
                 Some(c) => { return Err(self.error(format!("Unexpected character '{}'", c))); }
             }
         }
     }
 
+    /// The tokens of the whole input, or the first error.
     pub fn tokenize(&mut self) -> Result<Vec<Token>, LexError> {
+        let (tokens, errors) = self.tokenize_recovering();
+        match errors.into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(tokens),
+        }
+    }
+
+    /// The tokens of the whole input, with an `Error` token covering the text
+    /// of each error, which lexing skips to carry on. The tokens always end
+    /// with `Eof`; the errors are in source order.
+    pub fn tokenize_recovering(&mut self) -> (Vec<Token>, Vec<LexError>) {
         let mut tokens = Vec::new();
+        let mut errors = Vec::new();
         loop {
-            let tok = self.next_token()?;
-            let is_eof = matches!(tok.token_type, TokenType::Eof);
-            tokens.push(tok);
-            if is_eof { break; }
+            self.skip_whitespace();
+            let (first, line, column, start) = (self.position, self.line, self.column, self.offset);
+            let result = if self.current_char == Some('/') && matches!(self.peek(), Some('/' | '*')) {
+                self.skip_comment().map(|_| None)
+            } else {
+                self.next_token().map(Some)
+            };
+            match result {
+                Ok(None) => {}
+                Ok(Some(token)) => {
+                    let is_eof = matches!(token.token_type, TokenType::Eof);
+                    tokens.push(token);
+                    if is_eof { break; }
+                }
+                Err(err) => {
+                    self.skip_rest_of_token(first);
+                    self.token_start = start;
+                    let lexeme = self.input[first..self.position].iter().collect();
+                    tokens.push(self.token(TokenType::Error(err.message.clone()), lexeme, line, column));
+                    errors.push(err);
+                }
+            }
+        }
+        (tokens, errors)
+    }
+
+    /// After an error, skips the rest of the token that starts at `first` so
+    /// lexing resumes after it: a quoted literal up to its closing quote, a
+    /// number up to its last letter or digit, anything else one character.
+    fn skip_rest_of_token(&mut self, first: usize) {
+        if self.position == first {
+            self.advance();
+        }
+        let text = &self.input[first..];
+        if text.starts_with(&['"', '"', '"']) {
+            // Only indentation errors are found after the closing quotes
+            let closed = self.position >= first + 6 && self.input[..self.position].ends_with(&['"', '"', '"']);
+            if !closed {
+                while self.current_char.is_some() && !self.at_triple_quote() {
+                    self.advance();
+                }
+                while self.current_char == Some('"') {
+                    self.advance();
+                }
+            }
+        } else if let ['"' | '\'', ..] | ['b', '"' | '\'', ..] = text {
+            let quote = if text[0] == 'b' { text[1] } else { text[0] };
+            // Strings may span lines, runes and bytes may not
+            while let Some(ch) = self.current_char.filter(|&c| c != quote && (quote == '"' || c != '\n')) {
+                self.advance();
+                if ch == '\\' && self.current_char.is_some() {
+                    self.advance();
+                }
+            }
+            if self.current_char == Some(quote) {
+                self.advance();
+            }
+        } else if text[0].is_ascii_digit() {
+            while self.current_char.is_some_and(|c| c.is_alphanumeric() || c == '_')
+                || (self.current_char == Some('.') && self.peek().is_some_and(|c| c.is_ascii_digit()))
+            {
+                self.advance();
+            }
         }
-        Ok(tokens)
     }
 }
@@ -1,5 +1,9 @@
 pub mod token;
+#[allow(clippy::module_inception)]
 pub mod lexer;
 
-pub use token::{TokenType};
-pub use lexer::Lexer;
\ No newline at end of file
+pub use token::{NumberSuffix, StringPart, TokenType};
+pub use lexer::Lexer;
+
+// The file holds its own `#[cfg(test)] mod tests`
+include!("tests.rs");
@@ -1,6 +1,31 @@
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::lexer::LexError;
+    use super::token::Token;
+
+    /// Lexes `code` for the tests below, which build their input from a `&str`.
+    struct Lexer {
+        chars: Vec<char>,
+    }
+
+    impl Lexer {
+        fn new(code: &str) -> Self {
+            Lexer { chars: code.chars().collect() }
+        }
+
+        fn tokenize(&mut self) -> Result<Vec<Token>, LexError> {
+            super::Lexer::new(&self.chars).tokenize()
+        }
+
+        fn tokenize_recovering(&mut self) -> (Vec<Token>, Vec<LexError>) {
+            super::Lexer::new(&self.chars).tokenize_recovering()
+        }
+    }
+
+    fn tokenize(code: &str) -> Result<Vec<Token>, LexError> {
+        Lexer::new(code).tokenize()
+    }
 
     #[test]
     fn test_basic_tokens() {
@@ -65,14 +90,62 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_numbers() {
         let mut lexer = Lexer::new("42 3.14 0 123.456");
         let tokens = lexer.tokenize().unwrap();
         
-        assert_eq!(tokens[0].token_type, TokenType::IntLiteral(42));
-        assert_eq!(tokens[1].token_type, TokenType::FloatLiteral(3.14));
-        assert_eq!(tokens[2].token_type, TokenType::IntLiteral(0));
-        assert_eq!(tokens[3].token_type, TokenType::FloatLiteral(123.456));
+        assert_eq!(tokens[0].token_type, TokenType::IntLiteral(42, None));
+        assert_eq!(tokens[1].token_type, TokenType::FloatLiteral(3.14, None));
+        assert_eq!(tokens[2].token_type, TokenType::IntLiteral(0, None));
+        assert_eq!(tokens[3].token_type, TokenType::FloatLiteral(123.456, None));
+    }
+
+    #[test]
+    fn test_number_prefixes_separators_and_suffixes() {
+        let tokens = tokenize("0xFF 0o17 0b1010_0101 1_000_000 1.5e3 2E-2 7e+1 255u8 0x7fi8 1.5f32 3f64").unwrap();
+        let types: Vec<TokenType> = tokens.into_iter().map(|t| t.token_type).collect();
+
+        assert_eq!(
+            types,
+            vec![
+                TokenType::IntLiteral(255, None),
+                TokenType::IntLiteral(15, None),
+                TokenType::IntLiteral(0b1010_0101, None),
+                TokenType::IntLiteral(1_000_000, None),
+                TokenType::FloatLiteral(1500.0, None),
+                TokenType::FloatLiteral(0.02, None),
+                TokenType::FloatLiteral(70.0, None),
+                TokenType::IntLiteral(255, Some(NumberSuffix::U8)),
+                TokenType::IntLiteral(127, Some(NumberSuffix::I8)),
+                TokenType::FloatLiteral(1.5, Some(NumberSuffix::F32)),
+                TokenType::FloatLiteral(3.0, Some(NumberSuffix::F64)),
+                TokenType::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_number_errors() {
+        let cases = [
+            ("0x", "Expected digits after the number's prefix"),
+            ("0b102", "Invalid digit '2' in base 2 literal"),
+            ("1e", "Expected digits in the exponent"),
+            ("12abc", "Invalid suffix 'abc' for a number literal"),
+            ("1.5u8", "Integer suffix 'u8' on a float literal"),
+            ("0b1f32", "Float suffix 'f32' on a base 2 literal"),
+            ("1e999", "Float literal is out of range"),
+            ("18446744073709551616", "Integer literal is too large"),
+        ];
+        for (code, expected) in cases {
+            let err = tokenize(code).expect_err(code);
+            assert!(err.message.contains(expected), "{}: {}", code, err.message);
+        }
+
+        // The largest 64-bit value still lexes, and a lexeme is its source text
+        let tokens = tokenize("18446744073709551615 1_0u16").unwrap();
+        assert_eq!(tokens[0].token_type, TokenType::IntLiteral(u64::MAX, None));
+        assert_eq!(tokens[1].lexeme, "1_0u16");
     }
 
     #[test]
@@ -96,15 +169,56 @@ mod tests {
 
     #[test]
     fn test_string_interpolation() {
-        let mut lexer = Lexer::new(r#""Hello ${name}, you have ${count} messages""#);
-        let tokens = lexer.tokenize().unwrap();
-        
-        if let TokenType::StringLiteral(content) = &tokens[0].token_type {
-            assert!(content.contains("Hello ${name}"));
-            assert!(content.contains("${count} messages"));
-        } else {
-            panic!("Expected StringLiteral token");
-        }
+        let code = r#""Hello ${name}, you have ${count + 1} messages""#;
+        let tokens = tokenize(code).unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].lexeme, code);
+
+        let TokenType::InterpolatedString(parts) = &tokens[0].token_type else {
+            panic!("Expected InterpolatedString token, got {:?}", tokens[0].token_type);
+        };
+        assert_eq!(parts.len(), 5);
+        assert_eq!(parts[0], StringPart::Literal("Hello ".to_string()));
+        assert_eq!(parts[2], StringPart::Literal(", you have ".to_string()));
+        assert_eq!(parts[4], StringPart::Literal(" messages".to_string()));
+
+        // Embedded tokens keep their place in the source and end at the closing brace
+        let StringPart::Expr(name) = &parts[1] else { panic!("Expected embedded tokens") };
+        let types: Vec<&TokenType> = name.iter().map(|t| &t.token_type).collect();
+        assert_eq!(types, [&TokenType::Identifier("name".to_string()), &TokenType::Eof]);
+        assert_eq!((name[0].line, name[0].column, name[0].start, name[0].end), (1, 10, 9, 13));
+        assert_eq!((name[1].column, name[1].start), (14, 13));
+
+        let StringPart::Expr(sum) = &parts[3] else { panic!("Expected embedded tokens") };
+        assert_eq!(sum.len(), 4);
+        assert_eq!(sum[1].token_type, TokenType::Plus);
+    }
+
+    #[test]
+    fn test_nested_interpolation() {
+        let tokens = tokenize(r#""a${ { x: "b${y}" }.x }c" "\${literal}""#).unwrap();
+
+        let TokenType::InterpolatedString(parts) = &tokens[0].token_type else { panic!("Expected InterpolatedString") };
+        let StringPart::Expr(inner) = &parts[1] else { panic!("Expected embedded tokens") };
+        assert_eq!(inner[0].token_type, TokenType::LeftBrace);
+        assert!(inner.iter().any(|t| matches!(&t.token_type, TokenType::InterpolatedString(p) if p.len() == 2)));
+        assert_eq!(parts[2], StringPart::Literal("c".to_string()));
+
+        // An escaped `$` does not start an interpolation
+        assert_eq!(tokens[1].token_type, TokenType::StringLiteral("${literal}".to_string()));
+        assert_eq!(tokens[2].token_type, TokenType::Eof);
+    }
+
+    #[test]
+    fn test_interpolation_errors() {
+        let error = tokenize(r#"let s = "a ${b + 1";"#).unwrap_err();
+        assert!(error.message.contains("Unterminated string"), "{}", error);
+
+        let error = tokenize(r#"let s = "a ${b"#).unwrap_err();
+        assert_eq!((error.message.as_str(), error.line, error.column), ("Unterminated string interpolation", 1, 12));
+
+        let error = tokenize(r#""${ }""#).unwrap_err();
+        assert_eq!(error.message, "Empty string interpolation");
     }
 
     #[test]
@@ -115,6 +229,116 @@ mod tests {
         assert_eq!(tokens[0].token_type, TokenType::StringLiteral("ABC".to_string()));
     }
 
+    #[test]
+    fn test_rune_and_byte_literals() {
+        let tokens = tokenize(r#"'a' '\n' '\'' '"' '\u{1F600}' 'ß' b'A' b'\xFF' b'\'' b"GET\r\n\x00'" b"""#).unwrap();
+        let types: Vec<TokenType> = tokens.into_iter().map(|t| t.token_type).collect();
+
+        assert_eq!(
+            types,
+            vec![
+                TokenType::RuneLiteral('a'),
+                TokenType::RuneLiteral('\n'),
+                TokenType::RuneLiteral('\''),
+                TokenType::RuneLiteral('"'),
+                TokenType::RuneLiteral('😀'),
+                TokenType::RuneLiteral('ß'),
+                TokenType::ByteLiteral(b'A'),
+                TokenType::ByteLiteral(0xFF),
+                TokenType::ByteLiteral(b'\''),
+                TokenType::ByteStringLiteral(b"GET\r\n\x00'".to_vec()),
+                TokenType::ByteStringLiteral(Vec::new()),
+                TokenType::Eof,
+            ]
+        );
+
+        // `b` alone is still an identifier, and strings take the new escapes too
+        let tokens = tokenize(r#"b "\u{41}\x42\'""#).unwrap();
+        assert_eq!(tokens[0].token_type, TokenType::Identifier("b".to_string()));
+        assert_eq!(tokens[1].token_type, TokenType::StringLiteral("AB'".to_string()));
+    }
+
+    #[test]
+    fn test_rune_and_byte_errors() {
+        let cases = [
+            ("''", "Empty rune literal"),
+            ("'ab'", "Rune literal must contain exactly one character"),
+            ("'a", "Unterminated rune literal"),
+            (r"'\u{D800}'", "Invalid unicode code point"),
+            (r"'\u{1234567}'", "Invalid unicode escape"),
+            (r"'\xFF'", "\\x escapes above 7F are only allowed in byte literals"),
+            ("b''", "Empty byte literal"),
+            ("b'ab'", "Byte literal must contain exactly one character"),
+            ("b'é'", "Non-ASCII character 'é' in byte literal"),
+            (r"b'\u0041'", "Unicode escapes are not allowed in byte literals"),
+            (r"b'\xG0'", "Expected two hex digits after \\x"),
+            ("b\"abc", "Unterminated byte string"),
+        ];
+        for (code, expected) in cases {
+            let err = tokenize(code).expect_err(code);
+            assert!(err.message.contains(expected), "{}: {}", code, err.message);
+        }
+    }
+
+    #[test]
+    fn test_raw_strings() {
+        let tokens = tokenize(r####"r"C:\dir\${x}" r#"say "hi""# r##"a "# b"## r"" r x"####).unwrap();
+        let types: Vec<TokenType> = tokens.into_iter().map(|t| t.token_type).collect();
+
+        assert_eq!(
+            types,
+            vec![
+                TokenType::StringLiteral(r"C:\dir\${x}".to_string()),
+                TokenType::StringLiteral(r#"say "hi""#.to_string()),
+                TokenType::StringLiteral(r##"a "# b"##.to_string()),
+                TokenType::StringLiteral(String::new()),
+                TokenType::Identifier("r".to_string()),
+                TokenType::Identifier("x".to_string()),
+                TokenType::Eof,
+            ]
+        );
+
+        let err = tokenize("let p = r#\"open\";\nlet q = 1;").unwrap_err();
+        assert_eq!((err.message.as_str(), err.line, err.column), ("Unterminated raw string", 1, 9));
+    }
+
+    #[test]
+    fn test_multiline_strings() {
+        let code = "let page = \"\"\"\n    <ul>\n      <li>${name}</li>\\t\n\n    </ul>\n    \"\"\";\nlet raw = r\"a\nb\"; done";
+        let tokens = tokenize(code).unwrap();
+
+        let TokenType::InterpolatedString(parts) = &tokens[3].token_type else {
+            panic!("Expected interpolated string, got {:?}", tokens[3].token_type)
+        };
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0], StringPart::Literal("<ul>\n  <li>".to_string()));
+        assert_eq!(parts[2], StringPart::Literal("</li>\t\n\n</ul>".to_string()));
+        let StringPart::Expr(name) = &parts[1] else { panic!("Expected expression") };
+        assert_eq!((name[0].line, name[0].column), (3, 13));
+
+        // Positions after the string account for its lines
+        assert_eq!((tokens[3].line, tokens[3].column, tokens[3].end_line, tokens[3].end_column), (1, 12, 6, 8));
+        assert_eq!((tokens[4].token_type.clone(), tokens[4].line), (TokenType::Semicolon, 6));
+        assert_eq!(tokens[8].token_type, TokenType::StringLiteral("a\nb".to_string()));
+        assert_eq!((tokens[10].line, tokens[10].column), (8, 5));
+
+        let tokens = tokenize("\"\"\"\n  a\n    b\n  \"\"\" \"\"").unwrap();
+        assert_eq!(tokens[0].token_type, TokenType::StringLiteral("a\n  b".to_string()));
+        assert_eq!(tokens[1].token_type, TokenType::StringLiteral(String::new()));
+
+        let cases = [
+            ("\"\"\"text\n\"\"\"", "must start on the line after", 1),
+            ("\"\"\"\n  a\n  b\"\"\"", "must be on its own line", 3),
+            ("\"\"\"\n  a\n b\n  \"\"\"", "Line is indented less than the closing", 3),
+            ("\"\"\"\n  a\n", "Unterminated multi-line string", 1),
+        ];
+        for (code, expected, line) in cases {
+            let err = tokenize(code).expect_err(code);
+            assert!(err.message.contains(expected), "{:?}: {}", code, err.message);
+            assert_eq!(err.line, line, "{:?}", code);
+        }
+    }
+
     #[test]
     fn test_identifiers() {
         let mut lexer = Lexer::new("variable_name _private CamelCase test123");
@@ -180,7 +404,7 @@ mod tests {
         assert_eq!(tokens[0].token_type, TokenType::Let);
         assert_eq!(tokens[1].token_type, TokenType::Identifier("x".to_string()));
         assert_eq!(tokens[2].token_type, TokenType::Equal);
-        assert_eq!(tokens[3].token_type, TokenType::IntLiteral(42));
+        assert_eq!(tokens[3].token_type, TokenType::IntLiteral(42, None));
         assert_eq!(tokens[4].token_type, TokenType::Semicolon);
         assert_eq!(tokens[5].token_type, TokenType::Let);
         assert_eq!(tokens[6].token_type, TokenType::Identifier("y".to_string()));
@@ -263,7 +487,7 @@ mod tests {
         
         assert!(result.is_err());
         let error = result.unwrap_err();
-        assert!(error.message.contains("Did you mean '&&'?"));
+        assert!(error.message.contains("did you mean '&&'?"));
     }
 
     #[test]
@@ -273,7 +497,7 @@ mod tests {
         
         assert!(result.is_err());
         let error = result.unwrap_err();
-        assert!(error.message.contains("Did you mean '||'?"));
+        assert!(error.message.contains("did you mean '||'?"));
     }
 
     #[test]
@@ -306,6 +530,58 @@ mod tests {
         assert!(error.message.contains("Invalid unicode escape"));
     }
 
+    #[test]
+    fn test_tokenize_recovering() {
+        let code = "let a = 1 & 2;\nlet b = 'ab' + \"x\\q\" + 0b12;\nlet c = #;\n/* open";
+        let (tokens, errors) = Lexer::new(code).tokenize_recovering();
+
+        let messages: Vec<(&str, usize, usize)> = errors.iter().map(|e| (e.message.as_str(), e.line, e.column)).collect();
+        assert_eq!(
+            messages,
+            vec![
+                ("Unexpected character '&', did you mean '&&'?", 1, 11),
+                ("Rune literal must contain exactly one character", 2, 9),
+                ("Invalid escape sequence: \\q", 2, 19),
+                ("Invalid digit '2' in base 2 literal", 2, 27),
+                ("Unexpected character '#'", 3, 9),
+                ("Unterminated multi-line comment", 4, 1),
+            ]
+        );
+
+        // Each error becomes one token over the skipped text, and lexing resumes after it
+        let invalid: Vec<(&str, usize, usize, usize, usize)> = tokens
+            .iter()
+            .filter(|t| matches!(t.token_type, TokenType::Error(_)))
+            .map(|t| (t.lexeme.as_str(), t.line, t.column, t.start, t.end))
+            .collect();
+        assert_eq!(
+            invalid,
+            vec![
+                ("&", 1, 11, 10, 11),
+                ("'ab'", 2, 9, 23, 27),
+                ("\"x\\q\"", 2, 16, 30, 35),
+                ("0b12", 2, 24, 38, 42),
+                ("#", 3, 9, 52, 53),
+                ("/* open", 4, 1, 55, 62),
+            ]
+        );
+        assert_eq!(tokens[4].token_type, TokenType::Error("Unexpected character '&', did you mean '&&'?".to_string()));
+        assert_eq!(tokens[5].token_type, TokenType::IntLiteral(2, None));
+        assert_eq!(tokens.iter().filter(|t| t.token_type == TokenType::Let).count(), 3);
+        assert_eq!(tokens.last().map(|t| &t.token_type), Some(&TokenType::Eof));
+
+        // Without recovery the first error is returned
+        let err = tokenize(code).unwrap_err();
+        assert_eq!((err.line, err.column), (1, 11));
+
+        // Bad text inside a multi-line string doesn't leak into the code after it
+        let code = "let s = \"\"\"\n  a\\q\n  \"\"\"; let t = 1;";
+        let (tokens, errors) = Lexer::new(code).tokenize_recovering();
+        assert_eq!(errors.len(), 1);
+        let kinds: Vec<&TokenType> = tokens[3..].iter().map(|t| &t.token_type).collect();
+        assert!(matches!(kinds[..], [TokenType::Error(_), TokenType::Semicolon, TokenType::Let, ..]), "{:?}", kinds);
+    }
+
     #[test]
     fn test_error_line_column_tracking() {
         let code = r#"
@@ -364,4 +640,4 @@ mod tests {
         let dsl_found = tokens.iter().any(|t| matches!(t.token_type, TokenType::DSLContent { .. }));
         assert!(dsl_found, "Should find DSL content");
     }
-}
\ No newline at end of file
+}
@@ -1,15 +1,24 @@
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
     // Literals
-    IntLiteral(i64),
-    FloatLiteral(f64),
+    IntLiteral(u64, Option<NumberSuffix>),
+    FloatLiteral(f64, Option<NumberSuffix>),
     StringLiteral(String),
+    /// A string literal containing at least one `${...}`.
+    InterpolatedString(Vec<StringPart>),
+    /// `'x'`
+    RuneLiteral(char),
+    /// `b'x'`
+    ByteLiteral(u8),
+    /// `b"bytes"`
+    ByteStringLiteral(Vec<u8>),
     BooleanLiteral(bool),
     Identifier(String),
 
     // Keywords
     Use, Struct, Enum, Type, Let, Mut, Const, Fn,
-    If, Else, While, For, In, Return, Async, Sync, Par, Spawn, Await,
+    If, Else, While, For, In, Return, Match, Async, Sync, Par, Spawn, Await,
     Public, Private, Protected,
 
     // DSL Keywords
@@ -37,6 +46,7 @@ pub enum TokenType {
     Question,       // ?
     Colon,          // :
     Arrow,          // ->
+    FatArrow,       // =>
 
     // Delimiters
     LeftParen,      // (
@@ -51,22 +61,94 @@ pub enum TokenType {
 
     // Special
     Eof,
+    /// Text that failed to lex, with the lexer's message; only produced by
+    /// `Lexer::tokenize_recovering`.
+    Error(String),
     
     // DSL Content
    DSLContent { dsl_type: String, content: String },
 }
 
-#[derive(Debug, Clone)]
+/// The type suffix of a number literal, as in `255u8` or `1.5f32`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberSuffix {
+    I8, I16, I32, I64,
+    U8, U16, U32, U64,
+    F32, F64,
+}
+
+impl NumberSuffix {
+    pub fn parse(text: &str) -> Option<NumberSuffix> {
+        match text {
+            "i8" => Some(NumberSuffix::I8),
+            "i16" => Some(NumberSuffix::I16),
+            "i32" => Some(NumberSuffix::I32),
+            "i64" => Some(NumberSuffix::I64),
+            "u8" => Some(NumberSuffix::U8),
+            "u16" => Some(NumberSuffix::U16),
+            "u32" => Some(NumberSuffix::U32),
+            "u64" => Some(NumberSuffix::U64),
+            "f32" => Some(NumberSuffix::F32),
+            "f64" => Some(NumberSuffix::F64),
+            _ => None,
+        }
+    }
+
+    /// The primitive type the suffix stands for, e.g. `uint8` for `u8`.
+    pub fn type_name(self) -> &'static str {
+        match self {
+            NumberSuffix::I8 => "int8",
+            NumberSuffix::I16 => "int16",
+            NumberSuffix::I32 => "int32",
+            NumberSuffix::I64 => "int64",
+            NumberSuffix::U8 => "uint8",
+            NumberSuffix::U16 => "uint16",
+            NumberSuffix::U32 => "uint32",
+            NumberSuffix::U64 => "uint64",
+            NumberSuffix::F32 => "float32",
+            NumberSuffix::F64 => "float64",
+        }
+    }
+
+    pub fn is_float(self) -> bool {
+        matches!(self, NumberSuffix::F32 | NumberSuffix::F64)
+    }
+}
+
+/// A segment of an interpolated string literal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringPart {
+    /// Text between interpolations, with escapes already applied.
+    Literal(String),
+    /// The tokens between `${` and `}`, followed by an `Eof` token at the
+    /// closing brace.
+    Expr(Vec<Token>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Token {
     pub token_type: TokenType,
     pub lexeme: String,
     pub line: usize,
     pub column: usize,
+    /// Where the token's text ends, exclusive. The lexeme is not always
+    /// that text: string literals hold their unescaped value.
+    pub end_line: usize,
+    pub end_column: usize,
+    /// Byte offsets of the token's text in the source, end exclusive.
+    pub start: usize,
+    pub end: usize,
 }
 
 impl Token {
+    /// A token with no extent, such as one the parser makes up; see
+    /// `with_offsets`.
     pub fn new(token_type: TokenType, lexeme: String, line: usize, column: usize) -> Self {
-        Token { token_type, lexeme, line, column }
+        Token { token_type, lexeme, line, column, end_line: line, end_column: column, start: 0, end: 0 }
+    }
+
+    pub fn with_offsets(self, start: usize, end: usize) -> Self {
+        Token { start, end, ..self }
     }
 }
 
@@ -0,0 +1,15 @@
+//! The Flux front end: lexing, parsing and semantic analysis.
+//!
+//! ```
+//! let source = flux_core::SourceFile::new("main.kai", "let answer: int32 = 42;");
+//! let checked = flux_core::check(&source);
+//! assert!(!checked.has_errors());
+//! assert_eq!(checked.program.declarations.len(), 1);
+//! ```
+
+pub mod lexer;
+pub mod parser;
+mod pipeline;
+
+pub use parser::span::{NodeId, Span};
+pub use pipeline::{check, check_modules, check_with, lex, parse, Diagnostic, Lexed, Parsed, SourceFile, Stage};
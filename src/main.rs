@@ -1,17 +1,11 @@
-mod lexer;
-mod parser;
-
-use lexer::{Lexer, TokenType};
+use flux_core::lexer::TokenType;
+use flux_core::parser::{
+    ASTNode, FnDecl, Mutability, Program, SymbolTable, VarDecl, Visibility,
+};
+use flux_core::{SourceFile, Stage};
 use std::env;
-use std::fs;
+use std::path::Path;
 use std::process;
-use parser::recursive_descent::RecursiveDescentParser;
-
-use crate::parser::semantic::analyzer::SemanticAnalyzer;
-use crate::parser::ASTNode;
-use crate::parser::Mutability;
-use crate::parser::SymbolTable;
-use crate::parser::Visibility;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -51,70 +45,111 @@ fn main() {
 }
 
 fn tokenize_file(filename: &str) {
-    let contents = fs::read_to_string(filename)
+    let source = SourceFile::read(filename)
         .unwrap_or_else(|err| { 
             eprintln!("Error reading file '{}': {}", filename, err); 
             process::exit(1); 
         });
 
-    let code_chars: Vec<char> = contents.chars().collect();
-    let mut lexer = Lexer::new(&code_chars);
-    
-    match lexer.tokenize() {
-        Ok(tokens) => {
-            println!("✅ Tokenization successful! Found {} tokens:\n", tokens.len());
-            for (i, token) in tokens.iter().enumerate() {
-                if matches!(token.token_type, TokenType::Eof) {
-                    println!("{:3}: {:?}", i, token.token_type);
-                } else {
-                    println!("{:3}: {:?} '{}' @ {}:{}", 
-                        i, token.token_type, token.lexeme, token.line, token.column
-                    );
-                }
-            }
+    let lexed = flux_core::lex(&source);
+    if lexed.diagnostics.is_empty() {
+        println!("✅ Tokenization successful! Found {} tokens:\n", lexed.tokens.len());
+    } else {
+        println!("⚠️ Tokenization finished with errors. Found {} tokens:\n", lexed.tokens.len());
+    }
+    for (i, token) in lexed.tokens.iter().enumerate() {
+        if matches!(token.token_type, TokenType::Eof) {
+            println!("{:3}: {:?}", i, token.token_type);
+        } else {
+            println!("{:3}: {:?} '{}' @ {}:{}", 
+                i, token.token_type, token.lexeme, token.line, token.column
+            );
         }
-        Err(err) => {
-            eprintln!("❌ Lexer error: {}", err);
-            process::exit(1);
+    }
+
+    // Display diagnostics if any
+    if !lexed.diagnostics.is_empty() {
+        println!("\n⚠️ Diagnostics:");
+        for diagnostic in &lexed.diagnostics {
+            println!("  - {}", diagnostic);
         }
     }
 }
 
 fn parse_file(filename: &str) {
-    let contents = fs::read_to_string(filename)
-        .unwrap_or_else(|err| { 
-            eprintln!("Error reading file '{}': {}", filename, err); 
-            process::exit(1); 
-        });
-
-    // Lexing
-    let code_chars: Vec<char> = contents.chars().collect();
-    let mut lexer = Lexer::new(&code_chars);
-    let tokens = match lexer.tokenize() {
-        Ok(t) => t,
+    // The directory of the entry file is the project root for `use` paths
+    let modules = match flux_core::check_modules(Path::new(filename)) {
+        Ok(modules) => modules,
         Err(err) => {
-            eprintln!("❌ Lexer error: {}", err);
+            eprintln!("❌ {}", err);
             process::exit(1);
         }
     };
 
-    // ✅ Symbol table shared ke parser
-    let mut symbol_table = SymbolTable::new();
-    let mut parser = RecursiveDescentParser::new(tokens, &mut symbol_table);
-
-    match parser.parse_program() {
-        Ok(ast_node) => {
-            if let ASTNode::Program(program) = ast_node {
-                println!(
-                    "✅ Parsing successful! Program has {} variable declarations.",
-                    program.declarations.len()
-                );
-
-                // Display parsed variables
-                if !program.declarations.is_empty() {
-                    println!("\nVariable Declarations:");
-                    for (i, node) in program.declarations.iter().enumerate() {
-                        if let ASTNode::VarDecl(decl) = node {
+    for (source, checked) in &modules {
+        println!(
+            "✅ Parsed '{}': {} declarations.",
+            source.name,
+            checked.program.declarations.len()
+        );
+        print_declarations(&checked.program);
+
+        // Display diagnostics if any
+        if checked.has_errors() {
+            println!("\n⚠️ Diagnostics:");
+            for diagnostic in &checked.diagnostics {
+                println!("  - {}", diagnostic);
+            }
+        }
+        println!();
+    }
+}
+
+fn print_declarations(program: &Program) {
+    if program.declarations.is_empty() {
+        return;
+    }
+
+    println!("\nDeclarations:");
+    for (i, node) in program.declarations.iter().enumerate() {
+        match node {
+            ASTNode::VarDecl(decl) => print_var_decl(i + 1, decl),
+            ASTNode::FnDecl(decl) => print_fn_decl(i + 1, decl),
+            ASTNode::Import(decl) => println!("  {}. use {}", i + 1, decl.module_path()),
+            ASTNode::ConstDecl(decl) => {
+                // Constants whose initializer failed to fold have no value
+                let value = match &decl.value {
+                    Some(value) => format!("{:?}", value),
+                    None => "<error>".to_string(),
+                };
+                println!("  {}. const {} : {:?} = {}", i + 1, decl.name, decl.declared_type, value);
+            }
+            ASTNode::StructDecl(decl) => println!(
+                "  {}. struct {} ({} fields, {} methods)",
+                i + 1,
+                decl.name,
+                decl.fields.len(),
+                decl.methods.len()
+            ),
+            ASTNode::TypeAlias(decl) => println!(
+                "  {}. type {} = {:?}",
+                i + 1,
+                decl.name,
+                decl.aliased_type
+            ),
+            ASTNode::EnumDecl(decl) => println!(
+                "  {}. enum {} ({} variants)",
+                i + 1,
+                decl.name,
+                decl.variants.len()
+            ),
+            ASTNode::Error(_) => println!("  {}. <error>", i + 1),
+            other => println!("  {}. {:?}", i + 1, other),
+        }
+    }
+}
+
+fn print_var_decl(index: usize, decl: &VarDecl) {
     let vis_str = decl.visibility
         .as_ref()
         .map(|v| format!("{:?} ", v).to_lowercase())
@@ -138,7 +173,7 @@ fn parse_file(filename: &str) {
 
     println!(
         "  {}. {}{} {} : {}{}",
-        i + 1,
+        index,
         vis_str,
         mut_str,
         decl.name,
@@ -147,27 +182,38 @@ fn parse_file(filename: &str) {
     );
 }
 
-                    }
-                }
-
-                // Display semantic errors if any
-                let errors = parser.get_semantic_errors();
-                if !errors.is_empty() {
-                    println!("\n⚠️ Semantic warnings:");
-                    for e in errors {
-                        println!("  - {}", e);
-                    }
-                }
-            } else {
-                eprintln!("❌ Expected ASTNode::Program, got something else");
-                process::exit(1);
-            }
-        }
-        Err(err) => {
-            eprintln!("❌ Parser error: {}", err);
-            process::exit(1);
-        }
-    }
+fn print_fn_decl(index: usize, decl: &FnDecl) {
+    let vis_str = decl.visibility
+        .as_ref()
+        .map(|v| format!("{:?} ", v).to_lowercase())
+        .unwrap_or_default();
+
+    let conc_str = decl.concurrency
+        .as_ref()
+        .map(|c| format!("{:?} ", c).to_lowercase())
+        .unwrap_or_default();
+
+    let params = decl.parameters
+        .iter()
+        .map(|p| format!("{}: {:?}", p.name, p.param_type))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let ret_str = decl.return_type
+        .as_ref()
+        .map(|t| format!(" -> {:?}", t))
+        .unwrap_or_default();
+
+    println!(
+        "  {}. {}{}fn {}({}){} ({} statements)",
+        index,
+        vis_str,
+        conc_str,
+        decl.name,
+        params,
+        ret_str,
+        decl.body.statements.len()
+    );
 }
 
 fn start_repl() {
@@ -215,27 +261,17 @@ fn start_repl() {
             _ => {}
         }
 
-        // lexing
-        let tokens = match Lexer::new(&input.chars().collect::<Vec<_>>()).tokenize() {
-            Ok(t) => t,
-            Err(err) => {
-                eprintln!("  ❌ Lexer error: {}", err);
-                line_number += 1;
-                continue;
-            }
-        };
-
-        // ✅ bikin parser, inject symbol_table (dipinjam analyzer di dalam parser)
-        let mut parser = RecursiveDescentParser::new(tokens, &mut symbol_table);
-
-        match parser.parse_program() {
-            Ok(node) => handle_ast_node(node),
-            Err(err) => eprintln!("  ❌ Parse error: {}", err),
+        let checked = flux_core::check_with(&SourceFile::new("<repl>", input), &mut symbol_table);
+        for node in checked.program.declarations {
+            handle_ast_node(node);
         }
 
         // tampilkan warning
-        for error in parser.get_semantic_errors() {
-            eprintln!("  ⚠️ Warning: {}", error);
+        for diagnostic in &checked.diagnostics {
+            match diagnostic.stage {
+                Stage::Semantic => eprintln!("  ⚠️ Warning: {}", diagnostic),
+                Stage::Lex | Stage::Syntax => eprintln!("  ❌ {}", diagnostic),
+            }
         }
 
         line_number += 1;
@@ -329,35 +365,28 @@ fn run_quick_tests() {
         ("Boolean", "let flag = true;"),
         ("Private", "private mut counter: int32 = 0;"),
         ("Protected", "protected let secret: string;"),
+        ("Function", "public fn main() -> int32 { let x = 1; return x; }"),
+        ("Async function", "async fn ping(id: int64) { return; }"),
     ];
 
-    println!("📝 Variable Declaration Tests:");
+    println!("📝 Declaration Tests:");
     let mut passed = 0;
     let total = test_cases.len();
 
     for (name, code) in &test_cases {
         print!("  Testing {:<18} ... ", name);
 
-        let code_chars: Vec<char> = code.chars().collect();
-        let mut lexer = Lexer::new(&code_chars);
-
-        match lexer.tokenize() {
-            Ok(tokens) => {
-                let mut symbol_table = SymbolTable::new();
-                let mut parser = RecursiveDescentParser::new(tokens, &mut symbol_table);   
-                match parser.parse_program() {
-                    Ok(_) => {
-                        println!("✅");
-                        passed += 1;
-                    }
-                    Err(err) => println!("❌ Parse error: {}", err),
-                }
+        let checked = flux_core::check(&SourceFile::new(*name, *code));
+        match checked.diagnostics.first() {
+            None => {
+                println!("✅");
+                passed += 1;
             }
-            Err(err) => println!("❌ Lex error: {}", err),
+            Some(err) => println!("❌ {}", err),
         }
     }
 
-    println!("\n📊 Variable Declaration Results: {}/{} tests passed", passed, total);
+    println!("\n📊 Declaration Results: {}/{} tests passed", passed, total);
 
     // Multi-declaration test
     println!("\n📝 Multi-Declaration Test:");
@@ -369,30 +398,15 @@ fn run_quick_tests() {
     "#;
 
     print!("  Testing multiple declarations ... ");
-    let multi_chars: Vec<char> = multi_code.chars().collect();
-    let mut lexer = Lexer::new(&multi_chars);
-
-    match lexer.tokenize() {
-        Ok(tokens) => {
-            let mut symbol_table = SymbolTable::new();
-            let mut parser = RecursiveDescentParser::new(tokens, &mut symbol_table);
-            match parser.parse_program() {
-                Ok(ast_node) => {
-                    if let ASTNode::Program(program) = ast_node {
-                        if program.declarations.len() == 4 {
-                            println!("✅ (parsed {} declarations)", program.declarations.len());
-                            passed += 1;
-                        } else {
-                            println!("❌ Expected 4 declarations, got {}", program.declarations.len());
-                        }
-                    } else {
-                        println!("❌ Expected ASTNode::Program, got something else");
-                    }
-                }
-                Err(err) => println!("❌ Parse error: {}", err),
-            }
-        }
-        Err(err) => println!("❌ Lex error: {}", err),
+    let checked = flux_core::check(&SourceFile::new("multi", multi_code));
+    let declarations = checked.program.declarations.len();
+    if let Some(err) = checked.diagnostics.first() {
+        println!("❌ {}", err);
+    } else if declarations == 4 {
+        println!("✅ (parsed {} declarations)", declarations);
+        passed += 1;
+    } else {
+        println!("❌ Expected 4 declarations, got {}", declarations);
     }
 
     // Error cases
@@ -409,33 +423,21 @@ fn run_quick_tests() {
     for (name, code) in &error_cases {
         print!("  Testing {:<18} ... ", name);
 
-        let code_chars: Vec<char> = code.chars().collect();
-        let mut lexer = Lexer::new(&code_chars);
-
-        match lexer.tokenize() {
-            Ok(tokens) => {
-                let mut symbol_table = SymbolTable::new();
-                let mut parser = RecursiveDescentParser::new(tokens, &mut symbol_table);
-                match parser.parse_program() {
-                    Ok(_) => {
-                        let errors = parser.get_semantic_errors();
-                        if errors.is_empty() {
-                            println!("❌ Should have failed");
-                        } else {
-                            println!("✅ Correctly caught semantic error: {:?}", errors);
-                            error_passed += 1;
-                        }
-                    }
-                    Err(_) => {
-                        println!("✅ Correctly caught parser error");
-                        error_passed += 1;
-                    }
-                }
-            }
-            Err(_) => {
+        let checked = flux_core::check(&SourceFile::new(*name, *code));
+        match checked.diagnostics.first().map(|d| d.stage) {
+            None => println!("❌ Should have failed"),
+            Some(Stage::Lex) => {
                 println!("✅ Correctly caught lexer error");
                 error_passed += 1;
             }
+            Some(Stage::Syntax) => {
+                println!("✅ Correctly caught parser error");
+                error_passed += 1;
+            }
+            Some(Stage::Semantic) => {
+                println!("✅ Correctly caught semantic error: {}", checked.diagnostics[0].message);
+                error_passed += 1;
+            }
         }
     }
 
@@ -1,17 +1,58 @@
+use crate::parser::span::{NodeId, Span};
 use crate::parser::Expr;
 
 #[derive(Debug, Clone, PartialEq)]
 #[allow(dead_code)]
 pub enum ASTNode {
     Program(Program),
+    Import(ImportDecl),
     VarDecl(VarDecl),
     FnDecl(FnDecl),
+    StructDecl(StructDecl),
+    EnumDecl(EnumDecl),
+    TypeAlias(TypeAliasDecl),
+    ConstDecl(ConstDecl),
     Visibility(Visibility),
     Mutability(Mutability),
     Type(super::types::Type),
     Expr(super::expr::Expr),
     Literal(super::expr::Literal),
     Identifier(String),
+    Error(ErrorNode),
+}
+
+impl ASTNode {
+    /// The span of a declaration; other nodes have none.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ASTNode::Import(decl) => Some(decl.span),
+            ASTNode::VarDecl(decl) => Some(decl.span),
+            ASTNode::FnDecl(decl) => Some(decl.span),
+            ASTNode::StructDecl(decl) => Some(decl.span),
+            ASTNode::EnumDecl(decl) => Some(decl.span),
+            ASTNode::TypeAlias(decl) => Some(decl.span),
+            ASTNode::ConstDecl(decl) => Some(decl.span),
+            ASTNode::Expr(expr) => Some(expr.span),
+            ASTNode::Error(error) => Some(error.span),
+            _ => None,
+        }
+    }
+
+    /// The id of a declaration; other nodes have none.
+    pub fn id(&self) -> Option<NodeId> {
+        match self {
+            ASTNode::Import(decl) => Some(decl.id),
+            ASTNode::VarDecl(decl) => Some(decl.id),
+            ASTNode::FnDecl(decl) => Some(decl.id),
+            ASTNode::StructDecl(decl) => Some(decl.id),
+            ASTNode::EnumDecl(decl) => Some(decl.id),
+            ASTNode::TypeAlias(decl) => Some(decl.id),
+            ASTNode::ConstDecl(decl) => Some(decl.id),
+            ASTNode::Expr(expr) => Some(expr.id),
+            ASTNode::Error(error) => Some(error.id),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -19,6 +60,27 @@ pub struct Program {
     pub declarations: Vec<ASTNode>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportDecl {
+    /// Module path segments, e.g. `["std", "io"]` for `use std.io;`.
+    pub path: Vec<String>,
+    pub line: usize,
+    pub column: usize,
+    pub span: Span,
+    pub id: NodeId,
+}
+
+impl ImportDecl {
+    /// The name the module is referred to by in the importing file.
+    pub fn binding(&self) -> &str {
+        self.path.last().map(String::as_str).unwrap_or_default()
+    }
+
+    pub fn module_path(&self) -> String {
+        self.path.join(".")
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct VarDecl {
     pub visibility: Option<Visibility>,
@@ -29,17 +91,227 @@ pub struct VarDecl {
     pub initializer: Option<super::expr::Expr>,
     pub line: usize,
     pub column: usize,
+    pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct FnDecl {
     pub visibility: Option<Visibility>,
+    pub concurrency: Option<ConcurrencyModifier>,
     pub name: String,
+    pub type_params: Vec<String>,
     pub parameters: Vec<Parameter>,
     pub return_type: Option<super::types::Type>,
-    pub body: Option<Expr>,
+    pub body: Block,
+    pub line: usize,
+    pub column: usize,
+    pub span: Span,
+    pub id: NodeId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructDecl {
+    pub visibility: Option<Visibility>,
+    pub name: String,
+    pub type_params: Vec<String>,
+    pub fields: Vec<FieldDecl>,
+    pub methods: Vec<FnDecl>,
+    pub line: usize,
+    pub column: usize,
+    pub span: Span,
+    pub id: NodeId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDecl {
+    pub visibility: Option<Visibility>,
+    pub name: String,
+    pub field_type: super::types::Type,
+    pub line: usize,
+    pub column: usize,
+    pub span: Span,
+    pub id: NodeId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumDecl {
+    pub visibility: Option<Visibility>,
+    pub name: String,
+    pub type_params: Vec<String>,
+    pub variants: Vec<EnumVariant>,
+    pub line: usize,
+    pub column: usize,
+    pub span: Span,
+    pub id: NodeId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumVariant {
+    pub name: String,
+    pub payload: Vec<super::types::Type>,
+    pub line: usize,
+    pub column: usize,
+    pub span: Span,
+    pub id: NodeId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeAliasDecl {
+    pub visibility: Option<Visibility>,
+    pub name: String,
+    pub aliased_type: super::types::Type,
     pub line: usize,
     pub column: usize,
+    pub span: Span,
+    pub id: NodeId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub statements: Vec<Stmt>,
+    pub span: Span,
+    pub id: NodeId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    VarDecl(VarDecl),
+    ConstDecl(ConstDecl),
+    Assignment {
+        target: Expr,
+        value: Expr,
+        line: usize,
+        column: usize,
+        span: Span,
+        id: NodeId,
+    },
+    Return {
+        value: Option<Expr>,
+        line: usize,
+        column: usize,
+        span: Span,
+        id: NodeId,
+    },
+    If {
+        condition: Expr,
+        then_branch: Block,
+        else_branch: Option<Block>,
+        line: usize,
+        column: usize,
+        span: Span,
+        id: NodeId,
+    },
+    While {
+        modifier: Option<LoopModifier>,
+        condition: Expr,
+        body: Block,
+        line: usize,
+        column: usize,
+        span: Span,
+        id: NodeId,
+    },
+    For {
+        modifier: Option<LoopModifier>,
+        variable: String,
+        iterable: Expr,
+        body: Block,
+        line: usize,
+        column: usize,
+        span: Span,
+        id: NodeId,
+    },
+    Expression {
+        expr: Expr,
+        line: usize,
+        column: usize,
+        span: Span,
+        id: NodeId,
+    },
+    DSLBlock {
+        dsl_type: String,
+        content: String,
+        line: usize,
+        column: usize,
+        span: Span,
+        id: NodeId,
+    },
+    Block(Block),
+    Error(ErrorNode),
+}
+
+impl Stmt {
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::VarDecl(decl) => decl.span,
+            Stmt::ConstDecl(decl) => decl.span,
+            Stmt::Block(block) => block.span,
+            Stmt::Error(error) => error.span,
+            Stmt::Assignment { span, .. }
+            | Stmt::Return { span, .. }
+            | Stmt::If { span, .. }
+            | Stmt::While { span, .. }
+            | Stmt::For { span, .. }
+            | Stmt::Expression { span, .. }
+            | Stmt::DSLBlock { span, .. } => *span,
+        }
+    }
+
+    pub fn id(&self) -> NodeId {
+        match self {
+            Stmt::VarDecl(decl) => decl.id,
+            Stmt::ConstDecl(decl) => decl.id,
+            Stmt::Block(block) => block.id,
+            Stmt::Error(error) => error.id,
+            Stmt::Assignment { id, .. }
+            | Stmt::Return { id, .. }
+            | Stmt::If { id, .. }
+            | Stmt::While { id, .. }
+            | Stmt::For { id, .. }
+            | Stmt::Expression { id, .. }
+            | Stmt::DSLBlock { id, .. } => *id,
+        }
+    }
+
+    /// The id of a statement that does not wrap a node carrying its own.
+    pub(crate) fn own_id_mut(&mut self) -> Option<&mut NodeId> {
+        match self {
+            Stmt::VarDecl(_) | Stmt::ConstDecl(_) | Stmt::Block(_) | Stmt::Error(_) => None,
+            Stmt::Assignment { id, .. }
+            | Stmt::Return { id, .. }
+            | Stmt::If { id, .. }
+            | Stmt::While { id, .. }
+            | Stmt::For { id, .. }
+            | Stmt::Expression { id, .. }
+            | Stmt::DSLBlock { id, .. } => Some(id),
+        }
+    }
+}
+
+/// Stands in for a declaration or statement that failed to parse, so the rest
+/// of the tree survives; the diagnostic itself lives in the parser's error list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorNode {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    pub span: Span,
+    pub id: NodeId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstDecl {
+    pub visibility: Option<Visibility>,
+    pub name: String,
+    pub declared_type: super::types::Type,
+    pub initializer: Expr,
+    /// The initializer folded at compile time; `None` until the analyzer
+    /// has run (the table-driven parser leaves it unset).
+    pub value: Option<super::expr::Literal>,
+    pub line: usize,
+    pub column: usize,
+    pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -48,6 +320,8 @@ pub struct Parameter {
     pub param_type: super::types::Type,
     pub line: usize,
     pub column: usize,
+    pub span: Span,
+    pub id: NodeId,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -57,6 +331,19 @@ pub enum Visibility {
     Protected,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConcurrencyModifier {
+    Async,
+    Sync,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoopModifier {
+    Par,
+    Async,
+    Sync,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Mutability {
     Let,
@@ -1,8 +1,25 @@
 use crate::lexer::TokenType;
+use crate::parser::span::{NodeId, Span};
+use crate::parser::{Block, Type};
+
+/// An expression with the source text it was parsed from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+    pub id: NodeId,
+}
+
+impl Expr {
+    /// An expression not yet numbered by `number_nodes`.
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Expr { kind, span, id: NodeId::DUMMY }
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 #[allow(dead_code)]
-pub enum Expr {
+pub enum ExprKind {
     Literal(Literal),
     Identifier(String),
     Unary {
@@ -14,26 +31,171 @@ pub enum Expr {
         op: BinaryOp,
         right: Box<Expr>,
     },
-    Assignment {
-        name: String,
-        value: Box<Expr>,
-    },
     Grouping(Box<Expr>),
-    Block(Vec<Expr>),
+    Ternary {
+        condition: Box<Expr>,
+        then_branch: Box<Expr>,
+        else_branch: Box<Expr>,
+    },
     Call {
+        callee: Box<Expr>,
+        args: Vec<Expr>,
+    },
+    StructLiteral {
         name: String,
+        fields: Vec<(String, Expr)>,
+    },
+    ArrayLiteral(Vec<Expr>),
+    ObjectLiteral(Vec<(String, Expr)>),
+    /// `"Hello ${name}"`: literal text and embedded expressions, in order.
+    Interpolated {
+        parts: Vec<InterpolationPart>,
+    },
+    Member {
+        object: Box<Expr>,
+        member: String,
+    },
+    Index {
+        object: Box<Expr>,
+        index: Box<Expr>,
+    },
+    EnumVariant {
+        enum_name: String,
+        variant: String,
         args: Vec<Expr>,
     },
+    Match {
+        scrutinee: Box<Expr>,
+        arms: Vec<MatchArm>,
+    },
+    /// `spawn call(...)`; always wraps an `ExprKind::Call`.
+    Spawn(Box<Expr>),
+    Await(Box<Expr>),
+    /// `fn(x) => x + 1` or `fn(x: int32) -> int32 { ... }`.
+    Lambda {
+        params: Vec<LambdaParam>,
+        return_type: Option<Type>,
+        body: LambdaBody,
+        /// Local variables of the enclosing scopes the body refers to.
+        captures: Vec<Capture>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterpolationPart {
+    Literal(String),
+    Expr(Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LambdaParam {
+    pub name: String,
+    /// `None` when the type is inferred from the expected function type.
+    pub param_type: Option<Type>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LambdaBody {
+    Expr(Box<Expr>),
+    Block(Block),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capture {
+    pub name: String,
+    /// Whether the captured variable was declared with `mut`.
+    pub mutable: bool,
+    /// Whether the lambda body assigns to it.
+    pub mutated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: Expr,
+    pub span: Span,
+    pub id: NodeId,
+}
+
+/// A match pattern with the source text it was parsed from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub span: Span,
+    pub id: NodeId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternKind {
+    Wildcard,
+    Literal(Literal),
+    Binding(String),
+    Variant {
+        enum_name: String,
+        variant: String,
+        fields: Vec<Pattern>,
+    },
+}
+
+impl Pattern {
+    /// A pattern not yet numbered by `number_nodes`.
+    pub fn new(kind: PatternKind, span: Span) -> Self {
+        Pattern { kind, span, id: NodeId::DUMMY }
+    }
+
+    /// Whether the pattern matches every value.
+    pub fn is_catch_all(&self) -> bool {
+        matches!(self.kind, PatternKind::Wildcard | PatternKind::Binding(_))
+    }
+
+    /// Names the pattern binds, left to right.
+    pub fn bindings(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.collect_bindings(&mut names);
+        names
+    }
+
+    fn collect_bindings(&self, names: &mut Vec<String>) {
+        match &self.kind {
+            PatternKind::Binding(name) => names.push(name.clone()),
+            PatternKind::Variant { fields, .. } => fields.iter().for_each(|field| field.collect_bindings(names)),
+            PatternKind::Wildcard | PatternKind::Literal(_) => {}
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
-    Int(i64),
-    Float(f64),
+    /// A value and the type given by its suffix, as in `255u8`, if any.
+    Int(i128, Option<Type>),
+    Float(f64, Option<Type>),
     String(String),
+    Rune(char),
+    Byte(u8),
+    ByteString(Vec<u8>),
     Boolean(bool),
 }
 
+impl Literal {
+    /// The value of a number token, typed by its suffix.
+    pub fn number(token: &TokenType) -> Option<Literal> {
+        match token {
+            TokenType::IntLiteral(n, suffix) => Some(Literal::Int(*n as i128, suffix.map(Type::from_suffix))),
+            TokenType::FloatLiteral(f, suffix) => Some(Literal::Float(*f, suffix.map(Type::from_suffix))),
+            _ => None,
+        }
+    }
+
+    /// A number with its sign flipped, as in the pattern `-1`.
+    pub fn negated(self) -> Literal {
+        match self {
+            Literal::Int(n, ty) => Literal::Int(-n, ty),
+            Literal::Float(f, ty) => Literal::Float(-f, ty),
+            other => other,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum UnaryOp {
     Positive,
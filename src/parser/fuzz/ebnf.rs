@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+use std::fmt;
+
+/// The right-hand side of an EBNF rule.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// A quoted string, e.g. `"fn"`.
+    Terminal(String),
+    /// A reference to another rule.
+    NonTerminal(String),
+    /// `"A"…"Z"`: any character in the inclusive range.
+    Range(char, char),
+    /// `? prose ?`: text the notation can't describe.
+    Special(String),
+    Sequence(Vec<Expr>),
+    Choice(Vec<Expr>),
+    /// `[ e ]`
+    Optional(Box<Expr>),
+    /// `{ e }`
+    Repeat(Box<Expr>),
+    /// `e{n}`: exactly `n` repetitions.
+    Times(Box<Expr>, usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub name: String,
+    pub body: Expr,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EbnfError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for EbnfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EBNF error at line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for EbnfError {}
+
+/// The rules of an EBNF file; the first rule is the start symbol.
+#[derive(Debug, Clone)]
+pub struct Grammar {
+    pub rules: Vec<Rule>,
+    index: HashMap<String, usize>,
+}
+
+impl Grammar {
+    /// Reads the notation used by `docs/grammar.ebnf`: `Name = ... ;` rules
+    /// with `|`, `[ ]`, `{ }`, `( )`, quoted terminals, `"a"…"z"` ranges,
+    /// `e{n}` counts and `? ... ?` special sequences. `(* *)` comments and
+    /// `//` line comments are skipped. Every referenced rule must be defined.
+    pub fn parse(source: &str) -> Result<Grammar, EbnfError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, position: 0 };
+        let mut rules = Vec::new();
+        let mut index = HashMap::new();
+        while !parser.at_end() {
+            let rule = parser.parse_rule()?;
+            if index.insert(rule.name.clone(), rules.len()).is_some() {
+                return Err(EbnfError {
+                    message: format!("Rule '{}' is defined twice", rule.name),
+                    line: rule.line,
+                    column: 1,
+                });
+            }
+            rules.push(rule);
+        }
+        if rules.is_empty() {
+            return Err(EbnfError { message: "Grammar has no rules".to_string(), line: 1, column: 1 });
+        }
+
+        let grammar = Grammar { rules, index };
+        for rule in &grammar.rules {
+            let mut undefined = None;
+            rule.body.visit(&mut |expr| {
+                if let Expr::NonTerminal(name) = expr {
+                    if undefined.is_none() && grammar.rule(name).is_none() {
+                        undefined = Some(name.clone());
+                    }
+                }
+            });
+            if let Some(name) = undefined {
+                return Err(EbnfError {
+                    message: format!("Rule '{}' refers to undefined rule '{}'", rule.name, name),
+                    line: rule.line,
+                    column: 1,
+                });
+            }
+        }
+        Ok(grammar)
+    }
+
+    /// The language grammar in `docs/grammar.ebnf`.
+    pub fn kai() -> Result<Grammar, EbnfError> {
+        Self::parse(include_str!("../../../docs/grammar.ebnf"))
+    }
+
+    pub fn start(&self) -> &Rule {
+        &self.rules[0]
+    }
+
+    pub fn rule(&self, name: &str) -> Option<&Rule> {
+        self.index.get(name).map(|&i| &self.rules[i])
+    }
+
+    /// Every quoted terminal, in order of first appearance.
+    pub fn terminals(&self) -> Vec<&str> {
+        let mut terminals: Vec<&str> = Vec::new();
+        for rule in &self.rules {
+            rule.body.visit(&mut |expr| {
+                if let Expr::Terminal(text) = expr {
+                    if !terminals.contains(&text.as_str()) {
+                        terminals.push(text);
+                    }
+                }
+            });
+        }
+        terminals
+    }
+}
+
+impl Expr {
+    /// Calls `f` on this expression and each nested one, outermost first.
+    pub fn visit<'e>(&'e self, f: &mut impl FnMut(&'e Expr)) {
+        f(self);
+        match self {
+            Expr::Sequence(items) | Expr::Choice(items) => items.iter().for_each(|item| item.visit(f)),
+            Expr::Optional(inner) | Expr::Repeat(inner) | Expr::Times(inner, _) => inner.visit(f),
+            Expr::Terminal(_) | Expr::NonTerminal(_) | Expr::Range(..) | Expr::Special(_) => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Name(String),
+    Quoted(String),
+    Special(String),
+    Number(usize),
+    Ellipsis,
+    Punct(char),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, EbnfError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let (mut i, mut line, mut column) = (0, 1, 1);
+    let error = |message: &str, line, column| EbnfError { message: message.to_string(), line, column };
+
+    // Moves past `n` characters, keeping line and column up to date
+    let step = |i: &mut usize, line: &mut usize, column: &mut usize, n: usize| {
+        for _ in 0..n {
+            if chars.get(*i) == Some(&'\n') {
+                *line += 1;
+                *column = 1;
+            } else {
+                *column += 1;
+            }
+            *i += 1;
+        }
+    };
+
+    while i < chars.len() {
+        let (start_line, start_column) = (line, column);
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            c if c.is_whitespace() => step(&mut i, &mut line, &mut column, 1),
+            '(' if next == Some('*') => {
+                step(&mut i, &mut line, &mut column, 2);
+                loop {
+                    match chars.get(i) {
+                        None => return Err(error("Unterminated comment", start_line, start_column)),
+                        Some('*') if chars.get(i + 1) == Some(&')') => {
+                            step(&mut i, &mut line, &mut column, 2);
+                            break;
+                        }
+                        Some(_) => step(&mut i, &mut line, &mut column, 1),
+                    }
+                }
+            }
+            '/' if next == Some('/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    step(&mut i, &mut line, &mut column, 1);
+                }
+            }
+            '"' => {
+                step(&mut i, &mut line, &mut column, 1);
+                let mut text = String::new();
+                loop {
+                    match chars.get(i) {
+                        None | Some('\n') => return Err(error("Unterminated string", start_line, start_column)),
+                        Some('"') => break,
+                        Some('\\') if i + 1 < chars.len() => {
+                            text.push(chars[i + 1]);
+                            step(&mut i, &mut line, &mut column, 2);
+                        }
+                        Some(&c) => {
+                            text.push(c);
+                            step(&mut i, &mut line, &mut column, 1);
+                        }
+                    }
+                }
+                step(&mut i, &mut line, &mut column, 1);
+                tokens.push(Token { kind: TokenKind::Quoted(text), line: start_line, column: start_column });
+            }
+            '?' => {
+                step(&mut i, &mut line, &mut column, 1);
+                let mut text = String::new();
+                loop {
+                    match chars.get(i) {
+                        None => return Err(error("Unterminated special sequence", start_line, start_column)),
+                        Some('?') => break,
+                        Some(&c) => {
+                            text.push(c);
+                            step(&mut i, &mut line, &mut column, 1);
+                        }
+                    }
+                }
+                step(&mut i, &mut line, &mut column, 1);
+                let text = text.trim().to_string();
+                tokens.push(Token { kind: TokenKind::Special(text), line: start_line, column: start_column });
+            }
+            '…' => {
+                step(&mut i, &mut line, &mut column, 1);
+                tokens.push(Token { kind: TokenKind::Ellipsis, line: start_line, column: start_column });
+            }
+            '.' if next == Some('.') && chars.get(i + 2) == Some(&'.') => {
+                step(&mut i, &mut line, &mut column, 3);
+                tokens.push(Token { kind: TokenKind::Ellipsis, line: start_line, column: start_column });
+            }
+            '=' | ';' | '|' | '[' | ']' | '{' | '}' | '(' | ')' => {
+                step(&mut i, &mut line, &mut column, 1);
+                tokens.push(Token { kind: TokenKind::Punct(c), line: start_line, column: start_column });
+            }
+            c if c.is_ascii_digit() => {
+                let mut value = 0usize;
+                while let Some(digit) = chars.get(i).and_then(|c| c.to_digit(10)) {
+                    value = value.saturating_mul(10).saturating_add(digit as usize);
+                    step(&mut i, &mut line, &mut column, 1);
+                }
+                tokens.push(Token { kind: TokenKind::Number(value), line: start_line, column: start_column });
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut name = String::new();
+                while let Some(&c) = chars.get(i).filter(|c| c.is_alphanumeric() || **c == '_') {
+                    name.push(c);
+                    step(&mut i, &mut line, &mut column, 1);
+                }
+                tokens.push(Token { kind: TokenKind::Name(name), line: start_line, column: start_column });
+            }
+            c => return Err(error(&format!("Unexpected character '{}'", c), line, column)),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn at_end(&self) -> bool {
+        self.position >= self.tokens.len()
+    }
+
+    fn peek(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.position).map(|t| &t.kind)
+    }
+
+    fn error(&self, message: impl Into<String>) -> EbnfError {
+        let (line, column) = match self.tokens.get(self.position).or(self.tokens.last()) {
+            Some(token) => (token.line, token.column),
+            None => (1, 1),
+        };
+        EbnfError { message: message.into(), line, column }
+    }
+
+    fn expect_punct(&mut self, punct: char) -> Result<(), EbnfError> {
+        if self.peek() == Some(&TokenKind::Punct(punct)) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected '{}'", punct)))
+        }
+    }
+
+    fn parse_rule(&mut self) -> Result<Rule, EbnfError> {
+        let (name, line) = match self.tokens.get(self.position) {
+            Some(Token { kind: TokenKind::Name(name), line, .. }) => (name.clone(), *line),
+            _ => return Err(self.error("Expected a rule name")),
+        };
+        self.position += 1;
+        self.expect_punct('=')?;
+        let body = self.parse_choice()?;
+        self.expect_punct(';')?;
+        Ok(Rule { name, body, line })
+    }
+
+    fn parse_choice(&mut self) -> Result<Expr, EbnfError> {
+        let mut alternatives = vec![self.parse_sequence()?];
+        while self.peek() == Some(&TokenKind::Punct('|')) {
+            self.position += 1;
+            alternatives.push(self.parse_sequence()?);
+        }
+        Ok(if alternatives.len() == 1 { alternatives.remove(0) } else { Expr::Choice(alternatives) })
+    }
+
+    fn parse_sequence(&mut self) -> Result<Expr, EbnfError> {
+        let mut items = Vec::new();
+        while let Some(kind) = self.peek() {
+            if matches!(kind, TokenKind::Punct('|' | ';' | ']' | '}' | ')')) {
+                break;
+            }
+            items.push(self.parse_postfix()?);
+        }
+        Ok(if items.len() == 1 { items.remove(0) } else { Expr::Sequence(items) })
+    }
+
+    fn parse_postfix(&mut self) -> Result<Expr, EbnfError> {
+        let expr = self.parse_primary()?;
+        if self.peek() == Some(&TokenKind::Punct('{')) {
+            if let Some(TokenKind::Number(count)) = self.tokens.get(self.position + 1).map(|t| &t.kind) {
+                let count = *count;
+                self.position += 2;
+                self.expect_punct('}')?;
+                return Ok(Expr::Times(Box::new(expr), count));
+            }
+        }
+        Ok(expr)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, EbnfError> {
+        let Some(kind) = self.peek().cloned() else {
+            return Err(self.error("Unexpected end of grammar"));
+        };
+        self.position += 1;
+        match kind {
+            TokenKind::Name(name) => Ok(Expr::NonTerminal(name)),
+            TokenKind::Special(text) => Ok(Expr::Special(text)),
+            TokenKind::Quoted(text) if self.peek() == Some(&TokenKind::Ellipsis) => {
+                self.position += 1;
+                let end = match self.peek() {
+                    Some(TokenKind::Quoted(end)) => end.clone(),
+                    _ => return Err(self.error("Expected a quoted character after '…'")),
+                };
+                let (mut first, mut last) = (text.chars(), end.chars());
+                match (first.next(), first.next(), last.next(), last.next()) {
+                    (Some(from), None, Some(to), None) if from <= to => {
+                        self.position += 1;
+                        Ok(Expr::Range(from, to))
+                    }
+                    _ => Err(self.error("A range needs two single characters in order")),
+                }
+            }
+            TokenKind::Quoted(text) => Ok(Expr::Terminal(text)),
+            TokenKind::Punct(open @ ('(' | '[' | '{')) => {
+                let inner = self.parse_choice()?;
+                let close = match open {
+                    '(' => ')',
+                    '[' => ']',
+                    _ => '}',
+                };
+                self.expect_punct(close)?;
+                Ok(match open {
+                    '(' => inner,
+                    '[' => Expr::Optional(Box::new(inner)),
+                    _ => Expr::Repeat(Box::new(inner)),
+                })
+            }
+            _ => {
+                self.position -= 1;
+                Err(self.error("Expected a terminal, rule name or group"))
+            }
+        }
+    }
+}
@@ -0,0 +1,534 @@
+use std::collections::{HashMap, HashSet};
+
+use super::ebnf::{EbnfError, Expr, Grammar};
+
+/// Rules whose expansion forms a single token, written without spaces.
+const TOKEN_RULES: &[&str] = &[
+    "Identifier", "IntLiteral", "FloatLiteral", "StringLiteral", "InterpolatedString",
+    "RuneLiteral", "ByteLiteral", "ByteStringLiteral",
+];
+
+/// Context rules the grammar only states in comments: inside `within`,
+/// choosing one of `excluded` is not allowed until `lifted_by` happens.
+struct Restriction {
+    within: &'static [&'static str],
+    excluded: &'static [&'static str],
+    lifted_by: Lift,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Lift {
+    /// Any bracket opened inside the rule, e.g. `if (Point { x: 1 }.x) {}`.
+    Bracket,
+    /// The first token of the rule.
+    FirstToken,
+}
+
+const RESTRICTIONS: &[Restriction] = &[
+    // `{` opens the body of these
+    Restriction {
+        within: &["IfStmt", "WhileStmt", "ForStmt", "MatchExpr"],
+        excluded: &["StructLiteral", "ObjectLiteral"],
+        lifted_by: Lift::Bracket,
+    },
+    // A statement starting with `{` is a block
+    Restriction {
+        within: &["ExpressionStmt", "Assignment"],
+        excluded: &["ObjectLiteral"],
+        lifted_by: Lift::FirstToken,
+    },
+];
+
+const OPENING_BRACKETS: &[&str] = &["(", "[", "{", "${"];
+
+/// Produces the text of a `? ... ?` special sequence.
+type SpecialProducer = fn(&mut Rng) -> String;
+
+/// A small deterministic generator (SplitMix64), so a seed reproduces the
+/// same program on every platform.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`; `n` must not be zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.next_u64() % denominator < numerator
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+/// A generated program as the tokens it was written from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub tokens: Vec<String>,
+}
+
+impl Sample {
+    /// The program text: tokens separated by spaces, with a line break after
+    /// each `;`, `{` and `}`.
+    pub fn source(&self) -> String {
+        let mut text = String::new();
+        for token in &self.tokens {
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push(' ');
+            }
+            text.push_str(token);
+            if matches!(token.as_str(), ";" | "{" | "}") {
+                text.push('\n');
+            }
+        }
+        text
+    }
+}
+
+/// How `Generator::mutate` broke a program.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mutation {
+    DeleteToken(usize),
+    DuplicateToken(usize),
+    SwapTokens(usize),
+    InsertToken(usize, String),
+    ReplaceToken(usize, String),
+    Truncate(usize),
+    DeleteChar(usize),
+    InsertChar(usize, char),
+}
+
+/// Characters that don't appear in valid programs or that need care, for
+/// `Mutation::InsertChar`.
+const NOISE: &[char] = &['"', '\\', '$', '{', '}', '(', ')', '[', ']', '&', '|', '@', '#', '\'', '.', ';', '-', '0', '\n', 'é'];
+
+/// Random sentences of an EBNF grammar, for fuzzing the lexer and parsers.
+///
+/// Each rule is expanded within a depth budget: alternatives that can't
+/// finish within what is left are skipped, and once a program reaches its
+/// token budget every rule is finished the shortest way. Generation always
+/// terminates and the same seed always gives the same program.
+pub struct Generator<'g> {
+    grammar: &'g Grammar,
+    max_depth: usize,
+    max_repeat: usize,
+    max_tokens: usize,
+    /// Fewest nested rules needed to finish each rule.
+    heights: HashMap<&'g str, usize>,
+    /// Rules reachable from the start symbol without entering a token rule.
+    syntactic: HashSet<&'g str>,
+    /// Word terminals, which identifiers must not collide with.
+    keywords: HashSet<&'g str>,
+    specials: HashMap<&'g str, SpecialProducer>,
+}
+
+impl<'g> Generator<'g> {
+    /// A generator for `grammar`. Special sequences are only understood in
+    /// the rules of `docs/grammar.ebnf` that use them.
+    pub fn new(grammar: &'g Grammar) -> Result<Self, EbnfError> {
+        let mut generator = Generator {
+            grammar,
+            max_depth: 40,
+            max_repeat: 3,
+            max_tokens: 150,
+            heights: HashMap::new(),
+            syntactic: HashSet::new(),
+            keywords: grammar
+                .terminals()
+                .into_iter()
+                .filter(|t| t.chars().next().is_some_and(|c| c.is_alphabetic()))
+                .collect(),
+            specials: HashMap::new(),
+        };
+        for (name, producer) in [
+            ("Character", string_character as SpecialProducer),
+            ("DSLToken", dsl_character as SpecialProducer),
+            ("UnicodeEscape", unicode_escape as SpecialProducer),
+            ("RawChar", raw_character as SpecialProducer),
+            ("LineBreak", line_break as SpecialProducer),
+            ("RuneChar", rune_character as SpecialProducer),
+            ("ByteChar", byte_character as SpecialProducer),
+            ("ExponentDigits", exponent_digits as SpecialProducer),
+        ] {
+            if let Some(rule) = grammar.rule(name) {
+                generator.specials.insert(rule.name.as_str(), producer);
+            }
+        }
+        for rule in &grammar.rules {
+            let mut special = false;
+            rule.body.visit(&mut |expr| special |= matches!(expr, Expr::Special(_)));
+            if special && !generator.specials.contains_key(rule.name.as_str()) {
+                return Err(EbnfError {
+                    message: format!("No producer for the special sequence in rule '{}'", rule.name),
+                    line: rule.line,
+                    column: 1,
+                });
+            }
+        }
+        generator.compute_heights();
+        generator.compute_syntactic();
+        Ok(generator)
+    }
+
+    /// How many rules deep a program may nest; shallow rules always fit.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// The most repetitions generated for a `{ ... }`.
+    pub fn with_max_repeat(mut self, max_repeat: usize) -> Self {
+        self.max_repeat = max_repeat;
+        self
+    }
+
+    /// Roughly how long programs get: past this many tokens, every rule is
+    /// finished the shortest way.
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// A random program: a sentence of the grammar's start rule.
+    pub fn program(&self, seed: u64) -> Sample {
+        self.sentence(&self.grammar.start().name, seed)
+    }
+
+    /// A random sentence of `rule`.
+    pub fn sentence(&self, rule: &str, seed: u64) -> Sample {
+        let mut expansion = Expansion {
+            rng: Rng::new(seed),
+            tokens: Vec::new(),
+            lexeme: None,
+            restriction: None,
+        };
+        self.expand(&Expr::NonTerminal(rule.to_string()), self.max_depth, &mut expansion);
+        Sample { tokens: expansion.tokens }
+    }
+
+    /// Breaks `sample` with one random edit. The result is usually, but not
+    /// always, an invalid program.
+    pub fn mutate(&self, sample: &Sample, seed: u64) -> (String, Mutation) {
+        let mut rng = Rng::new(seed);
+        let mut tokens = sample.tokens.clone();
+        let terminals = self.grammar.terminals();
+        let position = |rng: &mut Rng, len: usize| if len == 0 { 0 } else { rng.below(len) };
+
+        let mutation = match rng.below(8) {
+            0 if !tokens.is_empty() => Mutation::DeleteToken(position(&mut rng, tokens.len())),
+            1 if !tokens.is_empty() => Mutation::DuplicateToken(position(&mut rng, tokens.len())),
+            2 if tokens.len() > 1 => Mutation::SwapTokens(position(&mut rng, tokens.len() - 1)),
+            3 => Mutation::InsertToken(position(&mut rng, tokens.len() + 1), rng.pick(&terminals).to_string()),
+            4 if !tokens.is_empty() => {
+                Mutation::ReplaceToken(position(&mut rng, tokens.len()), rng.pick(&terminals).to_string())
+            }
+            5 if !tokens.is_empty() => Mutation::Truncate(position(&mut rng, tokens.len())),
+            6 => {
+                let len = sample.source().chars().count();
+                Mutation::DeleteChar(position(&mut rng, len))
+            }
+            _ => {
+                let len = sample.source().chars().count();
+                Mutation::InsertChar(position(&mut rng, len + 1), *rng.pick(NOISE))
+            }
+        };
+
+        let source = match &mutation {
+            Mutation::DeleteToken(i) => {
+                tokens.remove(*i);
+                Sample { tokens }.source()
+            }
+            Mutation::DuplicateToken(i) => {
+                tokens.insert(*i, tokens[*i].clone());
+                Sample { tokens }.source()
+            }
+            Mutation::SwapTokens(i) => {
+                tokens.swap(*i, *i + 1);
+                Sample { tokens }.source()
+            }
+            Mutation::InsertToken(i, token) => {
+                tokens.insert(*i, token.clone());
+                Sample { tokens }.source()
+            }
+            Mutation::ReplaceToken(i, token) => {
+                tokens[*i] = token.clone();
+                Sample { tokens }.source()
+            }
+            Mutation::Truncate(len) => {
+                tokens.truncate(*len);
+                Sample { tokens }.source()
+            }
+            Mutation::DeleteChar(i) => {
+                let mut chars: Vec<char> = sample.source().chars().collect();
+                if *i < chars.len() {
+                    chars.remove(*i);
+                }
+                chars.into_iter().collect()
+            }
+            Mutation::InsertChar(i, c) => {
+                let mut chars: Vec<char> = sample.source().chars().collect();
+                chars.insert(*i, *c);
+                chars.into_iter().collect()
+            }
+        };
+        (source, mutation)
+    }
+
+    fn expand(&self, expr: &Expr, budget: usize, out: &mut Expansion) {
+        match expr {
+            Expr::Terminal(text) => out.emit(text),
+            Expr::Range(from, to) => {
+                let span = *to as u32 - *from as u32 + 1;
+                let offset = out.rng.below(span as usize) as u32;
+                let c = char::from_u32(*from as u32 + offset).unwrap_or(*from);
+                out.emit(&c.to_string());
+            }
+            Expr::Special(_) => unreachable!("special sequences are expanded with their rule"),
+            Expr::NonTerminal(name) => self.expand_rule(name, budget, out),
+            Expr::Sequence(items) => {
+                let saved = out.restriction;
+                for item in items {
+                    self.expand(item, budget, out);
+                    if out.restriction.is_some_and(|r| r.lifted_by == Lift::Bracket)
+                        && matches!(item, Expr::Terminal(t) if OPENING_BRACKETS.contains(&t.as_str()))
+                    {
+                        out.restriction = None;
+                    }
+                }
+                out.restriction = saved;
+            }
+            Expr::Choice(alternatives) => {
+                let allowed: Vec<&Expr> = alternatives.iter().filter(|alt| !out.excludes(alt)).collect();
+                let fitting: Vec<&Expr> = allowed
+                    .iter()
+                    .copied()
+                    .filter(|alt| self.height(alt) <= budget && !self.exhausted(out))
+                    .collect();
+                let chosen = if fitting.is_empty() {
+                    allowed.iter().copied().min_by_key(|alt| self.height(alt))
+                } else {
+                    Some(*out.rng.pick(&fitting))
+                };
+                let chosen = chosen.expect("every alternative of a choice is excluded");
+                self.expand(chosen, budget, out);
+            }
+            Expr::Optional(inner) => {
+                if self.height(inner) <= budget && !self.exhausted(out) && out.rng.chance(1, 2) {
+                    self.expand(inner, budget, out);
+                }
+            }
+            Expr::Repeat(inner) => {
+                if self.height(inner) <= budget {
+                    let count = out.rng.below(self.max_repeat + 1);
+                    for _ in 0..count {
+                        if self.exhausted(out) {
+                            break;
+                        }
+                        self.expand(inner, budget, out);
+                    }
+                }
+            }
+            Expr::Times(inner, count) => {
+                for _ in 0..*count {
+                    self.expand(inner, budget, out);
+                }
+            }
+        }
+    }
+
+    fn expand_rule(&self, name: &str, budget: usize, out: &mut Expansion) {
+        let rule = self.grammar.rule(name).expect("rules are checked when the grammar is read");
+        let budget = budget.saturating_sub(1);
+
+        let saved_restriction = out.restriction;
+        if let Some(restriction) = RESTRICTIONS.iter().find(|r| r.within.contains(&name)) {
+            out.restriction = Some(ActiveRestriction {
+                excluded: restriction.excluded,
+                lifted_by: restriction.lifted_by,
+                since: out.tokens.len(),
+            });
+        }
+
+        if let Some(producer) = self.specials.get(name) {
+            let text = producer(&mut out.rng);
+            out.emit(&text);
+        } else if self.syntactic.contains(name) {
+            // e.g. the expression of an interpolation inside a string
+            let outer = out.lexeme.take();
+            let start = out.tokens.len();
+            self.expand(&rule.body, budget, out);
+            if let Some(mut lexeme) = outer {
+                let inner = out.tokens.split_off(start);
+                lexeme.push_str(&inner.join(" "));
+                out.lexeme = Some(lexeme);
+            }
+        } else if out.lexeme.is_some() {
+            self.expand(&rule.body, budget, out);
+        } else {
+            out.lexeme = Some(String::new());
+            self.expand(&rule.body, budget, out);
+            let mut lexeme = out.lexeme.take().unwrap_or_default();
+            if name == "Identifier" && self.keywords.contains(lexeme.as_str()) {
+                lexeme.push('_');
+            }
+            out.tokens.push(lexeme);
+        }
+
+        out.restriction = saved_restriction;
+    }
+
+    fn exhausted(&self, out: &Expansion) -> bool {
+        out.lexeme.is_none() && out.tokens.len() >= self.max_tokens
+    }
+
+    fn height(&self, expr: &Expr) -> usize {
+        height_with(&self.heights, expr)
+    }
+
+    fn compute_heights(&mut self) {
+        // Fixpoint from "infinitely deep": a rule's height only ever drops
+        let mut heights: HashMap<&'g str, usize> =
+            self.grammar.rules.iter().map(|r| (r.name.as_str(), usize::MAX)).collect();
+        loop {
+            let mut changed = false;
+            for rule in &self.grammar.rules {
+                let height = height_with(&heights, &rule.body);
+                if height < heights[rule.name.as_str()] {
+                    heights.insert(rule.name.as_str(), height);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        self.heights = heights;
+    }
+
+    fn compute_syntactic(&mut self) {
+        let mut pending = vec![self.grammar.start().name.as_str()];
+        while let Some(name) = pending.pop() {
+            if TOKEN_RULES.contains(&name) || !self.syntactic.insert(name) {
+                continue;
+            }
+            let rule = self.grammar.rule(name).expect("rules are checked when the grammar is read");
+            rule.body.visit(&mut |expr| {
+                if let Expr::NonTerminal(next) = expr {
+                    pending.push(next);
+                }
+            });
+        }
+    }
+}
+
+fn height_with(heights: &HashMap<&str, usize>, expr: &Expr) -> usize {
+    match expr {
+        Expr::Terminal(_) | Expr::Range(..) | Expr::Special(_) | Expr::Optional(_) | Expr::Repeat(_) => 0,
+        Expr::NonTerminal(name) => heights.get(name.as_str()).copied().unwrap_or(usize::MAX).saturating_add(1),
+        Expr::Sequence(items) => items.iter().map(|item| height_with(heights, item)).max().unwrap_or(0),
+        Expr::Choice(alternatives) => {
+            alternatives.iter().map(|alt| height_with(heights, alt)).min().unwrap_or(usize::MAX)
+        }
+        Expr::Times(inner, _) => height_with(heights, inner),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ActiveRestriction {
+    excluded: &'static [&'static str],
+    lifted_by: Lift,
+    /// Number of tokens emitted when the restriction started.
+    since: usize,
+}
+
+struct Expansion {
+    rng: Rng,
+    tokens: Vec<String>,
+    /// The token being spelled out by a token rule.
+    lexeme: Option<String>,
+    restriction: Option<ActiveRestriction>,
+}
+
+impl Expansion {
+    fn emit(&mut self, text: &str) {
+        match &mut self.lexeme {
+            Some(lexeme) => lexeme.push_str(text),
+            None => self.tokens.push(text.to_string()),
+        }
+    }
+
+    fn excludes(&self, alternative: &Expr) -> bool {
+        let Some(restriction) = self.restriction else { return false };
+        let Expr::NonTerminal(name) = alternative else { return false };
+        restriction.excluded.contains(&name.as_str())
+            && (restriction.lifted_by == Lift::Bracket || self.tokens.len() == restriction.since)
+    }
+}
+
+/// A string character with no special meaning to the lexer.
+fn string_character(rng: &mut Rng) -> String {
+    const CHARACTERS: &[char] = &['a', 'Z', '0', ' ', '_', '.', ',', ':', ';', '+', '=', '(', ')', '<', '/', 'ß', '→'];
+    rng.pick(CHARACTERS).to_string()
+}
+
+/// A character of DSL block content; quotes and braces would need balancing.
+fn dsl_character(rng: &mut Rng) -> String {
+    const CHARACTERS: &[char] = &['a', 'Z', '0', ' ', '*', '=', ',', '.', ';', '<', '>', '(', ')', ':', '-'];
+    rng.pick(CHARACTERS).to_string()
+}
+
+/// A raw string character. No `#`, which could end a `r#"` string early
+/// after a quote; backslashes and `$` are chosen by the grammar.
+fn raw_character(rng: &mut Rng) -> String {
+    const CHARACTERS: &[char] = &['a', 'Z', '0', ' ', '{', '}', '.', '\'', 'ß'];
+    rng.pick(CHARACTERS).to_string()
+}
+
+fn line_break(rng: &mut Rng) -> String {
+    if rng.chance(1, 4) { "\r\n" } else { "\n" }.to_string()
+}
+
+fn rune_character(rng: &mut Rng) -> String {
+    const CHARACTERS: &[char] = &['a', 'Z', '0', ' ', '$', '{', '}', '.', 'ß', '→', '😀'];
+    rng.pick(CHARACTERS).to_string()
+}
+
+fn byte_character(rng: &mut Rng) -> String {
+    const CHARACTERS: &[char] = &['a', 'Z', '0', ' ', '$', '{', '}', '.', ';', '/', '~'];
+    rng.pick(CHARACTERS).to_string()
+}
+
+/// Four hex digits naming a Unicode scalar value, or up to six in braces.
+fn unicode_escape(rng: &mut Rng) -> String {
+    let value = loop {
+        let value = rng.below(0x1_0000) as u32;
+        if !(0xD800..=0xDFFF).contains(&value) {
+            break value;
+        }
+    };
+    match rng.below(3) {
+        0 => format!("{:04X}", value),
+        1 => format!("{:04x}", value),
+        _ => format!("{{{:X}}}", value),
+    }
+}
+
+/// An exponent small enough that no generated float overflows.
+fn exponent_digits(rng: &mut Rng) -> String {
+    rng.below(100).to_string()
+}
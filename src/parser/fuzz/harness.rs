@@ -0,0 +1,86 @@
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::parser::lalr::LALRParser;
+use crate::pipeline::{self, Diagnostic, SourceFile, Stage};
+
+/// Why a program failed a fuzzing check.
+#[derive(Debug, Clone)]
+pub enum FuzzFailure {
+    /// The lexer or one of the parsers panicked.
+    Panic { stage: &'static str, message: String },
+    /// A program generated from the grammar was rejected.
+    Rejected { stage: &'static str, errors: Vec<String> },
+}
+
+impl fmt::Display for FuzzFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FuzzFailure::Panic { stage, message } => write!(f, "{} panicked: {}", stage, message),
+            FuzzFailure::Rejected { stage, errors } => {
+                write!(f, "{} rejected a valid program:\n  {}", stage, errors.join("\n  "))
+            }
+        }
+    }
+}
+
+/// Lexes and parses `source` with both parsers, requiring that nothing
+/// panics. Any diagnostics are fine.
+pub fn check_no_panic(source: &str) -> Result<(), FuzzFailure> {
+    run(source).map(|_| ())
+}
+
+/// Like `check_no_panic`, and the program must lex and parse without
+/// errors. Semantic analysis is not run: generated programs use names they
+/// never declare.
+pub fn check_valid(source: &str) -> Result<(), FuzzFailure> {
+    let outcome = run(source)?;
+    let rejection = |stage, diagnostics: &[Diagnostic]| FuzzFailure::Rejected {
+        stage,
+        errors: diagnostics.iter().map(ToString::to_string).collect(),
+    };
+
+    let lex_errors: Vec<Diagnostic> = outcome.diagnostics.iter().filter(|d| d.stage == Stage::Lex).cloned().collect();
+    if !lex_errors.is_empty() {
+        return Err(rejection("lexer", &lex_errors));
+    }
+    let syntax_errors: Vec<Diagnostic> =
+        outcome.diagnostics.iter().filter(|d| d.stage == Stage::Syntax).cloned().collect();
+    if !syntax_errors.is_empty() {
+        return Err(rejection("recursive descent parser", &syntax_errors));
+    }
+    match outcome.lalr {
+        Some(Err(err)) => Err(FuzzFailure::Rejected { stage: "LALR parser", errors: vec![err.to_string()] }),
+        _ => Ok(()),
+    }
+}
+
+struct Outcome {
+    diagnostics: Vec<Diagnostic>,
+    /// `None` when the program didn't lex.
+    lalr: Option<Result<(), crate::parser::recursive_descent::errors::ParseError>>,
+}
+
+fn run(source: &str) -> Result<Outcome, FuzzFailure> {
+    let file = SourceFile::new("fuzz.kai", source);
+    let lexed = catch("lexer", || pipeline::lex(&file))?;
+    let parsed = catch("recursive descent parser", || pipeline::parse(&file))?;
+    let lalr = if lexed.diagnostics.is_empty() {
+        let result = catch("LALR parser", || LALRParser::new(lexed.tokens).parse_program())?;
+        Some(result.map(|_| ()))
+    } else {
+        None
+    };
+    Ok(Outcome { diagnostics: parsed.diagnostics, lalr })
+}
+
+fn catch<T>(stage: &'static str, f: impl FnOnce() -> T) -> Result<T, FuzzFailure> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "non-string panic payload".to_string());
+        FuzzFailure::Panic { stage, message }
+    })
+}
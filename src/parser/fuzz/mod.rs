@@ -0,0 +1,22 @@
+//! Grammar-driven fuzzing: random programs generated from
+//! `docs/grammar.ebnf`, mutated copies of them, and checks that the lexer
+//! and parsers neither panic nor reject valid programs.
+//!
+//! With proptest, draw a seed and check the program it gives:
+//!
+//! ```
+//! use flux_core::parser::fuzz::{check_valid, Generator, Grammar};
+//!
+//! let grammar = Grammar::kai().unwrap();
+//! let generator = Generator::new(&grammar).unwrap();
+//! let program = generator.program(7);
+//! check_valid(&program.source()).unwrap();
+//! ```
+
+pub mod ebnf;
+pub mod generator;
+pub mod harness;
+
+pub use ebnf::{EbnfError, Expr, Grammar, Rule};
+pub use generator::{Generator, Mutation, Rng, Sample};
+pub use harness::{check_no_panic, check_valid, FuzzFailure};
@@ -1,47 +1,732 @@
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+use std::fmt;
+
+use crate::lexer::token::TokenType;
+
+/// Which `{`-started constructs an expression may contain, mirroring the
+/// recursive descent parser's `allow_struct_literals` flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExprMode {
+    /// Anywhere a struct or object literal may appear.
+    Full,
+    /// `if`/`while`/`for` headers and `match` scrutinees, where `{` opens the body.
+    NoStruct,
+    /// Expression statements: a leading `{` is a block, not an object literal.
+    Leading,
+}
+
+impl ExprMode {
+    /// The mode of operands and other sub-expressions right of the start.
+    pub fn operand(self) -> ExprMode {
+        match self {
+            ExprMode::NoStruct => ExprMode::NoStruct,
+            ExprMode::Full | ExprMode::Leading => ExprMode::Full,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum NonTerminal {
     Start,
-    Program,   
-    VarDecl,
+    Program,
+    Declarations,
+    Declaration,
+    Import,
+    ModulePath,
     Visibility,
+    Concurrency,
+    LoopModifier,
     Mutability,
+    VarDecl,
+    TypeAnnotation,
+    Initializer,
+    ConstDecl,
+    FnDecl,
+    TypeParams,
+    TypeParamList,
+    Params,
+    ParamList,
+    Param,
+    ReturnType,
+    StructDecl,
+    Members,
+    FieldDecl,
+    EnumDecl,
+    Variants,
+    Variant,
+    TypeAlias,
+    Block,
+    Statements,
+    Statement,
+    IfStmt,
+    Else,
     Type,
-    Expr,
-    Literal,
+    OptionalType,
+    TypeAtom,
+    TypeName,
+    TypeList,
+    TypeItems,
+    TypeArgs,
+    Expr(ExprMode),
+    Ternary(ExprMode),
+    Or(ExprMode),
+    And(ExprMode),
+    Equality(ExprMode),
+    Relational(ExprMode),
+    Additive(ExprMode),
+    Multiplicative(ExprMode),
+    Unary(ExprMode),
+    Postfix(ExprMode),
+    Primary(ExprMode),
+    Args,
+    ArgList,
+    FieldInits,
+    FieldInitList,
+    FieldInit,
+    Properties,
+    PropertyList,
+    Property,
+    LambdaHead,
+    LambdaParams,
+    LambdaParamList,
+    LambdaParam,
+    MatchArms,
+    MatchArmList,
+    MatchArm,
+    Pattern,
+    PatternPath,
+    Patterns,
+    PatternList,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Symbol {
     Terminal(SymbolToken),
     NonTerminal(NonTerminal),
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+impl From<SymbolToken> for Symbol {
+    fn from(token: SymbolToken) -> Self {
+        Symbol::Terminal(token)
+    }
+}
+
+impl From<NonTerminal> for Symbol {
+    fn from(nt: NonTerminal) -> Self {
+        Symbol::NonTerminal(nt)
+    }
+}
+
+/// Terminals of the grammar: token kinds with their payloads stripped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum SymbolToken {
+    // Literals
+    IntLiteral, FloatLiteral, StringLiteral, InterpolatedString,
+    RuneLiteral, ByteLiteral, ByteStringLiteral, BooleanLiteral,
+    Identifier,
+
     // Keywords
+    Use, Struct, Enum, Type, Let, Mut, Const, Fn,
+    If, Else, While, For, In, Return, Match, Async, Sync, Par, Spawn, Await,
     Public, Private, Protected,
-    Let, Mut, Const,
-    
+    Dsl,
+
     // Operators
-    Equal, Semicolon, Colon,
-    
-    // Literals
-    IntLiteral,
-    FloatLiteral,
-    StringLiteral,
-    BooleanLiteral,
-    
-    // Identifiers
-    Identifier,
-    TypeName(String),
-    
+    Plus, Minus, Star, Slash, Percent,
+    Equal, EqualEqual, NotEqual, Less, Greater, LessEqual, GreaterEqual,
+    And, Or, Not,
+    Question, Colon, Arrow, FatArrow,
+
+    // Delimiters
+    LeftParen, RightParen, LeftBrace, RightBrace, LeftBracket, RightBracket,
+    Semicolon, Comma, Dot,
+
     // Special
     Eof,
+    DslContent,
+    /// Text the lexer couldn't make a token of; no production accepts it.
+    Error,
+}
+
+impl SymbolToken {
+    pub const ALL: [SymbolToken; 64] = {
+        use SymbolToken::*;
+        [
+            IntLiteral, FloatLiteral, StringLiteral, InterpolatedString,
+            RuneLiteral, ByteLiteral, ByteStringLiteral, BooleanLiteral, Identifier,
+            Use, Struct, Enum, Type, Let, Mut, Const, Fn,
+            If, Else, While, For, In, Return, Match, Async, Sync, Par, Spawn, Await,
+            Public, Private, Protected, Dsl,
+            Plus, Minus, Star, Slash, Percent,
+            Equal, EqualEqual, NotEqual, Less, Greater, LessEqual, GreaterEqual,
+            And, Or, Not, Question, Colon, Arrow, FatArrow,
+            LeftParen, RightParen, LeftBrace, RightBrace, LeftBracket, RightBracket,
+            Semicolon, Comma, Dot, Eof, DslContent, Error,
+        ]
+    };
+
+    pub fn of(token: &TokenType) -> SymbolToken {
+        match token {
+            TokenType::IntLiteral(..) => SymbolToken::IntLiteral,
+            TokenType::FloatLiteral(..) => SymbolToken::FloatLiteral,
+            TokenType::StringLiteral(_) => SymbolToken::StringLiteral,
+            TokenType::InterpolatedString(_) => SymbolToken::InterpolatedString,
+            TokenType::RuneLiteral(_) => SymbolToken::RuneLiteral,
+            TokenType::ByteLiteral(_) => SymbolToken::ByteLiteral,
+            TokenType::ByteStringLiteral(_) => SymbolToken::ByteStringLiteral,
+            TokenType::BooleanLiteral(_) => SymbolToken::BooleanLiteral,
+            TokenType::Identifier(_) => SymbolToken::Identifier,
+            TokenType::Use => SymbolToken::Use,
+            TokenType::Struct => SymbolToken::Struct,
+            TokenType::Enum => SymbolToken::Enum,
+            TokenType::Type => SymbolToken::Type,
+            TokenType::Let => SymbolToken::Let,
+            TokenType::Mut => SymbolToken::Mut,
+            TokenType::Const => SymbolToken::Const,
+            TokenType::Fn => SymbolToken::Fn,
+            TokenType::If => SymbolToken::If,
+            TokenType::Else => SymbolToken::Else,
+            TokenType::While => SymbolToken::While,
+            TokenType::For => SymbolToken::For,
+            TokenType::In => SymbolToken::In,
+            TokenType::Return => SymbolToken::Return,
+            TokenType::Match => SymbolToken::Match,
+            TokenType::Async => SymbolToken::Async,
+            TokenType::Sync => SymbolToken::Sync,
+            TokenType::Par => SymbolToken::Par,
+            TokenType::Spawn => SymbolToken::Spawn,
+            TokenType::Await => SymbolToken::Await,
+            TokenType::Public => SymbolToken::Public,
+            TokenType::Private => SymbolToken::Private,
+            TokenType::Protected => SymbolToken::Protected,
+            TokenType::DSL(_) => SymbolToken::Dsl,
+            TokenType::Plus => SymbolToken::Plus,
+            TokenType::Minus => SymbolToken::Minus,
+            TokenType::Star => SymbolToken::Star,
+            TokenType::Slash => SymbolToken::Slash,
+            TokenType::Percent => SymbolToken::Percent,
+            TokenType::Equal => SymbolToken::Equal,
+            TokenType::EqualEqual => SymbolToken::EqualEqual,
+            TokenType::NotEqual => SymbolToken::NotEqual,
+            TokenType::Less => SymbolToken::Less,
+            TokenType::Greater => SymbolToken::Greater,
+            TokenType::LessEqual => SymbolToken::LessEqual,
+            TokenType::GreaterEqual => SymbolToken::GreaterEqual,
+            TokenType::And => SymbolToken::And,
+            TokenType::Or => SymbolToken::Or,
+            TokenType::Not => SymbolToken::Not,
+            TokenType::Question => SymbolToken::Question,
+            TokenType::Colon => SymbolToken::Colon,
+            TokenType::Arrow => SymbolToken::Arrow,
+            TokenType::FatArrow => SymbolToken::FatArrow,
+            TokenType::LeftParen => SymbolToken::LeftParen,
+            TokenType::RightParen => SymbolToken::RightParen,
+            TokenType::LeftBrace => SymbolToken::LeftBrace,
+            TokenType::RightBrace => SymbolToken::RightBrace,
+            TokenType::LeftBracket => SymbolToken::LeftBracket,
+            TokenType::RightBracket => SymbolToken::RightBracket,
+            TokenType::Semicolon => SymbolToken::Semicolon,
+            TokenType::Comma => SymbolToken::Comma,
+            TokenType::Dot => SymbolToken::Dot,
+            TokenType::Eof => SymbolToken::Eof,
+            TokenType::DSLContent { .. } => SymbolToken::DslContent,
+            TokenType::Error(_) => SymbolToken::Error,
+        }
+    }
+
+    /// A token of this kind, for the `expected` list of a syntax error.
+    pub fn example(self) -> TokenType {
+        match self {
+            SymbolToken::IntLiteral => TokenType::IntLiteral(0, None),
+            SymbolToken::FloatLiteral => TokenType::FloatLiteral(0.0, None),
+            SymbolToken::StringLiteral => TokenType::StringLiteral(String::new()),
+            SymbolToken::InterpolatedString => TokenType::InterpolatedString(Vec::new()),
+            SymbolToken::RuneLiteral => TokenType::RuneLiteral('a'),
+            SymbolToken::ByteLiteral => TokenType::ByteLiteral(b'a'),
+            SymbolToken::ByteStringLiteral => TokenType::ByteStringLiteral(Vec::new()),
+            SymbolToken::BooleanLiteral => TokenType::BooleanLiteral(false),
+            SymbolToken::Identifier => TokenType::Identifier(String::new()),
+            SymbolToken::Use => TokenType::Use,
+            SymbolToken::Struct => TokenType::Struct,
+            SymbolToken::Enum => TokenType::Enum,
+            SymbolToken::Type => TokenType::Type,
+            SymbolToken::Let => TokenType::Let,
+            SymbolToken::Mut => TokenType::Mut,
+            SymbolToken::Const => TokenType::Const,
+            SymbolToken::Fn => TokenType::Fn,
+            SymbolToken::If => TokenType::If,
+            SymbolToken::Else => TokenType::Else,
+            SymbolToken::While => TokenType::While,
+            SymbolToken::For => TokenType::For,
+            SymbolToken::In => TokenType::In,
+            SymbolToken::Return => TokenType::Return,
+            SymbolToken::Match => TokenType::Match,
+            SymbolToken::Async => TokenType::Async,
+            SymbolToken::Sync => TokenType::Sync,
+            SymbolToken::Par => TokenType::Par,
+            SymbolToken::Spawn => TokenType::Spawn,
+            SymbolToken::Await => TokenType::Await,
+            SymbolToken::Public => TokenType::Public,
+            SymbolToken::Private => TokenType::Private,
+            SymbolToken::Protected => TokenType::Protected,
+            SymbolToken::Dsl => TokenType::DSL(String::new()),
+            SymbolToken::Plus => TokenType::Plus,
+            SymbolToken::Minus => TokenType::Minus,
+            SymbolToken::Star => TokenType::Star,
+            SymbolToken::Slash => TokenType::Slash,
+            SymbolToken::Percent => TokenType::Percent,
+            SymbolToken::Equal => TokenType::Equal,
+            SymbolToken::EqualEqual => TokenType::EqualEqual,
+            SymbolToken::NotEqual => TokenType::NotEqual,
+            SymbolToken::Less => TokenType::Less,
+            SymbolToken::Greater => TokenType::Greater,
+            SymbolToken::LessEqual => TokenType::LessEqual,
+            SymbolToken::GreaterEqual => TokenType::GreaterEqual,
+            SymbolToken::And => TokenType::And,
+            SymbolToken::Or => TokenType::Or,
+            SymbolToken::Not => TokenType::Not,
+            SymbolToken::Question => TokenType::Question,
+            SymbolToken::Colon => TokenType::Colon,
+            SymbolToken::Arrow => TokenType::Arrow,
+            SymbolToken::FatArrow => TokenType::FatArrow,
+            SymbolToken::LeftParen => TokenType::LeftParen,
+            SymbolToken::RightParen => TokenType::RightParen,
+            SymbolToken::LeftBrace => TokenType::LeftBrace,
+            SymbolToken::RightBrace => TokenType::RightBrace,
+            SymbolToken::LeftBracket => TokenType::LeftBracket,
+            SymbolToken::RightBracket => TokenType::RightBracket,
+            SymbolToken::Semicolon => TokenType::Semicolon,
+            SymbolToken::Comma => TokenType::Comma,
+            SymbolToken::Dot => TokenType::Dot,
+            SymbolToken::Eof => TokenType::Eof,
+            SymbolToken::DslContent => TokenType::DSLContent {
+                dsl_type: String::new(),
+                content: String::new(),
+            },
+            SymbolToken::Error => TokenType::Error(String::new()),
+        }
+    }
+}
+
+impl fmt::Display for SymbolToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            SymbolToken::IntLiteral => "int-literal",
+            SymbolToken::FloatLiteral => "float-literal",
+            SymbolToken::StringLiteral => "string-literal",
+            SymbolToken::InterpolatedString => "interpolated-string",
+            SymbolToken::RuneLiteral => "rune-literal",
+            SymbolToken::ByteLiteral => "byte-literal",
+            SymbolToken::ByteStringLiteral => "byte-string-literal",
+            SymbolToken::BooleanLiteral => "bool-literal",
+            SymbolToken::Identifier => "identifier",
+            SymbolToken::Use => "'use'",
+            SymbolToken::Struct => "'struct'",
+            SymbolToken::Enum => "'enum'",
+            SymbolToken::Type => "'type'",
+            SymbolToken::Let => "'let'",
+            SymbolToken::Mut => "'mut'",
+            SymbolToken::Const => "'const'",
+            SymbolToken::Fn => "'fn'",
+            SymbolToken::If => "'if'",
+            SymbolToken::Else => "'else'",
+            SymbolToken::While => "'while'",
+            SymbolToken::For => "'for'",
+            SymbolToken::In => "'in'",
+            SymbolToken::Return => "'return'",
+            SymbolToken::Match => "'match'",
+            SymbolToken::Async => "'async'",
+            SymbolToken::Sync => "'sync'",
+            SymbolToken::Par => "'par'",
+            SymbolToken::Spawn => "'spawn'",
+            SymbolToken::Await => "'await'",
+            SymbolToken::Public => "'public'",
+            SymbolToken::Private => "'private'",
+            SymbolToken::Protected => "'protected'",
+            SymbolToken::Dsl => "dsl-keyword",
+            SymbolToken::Plus => "'+'",
+            SymbolToken::Minus => "'-'",
+            SymbolToken::Star => "'*'",
+            SymbolToken::Slash => "'/'",
+            SymbolToken::Percent => "'%'",
+            SymbolToken::Equal => "'='",
+            SymbolToken::EqualEqual => "'=='",
+            SymbolToken::NotEqual => "'!='",
+            SymbolToken::Less => "'<'",
+            SymbolToken::Greater => "'>'",
+            SymbolToken::LessEqual => "'<='",
+            SymbolToken::GreaterEqual => "'>='",
+            SymbolToken::And => "'&&'",
+            SymbolToken::Or => "'||'",
+            SymbolToken::Not => "'!'",
+            SymbolToken::Question => "'?'",
+            SymbolToken::Colon => "':'",
+            SymbolToken::Arrow => "'->'",
+            SymbolToken::FatArrow => "'=>'",
+            SymbolToken::LeftParen => "'('",
+            SymbolToken::RightParen => "')'",
+            SymbolToken::LeftBrace => "'{'",
+            SymbolToken::RightBrace => "'}'",
+            SymbolToken::LeftBracket => "'['",
+            SymbolToken::RightBracket => "']'",
+            SymbolToken::Semicolon => "';'",
+            SymbolToken::Comma => "','",
+            SymbolToken::Dot => "'.'",
+            SymbolToken::Eof => "$end",
+            SymbolToken::DslContent => "dsl-block",
+            SymbolToken::Error => "invalid-token",
+        };
+        f.write_str(text)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Symbol::Terminal(token) => write!(f, "{}", token),
+            Symbol::NonTerminal(nt) => write!(f, "{:?}", nt),
+        }
+    }
+}
+
+/// How the values of a production's right-hand side are combined into the
+/// value of its left-hand side; interpreted by the LALR parser's tree builder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reduction {
+    /// The value of the symbol at this position.
+    Pass(usize),
+    /// An omitted optional part.
+    Empty,
+    EmptyList,
+    /// A one-element list holding the symbol at this position.
+    Single(usize),
+    /// The list at the first position with the symbol at the second appended.
+    Append(usize, usize),
+
+    // Declarations
+    Program,
+    Import,
+    Visibility,
+    Concurrency,
+    LoopModifier,
+    Mutability,
+    VarDecl,
+    ConstDecl,
+    FnDecl,
+    Param,
+    StructDecl,
+    FieldDecl,
+    EnumDecl,
+    Variant,
+    VariantWithPayload,
+    TypeAlias,
+
+    // Statements
+    Block,
+    LocalDecl,
+    Return,
+    ReturnValue,
+    If,
+    ElseIf,
+    While,
+    For,
+    DslBlock,
+    BlockStmt,
+    ExprStmt,
+    Assignment,
+
+    // Types
+    TypeName,
+    QualifiedTypeName,
+    NamedType,
+    GenericType,
+    ArrayType,
+    OptionalType,
+    FnType,
+    FnTypeReturning,
+
+    // Expressions
+    ArrowLambda,
+    BlockLambda,
+    LambdaHead,
+    LambdaParam,
+    Ternary,
+    Binary,
+    Unary,
+    Await,
+    Spawn,
+    Member,
+    Call,
+    Index,
+    StructLiteral,
+    Literal,
+    Identifier,
+    Grouping,
+    ArrayLiteral,
+    ObjectLiteral,
+    Interpolated,
+    /// `name: value` of a struct literal or object literal.
+    Field,
+    Match,
+    MatchArm,
+
+    // Patterns
+    PathPattern,
+    VariantPattern,
+    LiteralPattern,
+    NegativePattern,
 }
 
 #[derive(Debug, Clone)]
 pub struct Production {
     pub lhs: NonTerminal,
     pub rhs: Vec<Symbol>,
-}
\ No newline at end of file
+    pub reduction: Reduction,
+}
+
+impl Production {
+    pub fn new(lhs: NonTerminal, rhs: Vec<Symbol>, reduction: Reduction) -> Self {
+        Production { lhs, rhs, reduction }
+    }
+}
+
+impl fmt::Display for Production {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} →", self.lhs)?;
+        if self.rhs.is_empty() {
+            return write!(f, " ε");
+        }
+        for symbol in &self.rhs {
+            write!(f, " {}", symbol)?;
+        }
+        Ok(())
+    }
+}
+
+macro_rules! rule {
+    ($g:expr, $lhs:expr => [$($sym:expr),* $(,)?], $reduction:expr) => {
+        $g.push(Production::new($lhs, vec![$(Symbol::from($sym)),*], $reduction))
+    };
+}
+
+/// `opt → ε | list | list ,` and `list → item | list , item`: a possibly empty
+/// comma-separated list allowing a trailing comma.
+fn comma_list(g: &mut Vec<Production>, opt: NonTerminal, list: NonTerminal, item: NonTerminal) {
+    use Reduction as R;
+    use SymbolToken as T;
+    rule!(g, opt => [], R::EmptyList);
+    rule!(g, opt => [list], R::Pass(0));
+    rule!(g, opt => [list, T::Comma], R::Pass(0));
+    rule!(g, list => [item], R::Single(0));
+    rule!(g, list => [list, T::Comma, item], R::Append(0, 2));
+}
+
+/// The productions of the full language, accepting what the recursive
+/// descent parser accepts. Production 0 is the augmented start rule.
+pub fn productions() -> Vec<Production> {
+    use NonTerminal as N;
+    use Reduction as R;
+    use SymbolToken as T;
+
+    let mut g = vec![Production::new(N::Start, vec![N::Program.into()], R::Pass(0))];
+    rule!(g, N::Program => [N::Declarations], R::Program);
+    rule!(g, N::Declarations => [], R::EmptyList);
+    rule!(g, N::Declarations => [N::Declarations, N::Declaration], R::Append(0, 1));
+    for decl in [N::Import, N::VarDecl, N::ConstDecl, N::FnDecl, N::StructDecl, N::EnumDecl, N::TypeAlias] {
+        rule!(g, N::Declaration => [decl], R::Pass(0));
+    }
+
+    // Declarations
+    rule!(g, N::Import => [T::Use, N::ModulePath, T::Semicolon], R::Import);
+    rule!(g, N::ModulePath => [T::Identifier], R::Single(0));
+    rule!(g, N::ModulePath => [N::ModulePath, T::Dot, T::Identifier], R::Append(0, 2));
+
+    rule!(g, N::Visibility => [], R::Empty);
+    for token in [T::Public, T::Private, T::Protected] {
+        rule!(g, N::Visibility => [token], R::Visibility);
+    }
+    rule!(g, N::Concurrency => [], R::Empty);
+    for token in [T::Async, T::Sync] {
+        rule!(g, N::Concurrency => [token], R::Concurrency);
+    }
+    rule!(g, N::LoopModifier => [], R::Empty);
+    for token in [T::Par, T::Async, T::Sync] {
+        rule!(g, N::LoopModifier => [token], R::LoopModifier);
+    }
+    for token in [T::Let, T::Mut] {
+        rule!(g, N::Mutability => [token], R::Mutability);
+    }
+
+    rule!(g, N::VarDecl => [N::Visibility, N::Mutability, T::Identifier, N::TypeAnnotation, N::Initializer, T::Semicolon], R::VarDecl);
+    rule!(g, N::TypeAnnotation => [], R::Empty);
+    rule!(g, N::TypeAnnotation => [T::Colon, N::Type], R::Pass(1));
+    rule!(g, N::Initializer => [], R::Empty);
+    rule!(g, N::Initializer => [T::Equal, N::Expr(ExprMode::Full)], R::Pass(1));
+    rule!(g, N::ConstDecl => [N::Visibility, T::Const, T::Identifier, T::Colon, N::Type, T::Equal, N::Expr(ExprMode::Full), T::Semicolon], R::ConstDecl);
+
+    rule!(g, N::FnDecl => [N::Visibility, N::Concurrency, T::Fn, T::Identifier, N::TypeParams, T::LeftParen, N::Params, T::RightParen, N::ReturnType, N::Block], R::FnDecl);
+    rule!(g, N::TypeParams => [], R::EmptyList);
+    rule!(g, N::TypeParams => [T::Less, N::TypeParamList, T::Greater], R::Pass(1));
+    rule!(g, N::TypeParamList => [T::Identifier], R::Single(0));
+    rule!(g, N::TypeParamList => [N::TypeParamList, T::Comma, T::Identifier], R::Append(0, 2));
+    comma_list(&mut g, N::Params, N::ParamList, N::Param);
+    rule!(g, N::Param => [T::Identifier, T::Colon, N::Type], R::Param);
+    rule!(g, N::ReturnType => [], R::Empty);
+    rule!(g, N::ReturnType => [T::Arrow, N::Type], R::Pass(1));
+
+    rule!(g, N::StructDecl => [N::Visibility, T::Struct, T::Identifier, N::TypeParams, T::LeftBrace, N::Members, T::RightBrace], R::StructDecl);
+    rule!(g, N::Members => [], R::EmptyList);
+    rule!(g, N::Members => [N::Members, N::FieldDecl], R::Append(0, 1));
+    rule!(g, N::Members => [N::Members, N::FnDecl], R::Append(0, 1));
+    rule!(g, N::FieldDecl => [N::Visibility, T::Identifier, T::Colon, N::Type, T::Semicolon], R::FieldDecl);
+
+    rule!(g, N::EnumDecl => [N::Visibility, T::Enum, T::Identifier, N::TypeParams, T::LeftBrace, N::Variants, T::RightBrace], R::EnumDecl);
+    rule!(g, N::Variants => [], R::EmptyList);
+    rule!(g, N::Variants => [N::Variants, N::Variant], R::Append(0, 1));
+    rule!(g, N::Variants => [N::Variants, N::Variant, T::Comma], R::Append(0, 1));
+    rule!(g, N::Variant => [T::Identifier], R::Variant);
+    rule!(g, N::Variant => [T::Identifier, T::LeftParen, N::TypeArgs, T::RightParen], R::VariantWithPayload);
+
+    rule!(g, N::TypeAlias => [N::Visibility, T::Type, T::Identifier, T::Equal, N::Type, T::Semicolon], R::TypeAlias);
+
+    // Statements
+    rule!(g, N::Block => [T::LeftBrace, N::Statements, T::RightBrace], R::Block);
+    rule!(g, N::Statements => [], R::EmptyList);
+    rule!(g, N::Statements => [N::Statements, N::Statement], R::Append(0, 1));
+    rule!(g, N::Statement => [N::VarDecl], R::LocalDecl);
+    rule!(g, N::Statement => [N::ConstDecl], R::LocalDecl);
+    rule!(g, N::Statement => [T::Return, T::Semicolon], R::Return);
+    rule!(g, N::Statement => [T::Return, N::Expr(ExprMode::Full), T::Semicolon], R::ReturnValue);
+    rule!(g, N::Statement => [N::IfStmt], R::Pass(0));
+    rule!(g, N::Statement => [N::LoopModifier, T::While, N::Expr(ExprMode::NoStruct), N::Block], R::While);
+    rule!(g, N::Statement => [N::LoopModifier, T::For, T::Identifier, T::In, N::Expr(ExprMode::NoStruct), N::Block], R::For);
+    rule!(g, N::Statement => [T::DslContent], R::DslBlock);
+    rule!(g, N::Statement => [N::Block], R::BlockStmt);
+    rule!(g, N::Statement => [N::Expr(ExprMode::Leading), T::Semicolon], R::ExprStmt);
+    rule!(g, N::Statement => [N::Expr(ExprMode::Leading), T::Equal, N::Expr(ExprMode::Full), T::Semicolon], R::Assignment);
+    rule!(g, N::IfStmt => [T::If, N::Expr(ExprMode::NoStruct), N::Block, N::Else], R::If);
+    rule!(g, N::Else => [], R::Empty);
+    rule!(g, N::Else => [T::Else, N::Block], R::Pass(1));
+    rule!(g, N::Else => [T::Else, N::IfStmt], R::ElseIf);
+
+    // Types; in `fn(T) -> R?` the `?` belongs to `R`
+    rule!(g, N::Type => [N::OptionalType], R::Pass(0));
+    rule!(g, N::Type => [T::Fn, T::LeftParen, N::TypeList, T::RightParen, T::Arrow, N::Type], R::FnTypeReturning);
+    rule!(g, N::OptionalType => [N::TypeAtom], R::Pass(0));
+    rule!(g, N::OptionalType => [N::OptionalType, T::Question], R::OptionalType);
+    rule!(g, N::TypeAtom => [N::TypeName], R::NamedType);
+    rule!(g, N::TypeAtom => [N::TypeName, T::Less, N::TypeArgs, T::Greater], R::GenericType);
+    rule!(g, N::TypeAtom => [T::LeftBracket, N::Type, T::RightBracket], R::ArrayType);
+    rule!(g, N::TypeAtom => [T::Fn, T::LeftParen, N::TypeList, T::RightParen], R::FnType);
+    rule!(g, N::TypeName => [T::Identifier], R::TypeName);
+    rule!(g, N::TypeName => [T::Identifier, T::Dot, T::Identifier], R::QualifiedTypeName);
+    comma_list(&mut g, N::TypeList, N::TypeItems, N::Type);
+    rule!(g, N::TypeArgs => [N::Type], R::Single(0));
+    rule!(g, N::TypeArgs => [N::TypeArgs, T::Comma, N::Type], R::Append(0, 2));
+
+    // Expressions, one copy of each precedence level per mode
+    for mode in [ExprMode::Full, ExprMode::NoStruct, ExprMode::Leading] {
+        expression_levels(&mut g, mode);
+    }
+    comma_list(&mut g, N::Args, N::ArgList, N::Expr(ExprMode::Full));
+    comma_list(&mut g, N::FieldInits, N::FieldInitList, N::FieldInit);
+    rule!(g, N::FieldInit => [T::Identifier, T::Colon, N::Expr(ExprMode::Full)], R::Field);
+    comma_list(&mut g, N::Properties, N::PropertyList, N::Property);
+    rule!(g, N::Property => [T::Identifier, T::Colon, N::Expr(ExprMode::Full)], R::Field);
+    rule!(g, N::Property => [T::StringLiteral, T::Colon, N::Expr(ExprMode::Full)], R::Field);
+
+    rule!(g, N::LambdaHead => [T::Fn, T::LeftParen, N::LambdaParams, T::RightParen, N::ReturnType], R::LambdaHead);
+    comma_list(&mut g, N::LambdaParams, N::LambdaParamList, N::LambdaParam);
+    rule!(g, N::LambdaParam => [T::Identifier, N::TypeAnnotation], R::LambdaParam);
+
+    comma_list(&mut g, N::MatchArms, N::MatchArmList, N::MatchArm);
+    rule!(g, N::MatchArm => [N::Pattern, T::FatArrow, N::Expr(ExprMode::Full)], R::MatchArm);
+
+    // Patterns; the tree builder rejects paths longer than `module.Enum.Variant`
+    rule!(g, N::Pattern => [N::PatternPath], R::PathPattern);
+    rule!(g, N::Pattern => [N::PatternPath, T::LeftParen, N::Patterns, T::RightParen], R::VariantPattern);
+    for token in [T::IntLiteral, T::FloatLiteral, T::StringLiteral, T::RuneLiteral, T::ByteLiteral, T::BooleanLiteral] {
+        rule!(g, N::Pattern => [token], R::LiteralPattern);
+    }
+    for token in [T::IntLiteral, T::FloatLiteral] {
+        rule!(g, N::Pattern => [T::Minus, token], R::NegativePattern);
+    }
+    rule!(g, N::PatternPath => [T::Identifier], R::Single(0));
+    rule!(g, N::PatternPath => [N::PatternPath, T::Dot, T::Identifier], R::Append(0, 2));
+    comma_list(&mut g, N::Patterns, N::PatternList, N::Pattern);
+
+    g
+}
+
+/// `productions` with a full expression as the start symbol, for the
+/// `${...}` parts of interpolated strings.
+pub fn expression_productions() -> Vec<Production> {
+    let mut g = productions();
+    g[0] = Production::new(NonTerminal::Start, vec![NonTerminal::Expr(ExprMode::Full).into()], Reduction::Pass(0));
+    g
+}
+
+/// Ternary down to primary for one mode. `==`/`!=` and the relational
+/// operators don't associate; `=>` lambdas extend as far right as possible,
+/// so they only appear where a full expression starts.
+fn expression_levels(g: &mut Vec<Production>, mode: ExprMode) {
+    use NonTerminal as N;
+    use Reduction as R;
+    use SymbolToken as T;
+
+    let rest = mode.operand();
+    rule!(g, N::Expr(mode) => [N::Ternary(mode)], R::Pass(0));
+    rule!(g, N::Expr(mode) => [N::LambdaHead, T::FatArrow, N::Expr(rest)], R::ArrowLambda);
+    rule!(g, N::Ternary(mode) => [N::Or(mode)], R::Pass(0));
+    rule!(g, N::Ternary(mode) => [N::Or(mode), T::Question, N::Expr(rest), T::Colon, N::Expr(rest)], R::Ternary);
+    rule!(g, N::Or(mode) => [N::And(mode)], R::Pass(0));
+    rule!(g, N::Or(mode) => [N::Or(mode), T::Or, N::And(rest)], R::Binary);
+    rule!(g, N::And(mode) => [N::Equality(mode)], R::Pass(0));
+    rule!(g, N::And(mode) => [N::And(mode), T::And, N::Equality(rest)], R::Binary);
+    rule!(g, N::Equality(mode) => [N::Relational(mode)], R::Pass(0));
+    for op in [T::EqualEqual, T::NotEqual] {
+        rule!(g, N::Equality(mode) => [N::Relational(mode), op, N::Relational(rest)], R::Binary);
+    }
+    rule!(g, N::Relational(mode) => [N::Additive(mode)], R::Pass(0));
+    for op in [T::Less, T::Greater, T::LessEqual, T::GreaterEqual] {
+        rule!(g, N::Relational(mode) => [N::Additive(mode), op, N::Additive(rest)], R::Binary);
+    }
+    rule!(g, N::Additive(mode) => [N::Multiplicative(mode)], R::Pass(0));
+    for op in [T::Plus, T::Minus] {
+        rule!(g, N::Additive(mode) => [N::Additive(mode), op, N::Multiplicative(rest)], R::Binary);
+    }
+    rule!(g, N::Multiplicative(mode) => [N::Unary(mode)], R::Pass(0));
+    for op in [T::Star, T::Slash, T::Percent] {
+        rule!(g, N::Multiplicative(mode) => [N::Multiplicative(mode), op, N::Unary(rest)], R::Binary);
+    }
+
+    rule!(g, N::Unary(mode) => [N::Postfix(mode)], R::Pass(0));
+    for op in [T::Minus, T::Plus, T::Not] {
+        rule!(g, N::Unary(mode) => [op, N::Unary(rest)], R::Unary);
+    }
+    rule!(g, N::Unary(mode) => [T::Await, N::Unary(rest)], R::Await);
+    rule!(g, N::Unary(mode) => [T::Spawn, N::Postfix(rest)], R::Spawn);
+
+    rule!(g, N::Postfix(mode) => [N::Primary(mode)], R::Pass(0));
+    rule!(g, N::Postfix(mode) => [N::Postfix(mode), T::Dot, T::Identifier], R::Member);
+    rule!(g, N::Postfix(mode) => [N::Postfix(mode), T::LeftParen, N::Args, T::RightParen], R::Call);
+    rule!(g, N::Postfix(mode) => [N::Postfix(mode), T::LeftBracket, N::Expr(ExprMode::Full), T::RightBracket], R::Index);
+    if mode != ExprMode::NoStruct {
+        rule!(g, N::Postfix(mode) => [N::Postfix(mode), T::LeftBrace, N::FieldInits, T::RightBrace], R::StructLiteral);
+    }
+
+    for token in [
+        T::IntLiteral, T::FloatLiteral, T::StringLiteral,
+        T::RuneLiteral, T::ByteLiteral, T::ByteStringLiteral, T::BooleanLiteral,
+    ] {
+        rule!(g, N::Primary(mode) => [token], R::Literal);
+    }
+    rule!(g, N::Primary(mode) => [T::InterpolatedString], R::Interpolated);
+    rule!(g, N::Primary(mode) => [T::Identifier], R::Identifier);
+    rule!(g, N::Primary(mode) => [T::LeftParen, N::Expr(ExprMode::Full), T::RightParen], R::Grouping);
+    rule!(g, N::Primary(mode) => [T::LeftBracket, N::Args, T::RightBracket], R::ArrayLiteral);
+    rule!(g, N::Primary(mode) => [T::Match, N::Expr(ExprMode::NoStruct), T::LeftBrace, N::MatchArms, T::RightBrace], R::Match);
+    rule!(g, N::Primary(mode) => [N::LambdaHead, N::Block], R::BlockLambda);
+    if mode == ExprMode::Full {
+        rule!(g, N::Primary(mode) => [T::LeftBrace, N::Properties, T::RightBrace], R::ObjectLiteral);
+    }
+}
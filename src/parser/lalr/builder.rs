@@ -0,0 +1,595 @@
+use crate::lexer::token::{StringPart, Token, TokenType};
+use crate::parser::grammar::Reduction;
+use crate::parser::lalr::LALRParser;
+use crate::parser::recursive_descent::errors::ParseError;
+use crate::parser::{
+    ASTNode, BinaryOp, Block, ConcurrencyModifier, ConstDecl, EnumDecl, EnumVariant, Expr, ExprKind, FieldDecl,
+    FnDecl, ImportDecl, InterpolationPart, LambdaBody, LambdaParam, Literal, LoopModifier, MatchArm, Mutability, NodeId, Parameter,
+    Pattern, PatternKind, Program, Span, Stmt, StructDecl, Type, TypeAliasDecl, UnaryOp, VarDecl, Visibility,
+};
+
+/// The semantic value of a grammar symbol on the parse stack.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Token(Token),
+    /// An omitted optional part.
+    Empty,
+    List(Vec<Value>),
+    Node(ASTNode),
+    Stmt(Stmt),
+    Block(Block),
+    Expr(Expr),
+    Type(Type),
+    Name(String),
+    Pattern(Pattern),
+    Field(String, Expr),
+    Param(Parameter),
+    FieldDecl(FieldDecl),
+    EnumVariant(EnumVariant),
+    Visibility(Visibility),
+    Concurrency(ConcurrencyModifier),
+    LoopModifier(LoopModifier),
+    Mutability(Mutability),
+    LambdaParam(LambdaParam),
+    LambdaHead(Vec<LambdaParam>, Option<Type>),
+    MatchArm(MatchArm),
+}
+
+macro_rules! expect_value {
+    ($value:expr, $($pattern:pat => $result:expr),+) => {
+        match $value {
+            $($pattern => $result,)+
+            other => unreachable!("grammar and tree builder disagree: unexpected {:?}", other),
+        }
+    };
+}
+
+impl Value {
+    fn into_token(self) -> Token {
+        expect_value!(self, Value::Token(token) => token)
+    }
+
+    fn into_name(self) -> String {
+        expect_value!(self, Value::Token(token) => token.lexeme)
+    }
+
+    fn into_list(self) -> Vec<Value> {
+        expect_value!(self, Value::List(items) => items)
+    }
+
+    fn into_list_of<T>(self, item: fn(Value) -> T) -> Vec<T> {
+        self.into_list().into_iter().map(item).collect()
+    }
+
+    fn into_expr(self) -> Expr {
+        expect_value!(self, Value::Expr(expr) => expr)
+    }
+
+    fn into_type(self) -> Type {
+        expect_value!(self, Value::Type(ty) => ty)
+    }
+
+    fn into_block(self) -> Block {
+        expect_value!(self, Value::Block(block) => block)
+    }
+
+    fn into_stmt(self) -> Stmt {
+        expect_value!(self, Value::Stmt(stmt) => stmt)
+    }
+
+    fn into_node(self) -> ASTNode {
+        expect_value!(self, Value::Node(node) => node)
+    }
+
+    fn into_pattern(self) -> Pattern {
+        expect_value!(self, Value::Pattern(pattern) => pattern)
+    }
+
+    fn into_field(self) -> (String, Expr) {
+        expect_value!(self, Value::Field(name, expr) => (name, expr))
+    }
+
+    fn into_param(self) -> Parameter {
+        expect_value!(self, Value::Param(param) => param)
+    }
+
+    fn into_lambda_param(self) -> LambdaParam {
+        expect_value!(self, Value::LambdaParam(param) => param)
+    }
+
+    fn into_variant(self) -> EnumVariant {
+        expect_value!(self, Value::EnumVariant(variant) => variant)
+    }
+
+    fn into_arm(self) -> MatchArm {
+        expect_value!(self, Value::MatchArm(arm) => arm)
+    }
+
+    fn into_opt_type(self) -> Option<Type> {
+        expect_value!(self, Value::Empty => None, Value::Type(ty) => Some(ty))
+    }
+
+    fn into_opt_expr(self) -> Option<Expr> {
+        expect_value!(self, Value::Empty => None, Value::Expr(expr) => Some(expr))
+    }
+
+    fn into_visibility(self) -> Option<Visibility> {
+        expect_value!(self, Value::Empty => None, Value::Visibility(visibility) => Some(visibility))
+    }
+
+    fn into_concurrency(self) -> Option<ConcurrencyModifier> {
+        expect_value!(self, Value::Empty => None, Value::Concurrency(modifier) => Some(modifier))
+    }
+
+    fn into_loop_modifier(self) -> Option<LoopModifier> {
+        expect_value!(self, Value::Empty => None, Value::LoopModifier(modifier) => Some(modifier))
+    }
+}
+
+/// Builds the recursive descent parser's untyped AST from reductions.
+#[derive(Debug, Default)]
+pub struct TreeBuilder;
+
+impl TreeBuilder {
+    /// Combines the values of a production's right-hand side; `span` covers
+    /// the production's text.
+    pub fn reduce(&mut self, reduction: Reduction, mut values: Vec<Value>, span: Span) -> Result<Value, ParseError> {
+        let (line, column) = (span.line, span.column);
+        let mut take = |i: usize| std::mem::replace(&mut values[i], Value::Empty);
+        let value = match reduction {
+            Reduction::Pass(i) => take(i),
+            Reduction::Empty => Value::Empty,
+            Reduction::EmptyList => Value::List(Vec::new()),
+            Reduction::Single(i) => Value::List(vec![take(i)]),
+            Reduction::Append(list, item) => {
+                let mut items = take(list).into_list();
+                items.push(take(item));
+                Value::List(items)
+            }
+
+            // Declarations
+            Reduction::Program => Value::Node(ASTNode::Program(Program {
+                declarations: take(0).into_list_of(Value::into_node),
+            })),
+            Reduction::Import => Value::Node(ASTNode::Import(ImportDecl {
+                path: take(1).into_list_of(Value::into_name),
+                line,
+                column,
+                span,
+                id: NodeId::DUMMY,
+            })),
+            Reduction::Visibility => Value::Visibility(match take(0).into_token().token_type {
+                TokenType::Public => Visibility::Public,
+                TokenType::Private => Visibility::Private,
+                _ => Visibility::Protected,
+            }),
+            Reduction::Concurrency => Value::Concurrency(match take(0).into_token().token_type {
+                TokenType::Async => ConcurrencyModifier::Async,
+                _ => ConcurrencyModifier::Sync,
+            }),
+            Reduction::LoopModifier => Value::LoopModifier(match take(0).into_token().token_type {
+                TokenType::Par => LoopModifier::Par,
+                TokenType::Async => LoopModifier::Async,
+                _ => LoopModifier::Sync,
+            }),
+            Reduction::Mutability => Value::Mutability(match take(0).into_token().token_type {
+                TokenType::Let => Mutability::Let,
+                _ => Mutability::Mut,
+            }),
+            Reduction::VarDecl => Value::Node(ASTNode::VarDecl(VarDecl {
+                visibility: take(0).into_visibility(),
+                mutability: expect_value!(take(1), Value::Mutability(mutability) => mutability),
+                name: take(2).into_name(),
+                declared_type: take(3).into_opt_type(),
+                inferred_type: None,
+                initializer: take(4).into_opt_expr(),
+                line,
+                column,
+                span,
+                id: NodeId::DUMMY,
+            })),
+            Reduction::ConstDecl => Value::Node(ASTNode::ConstDecl(ConstDecl {
+                visibility: take(0).into_visibility(),
+                name: take(2).into_name(),
+                declared_type: take(4).into_type(),
+                initializer: take(6).into_expr(),
+                value: None,
+                line,
+                column,
+                span,
+                id: NodeId::DUMMY,
+            })),
+            Reduction::FnDecl => {
+                let name = take(3).into_token();
+                Value::Node(ASTNode::FnDecl(FnDecl {
+                    visibility: take(0).into_visibility(),
+                    concurrency: take(1).into_concurrency(),
+                    name: name.lexeme,
+                    type_params: take(4).into_list_of(Value::into_name),
+                    parameters: take(6).into_list_of(Value::into_param),
+                    return_type: take(8).into_opt_type(),
+                    body: take(9).into_block(),
+                    line: name.line,
+                    column: name.column,
+                    span,
+                    id: NodeId::DUMMY,
+                }))
+            }
+            Reduction::Param => {
+                let name = take(0).into_token();
+                Value::Param(Parameter {
+                    name: name.lexeme,
+                    param_type: take(2).into_type(),
+                    line: name.line,
+                    column: name.column,
+                    span,
+                    id: NodeId::DUMMY,
+                })
+            }
+            Reduction::StructDecl => {
+                let mut fields = Vec::new();
+                let mut methods = Vec::new();
+                for member in take(5).into_list() {
+                    match member {
+                        Value::FieldDecl(field) => fields.push(field),
+                        other => methods.push(expect_value!(other, Value::Node(ASTNode::FnDecl(method)) => method)),
+                    }
+                }
+                Value::Node(ASTNode::StructDecl(StructDecl {
+                    visibility: take(0).into_visibility(),
+                    name: take(2).into_name(),
+                    type_params: take(3).into_list_of(Value::into_name),
+                    fields,
+                    methods,
+                    line,
+                    column,
+                    span,
+                    id: NodeId::DUMMY,
+                }))
+            }
+            Reduction::FieldDecl => {
+                let name = take(1).into_token();
+                Value::FieldDecl(FieldDecl {
+                    visibility: take(0).into_visibility(),
+                    name: name.lexeme,
+                    field_type: take(3).into_type(),
+                    line: name.line,
+                    column: name.column,
+                    span,
+                    id: NodeId::DUMMY,
+                })
+            }
+            Reduction::EnumDecl => {
+                Value::Node(ASTNode::EnumDecl(EnumDecl {
+                    visibility: take(0).into_visibility(),
+                    name: take(2).into_name(),
+                    type_params: take(3).into_list_of(Value::into_name),
+                    variants: take(5).into_list_of(Value::into_variant),
+                    line,
+                    column,
+                    span,
+                    id: NodeId::DUMMY,
+                }))
+            }
+            Reduction::Variant | Reduction::VariantWithPayload => {
+                let name = take(0).into_token();
+                let payload = match reduction {
+                    Reduction::VariantWithPayload => take(2).into_list_of(Value::into_type),
+                    _ => Vec::new(),
+                };
+                Value::EnumVariant(EnumVariant {
+                    name: name.lexeme,
+                    payload,
+                    line: name.line,
+                    column: name.column,
+                    span,
+                    id: NodeId::DUMMY,
+                })
+            }
+            Reduction::TypeAlias => Value::Node(ASTNode::TypeAlias(TypeAliasDecl {
+                visibility: take(0).into_visibility(),
+                name: take(2).into_name(),
+                aliased_type: take(4).into_type(),
+                line,
+                column,
+                span,
+                id: NodeId::DUMMY,
+            })),
+
+            // Statements
+            Reduction::Block => Value::Block(Block {
+                statements: take(1).into_list_of(Value::into_stmt),
+                span,
+                id: NodeId::DUMMY,
+            }),
+            Reduction::LocalDecl => Value::Stmt(match take(0).into_node() {
+                ASTNode::VarDecl(decl) => Stmt::VarDecl(decl),
+                node => Stmt::ConstDecl(expect_value!(node, ASTNode::ConstDecl(decl) => decl)),
+            }),
+            Reduction::Return => Value::Stmt(Stmt::Return { value: None, line, column, span, id: NodeId::DUMMY }),
+            Reduction::ReturnValue => Value::Stmt(Stmt::Return {
+                value: Some(take(1).into_expr()),
+                line,
+                column,
+                span,
+                id: NodeId::DUMMY,
+            }),
+            Reduction::If => Value::Stmt(Stmt::If {
+                condition: take(1).into_expr(),
+                then_branch: take(2).into_block(),
+                else_branch: expect_value!(take(3), Value::Empty => None, Value::Block(block) => Some(block)),
+                line,
+                column,
+                span,
+                id: NodeId::DUMMY,
+            }),
+            // The block holds just the nested `if`, so it spans that alone
+            Reduction::ElseIf => {
+                let nested = take(1).into_stmt();
+                Value::Block(Block { span: nested.span(), statements: vec![nested], id: NodeId::DUMMY })
+            }
+            Reduction::While => {
+                let keyword = take(1).into_token();
+                Value::Stmt(Stmt::While {
+                    modifier: take(0).into_loop_modifier(),
+                    condition: take(2).into_expr(),
+                    body: take(3).into_block(),
+                    line: keyword.line,
+                    column: keyword.column,
+                    span,
+                    id: NodeId::DUMMY,
+                })
+            }
+            Reduction::For => {
+                let keyword = take(1).into_token();
+                Value::Stmt(Stmt::For {
+                    modifier: take(0).into_loop_modifier(),
+                    variable: take(2).into_name(),
+                    iterable: take(4).into_expr(),
+                    body: take(5).into_block(),
+                    line: keyword.line,
+                    column: keyword.column,
+                    span,
+                    id: NodeId::DUMMY,
+                })
+            }
+            Reduction::DslBlock => {
+                let token = take(0).into_token();
+                let (dsl_type, content) = expect_value!(
+                    token.token_type,
+                    TokenType::DSLContent { dsl_type, content } => (dsl_type, content)
+                );
+                Value::Stmt(Stmt::DSLBlock {
+                    dsl_type,
+                    content,
+                    line: token.line,
+                    column: token.column,
+                    span,
+                    id: NodeId::DUMMY,
+                })
+            }
+            Reduction::BlockStmt => Value::Stmt(Stmt::Block(take(0).into_block())),
+            Reduction::ExprStmt => Value::Stmt(Stmt::Expression {
+                expr: take(0).into_expr(),
+                line,
+                column,
+                span,
+                id: NodeId::DUMMY,
+            }),
+            Reduction::Assignment => Value::Stmt(Stmt::Assignment {
+                target: take(0).into_expr(),
+                value: take(2).into_expr(),
+                line,
+                column,
+                span,
+                id: NodeId::DUMMY,
+            }),
+
+            // Types
+            Reduction::TypeName => Value::Name(take(0).into_name()),
+            Reduction::QualifiedTypeName => {
+                Value::Name(format!("{}.{}", take(0).into_name(), take(2).into_name()))
+            }
+            Reduction::NamedType => {
+                let name = expect_value!(take(0), Value::Name(name) => name);
+                Value::Type(Type::from_type_name(&name).unwrap_or(Type::Custom(name)))
+            }
+            Reduction::GenericType => {
+                let name = expect_value!(take(0), Value::Name(name) => name);
+                let mut args = take(2).into_list_of(Value::into_type);
+                if name == "Task" {
+                    if args.len() != 1 {
+                        return Err(ParseError::new(
+                            format!("Type 'Task' expects 1 type argument, got {}", args.len()),
+                            line,
+                            column,
+                        ));
+                    }
+                    Value::Type(Type::Task(Box::new(args.remove(0))))
+                } else {
+                    Value::Type(Type::Generic { name, args })
+                }
+            }
+            Reduction::ArrayType => Value::Type(Type::Array(Box::new(take(1).into_type()))),
+            Reduction::OptionalType => Value::Type(Type::Optional(Box::new(take(0).into_type()))),
+            Reduction::FnType => Value::Type(Type::Function {
+                params: take(2).into_list_of(Value::into_type),
+                return_type: None,
+            }),
+            Reduction::FnTypeReturning => Value::Type(Type::Function {
+                params: take(2).into_list_of(Value::into_type),
+                return_type: Some(Box::new(take(5).into_type())),
+            }),
+
+            // Expressions
+            Reduction::ArrowLambda | Reduction::BlockLambda => {
+                let (params, return_type) =
+                    expect_value!(take(0), Value::LambdaHead(params, return_type) => (params, return_type));
+                let body = match reduction {
+                    Reduction::ArrowLambda => LambdaBody::Expr(Box::new(take(2).into_expr())),
+                    _ => LambdaBody::Block(take(1).into_block()),
+                };
+                Value::Expr(Expr::new(ExprKind::Lambda { params, return_type, body, captures: Vec::new() }, span))
+            }
+            Reduction::LambdaHead => Value::LambdaHead(
+                take(2).into_list_of(Value::into_lambda_param),
+                take(4).into_opt_type(),
+            ),
+            Reduction::LambdaParam => Value::LambdaParam(LambdaParam {
+                name: take(0).into_name(),
+                param_type: take(1).into_opt_type(),
+            }),
+            Reduction::Ternary => Value::Expr(Expr::new(ExprKind::Ternary {
+                condition: Box::new(take(0).into_expr()),
+                then_branch: Box::new(take(2).into_expr()),
+                else_branch: Box::new(take(4).into_expr()),
+            }, span)),
+            Reduction::Binary => {
+                let op = BinaryOp::from_token(&take(1).into_token().token_type)
+                    .expect("binary productions use operator tokens");
+                Value::Expr(Expr::new(ExprKind::Binary {
+                    left: Box::new(take(0).into_expr()),
+                    op,
+                    right: Box::new(take(2).into_expr()),
+                }, span))
+            }
+            Reduction::Unary => {
+                let op = UnaryOp::from_token(&take(0).into_token().token_type)
+                    .expect("unary productions use operator tokens");
+                Value::Expr(Expr::new(ExprKind::Unary { op, expr: Box::new(take(1).into_expr()) }, span))
+            }
+            Reduction::Await => Value::Expr(Expr::new(ExprKind::Await(Box::new(take(1).into_expr())), span)),
+            Reduction::Spawn => {
+                let call = take(1).into_expr();
+                if !matches!(call.kind, ExprKind::Call { .. }) {
+                    return Err(ParseError::at("'spawn' must be followed by a function call", span));
+                }
+                Value::Expr(Expr::new(ExprKind::Spawn(Box::new(call)), span))
+            }
+            Reduction::Member => Value::Expr(Expr::new(ExprKind::Member {
+                object: Box::new(take(0).into_expr()),
+                member: take(2).into_name(),
+            }, span)),
+            Reduction::Call => Value::Expr(Expr::new(ExprKind::Call {
+                callee: Box::new(take(0).into_expr()),
+                args: take(2).into_list_of(Value::into_expr),
+            }, span)),
+            Reduction::Index => Value::Expr(Expr::new(ExprKind::Index {
+                object: Box::new(take(0).into_expr()),
+                index: Box::new(take(2).into_expr()),
+            }, span)),
+            Reduction::StructLiteral => {
+                let brace = take(1).into_token();
+                let name = match take(0).into_expr().kind {
+                    ExprKind::Identifier(name) => Some(name),
+                    // `module.Type { ... }`
+                    ExprKind::Member { object, member } => match object.kind {
+                        ExprKind::Identifier(module) => Some(format!("{}.{}", module, member)),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                let name = match name {
+                    Some(name) => name,
+                    None => {
+                        return Err(ParseError::new(
+                            "Only a struct name may be followed by '{' here",
+                            brace.line,
+                            brace.column,
+                        ))
+                    }
+                };
+                Value::Expr(Expr::new(ExprKind::StructLiteral {
+                    name,
+                    fields: take(2).into_list_of(Value::into_field),
+                }, span))
+            }
+            Reduction::Literal => Value::Expr(Expr::new(ExprKind::Literal(Self::literal(take(0).into_token().token_type)), span)),
+            Reduction::Interpolated => {
+                let TokenType::InterpolatedString(parts) = take(0).into_token().token_type else {
+                    unreachable!("interpolations are built from interpolated string tokens")
+                };
+                let parts = parts
+                    .into_iter()
+                    .map(|part| match part {
+                        StringPart::Literal(text) => Ok(InterpolationPart::Literal(text)),
+                        StringPart::Expr(tokens) => LALRParser::new(tokens).parse_expression().map(|expr| InterpolationPart::Expr(Box::new(expr))),
+                    })
+                    .collect::<Result<_, _>>()?;
+                Value::Expr(Expr::new(ExprKind::Interpolated { parts }, span))
+            }
+            Reduction::Identifier => Value::Expr(Expr::new(ExprKind::Identifier(take(0).into_name()), span)),
+            Reduction::Grouping => Value::Expr(Expr::new(ExprKind::Grouping(Box::new(take(1).into_expr())), span)),
+            Reduction::ArrayLiteral => Value::Expr(Expr::new(ExprKind::ArrayLiteral(take(1).into_list_of(Value::into_expr)), span)),
+            Reduction::ObjectLiteral => Value::Expr(Expr::new(ExprKind::ObjectLiteral(take(1).into_list_of(Value::into_field)), span)),
+            Reduction::Field => {
+                let key = match take(0).into_token().token_type {
+                    TokenType::StringLiteral(text) => text,
+                    TokenType::Identifier(name) => name,
+                    other => unreachable!("field names are identifiers or strings, not {:?}", other),
+                };
+                Value::Field(key, take(2).into_expr())
+            }
+            Reduction::Match => Value::Expr(Expr::new(ExprKind::Match {
+                scrutinee: Box::new(take(1).into_expr()),
+                arms: take(3).into_list_of(Value::into_arm),
+            }, span)),
+            Reduction::MatchArm => Value::MatchArm(MatchArm {
+                pattern: take(0).into_pattern(),
+                body: take(2).into_expr(),
+                span,
+                id: NodeId::DUMMY,
+            }),
+
+            // Patterns
+            Reduction::PathPattern => {
+                Value::Pattern(self.path_pattern(take(0).into_list_of(Value::into_token), None, span)?)
+            }
+            Reduction::VariantPattern => {
+                let fields = take(2).into_list_of(Value::into_pattern);
+                Value::Pattern(self.path_pattern(take(0).into_list_of(Value::into_token), Some(fields), span)?)
+            }
+            Reduction::LiteralPattern => {
+                let literal = Self::literal(take(0).into_token().token_type);
+                Value::Pattern(Pattern::new(PatternKind::Literal(literal), span))
+            }
+            Reduction::NegativePattern => {
+                let literal = Self::literal(take(1).into_token().token_type).negated();
+                Value::Pattern(Pattern::new(PatternKind::Literal(literal), span))
+            }
+        };
+        Ok(value)
+    }
+
+    fn literal(token: TokenType) -> Literal {
+        match token {
+            TokenType::StringLiteral(text) => Literal::String(text),
+            TokenType::RuneLiteral(c) => Literal::Rune(c),
+            TokenType::ByteLiteral(b) => Literal::Byte(b),
+            TokenType::ByteStringLiteral(bytes) => Literal::ByteString(bytes),
+            TokenType::BooleanLiteral(b) => Literal::Boolean(b),
+            other => Literal::number(&other)
+                .unwrap_or_else(|| unreachable!("literal productions use literal tokens, not {:?}", other)),
+        }
+    }
+
+    /// `_`, `name`, `Enum.Variant` or `module.Enum.Variant`, the last two
+    /// optionally with field patterns.
+    fn path_pattern(&self, path: Vec<Token>, fields: Option<Vec<Pattern>>, span: Span) -> Result<Pattern, ParseError> {
+        let mut names: Vec<String> = path.iter().map(|token| token.lexeme.clone()).collect();
+        let kind = match (names.len(), fields) {
+            (1, None) if names[0] == "_" => PatternKind::Wildcard,
+            (1, None) => PatternKind::Binding(names.remove(0)),
+            (2 | 3, fields) if names[0] != "_" => {
+                let variant = names.pop().expect("a path has a last segment");
+                PatternKind::Variant {
+                    enum_name: names.join("."),
+                    variant,
+                    fields: fields.unwrap_or_default(),
+                }
+            }
+            _ => return Err(ParseError::at("Expected '=>' after match pattern", span)),
+        };
+        Ok(Pattern::new(kind, span))
+    }
+}
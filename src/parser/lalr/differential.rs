@@ -0,0 +1,75 @@
+use std::fmt;
+
+use crate::lexer::token::Token;
+use crate::parser::recursive_descent::errors::ParseError;
+use crate::parser::recursive_descent::RecursiveDescentParser;
+use crate::parser::ASTNode;
+
+/// How the two parsers disagreed on a program.
+#[derive(Debug, Clone)]
+pub enum Disagreement {
+    /// Only the recursive descent parser reported a syntax error.
+    RecursiveDescentRejects(Box<ParseError>),
+    /// Only the LALR parser reported a syntax error.
+    LalrRejects(Box<ParseError>),
+    /// Both accepted the program but built different trees.
+    Tree {
+        /// Index of the first differing top-level declaration.
+        index: usize,
+        recursive_descent: Option<Box<ASTNode>>,
+        lalr: Option<Box<ASTNode>>,
+    },
+}
+
+impl fmt::Display for Disagreement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Disagreement::RecursiveDescentRejects(err) => {
+                write!(f, "only the recursive descent parser rejects the program: {}", err)
+            }
+            Disagreement::LalrRejects(err) => write!(f, "only the LALR parser rejects the program: {}", err),
+            Disagreement::Tree { index, recursive_descent, lalr } => write!(
+                f,
+                "declaration {} differs:\n  recursive descent: {:?}\n  lalr: {:?}",
+                index, recursive_descent, lalr
+            ),
+        }
+    }
+}
+
+/// Parses `tokens` with both parsers and checks that they agree: a syntax
+/// error must be reported by both parsers or neither, and accepted programs
+/// must produce the same tree.
+pub fn compare_parsers(tokens: &[Token]) -> Result<(), Disagreement> {
+    let mut rd_parser = RecursiveDescentParser::new(tokens.to_vec());
+    let rd_result = rd_parser.parse_program();
+    let mut rd_errors = rd_parser.errors().to_vec();
+    let rd_program = match rd_result {
+        Ok(ASTNode::Program(program)) => Some(program),
+        Ok(_) => unreachable!("parse_program always returns a Program node"),
+        Err(err) => {
+            rd_errors.push(err);
+            None
+        }
+    };
+    let lalr_result = super::LALRParser::new(tokens.to_vec()).parse_program();
+
+    match (rd_errors.into_iter().next(), lalr_result) {
+        (Some(_), Err(_)) => Ok(()),
+        (Some(err), Ok(_)) => Err(Disagreement::RecursiveDescentRejects(Box::new(err))),
+        (None, Err(err)) => Err(Disagreement::LalrRejects(Box::new(err))),
+        (None, Ok(lalr_program)) => {
+            let rd_program = rd_program.expect("a parse without errors yields a program");
+            let count = rd_program.declarations.len().max(lalr_program.declarations.len());
+            (0..count)
+                .find(|&i| rd_program.declarations.get(i) != lalr_program.declarations.get(i))
+                .map_or(Ok(()), |index| {
+                    Err(Disagreement::Tree {
+                        index,
+                        recursive_descent: rd_program.declarations.get(index).cloned().map(Box::new),
+                        lalr: lalr_program.declarations.get(index).cloned().map(Box::new),
+                    })
+                })
+        }
+    }
+}
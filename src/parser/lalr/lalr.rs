@@ -1,16 +1,418 @@
-use std::collections::{HashMap, HashSet};
-use crate::parser::{grammar::Symbol, lalr::LR1Item};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+use crate::parser::grammar::{NonTerminal, Production, Symbol, SymbolToken};
+use crate::parser::lalr::LR1Item;
 
 #[derive(Debug, Clone)]
 pub struct LALRState {
+    /// Kernel items, one per lookahead.
     pub items: HashSet<LR1Item>,
     pub transitions: HashMap<Symbol, usize>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Action {
     Shift(usize),
     Reduce(usize),
     Accept,
     Error,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    ShiftReduce,
+    ReduceReduce,
+}
+
+/// Two actions competing for one lookahead in one state.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub kind: ConflictKind,
+    pub state: usize,
+    pub lookahead: SymbolToken,
+    /// The items asking to shift or reduce on `lookahead`, rendered.
+    pub items: Vec<String>,
+    /// A shortest symbol sequence leading from the start state to `state`.
+    pub path: Vec<Symbol>,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            ConflictKind::ShiftReduce => "shift/reduce",
+            ConflictKind::ReduceReduce => "reduce/reduce",
+        };
+        writeln!(f, "{} conflict in state {} on {}", kind, self.state, self.lookahead)?;
+        let path: Vec<String> = self.path.iter().map(Symbol::to_string).collect();
+        write!(f, "  after: {}", path.join(" "))?;
+        for item in &self.items {
+            write!(f, "\n  {}", item)?;
+        }
+        Ok(())
+    }
+}
+
+/// A set of terminals; the top bit stands for the "#" placeholder used while
+/// discovering which lookaheads propagate between kernel items.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct TokenSet(u128);
+
+const PROPAGATE: u128 = 1 << 127;
+
+impl TokenSet {
+    fn of(token: SymbolToken) -> Self {
+        TokenSet(1 << token as u32)
+    }
+
+    fn contains(self, token: SymbolToken) -> bool {
+        self.0 & (1 << token as u32) != 0
+    }
+
+    /// Adds `other`'s members, returning whether any were new.
+    fn union(&mut self, other: TokenSet) -> bool {
+        let before = self.0;
+        self.0 |= other.0;
+        self.0 != before
+    }
+
+    fn tokens(self) -> impl Iterator<Item = SymbolToken> {
+        SymbolToken::ALL.into_iter().filter(move |&token| self.contains(token))
+    }
+}
+
+/// A production with a dot: `(production index, position)`.
+type Core = (usize, usize);
+
+/// FIRST sets and nullability of a production list.
+struct Grammar<'g> {
+    productions: &'g [Production],
+    by_lhs: HashMap<NonTerminal, Vec<usize>>,
+    nullable: HashSet<NonTerminal>,
+    first: HashMap<NonTerminal, TokenSet>,
+}
+
+impl<'g> Grammar<'g> {
+    fn new(productions: &'g [Production]) -> Self {
+        let mut by_lhs: HashMap<NonTerminal, Vec<usize>> = HashMap::new();
+        for (index, production) in productions.iter().enumerate() {
+            by_lhs.entry(production.lhs).or_default().push(index);
+        }
+        let mut grammar = Grammar {
+            productions,
+            by_lhs,
+            nullable: HashSet::new(),
+            first: HashMap::new(),
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for production in productions {
+                let first = grammar.first_of(&production.rhs, TokenSet::default());
+                changed |= grammar.first.entry(production.lhs).or_default().union(first);
+                if !grammar.nullable.contains(&production.lhs)
+                    && production.rhs.iter().all(|symbol| grammar.is_nullable(symbol))
+                {
+                    grammar.nullable.insert(production.lhs);
+                    changed = true;
+                }
+            }
+        }
+        grammar
+    }
+
+    fn is_nullable(&self, symbol: &Symbol) -> bool {
+        matches!(symbol, Symbol::NonTerminal(nt) if self.nullable.contains(nt))
+    }
+
+    /// FIRST of `symbols` followed by anything in `then`.
+    fn first_of(&self, symbols: &[Symbol], then: TokenSet) -> TokenSet {
+        let mut first = TokenSet::default();
+        for symbol in symbols {
+            match symbol {
+                Symbol::Terminal(token) => {
+                    first.union(TokenSet::of(*token));
+                    return first;
+                }
+                Symbol::NonTerminal(nt) => {
+                    first.union(self.first.get(nt).copied().unwrap_or_default());
+                    if !self.nullable.contains(nt) {
+                        return first;
+                    }
+                }
+            }
+        }
+        first.union(then);
+        first
+    }
+
+    fn next_symbol(&self, (production, position): Core) -> Option<Symbol> {
+        self.productions[production].rhs.get(position).copied()
+    }
+
+    /// LR(1) closure of `kernel`, lookaheads merged per core, in discovery order.
+    fn closure(&self, kernel: impl IntoIterator<Item = (Core, TokenSet)>) -> Vec<(Core, TokenSet)> {
+        let mut items: Vec<(Core, TokenSet)> = Vec::new();
+        let mut index: HashMap<Core, usize> = HashMap::new();
+        let mut work = VecDeque::new();
+        for (core, lookahead) in kernel {
+            index.insert(core, items.len());
+            work.push_back(items.len());
+            items.push((core, lookahead));
+        }
+
+        while let Some(i) = work.pop_front() {
+            let ((production, position), lookahead) = items[i];
+            let Some(Symbol::NonTerminal(nt)) = self.next_symbol((production, position)) else {
+                continue;
+            };
+            let rest = &self.productions[production].rhs[position + 1..];
+            let follow = self.first_of(rest, lookahead);
+            for &expansion in self.by_lhs.get(&nt).map(Vec::as_slice).unwrap_or_default() {
+                let core = (expansion, 0);
+                match index.get(&core) {
+                    Some(&j) => {
+                        if items[j].1.union(follow) {
+                            work.push_back(j);
+                        }
+                    }
+                    None => {
+                        index.insert(core, items.len());
+                        work.push_back(items.len());
+                        items.push((core, follow));
+                    }
+                }
+            }
+        }
+        items
+    }
+
+    fn render(&self, (production, position): Core) -> String {
+        let production = &self.productions[production];
+        let mut text = format!("{:?} →", production.lhs);
+        for (i, symbol) in production.rhs.iter().enumerate() {
+            if i == position {
+                text.push_str(" •");
+            }
+            text.push_str(&format!(" {}", symbol));
+        }
+        if position == production.rhs.len() {
+            text.push_str(" •");
+        }
+        text
+    }
+}
+
+/// Parse tables for a production list whose first production is the
+/// augmented start rule `Start → X`.
+#[derive(Debug, Clone)]
+pub struct LALRTable {
+    pub productions: Vec<Production>,
+    pub states: Vec<LALRState>,
+    action: Vec<Vec<Action>>,
+    goto: Vec<HashMap<NonTerminal, usize>>,
+}
+
+impl LALRTable {
+    /// Builds the LALR(1) automaton: the LR(0) collection by closure/goto,
+    /// then kernel lookaheads found spontaneously or propagated between
+    /// states, which amounts to merging LR(1) states with equal cores.
+    /// Fails with every conflict if the grammar is not LALR(1).
+    pub fn build(productions: Vec<Production>) -> Result<LALRTable, Vec<Conflict>> {
+        let grammar = Grammar::new(&productions);
+
+        // LR(0) states, identified by their sorted kernels
+        let mut kernels: Vec<Vec<Core>> = vec![vec![(0, 0)]];
+        let mut state_of: HashMap<Vec<Core>, usize> = HashMap::from([(vec![(0, 0)], 0)]);
+        let mut transitions: Vec<HashMap<Symbol, usize>> = Vec::new();
+        let mut state = 0;
+        while state < kernels.len() {
+            let closure = grammar.closure(kernels[state].iter().map(|&core| (core, TokenSet::default())));
+            let mut moves: Vec<(Symbol, Vec<Core>)> = Vec::new();
+            for ((production, position), _) in closure {
+                let Some(symbol) = grammar.next_symbol((production, position)) else {
+                    continue;
+                };
+                match moves.iter_mut().find(|(s, _)| *s == symbol) {
+                    Some((_, cores)) => cores.push((production, position + 1)),
+                    None => moves.push((symbol, vec![(production, position + 1)])),
+                }
+            }
+
+            let mut edges = HashMap::new();
+            for (symbol, mut kernel) in moves {
+                kernel.sort_unstable();
+                let target = *state_of.entry(kernel.clone()).or_insert_with(|| {
+                    kernels.push(kernel);
+                    kernels.len() - 1
+                });
+                edges.insert(symbol, target);
+            }
+            transitions.push(edges);
+            state += 1;
+        }
+
+        // Spontaneous lookaheads and propagation links between kernel items
+        let mut lookaheads: Vec<Vec<TokenSet>> =
+            kernels.iter().map(|kernel| vec![TokenSet::default(); kernel.len()]).collect();
+        lookaheads[0][0] = TokenSet::of(SymbolToken::Eof);
+        let mut links: Vec<Vec<Vec<(usize, usize)>>> =
+            kernels.iter().map(|kernel| vec![Vec::new(); kernel.len()]).collect();
+        for (state, kernel) in kernels.iter().enumerate() {
+            for (k, &core) in kernel.iter().enumerate() {
+                for (item, lookahead) in grammar.closure([(core, TokenSet(PROPAGATE))]) {
+                    let Some(symbol) = grammar.next_symbol(item) else {
+                        continue;
+                    };
+                    let target = transitions[state][&symbol];
+                    let advanced = (item.0, item.1 + 1);
+                    let index = kernels[target]
+                        .binary_search(&advanced)
+                        .expect("goto kernels contain every advanced item");
+                    if lookahead.0 & PROPAGATE != 0 {
+                        links[state][k].push((target, index));
+                    }
+                    lookaheads[target][index].union(TokenSet(lookahead.0 & !PROPAGATE));
+                }
+            }
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (state, kernel_links) in links.iter().enumerate() {
+                for (k, targets) in kernel_links.iter().enumerate() {
+                    let lookahead = lookaheads[state][k];
+                    for &(target, index) in targets {
+                        changed |= lookaheads[target][index].union(lookahead);
+                    }
+                }
+            }
+        }
+
+        // Action and goto tables
+        let paths = Self::paths(&transitions);
+        let mut conflicts = Vec::new();
+        let mut action = Vec::with_capacity(kernels.len());
+        let mut goto = Vec::with_capacity(kernels.len());
+        for (state, kernel) in kernels.iter().enumerate() {
+            let closure = grammar.closure(kernel.iter().copied().zip(lookaheads[state].iter().copied()));
+            let mut row = vec![Action::Error; SymbolToken::ALL.len()];
+            let mut gotos = HashMap::new();
+            for (symbol, &target) in &transitions[state] {
+                match symbol {
+                    Symbol::Terminal(token) => row[*token as usize] = Action::Shift(target),
+                    Symbol::NonTerminal(nt) => {
+                        gotos.insert(*nt, target);
+                    }
+                }
+            }
+
+            let mut conflicted = HashSet::new();
+            for &((production, position), lookahead) in &closure {
+                if position < productions[production].rhs.len() {
+                    continue;
+                }
+                for token in lookahead.tokens() {
+                    let wanted = if production == 0 { Action::Accept } else { Action::Reduce(production) };
+                    let kind = match row[token as usize] {
+                        Action::Error => {
+                            row[token as usize] = wanted;
+                            continue;
+                        }
+                        Action::Shift(_) => ConflictKind::ShiftReduce,
+                        existing if existing == wanted => continue,
+                        _ => ConflictKind::ReduceReduce,
+                    };
+                    if conflicted.insert(token) {
+                        conflicts.push(Conflict {
+                            kind,
+                            state,
+                            lookahead: token,
+                            items: Self::items_on(&grammar, &closure, token),
+                            path: paths[state].clone(),
+                        });
+                    }
+                }
+            }
+            action.push(row);
+            goto.push(gotos);
+        }
+        if !conflicts.is_empty() {
+            return Err(conflicts);
+        }
+
+        let states = kernels
+            .iter()
+            .zip(&lookaheads)
+            .zip(transitions)
+            .map(|((kernel, lookaheads), transitions)| LALRState {
+                items: kernel
+                    .iter()
+                    .zip(lookaheads)
+                    .flat_map(|(&(production_index, position), lookahead)| {
+                        lookahead.tokens().map(move |lookahead| LR1Item {
+                            production_index,
+                            position,
+                            lookahead,
+                        })
+                    })
+                    .collect(),
+                transitions,
+            })
+            .collect();
+        Ok(LALRTable { productions, states, action, goto })
+    }
+
+    /// Shortest symbol paths from the start state, for conflict traces.
+    fn paths(transitions: &[HashMap<Symbol, usize>]) -> Vec<Vec<Symbol>> {
+        let mut paths: Vec<Option<Vec<Symbol>>> = vec![None; transitions.len()];
+        paths[0] = Some(Vec::new());
+        let mut queue = VecDeque::from([0]);
+        while let Some(state) = queue.pop_front() {
+            let mut edges: Vec<_> = transitions[state].iter().collect();
+            edges.sort_by_key(|(_, &target)| target);
+            for (symbol, &target) in edges {
+                if paths[target].is_none() {
+                    let mut path = paths[state].clone().unwrap_or_default();
+                    path.push(*symbol);
+                    paths[target] = Some(path);
+                    queue.push_back(target);
+                }
+            }
+        }
+        paths.into_iter().map(Option::unwrap_or_default).collect()
+    }
+
+    /// The items of a state that shift or reduce on `token`.
+    fn items_on(grammar: &Grammar, closure: &[(Core, TokenSet)], token: SymbolToken) -> Vec<String> {
+        closure
+            .iter()
+            .filter_map(|&(core, lookahead)| match grammar.next_symbol(core) {
+                Some(Symbol::Terminal(next)) if next == token => {
+                    Some(format!("{}   [shift]", grammar.render(core)))
+                }
+                None if lookahead.contains(token) => {
+                    Some(format!("{}   [reduce on {}]", grammar.render(core), token))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn action(&self, state: usize, token: SymbolToken) -> Action {
+        self.action[state][token as usize]
+    }
+
+    pub fn goto(&self, state: usize, nt: NonTerminal) -> Option<usize> {
+        self.goto[state].get(&nt).copied()
+    }
+
+    /// The terminals with a non-error action in `state`.
+    pub fn expected(&self, state: usize) -> Vec<SymbolToken> {
+        SymbolToken::ALL
+            .into_iter()
+            .filter(|&token| self.action(state, token) != Action::Error)
+            .collect()
+    }
+}
@@ -1,7 +1,11 @@
+pub mod builder;
+pub mod differential;
 pub mod item;
+#[allow(clippy::module_inception)]
 pub mod lalr;
 pub mod parser;
 
+pub use differential::{compare_parsers, Disagreement};
 pub use item::LR1Item;
-pub use lalr::{LALRState, Action};
-pub use parser::LALRParser;
\ No newline at end of file
+pub use lalr::{Action, Conflict, ConflictKind, LALRState, LALRTable};
+pub use parser::LALRParser;
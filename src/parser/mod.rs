@@ -1,11 +1,16 @@
 pub mod ast;
-pub mod rd_parser;
+pub mod fuzz;
+pub mod grammar;
+pub mod lalr;
 pub mod symbol_table;
 pub mod expr;
 pub mod types;
 pub mod semantic;
+pub mod modules;
+pub mod span;
+pub mod visit;
 
-pub use rd_parser::{RecursiveDescentParser};
+pub use span::{NodeId, Span};
 pub use symbol_table::{SymbolTable};
 
 
@@ -14,4 +19,7 @@ pub use expr::*;
 pub use types::*;
 
 
-pub mod recursive_descent;
\ No newline at end of file
+pub mod recursive_descent;
+
+#[cfg(test)]
+mod tests;
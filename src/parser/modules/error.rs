@@ -0,0 +1,32 @@
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub enum ModuleError {
+    NotFound { module: String, path: PathBuf },
+    Io { path: PathBuf, message: String },
+    /// An entry file without the `.kai` extension.
+    NotASourceFile(PathBuf),
+    /// Module paths forming the cycle, starting and ending with the same module.
+    Cycle(Vec<String>),
+}
+
+impl std::fmt::Display for ModuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModuleError::NotFound { module, path } => {
+                write!(f, "Module '{}' not found (looked for {})", module, path.display())
+            }
+            ModuleError::Io { path, message } => {
+                write!(f, "Cannot read {}: {}", path.display(), message)
+            }
+            ModuleError::NotASourceFile(path) => {
+                write!(f, "'{}' is not a .kai source file", path.display())
+            }
+            ModuleError::Cycle(modules) => {
+                write!(f, "Import cycle detected: {}", modules.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ModuleError {}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::parser::symbol_table::{ModuleInfo, SymbolTable};
+use crate::parser::{ASTNode, Program};
+use crate::pipeline::{self, Parsed, SourceFile};
+
+use super::error::ModuleError;
+use super::loader::ModuleLoader;
+
+/// A parsed and analysed source file.
+#[derive(Debug, Clone)]
+pub struct Module {
+    /// Dotted module path, e.g. `net.http`.
+    pub name: String,
+    pub path: PathBuf,
+    pub source: SourceFile,
+    /// Module paths imported with `use` that loaded, in source order.
+    pub imports: Vec<String>,
+    /// The analysed program with its lexical, syntax and semantic errors.
+    pub parsed: Parsed,
+    pub exports: ModuleInfo,
+}
+
+/// All modules reachable from an entry module, ordered so that every module
+/// comes after the modules it imports.
+#[derive(Debug, Clone)]
+pub struct ModuleGraph {
+    modules: Vec<Module>,
+}
+
+struct Discovered {
+    path: PathBuf,
+    source: SourceFile,
+    parsed: Parsed,
+    imports: Vec<String>,
+    /// Imports that could not be loaded; their `use` reports why.
+    failed: Vec<(String, ModuleError)>,
+}
+
+impl ModuleGraph {
+    /// Loads `entry` and everything it imports, then analyses each module
+    /// with the public items of its imports in scope. A missing module or an
+    /// import cycle is reported at the `use` naming it and the remaining
+    /// modules are still analysed; only an entry that cannot be read fails
+    /// the load.
+    pub fn load(loader: &ModuleLoader, entry: &str) -> Result<Self, ModuleError> {
+        let mut discovered = HashMap::new();
+        let mut order = Vec::new();
+        Self::discover(loader, entry, &mut Vec::new(), &mut discovered, &mut order)?;
+
+        let mut modules: Vec<Module> = Vec::with_capacity(order.len());
+        for name in order {
+            let Discovered { path, source, mut parsed, imports, failed } = discovered
+                .remove(&name)
+                .expect("every module in the load order was discovered");
+
+            let mut symbol_table = SymbolTable::new();
+            for import in &imports {
+                let exports = &modules
+                    .iter()
+                    .find(|m| &m.name == import)
+                    .expect("imports are analysed before their importers")
+                    .exports;
+                let binding = import.rsplit('.').next().unwrap_or(import);
+                // A second module bound to the same name is reported by the
+                // analyzer when it reaches the offending `use`.
+                let _ = symbol_table.import_module(binding, exports);
+            }
+            for (import, err) in failed {
+                symbol_table.fail_import(&import, err.to_string());
+            }
+
+            pipeline::analyze(&mut parsed, &mut symbol_table);
+            let exports = symbol_table.exports(&name);
+            modules.push(Module { name, path, source, imports, parsed, exports });
+        }
+
+        Ok(ModuleGraph { modules })
+    }
+
+    fn discover(
+        loader: &ModuleLoader,
+        module: &str,
+        stack: &mut Vec<String>,
+        discovered: &mut HashMap<String, Discovered>,
+        order: &mut Vec<String>,
+    ) -> Result<(), ModuleError> {
+        if let Some(start) = stack.iter().position(|m| m == module) {
+            let mut cycle = stack[start..].to_vec();
+            cycle.push(module.to_string());
+            return Err(ModuleError::Cycle(cycle));
+        }
+        if discovered.contains_key(module) {
+            return Ok(());
+        }
+
+        let path = loader.module_file(module);
+        let source = SourceFile::new(path.display().to_string(), loader.load_source(module)?);
+        let parsed = pipeline::parse(&source);
+        let mut imports = Self::imports(&parsed.program);
+
+        let mut failed = Vec::new();
+        stack.push(module.to_string());
+        imports.retain(|import| match Self::discover(loader, import, stack, discovered, order) {
+            Ok(()) => true,
+            Err(err) => {
+                failed.push((import.clone(), err));
+                false
+            }
+        });
+        stack.pop();
+
+        discovered.insert(module.to_string(), Discovered { path, source, parsed, imports, failed });
+        order.push(module.to_string());
+        Ok(())
+    }
+
+    /// The modules named by the well-formed `use` declarations. Malformed
+    /// ones are already syntax errors.
+    fn imports(program: &Program) -> Vec<String> {
+        let mut imports: Vec<String> = Vec::new();
+        for node in &program.declarations {
+            if let ASTNode::Import(import) = node {
+                let module = import.module_path();
+                if !imports.contains(&module) {
+                    imports.push(module);
+                }
+            }
+        }
+        imports
+    }
+
+    pub fn modules(&self) -> &[Module] {
+        &self.modules
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Module> {
+        self.modules.iter().find(|m| m.name == name)
+    }
+
+    pub fn into_modules(self) -> Vec<Module> {
+        self.modules
+    }
+
+    /// The module the graph was loaded from.
+    pub fn entry(&self) -> &Module {
+        self.modules.last().expect("a module graph always contains its entry module")
+    }
+
+    /// True when no module reported lexical, syntax or semantic errors.
+    pub fn is_ok(&self) -> bool {
+        self.modules.iter().all(|m| !m.parsed.has_errors())
+    }
+}
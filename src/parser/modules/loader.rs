@@ -0,0 +1,60 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::error::ModuleError;
+
+/// Extension of Kai source files.
+pub const SOURCE_EXTENSION: &str = "kai";
+
+/// Maps dotted module paths to source files below a project root:
+/// `use net.http.client;` is read from `<root>/net/http/client.kai`.
+#[derive(Debug, Clone)]
+pub struct ModuleLoader {
+    root: PathBuf,
+}
+
+impl ModuleLoader {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        ModuleLoader { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn module_file(&self, module: &str) -> PathBuf {
+        let mut path = self.root.clone();
+        path.extend(module.split('.'));
+        path.set_extension(SOURCE_EXTENSION);
+        path
+    }
+
+    /// The module path of a source file inside the project root, e.g.
+    /// `<root>/net/http.kai` becomes `net.http`.
+    pub fn module_name(&self, file: &Path) -> Option<String> {
+        let relative = file.strip_prefix(&self.root).ok()?;
+        if relative.extension()? != SOURCE_EXTENSION {
+            return None;
+        }
+        let segments = relative
+            .with_extension("")
+            .iter()
+            .map(|part| part.to_str().map(str::to_string))
+            .collect::<Option<Vec<_>>>()?;
+        Some(segments.join("."))
+    }
+
+    pub fn load_source(&self, module: &str) -> Result<String, ModuleError> {
+        let path = self.module_file(module);
+        if !path.is_file() {
+            return Err(ModuleError::NotFound {
+                module: module.to_string(),
+                path,
+            });
+        }
+        fs::read_to_string(&path).map_err(|err| ModuleError::Io {
+            path,
+            message: err.to_string(),
+        })
+    }
+}
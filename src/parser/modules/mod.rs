@@ -0,0 +1,6 @@
+pub mod error;
+pub mod graph;
+pub mod loader;
+
+pub use graph::ModuleGraph;
+pub use loader::ModuleLoader;
@@ -1,24 +1,29 @@
 use crate::parser::ast::*;
 use crate::lexer::token::{Token, TokenType};
-use crate::parser::symbol_table::SymbolTable;
-use crate::parser::semantic::analyzer::SemanticAnalyzer;
-use super::statements::StatementParser;
-use super::errors::{ParseError, ErrorRecovery};
+use super::{StatementParser, ErrorRecovery};
+use super::errors::{ParseError, Span};
+use crate::parser::span::number_nodes;
 
-pub struct RecursiveDescentParser<'a> {
+/// Builds the untyped tree; names are resolved and types checked afterwards
+/// by the passes in `semantic`.
+pub struct RecursiveDescentParser {
     tokens: Vec<Token>,
     position: usize,
-    semantic_errors: Vec<String>,
-    pub semantic_analyzer: SemanticAnalyzer<'a>,
+    errors: Vec<ParseError>,
+    /// Cleared while parsing `if`/`while`/`for` headers, where `{` opens the body.
+    pub allow_struct_literals: bool,
+    /// Token position where the innermost expression being parsed started.
+    pub expression_start: usize,
 }
 
-impl<'a> RecursiveDescentParser<'a> {
-    pub fn new(tokens: Vec<Token>, symbol_table: &'a mut SymbolTable) -> Self {
+impl RecursiveDescentParser {
+    pub fn new(tokens: Vec<Token>) -> Self {
         RecursiveDescentParser {
             tokens,
             position: 0,
-            semantic_errors: Vec::new(),
-            semantic_analyzer: SemanticAnalyzer::new(symbol_table),
+            errors: Vec::new(),
+            allow_struct_literals: true,
+            expression_start: 0,
         }
     }
 
@@ -38,6 +43,10 @@ impl<'a> RecursiveDescentParser<'a> {
         self.tokens.get(self.position + 1)
     }
 
+    pub fn peek_token_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.position + offset)
+    }
+
     pub fn advance(&mut self) -> Result<&Token, ParseError> {
     if self.position >= self.tokens.len() {
         return Err(ParseError::new("Unexpected end of input", 0, 0));
@@ -47,18 +56,55 @@ impl<'a> RecursiveDescentParser<'a> {
 }
 
 
+    pub fn previous_token(&self) -> Option<&Token> {
+        self.position.checked_sub(1).and_then(|index| self.tokens.get(index))
+    }
+
+    pub fn token_at(&self, index: usize) -> Option<&Token> {
+        self.tokens.get(index)
+    }
+
+    /// The span from the token at index `start` to the last token consumed,
+    /// or an empty span before it when nothing has been consumed since.
+    pub fn span_from(&self, start: usize) -> Span {
+        let Some(first) = self.tokens.get(start).or(self.tokens.last()) else {
+            return Span::default();
+        };
+        match self.previous_token() {
+            Some(last) if self.position > start => Span::of_token(first).to(Span::of_token(last)),
+            _ => Span::before(first),
+        }
+    }
+
     pub fn consume(&mut self, expected: &TokenType, message: &str) -> Result<Token, ParseError> {
         let token = self.current_token()?.clone();
         if self.matches_token(&token.token_type, expected) {
             self.advance()?;
-            Ok(token)
-        } else {
-            Err(ParseError::new(
-                &format!("{} (found {:?})", message, token.token_type),
-                token.line,
-                token.column,
-            ))
+            return Ok(token);
+        }
+        let error = ParseError::unexpected(message, &token, vec![expected.clone()]);
+        if *expected == TokenType::Semicolon {
+            if let Some(inserted) = self.insert_semicolon(&token, error.clone()) {
+                return Ok(inserted);
+            }
+        }
+        Err(error)
+    }
+
+    /// A missing `;` is assumed at the end of the previous line or before a
+    /// closing `}`; the error is reported and parsing carries on.
+    fn insert_semicolon(&mut self, found: &Token, mut error: ParseError) -> Option<Token> {
+        let previous = self.previous_token()?;
+        let ends_line = found.line > previous.line;
+        if !ends_line && !matches!(found.token_type, TokenType::RightBrace | TokenType::Eof) {
+            return None;
         }
+        let end = Span::of_token(previous);
+        error.line = end.end_line;
+        error.column = end.end_column;
+        error.span = Span::point(end.end_line, end.end_column);
+        self.report_error(error);
+        Some(Token::new(TokenType::Semicolon, String::new(), end.end_line, end.end_column).with_offsets(end.end, end.end))
     }
 
     pub fn consume_one_of(
@@ -73,11 +119,7 @@ impl<'a> RecursiveDescentParser<'a> {
                 return Ok(token);
             }
         }
-        Err(ParseError::new(
-            &format!("{} (found {:?})", message, token.token_type),
-            token.line,
-            token.column,
-        ))
+        Err(ParseError::unexpected(message, &token, options.to_vec()))
     }
 
     pub fn consume_identifier(&mut self, message: &str) -> Result<Token, ParseError> {
@@ -86,15 +128,19 @@ impl<'a> RecursiveDescentParser<'a> {
             self.advance()?;
             Ok(token)
         } else {
-            Err(ParseError::new(message, token.line, token.column))
+            Err(ParseError::unexpected(
+                message,
+                &token,
+                vec![TokenType::Identifier(String::new())],
+            ))
         }
     }
 
    pub fn matches_token(&self, actual: &TokenType, expected: &TokenType) -> bool {
     match (actual, expected) {
         (TokenType::Identifier(_), TokenType::Identifier(_)) => true,
-        (TokenType::IntLiteral(_), TokenType::IntLiteral(_)) => true,
-        (TokenType::FloatLiteral(_), TokenType::FloatLiteral(_)) => true,
+        (TokenType::IntLiteral(..), TokenType::IntLiteral(..)) => true,
+        (TokenType::FloatLiteral(..), TokenType::FloatLiteral(..)) => true,
         (TokenType::StringLiteral(_), TokenType::StringLiteral(_)) => true,
         (TokenType::BooleanLiteral(_), TokenType::BooleanLiteral(_)) => true,
         (TokenType::Eof, TokenType::Eof) => true,
@@ -123,36 +169,44 @@ impl<'a> RecursiveDescentParser<'a> {
         let mut declarations = Vec::new();
 
         while !self.is_at_end() {
-            if let Ok(token) = self.current_token() {
-                if matches!(token.token_type, TokenType::Eof) {
-                    break;
-                }
-            }
+            let start = self.position;
             match StatementParser::parse_declaration(self) {
                 Ok(decl) => declarations.push(decl),
                 Err(err) => {
-                    self.semantic_errors.push(format!("Parse error: {}", err.message));
-                    ErrorRecovery::synchronize(self);
+                    let node = ErrorNode::from(&err);
+                    self.report_error(err);
+                    ErrorRecovery::synchronize_declaration(self, start);
+                    if self.position == start {
+                        let _ = self.advance();
+                    }
+                    // The error node covers the text skipped to recover
+                    declarations.push(ASTNode::Error(ErrorNode { span: self.span_from(start), ..node }));
                 }
             }
         }
 
-        Ok(ASTNode::Program(Program { declarations }))
+        let mut program = Program { declarations };
+        number_nodes(&mut program);
+        Ok(ASTNode::Program(program))
     }
 
     // Utility
-    pub fn get_semantic_errors(&self) -> &[String] {
-        &self.semantic_errors
+    /// Errors recorded while recovering, in source order of detection.
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
     }
 
-    pub fn add_semantic_error(&mut self, error: String) {
-        self.semantic_errors.push(error);
+    /// Records an error, unless it is about a token the lexer already
+    /// reported as invalid.
+    pub fn report_error(&mut self, error: ParseError) {
+        if !matches!(error.found.as_deref(), Some(TokenType::Error(_))) {
+            self.errors.push(error);
+        }
     }
 
     pub fn reset(&mut self) {
         self.position = 0;
-        self.semantic_errors.clear();
-        self.semantic_analyzer.reset();
+        self.errors.clear();
     }
 
     pub fn current_position(&self) -> usize {
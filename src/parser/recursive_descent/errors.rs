@@ -1,13 +1,19 @@
-use crate::{lexer::token::TokenType, parser::symbol_table::SymbolError};
+use crate::{lexer::token::{Token, TokenType}, parser::ast::ErrorNode};
+pub use crate::parser::span::Span;
+use crate::parser::span::NodeId;
 use super::core::RecursiveDescentParser;
 
-
-
 #[derive(Debug, Clone)]
 pub struct ParseError {
     pub message: String,
     pub line: usize,
     pub column: usize,
+    pub span: Span,
+    /// Tokens the parser would have accepted here; empty for semantic errors.
+    pub expected: Vec<TokenType>,
+    /// The token that was found instead of one of `expected`; boxed to keep
+    /// `Result<_, ParseError>` small.
+    pub found: Option<Box<TokenType>>,
 }
 
 impl std::fmt::Display for ParseError {
@@ -25,47 +31,201 @@ impl ParseError {
             message: message.into(),
             line,
             column,
+            span: Span::point(line, column),
+            expected: Vec::new(),
+            found: None,
+        }
+    }
+
+    /// An error about the source text in `span`.
+    pub fn at<M: Into<String>>(message: M, span: Span) -> Self {
+        Self { span, ..Self::new(message, span.line, span.column) }
+    }
+
+    /// A syntax error at `token`, which is not one of `expected`.
+    pub fn unexpected<M: Into<String>>(message: M, token: &Token, expected: Vec<TokenType>) -> Self {
+        Self {
+            message: message.into(),
+            line: token.line,
+            column: token.column,
+            span: Span::of_token(token),
+            expected,
+            found: Some(Box::new(token.token_type.clone())),
+        }
+    }
+
+    /// Summary of a syntax error such as `expected Semicolon or Comma, found identifier`.
+    pub fn expectation(&self) -> Option<String> {
+        let found = self.found.as_deref()?;
+        if self.expected.is_empty() {
+            return Some(format!("unexpected {}", Self::describe(found)));
+        }
+        let mut expected: Vec<String> = Vec::new();
+        for description in self.expected.iter().map(Self::describe) {
+            // Kinds of token described alike, such as int and float numbers
+            if !expected.contains(&description) {
+                expected.push(description);
+            }
+        }
+        Some(format!("expected {}, found {}", expected.join(" or "), Self::describe(found)))
+    }
+
+    fn describe(token_type: &TokenType) -> String {
+        match token_type {
+            TokenType::Identifier(_) => "identifier".to_string(),
+            TokenType::IntLiteral(..) | TokenType::FloatLiteral(..) => "number".to_string(),
+            TokenType::StringLiteral(_) | TokenType::InterpolatedString(_) => "string".to_string(),
+            TokenType::RuneLiteral(_) => "rune".to_string(),
+            TokenType::ByteLiteral(_) => "byte".to_string(),
+            TokenType::ByteStringLiteral(_) => "byte string".to_string(),
+            TokenType::BooleanLiteral(_) => "boolean".to_string(),
+            TokenType::Eof => "end of input".to_string(),
+            TokenType::Error(_) => "invalid token".to_string(),
+            other => format!("{:?}", other),
         }
     }
 }
 
 
-impl From<SymbolError> for ParseError {
-    fn from(err: SymbolError) -> Self {
-        ParseError {
-            message: err.to_string(),
-            line: 0,   
-            column: 0, 
+impl From<&ParseError> for ErrorNode {
+    fn from(err: &ParseError) -> Self {
+        ErrorNode {
+            message: err.message.clone(),
+            line: err.line,
+            column: err.column,
+            span: err.span,
+            id: NodeId::DUMMY,
         }
     }
 }
 
+/// Tokens that can begin a top-level declaration.
+const DECLARATION_START: &[TokenType] = &[
+    TokenType::Use, TokenType::Struct, TokenType::Enum, TokenType::Type,
+    TokenType::Let, TokenType::Mut, TokenType::Const, TokenType::Fn,
+    TokenType::Async, TokenType::Sync,
+    TokenType::Public, TokenType::Private, TokenType::Protected,
+];
+
+/// Keywords that can begin a statement inside a block.
+const STATEMENT_START: &[TokenType] = &[
+    TokenType::Let, TokenType::Mut, TokenType::Const, TokenType::Return,
+    TokenType::If, TokenType::While, TokenType::For,
+    TokenType::Par, TokenType::Async, TokenType::Sync,
+    TokenType::Public, TokenType::Private, TokenType::Protected,
+];
+
 pub struct ErrorRecovery;
 
 impl ErrorRecovery {
-    pub fn synchronize(parser: &mut RecursiveDescentParser) {
-        while !parser.is_at_end() {
-            if let Ok(tok) = parser.current_token() {
-                if matches!(tok.token_type, TokenType::Semicolon) {
-                    let _ = parser.advance();
-                    break;
+    /// Skips past the rest of a top-level declaration that failed after starting
+    /// at token index `start`.
+    pub fn synchronize_declaration(parser: &mut RecursiveDescentParser, start: usize) {
+        Self::synchronize(parser, start, DECLARATION_START);
+    }
+
+    /// Skips past the rest of a statement that failed after starting at token
+    /// index `start`, stopping before the `}` of the enclosing block.
+    pub fn synchronize_statement(parser: &mut RecursiveDescentParser, start: usize) {
+        Self::synchronize(parser, start, STATEMENT_START);
+    }
+
+    /// Blocks opened by the failed construct are skipped as a unit, so the
+    /// construct ends at a `;` or closing `}` at its outer level, or before a
+    /// token in `follow`. An unmatched `}` is left for the enclosing block.
+    /// Parentheses are not tracked: an unclosed `(` must not swallow the block.
+    fn synchronize(parser: &mut RecursiveDescentParser, start: usize, follow: &[TokenType]) {
+        let (mut depth, at_boundary) = Self::consumed_nesting(parser, start);
+        if at_boundary && !Self::continues_after_block(parser) {
+            // e.g. a semantic error reported after the terminating ';' was read
+            return;
+        }
+
+        while let Ok(tok) = parser.current_token() {
+            let token_type = tok.token_type.clone();
+            match token_type {
+                TokenType::Eof => return,
+                TokenType::LeftBrace => depth += 1,
+                TokenType::RightBrace if depth == 0 => return,
+                TokenType::RightBrace => {
+                    depth -= 1;
+                    if depth == 0 {
+                        let _ = parser.advance();
+                        if parser.match_tokens(&[TokenType::Semicolon]) {
+                            let _ = parser.advance();
+                        } else if Self::continues_after_block(parser) {
+                            continue;
+                        }
+                        return;
+                    }
                 }
-                if matches!(
-                    tok.token_type,
-                    TokenType::Let | TokenType::Mut | TokenType::Fn |
-                    TokenType::Struct | TokenType::Public | TokenType::Private |
-                    TokenType::Protected
-                ) {
-                    break;
+                TokenType::Semicolon if depth == 0 => {
+                    let _ = parser.advance();
+                    return;
                 }
-                if matches!(tok.token_type, TokenType::LeftBrace | TokenType::RightBrace) {
-                    break;
+                // `fn(` starts a lambda inside the failed construct, not a declaration
+                TokenType::Fn if parser.peek_token().is_some_and(|t| t.token_type == TokenType::LeftParen) => {}
+                ref other if depth == 0
+                    && parser.current_position() > start
+                    && follow.iter().any(|f| parser.matches_token(other, f)) =>
+                {
+                    return;
                 }
+                _ => {}
             }
             let _ = parser.advance();
         }
     }
 
+    /// Whether the last consumed token is a `}` that the construct goes on
+    /// after, as when an error is reported at the end of a block lambda. A
+    /// `{` continues it too: the lambda ended an `if` condition or a `match`
+    /// scrutinee.
+    fn continues_after_block(parser: &RecursiveDescentParser) -> bool {
+        let closed_block = parser
+            .current_position()
+            .checked_sub(1)
+            .and_then(|index| parser.token_at(index))
+            .is_some_and(|tok| tok.token_type == TokenType::RightBrace);
+        closed_block
+            && parser.current_token().is_ok_and(|tok| {
+                matches!(
+                    tok.token_type,
+                    TokenType::Semicolon | TokenType::Comma | TokenType::Dot |
+                    TokenType::LeftBrace | TokenType::LeftParen | TokenType::RightParen |
+                    TokenType::LeftBracket | TokenType::RightBracket |
+                    TokenType::Plus | TokenType::Minus | TokenType::Star |
+                    TokenType::Slash | TokenType::Percent |
+                    TokenType::EqualEqual | TokenType::NotEqual |
+                    TokenType::Less | TokenType::Greater |
+                    TokenType::LessEqual | TokenType::GreaterEqual |
+                    TokenType::And | TokenType::Or |
+                    TokenType::Question | TokenType::Colon | TokenType::Equal
+                )
+            })
+    }
+
+    /// Brace depth left open by the tokens consumed since `start`, and whether
+    /// they already end on a construct boundary.
+    fn consumed_nesting(parser: &RecursiveDescentParser, start: usize) -> (usize, bool) {
+        let mut depth = 0usize;
+        let mut at_boundary = false;
+        for index in start..parser.current_position() {
+            let Some(tok) = parser.token_at(index) else { break };
+            at_boundary = false;
+            match tok.token_type {
+                TokenType::LeftBrace => depth += 1,
+                TokenType::RightBrace => {
+                    depth = depth.saturating_sub(1);
+                    at_boundary = depth == 0;
+                }
+                TokenType::Semicolon => at_boundary = depth == 0,
+                _ => {}
+            }
+        }
+        (depth, at_boundary)
+    }
+
     pub fn recover_from_expression_error(parser: &mut RecursiveDescentParser) {
         while !parser.is_at_end() {
             if let Ok(tok) = parser.current_token() {
@@ -1,42 +1,61 @@
 use super::core::RecursiveDescentParser;
 use super::errors::ParseError;
-use crate::lexer::token::TokenType;
-use crate::parser::Expr;
-use crate::parser::{BinaryOp, Literal, UnaryOp};
+use super::{StatementParser, TypeParser};
+use crate::lexer::token::{StringPart, Token, TokenType};
+use crate::parser::{Expr, ExprKind};
+use crate::parser::{
+    BinaryOp, InterpolationPart, LambdaBody, LambdaParam, Literal, MatchArm, NodeId, Pattern, PatternKind, UnaryOp,
+};
 
 pub struct ExpressionParser;
 
+const RELATIONAL_PREC: u8 = 6;
+const EQUALITY_PREC: u8 = 5;
+
 impl ExpressionParser {
     pub fn parse_expression(parser: &mut RecursiveDescentParser) -> Result<Expr, ParseError> {
-        Self::parse_assignment(parser)
+        parser.expression_start = parser.current_position();
+        Self::parse_ternary(parser)
     }
 
-    fn parse_assignment(parser: &mut RecursiveDescentParser) -> Result<Expr, ParseError> {
-        let expr = Self::parse_binary_expr(parser, 0)?;
-
-        if parser.match_tokens(&[TokenType::Equal]) {
-            let equals_token = parser.current_token()?.clone();
-            parser.advance()?;
-            if let Expr::Identifier(name) = expr {
-                if let Err(err) = parser.semantic_analyzer.validate_assignment(
-                    &name,
-                    equals_token.line,
-                    equals_token.column,
-                ) {
-                    return Err(err);
-                }
-                let value = Box::new(Self::parse_assignment(parser)?);
-                return Ok(Expr::Assignment { name, value });
-            } else {
-                return Err(ParseError::new(
-                    "Invalid assignment target",
-                    equals_token.line,
-                    equals_token.column,
-                ));
-            }
+    /// `cond ? a : b`; both branches are full expressions, so ternaries nest
+    /// to the right.
+    fn parse_ternary(parser: &mut RecursiveDescentParser) -> Result<Expr, ParseError> {
+        let start = parser.current_position();
+        let condition = Self::parse_binary_expr(parser, 0)?;
+        if !parser.match_tokens(&[TokenType::Question]) {
+            return Ok(condition);
         }
+        parser.advance()?;
 
-        Ok(expr)
+        let then_branch = Self::parse_expression(parser)?;
+        parser.consume(&TokenType::Colon, "Expected ':' in ternary expression")?;
+        let else_branch = Self::parse_expression(parser)?;
+
+        let kind = ExprKind::Ternary {
+            condition: Box::new(condition),
+            then_branch: Box::new(then_branch),
+            else_branch: Box::new(else_branch),
+        };
+        Ok(Expr::new(kind, parser.span_from(start)))
+    }
+
+    /// Parses the condition of `if`/`while` or the iterable of `for`, where a
+    /// following `{` starts the body rather than a struct literal.
+    pub fn parse_condition(parser: &mut RecursiveDescentParser) -> Result<Expr, ParseError> {
+        Self::with_struct_literals(parser, false, Self::parse_expression)
+    }
+
+    fn with_struct_literals<T>(
+        parser: &mut RecursiveDescentParser,
+        allowed: bool,
+        parse: fn(&mut RecursiveDescentParser) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        let previous = parser.allow_struct_literals;
+        parser.allow_struct_literals = allowed;
+        let result = parse(parser);
+        parser.allow_struct_literals = previous;
+        result
     }
 
     fn parse_binary_expr(
@@ -53,11 +72,23 @@ impl ExpressionParser {
 
                 let _ = parser.advance()?;
                 let right = Self::parse_binary_expr(parser, prec + 1)?;
-                left = Expr::Binary {
-                    op,
-                    left: Box::new(left),
-                    right: Box::new(right),
-                };
+                let span = left.span.to(right.span);
+                left = Expr::new(ExprKind::Binary { op, left: Box::new(left), right: Box::new(right) }, span);
+
+                // Comparisons are non-associative: `a < b < c` is rejected
+                if prec == RELATIONAL_PREC || prec == EQUALITY_PREC {
+                    let next = parser.current_token()?.clone();
+                    if matches!(Self::binary_precedence(&next.token_type), Some((_, p)) if p == prec) {
+                        return Err(ParseError::unexpected(
+                            format!(
+                                "Comparison operators cannot be chained; found '{}' after a comparison, use '&&' to combine them",
+                                next.lexeme
+                            ),
+                            &next,
+                            Vec::new(),
+                        ));
+                    }
+                }
             } else {
                 break;
             }
@@ -67,60 +98,463 @@ impl ExpressionParser {
     }
 
     fn parse_unary(parser: &mut RecursiveDescentParser) -> Result<Expr, ParseError> {
+        let start = parser.current_position();
+        if parser.match_tokens(&[TokenType::Await]) {
+            parser.advance()?;
+            let expr = Self::parse_unary(parser)?;
+            return Ok(Expr::new(ExprKind::Await(Box::new(expr)), parser.span_from(start)));
+        }
         if let Ok(token) = parser.current_token() {
             if let Some(op) = UnaryOp::from_token(&token.token_type) {
                 parser.advance()?;
                 let expr = Self::parse_unary(parser)?;
-                return Ok(Expr::Unary {
-                    op,
-                    expr: Box::new(expr),
-                });
-            } else if matches!(token.token_type, TokenType::LeftParen) {
+                return Ok(Expr::new(ExprKind::Unary { op, expr: Box::new(expr) }, parser.span_from(start)));
+            }
+        }
+        Self::parse_postfix(parser)
+    }
+
+    fn parse_postfix(parser: &mut RecursiveDescentParser) -> Result<Expr, ParseError> {
+        let start = parser.current_position();
+        let mut expr = Self::parse_primary(parser)?;
+
+        loop {
+            let kind = if parser.match_tokens(&[TokenType::Dot]) {
                 parser.advance()?;
-                let expr = Self::parse_expression(parser)?;
-                parser.consume(&TokenType::RightParen, "Expected ')' after expression")?;
-                return Ok(Expr::Grouping(Box::new(expr)));
+                let member = parser.consume_identifier("Expected member name after '.'")?;
+                ExprKind::Member { object: Box::new(expr), member: member.lexeme }
+            } else if parser.match_tokens(&[TokenType::LeftParen]) {
+                let args = Self::parse_arguments(parser)?;
+                ExprKind::Call { callee: Box::new(expr), args }
+            } else if parser.match_tokens(&[TokenType::LeftBracket]) {
+                parser.advance()?;
+                let index = Self::with_struct_literals(parser, true, Self::parse_expression)?;
+                parser.consume(&TokenType::RightBracket, "Expected ']' after index")?;
+                ExprKind::Index { object: Box::new(expr), index: Box::new(index) }
+            } else {
+                break;
+            };
+            expr = Expr::new(kind, parser.span_from(start));
+        }
+
+        Ok(expr)
+    }
+
+    /// Parses a parenthesised, comma-separated argument list.
+    fn parse_arguments(parser: &mut RecursiveDescentParser) -> Result<Vec<Expr>, ParseError> {
+        parser.consume(&TokenType::LeftParen, "Expected '(' before arguments")?;
+
+        let mut args = Vec::new();
+        while !parser.match_tokens(&[TokenType::RightParen]) {
+            args.push(Self::with_struct_literals(parser, true, Self::parse_expression)?);
+            if parser.match_tokens(&[TokenType::Comma]) {
+                parser.advance()?;
+            } else {
+                break;
             }
         }
-        Self::parse_primary(parser)
+        parser.consume(&TokenType::RightParen, "Expected ')' after arguments")?;
+
+        Ok(args)
     }
 
     fn parse_primary(parser: &mut RecursiveDescentParser) -> Result<Expr, ParseError> {
+        let start = parser.current_position();
+        let token = parser.current_token()?;
+        let literal = match &token.token_type {
+            TokenType::StringLiteral(s) => Some(Literal::String(s.clone())),
+            TokenType::RuneLiteral(c) => Some(Literal::Rune(*c)),
+            TokenType::ByteLiteral(b) => Some(Literal::Byte(*b)),
+            TokenType::ByteStringLiteral(bytes) => Some(Literal::ByteString(bytes.clone())),
+            TokenType::BooleanLiteral(b) => Some(Literal::Boolean(*b)),
+            other => Literal::number(other),
+        };
+        if let Some(literal) = literal {
+            parser.advance()?;
+            return Ok(Expr::new(ExprKind::Literal(literal), parser.span_from(start)));
+        }
+
         let token = parser.current_token()?;
         match &token.token_type {
-            TokenType::IntLiteral(n) => {
-                let val = *n;
+            TokenType::Identifier(name) => {
+                let mut id = name.clone();
                 parser.advance()?;
-                Ok(Expr::Literal(Literal::Int(val)))
+                // `module.Type { ... }`; other dotted names are member accesses
+                // until name resolution tells enum variants and module items apart
+                if let Some(TokenType::Identifier(member)) = parser.peek_token().map(|t| &t.token_type) {
+                    if parser.match_tokens(&[TokenType::Dot]) && Self::at_struct_literal(parser, 2) {
+                        id = format!("{}.{}", id, member);
+                        parser.advance()?;
+                        parser.advance()?;
+                    }
+                }
+                if Self::at_struct_literal(parser, 0) {
+                    return Self::parse_struct_literal(parser, id, start);
+                }
+                Ok(Expr::new(ExprKind::Identifier(id), parser.span_from(start)))
             }
-            TokenType::FloatLiteral(f) => {
-                let val = *f;
+            TokenType::InterpolatedString(parts) => {
+                let parts = parts.clone();
                 parser.advance()?;
-                Ok(Expr::Literal(Literal::Float(val)))
+                let parts = parts
+                    .into_iter()
+                    .map(|part| match part {
+                        StringPart::Literal(text) => Ok(InterpolationPart::Literal(text)),
+                        StringPart::Expr(tokens) => Self::parse_interpolation(parser, tokens).map(|expr| InterpolationPart::Expr(Box::new(expr))),
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(Expr::new(ExprKind::Interpolated { parts }, parser.span_from(start)))
+            }
+            TokenType::Match => Self::parse_match(parser),
+            TokenType::Spawn => Self::parse_spawn(parser),
+            TokenType::Fn => {
+                // An `=>` body extends as far right as possible, so such a
+                // lambda can only start an expression, never be an operand
+                let starts_expression = parser.current_position() == parser.expression_start;
+                Self::parse_lambda(parser, starts_expression)
             }
-            TokenType::StringLiteral(s) => {
-                let val = s.clone();
+            TokenType::LeftBracket => Self::parse_array_literal(parser),
+            TokenType::LeftBrace if parser.allow_struct_literals => Self::parse_object_literal(parser),
+            TokenType::LeftParen => {
                 parser.advance()?;
-                Ok(Expr::Literal(Literal::String(val)))
+                let expr = Self::with_struct_literals(parser, true, Self::parse_expression)?;
+                parser.consume(&TokenType::RightParen, "Expected ')' after expression")?;
+                Ok(Expr::new(ExprKind::Grouping(Box::new(expr)), parser.span_from(start)))
             }
-            TokenType::BooleanLiteral(b) => {
-                let val = *b;
+            _ => Err(ParseError::unexpected(
+                "Expected expression",
+                token,
+                Self::expression_start(parser.allow_struct_literals),
+            )),
+        }
+    }
+
+    /// The tokens an expression can start with; `{` only where object
+    /// literals are allowed.
+    fn expression_start(allow_object_literals: bool) -> Vec<TokenType> {
+        let mut tokens = vec![
+            TokenType::Identifier(String::new()),
+            TokenType::IntLiteral(0, None),
+            TokenType::FloatLiteral(0.0, None),
+            TokenType::StringLiteral(String::new()),
+            TokenType::RuneLiteral('\0'),
+            TokenType::ByteLiteral(0),
+            TokenType::ByteStringLiteral(Vec::new()),
+            TokenType::BooleanLiteral(false),
+            TokenType::LeftParen,
+            TokenType::LeftBracket,
+        ];
+        if allow_object_literals {
+            tokens.push(TokenType::LeftBrace);
+        }
+        tokens.extend([
+            TokenType::Not,
+            TokenType::Minus,
+            TokenType::Plus,
+            TokenType::Await,
+            TokenType::Spawn,
+            TokenType::Match,
+            TokenType::Fn,
+        ]);
+        tokens
+    }
+
+    /// `Name {` starts a struct literal when followed by `}` or `field:`;
+    /// `offset` is the position of the `{` relative to the current token.
+    fn at_struct_literal(parser: &RecursiveDescentParser, offset: usize) -> bool {
+        let token_type = |extra: usize| parser.peek_token_at(offset + extra).map(|t| &t.token_type);
+        if !parser.allow_struct_literals || !matches!(token_type(0), Some(TokenType::LeftBrace)) {
+            return false;
+        }
+        match token_type(1) {
+            Some(TokenType::RightBrace) => true,
+            Some(TokenType::Identifier(_)) => matches!(token_type(2), Some(TokenType::Colon)),
+            _ => false,
+        }
+    }
+
+    /// `start` is the position of the struct name's first token.
+    fn parse_struct_literal(
+        parser: &mut RecursiveDescentParser,
+        name: String,
+        start: usize,
+    ) -> Result<Expr, ParseError> {
+        parser.consume(&TokenType::LeftBrace, "Expected '{' to start struct literal")?;
+
+        let mut fields = Vec::new();
+        while !parser.match_tokens(&[TokenType::RightBrace]) {
+            let field = parser.consume_identifier("Expected field name")?;
+            parser.consume(&TokenType::Colon, "Expected ':' after field name")?;
+            let value = Self::parse_expression(parser)?;
+            fields.push((field.lexeme, value));
+
+            if parser.match_tokens(&[TokenType::Comma]) {
+                parser.advance()?;
+            } else {
+                break;
+            }
+        }
+        parser.consume(&TokenType::RightBrace, "Expected '}' to close struct literal")?;
+
+        Ok(Expr::new(ExprKind::StructLiteral { name, fields }, parser.span_from(start)))
+    }
+
+    fn parse_spawn(parser: &mut RecursiveDescentParser) -> Result<Expr, ParseError> {
+        let start = parser.current_position();
+        parser.consume(&TokenType::Spawn, "Expected 'spawn'")?;
+        let call = Self::parse_postfix(parser)?;
+        if !matches!(call.kind, ExprKind::Call { .. }) {
+            return Err(ParseError::at(
+                "'spawn' must be followed by a function call",
+                parser.span_from(start),
+            ));
+        }
+        Ok(Expr::new(ExprKind::Spawn(Box::new(call)), parser.span_from(start)))
+    }
+
+    /// `fn(params) [-> Type] => expr` or `fn(params) [-> Type] { ... }`.
+    /// Parameter types may be left out of the expression form only.
+    fn parse_lambda(
+        parser: &mut RecursiveDescentParser,
+        allow_expression_body: bool,
+    ) -> Result<Expr, ParseError> {
+        let start = parser.current_position();
+        parser.consume(&TokenType::Fn, "Expected 'fn'")?;
+        parser.consume(&TokenType::LeftParen, "Expected '(' after 'fn' in lambda")?;
+
+        let mut params = Vec::new();
+        while !parser.match_tokens(&[TokenType::RightParen]) {
+            let name_token = parser.consume_identifier("Expected lambda parameter name")?;
+            let param_type = if parser.match_tokens(&[TokenType::Colon]) {
+                parser.advance()?;
+                Some(TypeParser::parse_type(parser)?)
+            } else {
+                None
+            };
+            params.push(LambdaParam { name: name_token.lexeme, param_type });
+
+            if parser.match_tokens(&[TokenType::Comma]) {
+                parser.advance()?;
+            } else {
+                break;
+            }
+        }
+        parser.consume(&TokenType::RightParen, "Expected ')' after lambda parameters")?;
+
+        let return_type = if parser.match_tokens(&[TokenType::Arrow]) {
+            parser.advance()?;
+            Some(TypeParser::parse_type(parser)?)
+        } else {
+            None
+        };
+
+        let body = if parser.match_tokens(&[TokenType::FatArrow]) {
+            let arrow = parser.advance()?.clone();
+            if !allow_expression_body {
+                return Err(ParseError::unexpected(
+                    "A lambda with an '=>' body must be parenthesised when used as an operand",
+                    &arrow,
+                    vec![TokenType::LeftBrace],
+                ));
+            }
+            // Like an operand, the body keeps the header's struct literal rule
+            let body = Self::parse_expression(parser)?;
+            LambdaBody::Expr(Box::new(body))
+        } else {
+            LambdaBody::Block(Self::with_struct_literals(parser, true, StatementParser::parse_block)?)
+        };
+
+        let kind = ExprKind::Lambda { params, return_type, body, captures: Vec::new() };
+        Ok(Expr::new(kind, parser.span_from(start)))
+    }
+
+    fn parse_array_literal(parser: &mut RecursiveDescentParser) -> Result<Expr, ParseError> {
+        let start = parser.current_position();
+        parser.consume(&TokenType::LeftBracket, "Expected '['")?;
+
+        let mut elements = Vec::new();
+        while !parser.match_tokens(&[TokenType::RightBracket]) {
+            elements.push(Self::with_struct_literals(parser, true, Self::parse_expression)?);
+            if parser.match_tokens(&[TokenType::Comma]) {
+                parser.advance()?;
+            } else {
+                break;
+            }
+        }
+        parser.consume(&TokenType::RightBracket, "Expected ']' after array elements")?;
+
+        Ok(Expr::new(ExprKind::ArrayLiteral(elements), parser.span_from(start)))
+    }
+
+    fn parse_object_literal(parser: &mut RecursiveDescentParser) -> Result<Expr, ParseError> {
+        let start = parser.current_position();
+        parser.consume(&TokenType::LeftBrace, "Expected '{'")?;
+
+        let mut fields = Vec::new();
+        while !parser.match_tokens(&[TokenType::RightBrace]) {
+            let key_token = parser.current_token()?.clone();
+            let key = match &key_token.token_type {
+                TokenType::Identifier(name) => name.clone(),
+                TokenType::StringLiteral(text) => text.clone(),
+                _ => {
+                    return Err(ParseError::unexpected(
+                        "Expected property name",
+                        &key_token,
+                        vec![TokenType::Identifier(String::new()), TokenType::StringLiteral(String::new())],
+                    ))
+                }
+            };
+            parser.advance()?;
+            parser.consume(&TokenType::Colon, "Expected ':' after property name")?;
+            let value = Self::parse_expression(parser)?;
+            fields.push((key, value));
+
+            if parser.match_tokens(&[TokenType::Comma]) {
+                parser.advance()?;
+            } else {
+                break;
+            }
+        }
+        parser.consume(&TokenType::RightBrace, "Expected '}' after object properties")?;
+
+        Ok(Expr::new(ExprKind::ObjectLiteral(fields), parser.span_from(start)))
+    }
+
+    fn parse_match(parser: &mut RecursiveDescentParser) -> Result<Expr, ParseError> {
+        let start = parser.current_position();
+        parser.consume(&TokenType::Match, "Expected 'match'")?;
+        let scrutinee = Self::parse_condition(parser)?;
+        parser.consume(&TokenType::LeftBrace, "Expected '{' after match value")?;
+
+        let mut arms = Vec::new();
+        while !parser.match_tokens(&[TokenType::RightBrace]) {
+            let arm_start = parser.current_position();
+            let pattern = Self::parse_pattern(parser)?;
+            parser.consume(&TokenType::FatArrow, "Expected '=>' after match pattern")?;
+            let body = Self::with_struct_literals(parser, true, Self::parse_expression)?;
+            arms.push(MatchArm { pattern, body, span: parser.span_from(arm_start), id: NodeId::DUMMY });
+
+            if parser.match_tokens(&[TokenType::Comma]) {
+                parser.advance()?;
+            } else {
+                break;
+            }
+        }
+        parser.consume(&TokenType::RightBrace, "Expected '}' to close match")?;
+
+        let kind = ExprKind::Match { scrutinee: Box::new(scrutinee), arms };
+        Ok(Expr::new(kind, parser.span_from(start)))
+    }
+
+    fn parse_pattern(parser: &mut RecursiveDescentParser) -> Result<Pattern, ParseError> {
+        let start = parser.current_position();
+        let kind = Self::parse_pattern_kind(parser)?;
+        Ok(Pattern::new(kind, parser.span_from(start)))
+    }
+
+    fn parse_pattern_kind(parser: &mut RecursiveDescentParser) -> Result<PatternKind, ParseError> {
+        let token = parser.current_token()?.clone();
+        match &token.token_type {
+            TokenType::Identifier(name) if name == "_" => {
                 parser.advance()?;
-                Ok(Expr::Literal(Literal::Boolean(val)))
+                Ok(PatternKind::Wildcard)
             }
             TokenType::Identifier(name) => {
-                let id = name.clone();
                 parser.advance()?;
-                Ok(Expr::Identifier(id))
+                if !parser.match_tokens(&[TokenType::Dot]) {
+                    return Ok(PatternKind::Binding(name.clone()));
+                }
+                parser.advance()?;
+                let mut name = name.clone();
+                let mut variant = parser.consume_identifier("Expected variant name in pattern")?;
+                // `module.Enum.Variant`
+                if parser.match_tokens(&[TokenType::Dot]) {
+                    parser.advance()?;
+                    name = format!("{}.{}", name, variant.lexeme);
+                    variant = parser.consume_identifier("Expected variant name in pattern")?;
+                }
+
+                let mut fields = Vec::new();
+                if parser.match_tokens(&[TokenType::LeftParen]) {
+                    parser.advance()?;
+                    while !parser.match_tokens(&[TokenType::RightParen]) {
+                        fields.push(Self::parse_pattern(parser)?);
+                        if parser.match_tokens(&[TokenType::Comma]) {
+                            parser.advance()?;
+                        } else {
+                            break;
+                        }
+                    }
+                    parser.consume(&TokenType::RightParen, "Expected ')' after variant pattern")?;
+                }
+
+                Ok(PatternKind::Variant {
+                    enum_name: name,
+                    variant: variant.lexeme,
+                    fields,
+                })
+            }
+            TokenType::Minus => {
+                parser.advance()?;
+                let number = parser.current_token()?.clone();
+                parser.advance()?;
+                match Literal::number(&number.token_type) {
+                    Some(literal) => Ok(PatternKind::Literal(literal.negated())),
+                    None => Err(ParseError::unexpected(
+                        "Expected number after '-' in pattern",
+                        &number,
+                        vec![TokenType::IntLiteral(0, None), TokenType::FloatLiteral(0.0, None)],
+                    )),
+                }
+            }
+            TokenType::IntLiteral(..) | TokenType::FloatLiteral(..) => {
+                let literal = Literal::number(&token.token_type);
+                parser.advance()?;
+                Ok(PatternKind::Literal(literal.expect("number tokens are numbers")))
+            }
+            TokenType::StringLiteral(text) => {
+                parser.advance()?;
+                Ok(PatternKind::Literal(Literal::String(text.clone())))
             }
-            _ => Err(ParseError::new(
-                &format!("Expected expression, found {:?}", token.token_type),
-                token.line,
-                token.column,
+            TokenType::RuneLiteral(c) => {
+                parser.advance()?;
+                Ok(PatternKind::Literal(Literal::Rune(*c)))
+            }
+            TokenType::ByteLiteral(b) => {
+                parser.advance()?;
+                Ok(PatternKind::Literal(Literal::Byte(*b)))
+            }
+            TokenType::BooleanLiteral(b) => {
+                parser.advance()?;
+                Ok(PatternKind::Literal(Literal::Boolean(*b)))
+            }
+            _ => Err(ParseError::unexpected(
+                "Expected pattern",
+                &token,
+                Vec::new(),
             )),
         }
     }
 
+    /// The expression of one `${...}`, parsed on its own tokens. Errors
+    /// recovered from inside it are reported through `parser`.
+    fn parse_interpolation(parser: &mut RecursiveDescentParser, tokens: Vec<Token>) -> Result<Expr, ParseError> {
+        let mut inner = RecursiveDescentParser::new(tokens);
+        let expr = Self::parse_expression(&mut inner);
+        inner.errors().iter().for_each(|err| parser.report_error(err.clone()));
+        let expr = expr?;
+
+        let rest = inner.current_token()?.clone();
+        if !matches!(rest.token_type, TokenType::Eof) {
+            return Err(ParseError::unexpected(
+                "Expected '}' after interpolated expression",
+                &rest,
+                vec![TokenType::RightBrace],
+            ));
+        }
+        Ok(expr)
+    }
+
     fn binary_precedence(token: &TokenType) -> Option<(BinaryOp, u8)> {
         match token {
             TokenType::Star | TokenType::Slash | TokenType::Percent => Some((
@@ -140,22 +574,26 @@ impl ExpressionParser {
                 },
                 10,
             )),
-            TokenType::EqualEqual
-            | TokenType::NotEqual
-            | TokenType::Less
+            TokenType::Less
             | TokenType::LessEqual
             | TokenType::Greater
             | TokenType::GreaterEqual => Some((
                 match token {
-                    TokenType::EqualEqual => BinaryOp::Equal,
-                    TokenType::NotEqual => BinaryOp::NotEqual,
                     TokenType::Less => BinaryOp::Less,
                     TokenType::LessEqual => BinaryOp::LessEqual,
                     TokenType::Greater => BinaryOp::Greater,
                     TokenType::GreaterEqual => BinaryOp::GreaterEqual,
                     _ => unreachable!(),
                 },
-                5,
+                RELATIONAL_PREC,
+            )),
+            TokenType::EqualEqual | TokenType::NotEqual => Some((
+                match token {
+                    TokenType::EqualEqual => BinaryOp::Equal,
+                    TokenType::NotEqual => BinaryOp::NotEqual,
+                    _ => unreachable!(),
+                },
+                EQUALITY_PREC,
             )),
             TokenType::And => Some((BinaryOp::And, 3)),
             TokenType::Or => Some((BinaryOp::Or, 2)),
@@ -9,4 +9,3 @@ pub use expressions::ExpressionParser;
 pub use statements::StatementParser;
 pub use types::TypeParser;
 pub use errors::ErrorRecovery;
-pub use crate::parser::semantic::analyzer;
\ No newline at end of file
@@ -1,16 +1,48 @@
-use crate::lexer::token::TokenType;
+use crate::lexer::token::{Token, TokenType};
 use crate::parser::recursive_descent::{ExpressionParser, TypeParser};
-use crate::parser::{ast::*, Expr};
-use super::errors::ParseError;
-use crate::parser::{Type};
+use crate::parser::{ast::*, Expr, NodeId, Type};
+use super::errors::{ErrorRecovery, ParseError};
 use super::core::RecursiveDescentParser;
 
 pub struct StatementParser;
 
+struct FunctionHeader {
+    /// Position of the header's first token.
+    start: usize,
+    visibility: Option<Visibility>,
+    concurrency: Option<ConcurrencyModifier>,
+    name_token: Token,
+    type_params: Vec<String>,
+    parameters: Vec<Parameter>,
+    return_type: Option<Type>,
+}
+
 impl StatementParser {
     pub fn parse_declaration(parser: &mut RecursiveDescentParser) -> Result<ASTNode, ParseError> {
-        let var_decl = Self::parse_var_decl(parser)?;
-        Ok(ASTNode::VarDecl(var_decl))
+        match Self::peek_past_visibility(parser) {
+            Some(TokenType::Fn | TokenType::Async | TokenType::Sync) => {
+                Ok(ASTNode::FnDecl(Self::parse_function_decl(parser)?))
+            }
+            Some(TokenType::Struct) => Ok(ASTNode::StructDecl(Self::parse_struct_decl(parser)?)),
+            Some(TokenType::Enum) => Ok(ASTNode::EnumDecl(Self::parse_enum_decl(parser)?)),
+            Some(TokenType::Type) => Ok(ASTNode::TypeAlias(Self::parse_type_alias(parser)?)),
+            Some(TokenType::Use) => Ok(ASTNode::Import(Self::parse_import(parser)?)),
+            Some(TokenType::Const) => Ok(ASTNode::ConstDecl(Self::parse_const_decl(parser)?)),
+            _ => Ok(ASTNode::VarDecl(Self::parse_var_decl(parser)?)),
+        }
+    }
+
+    /// Returns the token type following an optional visibility modifier.
+    fn peek_past_visibility(parser: &RecursiveDescentParser) -> Option<&TokenType> {
+        let token = parser.current_token().ok()?;
+        if matches!(
+            token.token_type,
+            TokenType::Public | TokenType::Private | TokenType::Protected
+        ) {
+            parser.peek_token().map(|t| &t.token_type)
+        } else {
+            Some(&token.token_type)
+        }
     }
 
     pub fn parse_expression(parser: &mut RecursiveDescentParser) -> Result<Expr, ParseError> {
@@ -18,6 +50,7 @@ impl StatementParser {
     }
 
     pub fn parse_var_decl(parser: &mut RecursiveDescentParser) -> Result<VarDecl, ParseError> {
+        let start = parser.current_position();
         let start_token = parser.current_token()?.clone();
 
         // 1. Parse visibility
@@ -57,35 +90,18 @@ impl StatementParser {
         // 6. Semicolon
         parser.consume(&TokenType::Semicolon, "Expected ';' after variable declaration")?;
 
-        // 7. Semantic analysis
-        let var_decl = parser.semantic_analyzer.analyze_var_declaration(
+        Ok(VarDecl {
             visibility,
             mutability,
             name,
             declared_type,
+            inferred_type: None,
             initializer,
-            start_token.line,
-            start_token.column,
-        )?;
-
-        // 8. Update symbol table
-        // Fixed: Access the inferred_type field properly
-        if let Some(ref inferred_type) = var_decl.inferred_type {
-            if let Err(err) = parser.semantic_analyzer.get_symbol_table_mut().declare_variable(
-                var_decl.name.clone(),
-                inferred_type.clone(),
-                var_decl.visibility.clone(),
-                var_decl.mutability.clone(),
-                var_decl.initializer.is_some(),
-                start_token.line,
-                start_token.column,
-            ) {
-                parser.add_semantic_error(err.to_string());
-            }
-        }
-
-
-        Ok(var_decl)
+            line: start_token.line,
+            column: start_token.column,
+            span: parser.span_from(start),
+            id: NodeId::DUMMY,
+        })
     }
 
     fn parse_visibility(parser: &mut RecursiveDescentParser) -> Result<Option<Visibility>, ParseError> {
@@ -101,64 +117,57 @@ impl StatementParser {
         }
     }
 
-    pub fn parse_function_decl(parser: &mut RecursiveDescentParser) -> Result<FnDecl, ParseError>{
-        // 1. Visibility (optional)
-        let visibility = if parser.match_tokens(&[
-            TokenType::Public,
-            TokenType::Private,
-            TokenType::Protected,
-        ]) {
-            let vis_token = parser.advance()?;
-            Some(match vis_token.token_type {
-                TokenType::Public => Visibility::Public,
-                TokenType::Private => Visibility::Private,
-                TokenType::Protected => Visibility::Protected,
-                _ => unreachable!(),
-            })
+    fn parse_concurrency(parser: &mut RecursiveDescentParser) -> Result<Option<ConcurrencyModifier>, ParseError> {
+        if parser.match_tokens(&[TokenType::Async, TokenType::Sync]) {
+            match parser.advance()?.token_type {
+                TokenType::Async => Ok(Some(ConcurrencyModifier::Async)),
+                TokenType::Sync => Ok(Some(ConcurrencyModifier::Sync)),
+                _ => Ok(None),
+            }
         } else {
-            None
-        };
+            Ok(None)
+        }
+    }
+
+    pub fn parse_function_decl(parser: &mut RecursiveDescentParser) -> Result<FnDecl, ParseError> {
+        let header = Self::parse_function_header(parser)?;
+        Self::parse_function_body(parser, header)
+    }
+
+    fn parse_function_header(parser: &mut RecursiveDescentParser) -> Result<FunctionHeader, ParseError> {
+        let start = parser.current_position();
+
+        // 1. Modifiers (optional)
+        let visibility = Self::parse_visibility(parser)?;
+        let concurrency = Self::parse_concurrency(parser)?;
 
         // 2. 'fn' keyword
         parser.consume(&TokenType::Fn, "Expected 'fn' keyword")?;
 
         // 3. Function name
         let name_token = parser.consume_identifier("Expected function name")?;
-        let name = if let TokenType::Identifier(n) = name_token.token_type {
-            n
-        } else {
-            unreachable!()
-        };
+        let type_params = TypeParser::parse_type_params(parser)?;
 
         // 4. Parameter list
         parser.consume(&TokenType::LeftParen, "Expected '(' before parameters")?;
         let mut parameters = Vec::new();
         while !parser.match_tokens(&[TokenType::RightParen]) {
-            // parameter name
+            let param_start = parser.current_position();
             let param_token = parser.consume_identifier("Expected parameter name")?;
-            let param_name = if let TokenType::Identifier(n) = param_token.token_type {
-                n
-            } else { unreachable!() };
-
-            // colon
             parser.consume(&TokenType::Colon, "Expected ':' after parameter name")?;
-
-            // parameter type
-            let type_token = parser.consume_identifier("Expected parameter type")?;
-           let param_type = Type::from_type_name(&type_token.lexeme)
-           .ok_or_else(|| ParseError::new("Invalid parameter type", type_token.line, type_token.column))?;
-
+            let param_type = TypeParser::parse_type(parser)?;
 
             parameters.push(Parameter {
-                name: param_name,
+                name: param_token.lexeme.clone(),
                 param_type,
                 line: param_token.line,
                 column: param_token.column,
+                span: parser.span_from(param_start),
+                id: NodeId::DUMMY,
             });
 
-            // comma or end
             if parser.match_tokens(&[TokenType::Comma]) {
-                let _ = parser.advance()?;
+                parser.advance()?;
             } else {
                 break;
             }
@@ -167,36 +176,436 @@ impl StatementParser {
 
         // 5. Optional return type
         let return_type = if parser.match_tokens(&[TokenType::Arrow]) {
-            let _ = parser.advance()?; // consume '->'
-            let ret_token = parser.consume_identifier("Expected return type")?;
-            Some(Type::from_type_name(&ret_token.lexeme)
-                .ok_or_else(|| ParseError::new("Invalid return type", ret_token.line, ret_token.column))?)
+            parser.advance()?;
+            Some(TypeParser::parse_type(parser)?)
         } else {
             None
         };
 
-        // 6. Function body
-        parser.consume(&TokenType::LeftBrace, "Expected '{' to start function body")?;
-        let mut body_exprs = Vec::new();
+        Ok(FunctionHeader {
+            start,
+            visibility,
+            concurrency,
+            name_token,
+            type_params,
+            parameters,
+            return_type,
+        })
+    }
+
+    fn parse_function_body(
+        parser: &mut RecursiveDescentParser,
+        header: FunctionHeader,
+    ) -> Result<FnDecl, ParseError> {
+        let body = Self::parse_block(parser)?;
+
+        Ok(FnDecl {
+            visibility: header.visibility,
+            concurrency: header.concurrency,
+            name: header.name_token.lexeme,
+            type_params: header.type_params,
+            parameters: header.parameters,
+            return_type: header.return_type,
+            body,
+            line: header.name_token.line,
+            column: header.name_token.column,
+            span: parser.span_from(header.start),
+            id: NodeId::DUMMY,
+        })
+    }
+
+    pub fn parse_struct_decl(parser: &mut RecursiveDescentParser) -> Result<StructDecl, ParseError> {
+        let start = parser.current_position();
+        let start_token = parser.current_token()?.clone();
 
+        let visibility = Self::parse_visibility(parser)?;
+        parser.consume(&TokenType::Struct, "Expected 'struct'")?;
+        let name_tok = parser.consume_identifier("Expected struct name")?;
+        let name = name_tok.lexeme.clone();
+        let type_params = TypeParser::parse_type_params(parser)?;
+        let (fields, methods) = Self::parse_struct_members(parser)?;
+
+        Ok(StructDecl {
+            visibility,
+            name,
+            type_params,
+            fields,
+            methods,
+            line: start_token.line,
+            column: start_token.column,
+            span: parser.span_from(start),
+            id: NodeId::DUMMY,
+        })
+    }
+
+    fn parse_struct_members(
+        parser: &mut RecursiveDescentParser,
+    ) -> Result<(Vec<FieldDecl>, Vec<FnDecl>), ParseError> {
+        parser.consume(&TokenType::LeftBrace, "Expected '{' after struct name")?;
+
+        let mut fields = Vec::new();
+        let mut methods = Vec::new();
         while !parser.match_tokens(&[TokenType::RightBrace]) && !parser.is_at_end() {
-            // Ini sementara parse statement sebagai Expr
-            let expr = super::statements::StatementParser::parse_expression(parser)?;
-            body_exprs.push(expr);
+            match Self::peek_past_visibility(parser) {
+                Some(TokenType::Fn | TokenType::Async | TokenType::Sync) => {
+                    methods.push(Self::parse_function_decl(parser)?);
+                }
+                _ => fields.push(Self::parse_field_decl(parser)?),
+            }
         }
 
-        parser.consume(&TokenType::RightBrace, "Expected '}' to close function body")?;
+        parser.consume(&TokenType::RightBrace, "Expected '}' to close struct body")?;
+        Ok((fields, methods))
+    }
 
-        let body = Expr::Block(body_exprs);
+    pub fn parse_enum_decl(parser: &mut RecursiveDescentParser) -> Result<EnumDecl, ParseError> {
+        let start = parser.current_position();
+        let start_token = parser.current_token()?.clone();
 
-        Ok(FnDecl {
+        let visibility = Self::parse_visibility(parser)?;
+        parser.consume(&TokenType::Enum, "Expected 'enum'")?;
+        let name_tok = parser.consume_identifier("Expected enum name")?;
+        let type_params = TypeParser::parse_type_params(parser)?;
+        parser.consume(&TokenType::LeftBrace, "Expected '{' after enum name")?;
+
+        let mut variants = Vec::new();
+        while !parser.match_tokens(&[TokenType::RightBrace]) && !parser.is_at_end() {
+            let variant_start = parser.current_position();
+            let variant_tok = parser.consume_identifier("Expected enum variant name")?;
+
+            let mut payload = Vec::new();
+            if parser.match_tokens(&[TokenType::LeftParen]) {
+                parser.advance()?;
+                loop {
+                    payload.push(TypeParser::parse_type(parser)?);
+                    if parser.match_tokens(&[TokenType::Comma]) {
+                        parser.advance()?;
+                    } else {
+                        break;
+                    }
+                }
+                parser.consume(&TokenType::RightParen, "Expected ')' after variant payload")?;
+            }
+
+            variants.push(EnumVariant {
+                name: variant_tok.lexeme.clone(),
+                payload,
+                line: variant_tok.line,
+                column: variant_tok.column,
+                span: parser.span_from(variant_start),
+                id: NodeId::DUMMY,
+            });
+
+            // Variants may optionally be separated by commas
+            if parser.match_tokens(&[TokenType::Comma]) {
+                parser.advance()?;
+            }
+        }
+        parser.consume(&TokenType::RightBrace, "Expected '}' to close enum body")?;
+
+        Ok(EnumDecl {
             visibility,
-            name,
-            parameters,
-            return_type,
+            name: name_tok.lexeme,
+            type_params,
+            variants,
+            line: start_token.line,
+            column: start_token.column,
+            span: parser.span_from(start),
+            id: NodeId::DUMMY,
+        })
+    }
+
+    pub fn parse_import(parser: &mut RecursiveDescentParser) -> Result<ImportDecl, ParseError> {
+        let start = parser.current_position();
+        let use_token = parser.consume(&TokenType::Use, "Expected 'use'")?;
+
+        let mut path = vec![parser.consume_identifier("Expected module name after 'use'")?.lexeme];
+        while parser.match_tokens(&[TokenType::Dot]) {
+            parser.advance()?;
+            path.push(parser.consume_identifier("Expected module name after '.'")?.lexeme);
+        }
+        parser.consume(&TokenType::Semicolon, "Expected ';' after import")?;
+
+        Ok(ImportDecl {
+            path,
+            line: use_token.line,
+            column: use_token.column,
+            span: parser.span_from(start),
+            id: NodeId::DUMMY,
+        })
+    }
+
+    pub fn parse_type_alias(parser: &mut RecursiveDescentParser) -> Result<TypeAliasDecl, ParseError> {
+        let start = parser.current_position();
+        let start_token = parser.current_token()?.clone();
+
+        let visibility = Self::parse_visibility(parser)?;
+        parser.consume(&TokenType::Type, "Expected 'type'")?;
+        let name_tok = parser.consume_identifier("Expected alias name after 'type'")?;
+        parser.consume(&TokenType::Equal, "Expected '=' after alias name")?;
+        let aliased_type = TypeParser::parse_type(parser)?;
+        parser.consume(&TokenType::Semicolon, "Expected ';' after type alias")?;
+
+        Ok(TypeAliasDecl {
+            visibility,
+            name: name_tok.lexeme,
+            aliased_type,
+            line: start_token.line,
+            column: start_token.column,
+            span: parser.span_from(start),
+            id: NodeId::DUMMY,
+        })
+    }
+
+    fn parse_field_decl(parser: &mut RecursiveDescentParser) -> Result<FieldDecl, ParseError> {
+        let start = parser.current_position();
+        let visibility = Self::parse_visibility(parser)?;
+        let name_tok = parser.consume_identifier("Expected field name")?;
+        parser.consume(&TokenType::Colon, "Expected ':' after field name")?;
+        let field_type = TypeParser::parse_type(parser)?;
+        parser.consume(&TokenType::Semicolon, "Expected ';' after field declaration")?;
+
+        Ok(FieldDecl {
+            visibility,
+            name: name_tok.lexeme.clone(),
+            field_type,
+            line: name_tok.line,
+            column: name_tok.column,
+            span: parser.span_from(start),
+            id: NodeId::DUMMY,
+        })
+    }
+
+    pub fn parse_block(parser: &mut RecursiveDescentParser) -> Result<Block, ParseError> {
+        let start = parser.current_position();
+        parser.consume(&TokenType::LeftBrace, "Expected '{' to start block")?;
+        let statements = Self::parse_block_statements(parser)?;
+
+        parser.consume(&TokenType::RightBrace, "Expected '}' to close block")?;
+        Ok(Block { statements, span: parser.span_from(start), id: NodeId::DUMMY })
+    }
+
+    fn parse_block_statements(parser: &mut RecursiveDescentParser) -> Result<Vec<Stmt>, ParseError> {
+        let mut statements = Vec::new();
+        while !parser.match_tokens(&[TokenType::RightBrace]) && !parser.is_at_end() {
+            let start = parser.current_position();
+            match Self::parse_statement(parser) {
+                Ok(stmt) => statements.push(stmt),
+                Err(err) => {
+                    let node = ErrorNode::from(&err);
+                    parser.report_error(err);
+                    ErrorRecovery::synchronize_statement(parser, start);
+                    if parser.current_position() == start {
+                        parser.advance()?;
+                    }
+                    statements.push(Stmt::Error(ErrorNode { span: parser.span_from(start), ..node }));
+                }
+            }
+        }
+        Ok(statements)
+    }
+
+    pub fn parse_statement(parser: &mut RecursiveDescentParser) -> Result<Stmt, ParseError> {
+        let start = parser.current_position();
+        let token = parser.current_token()?.clone();
+        match &token.token_type {
+            TokenType::Let | TokenType::Mut => Ok(Stmt::VarDecl(Self::parse_var_decl(parser)?)),
+            TokenType::Const => Ok(Stmt::ConstDecl(Self::parse_const_decl(parser)?)),
+            TokenType::Public | TokenType::Private | TokenType::Protected => {
+                match parser.peek_token().map(|t| &t.token_type) {
+                    Some(TokenType::Const) => Ok(Stmt::ConstDecl(Self::parse_const_decl(parser)?)),
+                    _ => Ok(Stmt::VarDecl(Self::parse_var_decl(parser)?)),
+                }
+            }
+            TokenType::Return => Self::parse_return(parser),
+            TokenType::If => Self::parse_if(parser),
+            TokenType::While | TokenType::For => Self::parse_loop(parser, None, start),
+            TokenType::Par | TokenType::Async | TokenType::Sync => {
+                let modifier = match parser.advance()?.token_type {
+                    TokenType::Par => LoopModifier::Par,
+                    TokenType::Async => LoopModifier::Async,
+                    _ => LoopModifier::Sync,
+                };
+                if !parser.match_tokens(&[TokenType::While, TokenType::For]) {
+                    let next = parser.current_token()?;
+                    return Err(ParseError::unexpected(
+                        "Expected 'while' or 'for' after loop modifier",
+                        next,
+                        vec![TokenType::While, TokenType::For],
+                    ));
+                }
+                Self::parse_loop(parser, Some(modifier), start)
+            }
+            TokenType::DSLContent { dsl_type, content } => {
+                parser.advance()?;
+                Ok(Stmt::DSLBlock {
+                    dsl_type: dsl_type.clone(),
+                    content: content.clone(),
+                    line: token.line,
+                    column: token.column,
+                    span: parser.span_from(start),
+                    id: NodeId::DUMMY,
+                })
+            }
+            TokenType::DSL(dsl_type) => Err(ParseError::unexpected(
+                format!("Expected '{{' after '{}'", dsl_type),
+                &token,
+                vec![TokenType::LeftBrace],
+            )),
+            TokenType::LeftBrace => Ok(Stmt::Block(Self::parse_block(parser)?)),
+            _ => Self::parse_expression_or_assignment(parser),
+        }
+    }
+
+    fn parse_expression_or_assignment(parser: &mut RecursiveDescentParser) -> Result<Stmt, ParseError> {
+        let start = parser.current_position();
+        let start_token = parser.current_token()?.clone();
+        let expr = ExpressionParser::parse_expression(parser)?;
+
+        if parser.match_tokens(&[TokenType::Equal]) {
+            parser.advance()?;
+            let value = ExpressionParser::parse_expression(parser)?;
+            parser.consume(&TokenType::Semicolon, "Expected ';' after assignment")?;
+
+            return Ok(Stmt::Assignment {
+                target: expr,
+                value,
+                line: start_token.line,
+                column: start_token.column,
+                span: parser.span_from(start),
+                id: NodeId::DUMMY,
+            });
+        }
+
+        parser.consume(&TokenType::Semicolon, "Expected ';' after expression")?;
+        Ok(Stmt::Expression {
+            expr,
+            line: start_token.line,
+            column: start_token.column,
+            span: parser.span_from(start),
+            id: NodeId::DUMMY,
+        })
+    }
+
+    pub fn parse_const_decl(parser: &mut RecursiveDescentParser) -> Result<ConstDecl, ParseError> {
+        let start = parser.current_position();
+        let start_token = parser.current_token()?.clone();
+
+        let visibility = Self::parse_visibility(parser)?;
+        parser.consume(&TokenType::Const, "Expected 'const'")?;
+        let name_tok = parser.consume_identifier("Expected constant name")?;
+
+        parser.consume(&TokenType::Colon, "Constant declarations require a type annotation")?;
+        let declared_type = TypeParser::parse_type(parser)?;
+
+        parser.consume(&TokenType::Equal, "Constant declarations require an initializer")?;
+        let initializer = ExpressionParser::parse_expression(parser)?;
+        parser.consume(&TokenType::Semicolon, "Expected ';' after constant declaration")?;
+
+        Ok(ConstDecl {
+            visibility,
+            name: name_tok.lexeme,
+            declared_type,
+            initializer,
+            value: None,
+            line: start_token.line,
+            column: start_token.column,
+            span: parser.span_from(start),
+            id: NodeId::DUMMY,
+        })
+    }
+
+    fn parse_return(parser: &mut RecursiveDescentParser) -> Result<Stmt, ParseError> {
+        let start = parser.current_position();
+        let return_token = parser.consume(&TokenType::Return, "Expected 'return'")?;
+
+        let value = if parser.match_tokens(&[TokenType::Semicolon]) {
+            None
+        } else {
+            Some(ExpressionParser::parse_expression(parser)?)
+        };
+        parser.consume(&TokenType::Semicolon, "Expected ';' after return statement")?;
+
+        Ok(Stmt::Return {
+            value,
+            line: return_token.line,
+            column: return_token.column,
+            span: parser.span_from(start),
+            id: NodeId::DUMMY,
+        })
+    }
+
+    fn parse_if(parser: &mut RecursiveDescentParser) -> Result<Stmt, ParseError> {
+        let start = parser.current_position();
+        let if_token = parser.consume(&TokenType::If, "Expected 'if'")?;
+
+        let condition = ExpressionParser::parse_condition(parser)?;
+        let then_branch = Self::parse_block(parser)?;
+
+        let else_branch = if parser.match_tokens(&[TokenType::Else]) {
+            parser.advance()?;
+            if parser.match_tokens(&[TokenType::If]) {
+                // `else if` is sugar for an else block holding a single if statement
+                let nested = Self::parse_if(parser)?;
+                Some(Block { span: nested.span(), statements: vec![nested], id: NodeId::DUMMY })
+            } else {
+                Some(Self::parse_block(parser)?)
+            }
+        } else {
+            None
+        };
+
+        Ok(Stmt::If {
+            condition,
+            then_branch,
+            else_branch,
+            line: if_token.line,
+            column: if_token.column,
+            span: parser.span_from(start),
+            id: NodeId::DUMMY,
+        })
+    }
+
+    /// `start` is the position of the loop modifier, if any, or the keyword.
+    fn parse_loop(
+        parser: &mut RecursiveDescentParser,
+        modifier: Option<LoopModifier>,
+        start: usize,
+    ) -> Result<Stmt, ParseError> {
+        let loop_token = parser.consume_one_of(
+            &[TokenType::While, TokenType::For],
+            "Expected 'while' or 'for'",
+        )?;
+
+        if matches!(loop_token.token_type, TokenType::While) {
+            let condition = ExpressionParser::parse_condition(parser)?;
+            let body = Self::parse_block(parser)?;
+
+            return Ok(Stmt::While {
+                modifier,
+                condition,
+                body,
+                line: loop_token.line,
+                column: loop_token.column,
+                span: parser.span_from(start),
+                id: NodeId::DUMMY,
+            });
+        }
+
+        let var_token = parser.consume_identifier("Expected loop variable after 'for'")?;
+        parser.consume(&TokenType::In, "Expected 'in' after loop variable")?;
+        let iterable = ExpressionParser::parse_condition(parser)?;
+        let body = Self::parse_block(parser)?;
+
+        Ok(Stmt::For {
+            modifier,
+            variable: var_token.lexeme,
+            iterable,
             body,
-            line: name_token.line,
-            column: name_token.column,
+            line: loop_token.line,
+            column: loop_token.column,
+            span: parser.span_from(start),
+            id: NodeId::DUMMY,
         })
     }
-}
\ No newline at end of file
+}
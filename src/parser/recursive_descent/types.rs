@@ -5,40 +5,119 @@ use super::core::RecursiveDescentParser;
 
 pub struct TypeParser;
 
-
 impl TypeParser {
     pub fn parse_type(parser: &mut RecursiveDescentParser) -> Result<Type, ParseError> {
-        let token = parser.current_token()?;
+        let mut base_type = Self::parse_base_type(parser)?;
+
+        // Handle optional types (Type?), which may nest (Type??)
+        while parser.match_tokens(&[TokenType::Question]) {
+            parser.advance()?;
+            base_type = Type::Optional(Box::new(base_type));
+        }
+        Ok(base_type)
+    }
+
+    fn parse_base_type(parser: &mut RecursiveDescentParser) -> Result<Type, ParseError> {
+        let token = parser.current_token()?.clone();
         match &token.token_type {
             TokenType::Identifier(name) => {
-                let type_name = name.clone();
                 parser.advance()?;
-                
-                let base_type = if let Some(primitive) = Type::from_type_name(&type_name) {
-                    primitive
+                // Types exported by an imported module are written `module.Type`
+                let name = if parser.match_tokens(&[TokenType::Dot]) {
+                    parser.advance()?;
+                    let member = parser.consume_identifier("Expected type name after '.'")?;
+                    format!("{}.{}", name, member.lexeme)
                 } else {
-                    Type::Custom(type_name)
+                    name.clone()
                 };
 
-                // Handle optional types (Type?)
-                if parser.match_tokens(&[TokenType::Question]) {
-                    parser.advance()?;
-                    Ok(Type::Optional(Box::new(base_type)))
-                } else {
-                    Ok(base_type)
+                if !parser.match_tokens(&[TokenType::Less]) {
+                    return Ok(Type::from_type_name(&name).unwrap_or(Type::Custom(name)));
+                }
+                let mut args = Self::parse_type_arguments(parser)?;
+                if name == "Task" {
+                    if args.len() != 1 {
+                        return Err(ParseError::new(
+                            format!("Type 'Task' expects 1 type argument, got {}", args.len()),
+                            token.line,
+                            token.column,
+                        ));
+                    }
+                    return Ok(Type::Task(Box::new(args.remove(0))));
                 }
+                Ok(Type::Generic { name, args })
+            }
+            // Array types ([Type])
+            TokenType::LeftBracket => {
+                parser.advance()?;
+                let element_type = Self::parse_type(parser)?;
+                parser.consume(&TokenType::RightBracket, "Expected ']' after array element type")?;
+                Ok(Type::Array(Box::new(element_type)))
             }
-            _ => Err(ParseError::new(
-                &format!("Expected type, found {:?}", token.token_type),
-                token.line,
-                token.column,
+            TokenType::Fn => Self::parse_function_type(parser),
+            _ => Err(ParseError::unexpected(
+                "Expected type",
+                &token,
+                Vec::new(),
             )),
         }
     }
 
+    /// Parses `<Type, ...>` after the name of a generic type.
+    fn parse_type_arguments(parser: &mut RecursiveDescentParser) -> Result<Vec<Type>, ParseError> {
+        parser.consume(&TokenType::Less, "Expected '<' before type arguments")?;
+        let mut args = vec![Self::parse_type(parser)?];
+        while parser.match_tokens(&[TokenType::Comma]) {
+            parser.advance()?;
+            args.push(Self::parse_type(parser)?);
+        }
+        parser.consume(&TokenType::Greater, "Expected '>' after type arguments")?;
+        Ok(args)
+    }
+
+    /// Parses an optional `<T, U>` list of type parameter names after the name
+    /// of a generic declaration.
+    pub fn parse_type_params(parser: &mut RecursiveDescentParser) -> Result<Vec<String>, ParseError> {
+        let mut params = Vec::new();
+        if !parser.match_tokens(&[TokenType::Less]) {
+            return Ok(params);
+        }
+        parser.advance()?;
+        loop {
+            params.push(parser.consume_identifier("Expected type parameter name")?.lexeme);
+            if parser.match_tokens(&[TokenType::Comma]) {
+                parser.advance()?;
+            } else {
+                break;
+            }
+        }
+        parser.consume(&TokenType::Greater, "Expected '>' after type parameters")?;
+        Ok(params)
+    }
+
+    /// Parses `fn(param_types...) [-> return_type]`.
     pub fn parse_function_type(parser: &mut RecursiveDescentParser) -> Result<Type, ParseError> {
-        // For future implementation of function types
-        // fn(param_types...) -> return_type
-        todo!("Function type parsing not implemented yet")
+        parser.consume(&TokenType::Fn, "Expected 'fn'")?;
+        parser.consume(&TokenType::LeftParen, "Expected '(' after 'fn' in function type")?;
+
+        let mut params = Vec::new();
+        while !parser.match_tokens(&[TokenType::RightParen]) {
+            params.push(Self::parse_type(parser)?);
+            if parser.match_tokens(&[TokenType::Comma]) {
+                parser.advance()?;
+            } else {
+                break;
+            }
+        }
+        parser.consume(&TokenType::RightParen, "Expected ')' after function type parameters")?;
+
+        let return_type = if parser.match_tokens(&[TokenType::Arrow]) {
+            parser.advance()?;
+            Some(Box::new(Self::parse_type(parser)?))
+        } else {
+            None
+        };
+
+        Ok(Type::Function { params, return_type })
     }
 }
@@ -1,197 +1,1082 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
 use crate::parser::{
-    BinaryOp, Expr, FnDecl, Mutability, Parameter, Type, UnaryOp, VarDecl, Visibility,
+    BinaryOp, Block, Capture, ConcurrencyModifier, ConstDecl, EnumDecl, Expr, ExprKind,
+    FieldDecl, FnDecl, ImportDecl, InterpolationPart, LambdaBody, LambdaParam, Literal, MatchArm, Mutability,
+    Parameter, Pattern, PatternKind, Span, Stmt, StructDecl, Type, TypeAliasDecl, UnaryOp, VarDecl, Visibility,
 };
 use crate::parser::recursive_descent::errors::ParseError;
-use crate::parser::symbol_table::{SymbolError, SymbolTable};
+use crate::parser::semantic::captures;
+use crate::parser::semantic::const_eval::{ConstEvalError, ConstEvaluator};
+use crate::parser::symbol_table::{FieldInfo, FunctionInfo, ScopeType, SymbolTable};
 
 pub struct SemanticAnalyzer<'a> {
     symbol_table: &'a mut SymbolTable,
+    return_types: Vec<Option<Type>>,
+    current_struct: Option<String>,
+    /// Names bound while inferring an expression (e.g. match arm patterns).
+    /// They only live for the duration of `infer_expression_type`, which takes
+    /// `&self`, hence the interior mutability.
+    expr_scopes: RefCell<Vec<HashMap<String, Type>>>,
+    /// How many expression-bodied lambdas are being inferred; their bodies
+    /// are not part of the enclosing (possibly async) function.
+    expr_lambdas: Cell<usize>,
+    /// Concurrency modifiers of the enclosing functions, innermost last.
+    concurrency: Vec<Option<ConcurrencyModifier>>,
+    /// Type parameters of the enclosing generic declarations, innermost last.
+    type_params: Vec<Vec<String>>,
+}
+
+/// What the type parameters of a generic declaration stand for at a use site.
+type TypeBindings = HashMap<String, Type>;
+
+/// What a call expression invokes, as seen by the checker.
+struct Signature {
+    /// Name used in diagnostics.
+    name: String,
+    /// Type parameters that are instantiated afresh at every call.
+    type_params: Vec<String>,
+    params: Vec<Type>,
+    return_type: Option<Type>,
 }
 
 impl<'a> SemanticAnalyzer<'a> {
     pub fn new(symbol_table: &'a mut SymbolTable) -> Self {
-        Self { symbol_table }
+        Self {
+            symbol_table,
+            return_types: Vec::new(),
+            current_struct: None,
+            expr_scopes: RefCell::new(Vec::new()),
+            expr_lambdas: Cell::new(0),
+            concurrency: Vec::new(),
+            type_params: Vec::new(),
+        }
     }
 
     pub fn reset(&mut self) {
         self.symbol_table.clear();
+        self.return_types.clear();
+        self.current_struct = None;
+        self.expr_scopes.borrow_mut().clear();
+        self.expr_lambdas.set(0);
+        self.concurrency.clear();
+        self.type_params.clear();
+    }
+
+    /// Checks that every named type in `ty` exists and expands type aliases,
+    /// returning the type the rest of the analyzer should work with.
+    pub fn resolve_type(&self, ty: &Type, span: Span) -> Result<Type, ParseError> {
+        match ty {
+            Type::Custom(name) => {
+                if self.type_params.iter().any(|scope| scope.contains(name)) {
+                    Ok(Type::Param(name.clone()))
+                } else if let Some(alias) = self.symbol_table.get_type_alias(name) {
+                    Ok(alias.target.clone())
+                } else if self.symbol_table.type_exists(name) {
+                    self.check_type_arity(name, 0, span)?;
+                    Ok(ty.clone())
+                } else {
+                    Err(ParseError::at(format!("Unknown type '{}'", name), span))
+                }
+            }
+            Type::Generic { name, args } => {
+                if !self.symbol_table.type_exists(name) {
+                    return Err(ParseError::at(format!("Unknown type '{}'", name), span));
+                }
+                self.check_type_arity(name, args.len(), span)?;
+                Ok(Type::Generic {
+                    name: name.clone(),
+                    args: args
+                        .iter()
+                        .map(|arg| self.resolve_type(arg, span))
+                        .collect::<Result<_, _>>()?,
+                })
+            }
+            Type::Optional(inner) => Ok(Type::Optional(Box::new(self.resolve_type(inner, span)?))),
+            Type::Array(inner) => Ok(Type::Array(Box::new(self.resolve_type(inner, span)?))),
+            Type::Task(inner) => Ok(Type::Task(Box::new(self.resolve_type(inner, span)?))),
+            Type::Function { params, return_type } => Ok(Type::Function {
+                params: params
+                    .iter()
+                    .map(|p| self.resolve_type(p, span))
+                    .collect::<Result<_, _>>()?,
+                return_type: match return_type {
+                    Some(ret) => Some(Box::new(self.resolve_type(ret, span)?)),
+                    None => None,
+                },
+            }),
+            _ => Ok(ty.clone()),
+        }
+    }
+
+    /// Type parameters declared by the struct or enum `name`.
+    fn declared_type_params(&self, name: &str) -> &[String] {
+        if let Ok(info) = self.symbol_table.get_struct_info(name) {
+            &info.type_params
+        } else if let Ok(info) = self.symbol_table.get_enum_info(name) {
+            &info.type_params
+        } else {
+            &[]
+        }
+    }
+
+    fn check_type_arity(&self, name: &str, given: usize, span: Span) -> Result<(), ParseError> {
+        let expected = self.declared_type_params(name).len();
+        if expected != given {
+            return Err(ParseError::at(
+                format!("Type '{}' expects {} type arguments, got {}", name, expected, given),
+                span,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Brings the type parameters of a generic declaration into scope; they
+    /// are removed again together with the declaration's scope.
+    fn enter_type_params(&mut self, params: &[String], span: Span) -> Result<(), ParseError> {
+        for (i, param) in params.iter().enumerate() {
+            if params[..i].contains(param) {
+                return Err(ParseError::at(
+                    format!("Type parameter '{}' declared more than once", param),
+                    span,
+                ));
+            }
+        }
+        self.type_params.push(params.to_vec());
+        Ok(())
+    }
+
+    /// The name of a struct or enum type together with what its type
+    /// parameters stand for in `ty`.
+    fn nominal_type<'t>(&self, ty: &'t Type) -> Option<(&'t String, TypeBindings)> {
+        match ty {
+            Type::Custom(name) => Some((name, HashMap::new())),
+            Type::Generic { name, args } => Some((
+                name,
+                self.declared_type_params(name)
+                    .iter()
+                    .cloned()
+                    .zip(args.iter().cloned())
+                    .collect(),
+            )),
+            _ => None,
+        }
     }
 
     // ===========================
     // Variable Declaration
     // ===========================
-    pub fn analyze_var_declaration(
-        &mut self,
-        visibility: Option<Visibility>,
-        mutability: Mutability,
-        name: String,
-        declared_type: Option<Type>,
-        initializer: Option<Expr>,
-        line: usize,
-        column: usize,
-    ) -> Result<VarDecl, ParseError> {
-        let inferred_type = match (declared_type.as_ref(), &initializer) {
+    /// Checks a variable declaration, declares the variable and returns the
+    /// type it was given.
+    pub fn analyze_var_declaration(&mut self, decl: &VarDecl) -> Result<Type, ParseError> {
+        let span = decl.span;
+        let resolved_type = match &decl.declared_type {
+            Some(t) => Some(self.resolve_type(t, span)?),
+            None => None,
+        };
+
+        let var_type = match (resolved_type, &decl.initializer) {
             (None, None) => {
-                return Err(ParseError::new(
+                return Err(ParseError::at(
                     "Variable must have type annotation or initializer",
-                    line,
-                    column,
+                    span,
                 ))
             }
-            (Some(t), None) => Some(t.clone()),
-            (None, Some(expr)) => Some(self.infer_expression_type(expr, line, column)?),
+            (Some(t), None) => t,
+            (None, Some(expr)) => self.infer_expression_type(expr)?,
             (Some(t), Some(expr)) => {
-                let init_type = self.infer_expression_type(expr, line, column)?;
-                if self.types_compatible(t, &init_type) {
-                    Some(t.clone())
-                } else {
-                    return Err(ParseError::new(
-                        &format!("Type mismatch: declared {:?}, but got {:?}", t, init_type),
-                        line,
-                        column,
+                let init_type = self.infer_with_expected(expr, Some(&t))?;
+                if !self.types_compatible(&t, &init_type) {
+                    return Err(ParseError::at(
+                        format!("Type mismatch: declared {:?}, but got {:?}", t, init_type),
+                        expr.span,
                     ));
                 }
+                t
             }
         };
 
         // Masukkan ke symbol table
         self.symbol_table
             .declare_variable(
-                name.clone(),
-                inferred_type.clone().unwrap_or(Type::Unknown),
-                visibility,
-                mutability,
-                initializer.is_some(),
-                line,
-                column,
+                decl.name.clone(),
+                var_type.clone(),
+                decl.visibility.clone(),
+                decl.mutability.clone(),
+                decl.initializer.is_some(),
+                span.line,
+                span.column,
             )
-            .map_err(|e| ParseError::new(&e.to_string(), line, column))?;
+            .map_err(|e| ParseError::at(e.to_string(), span))?;
 
-        Ok(VarDecl {
-            visibility,
-            mutability,
-            name,
-            declared_type,
-            inferred_type,
-            initializer,
-            line,
-            column,
-        })
+        Ok(var_type)
     }
 
     // ===========================
     // Function Declaration
     // ===========================
-    pub fn analyze_func_declaration(
-    &mut self,
-    visibility: Option<Visibility>,
-    name: String,
-    params: Vec<Parameter>,
-    return_type: Option<Type>,
-    body: Option<Expr>, // bisa Expr::Block atau stmts
-    line: usize,
-    column: usize,
-) -> Result<FnDecl, ParseError> {
-    // Cek duplicate
-    if self.symbol_table.function_exists(&name) {
-        return Err(ParseError::new(
-            &format!("Function '{}' already declared", name),
-            line,
-            column,
-        ));
-    }
-
-    // Masukkan function ke symbol table dulu supaya rekursi bisa
-    let param_pairs: Vec<(String, Type)> = params.iter()
-        .map(|p| (p.name.clone(), p.param_type.clone()))
-        .collect();
-    self.symbol_table.declare_function(
-        name.clone(),
-        param_pairs,
-        return_type.clone(),
-        visibility.clone(),
-        line,
-        column,
-    )?;
-
-    // Analisis parameter types
-    for param in &params {
-        if param.param_type.clone() == Type::Unknown {
-            return Err(ParseError::new(
-                &format!("Parameter '{}' must have type annotation", param.name),
-                line,
-                column,
+
+    /// Registers a function's signature, so that calls anywhere in the
+    /// program (including the function's own body) can be checked.
+    pub fn declare_function(&mut self, decl: &FnDecl) -> Result<(), ParseError> {
+        let span = decl.span;
+        if self.symbol_table.function_exists(&decl.name) {
+            return Err(ParseError::at(
+                format!("Function '{}' already declared", decl.name),
+                span,
             ));
         }
+
+        self.enter_type_params(&decl.type_params, span)?;
+        let resolved = self.resolve_signature(&decl.parameters, decl.return_type.as_ref(), span);
+        self.type_params.pop();
+        let (params, return_type) = resolved?;
+
+        self.symbol_table
+            .declare_function(
+                decl.name.clone(),
+                decl.type_params.clone(),
+                params.into_iter().map(|p| (p.name, p.param_type)).collect(),
+                return_type,
+                decl.visibility.clone(),
+                span.line,
+                span.column,
+            )
+            .map_err(|e| ParseError::at(e.to_string(), span))
     }
 
-    // Analisis body jika ada
-    if let Some(body_expr) = &body {
-        let body_type = self.infer_expression_type(body_expr, line, column)?;
-        if let Some(ret_type) = &return_type {
-            if !self.types_compatible(ret_type, &body_type) {
-                return Err(ParseError::new(
-                    &format!("Return type mismatch: expected {:?}, got {:?}", ret_type, body_type),
-                    line,
-                    column,
+    /// Opens the scope of a declared function's body and binds its parameters.
+    pub fn enter_function(&mut self, decl: &FnDecl) -> Result<(), ParseError> {
+        let (params, return_type) = self.enter_signature(decl)?;
+        self.enter_function_scope(&params, return_type, decl.concurrency.clone(), None)
+    }
+
+    /// Brings the type parameters of `decl` into scope and resolves its
+    /// signature against them.
+    fn enter_signature(&mut self, decl: &FnDecl) -> Result<(Vec<Parameter>, Option<Type>), ParseError> {
+        self.enter_type_params(&decl.type_params, decl.span)?;
+        self.resolve_signature(&decl.parameters, decl.return_type.as_ref(), decl.span)
+            .inspect_err(|_| {
+                self.type_params.pop();
+            })
+    }
+
+    /// Validates a signature and returns it with all aliases expanded.
+    fn resolve_signature(
+        &self,
+        params: &[Parameter],
+        return_type: Option<&Type>,
+        span: Span,
+    ) -> Result<(Vec<Parameter>, Option<Type>), ParseError> {
+        let mut resolved_params = Vec::with_capacity(params.len());
+        for param in params {
+            if param.param_type == Type::Unknown {
+                return Err(ParseError::at(
+                    format!("Parameter '{}' must have type annotation", param.name),
+                    param.span,
                 ));
             }
+            resolved_params.push(Parameter {
+                param_type: self.resolve_type(&param.param_type, param.span)?,
+                ..param.clone()
+            });
         }
+        let resolved_return = match return_type {
+            Some(ret_type) => Some(self.resolve_type(ret_type, span)?),
+            None => None,
+        };
+        Ok((resolved_params, resolved_return))
     }
 
-    Ok(FnDecl {
-        visibility,
-        name,
-        parameters: params,
-        return_type,
-        body, // tetap disimpan sebagai Option<Expr>
-        line,
-        column,
-    })
-}
+    fn enter_function_scope(
+        &mut self,
+        params: &[Parameter],
+        return_type: Option<Type>,
+        concurrency: Option<ConcurrencyModifier>,
+        self_type: Option<(Type, Span)>,
+    ) -> Result<(), ParseError> {
+        self.symbol_table.enter_scope(ScopeType::Function);
+        self.return_types.push(return_type);
+        self.concurrency.push(concurrency);
+
+        let self_binding = self_type.map(|(ty, span)| ("self".to_string(), ty, span));
+        let bindings = self_binding.into_iter().chain(
+            params
+                .iter()
+                .map(|p| (p.name.clone(), p.param_type.clone(), p.span)),
+        );
+
+        for (name, ty, span) in bindings {
+            if let Err(e) = self.symbol_table.declare_variable(
+                name,
+                ty,
+                None,
+                Mutability::Let,
+                true,
+                span.line,
+                span.column,
+            ) {
+                self.exit_function_scope();
+                return Err(ParseError::at(e.to_string(), span));
+            }
+        }
+
+        Ok(())
+    }
 
+    /// Leaves a function or lambda scope without checking the body.
+    fn exit_function_scope(&mut self) {
+        self.symbol_table.exit_scope();
+        self.return_types.pop();
+        self.concurrency.pop();
+        self.type_params.pop();
+    }
+
+    /// Leaves the body opened by `enter_function` or `enter_method` and
+    /// checks that a function with a return type returns on every path.
+    pub fn exit_function(&mut self, decl: &FnDecl) -> Result<(), ParseError> {
+        self.exit_function_scope();
+
+        match &decl.return_type {
+            Some(ret_type) if !Self::block_returns(&decl.body) => Err(ParseError::at(
+                format!(
+                    "Function '{}' must return a value of type {:?} on every path",
+                    decl.name, ret_type
+                ),
+                decl.span,
+            )),
+            _ => Ok(()),
+        }
+    }
 
     // ===========================
-    // Expression Type Inference
+    // Lambdas
     // ===========================
-    pub fn infer_expression_type(
+
+    /// Opens the scope of a lambda with a block body. The body sees the
+    /// enclosing locals; `return` inside it refers to the lambda.
+    pub fn enter_lambda_scope(
+        &mut self,
+        params: &[Parameter],
+        return_type: Option<&Type>,
+        span: Span,
+    ) -> Result<(), ParseError> {
+        let (params, return_type) = self.resolve_signature(params, return_type, span)?;
+        // Enclosing type parameters stay visible; the empty entry is what
+        // `exit_function_scope` pops
+        self.type_params.push(Vec::new());
+        self.enter_function_scope(&params, return_type, None, None)
+    }
+
+    pub fn exit_lambda_scope(
+        &mut self,
+        body: &Block,
+        return_type: Option<&Type>,
+        span: Span,
+    ) -> Result<(), ParseError> {
+        self.exit_function_scope();
+        match return_type {
+            Some(ret_type) if !Self::block_returns(body) => Err(ParseError::at(
+                format!("Lambda must return a value of type {:?} on every path", ret_type),
+                span,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Local variables of the enclosing scopes that a lambda body refers to.
+    /// Globals are reachable from anywhere and are not captured.
+    pub fn lambda_captures(&self, params: &[LambdaParam], body: &LambdaBody) -> Vec<Capture> {
+        let free = captures::free_variables(params.iter().map(|param| param.name.as_str()), body);
+        free.used
+            .iter()
+            .filter_map(|name| {
+                let info = self.symbol_table.get_local_variable(name)?;
+                Some(Capture {
+                    name: name.clone(),
+                    mutable: info.is_mutable(),
+                    mutated: free.assigned.contains(name),
+                })
+            })
+            .collect()
+    }
+
+    fn infer_lambda(
         &self,
-        expr: &Expr,
-        line: usize,
-        column: usize,
+        params: &[LambdaParam],
+        return_type: Option<&Type>,
+        body: &LambdaBody,
+        expected: Option<&Type>,
+        span: Span,
     ) -> Result<Type, ParseError> {
-        match expr {
-            Expr::Literal(lit) => Ok(Type::infer_from_literal(lit)),
+        // Parts of the expected type that are still `Unknown` carry no information
+        let known = |ty: &&Type| **ty != Type::Unknown;
+        let (expected_params, expected_return) = match expected {
+            Some(Type::Function { params, return_type }) => {
+                (Some(params), return_type.as_deref().filter(known))
+            }
+            _ => (None, None),
+        };
+        if let Some(expected_params) = expected_params {
+            if expected_params.len() != params.len() {
+                return Err(ParseError::at(
+                    format!(
+                        "Lambda takes {} parameters, but {} were expected",
+                        params.len(),
+                        expected_params.len()
+                    ),
+                    span,
+                ));
+            }
+        }
 
-            Expr::Identifier(name) => self
-                .symbol_table
-                .get_variable_type(name)
-                .map_err(|err| ParseError::new(&err.to_string(), line, column)),
+        let mut param_types = Vec::with_capacity(params.len());
+        for (i, param) in params.iter().enumerate() {
+            let param_type = match &param.param_type {
+                Some(ty) => self.resolve_type(ty, span)?,
+                None => expected_params
+                    .and_then(|expected| expected.get(i))
+                    .filter(known)
+                    .cloned()
+                    .ok_or_else(|| {
+                        ParseError::at(
+                            format!(
+                                "Cannot infer the type of lambda parameter '{}'; add a type annotation",
+                                param.name
+                            ),
+                            span,
+                        )
+                    })?,
+            };
+            param_types.push(param_type);
+        }
+
+        let declared_return = match return_type {
+            Some(ty) => Some(self.resolve_type(ty, span)?),
+            None => None,
+        };
+        let return_type = match body {
+            // Block bodies were checked against the declared signature when parsed
+            LambdaBody::Block(_) => declared_return,
+            LambdaBody::Expr(body) => {
+                let bindings = params
+                    .iter()
+                    .map(|param| param.name.clone())
+                    .zip(param_types.iter().cloned())
+                    .collect();
+                let hint = declared_return.clone().or_else(|| expected_return.cloned());
+                self.expr_lambdas.set(self.expr_lambdas.get() + 1);
+                let body_type = self.with_expr_scope(bindings, || {
+                    self.infer_with_expected(body, hint.as_ref())
+                });
+                self.expr_lambdas.set(self.expr_lambdas.get() - 1);
+                let body_type = body_type?;
+                match declared_return {
+                    Some(declared) if !self.types_compatible(&declared, &body_type) => {
+                        return Err(ParseError::at(
+                            format!(
+                                "Lambda body has type {:?}, but its return type is {:?}",
+                                body_type, declared
+                            ),
+                            span,
+                        ))
+                    }
+                    Some(declared) => Some(declared),
+                    None => Some(body_type),
+                }
+            }
+        };
+
+        Ok(Type::Function {
+            params: param_types,
+            return_type: return_type.map(Box::new),
+        })
+    }
+
+    fn block_returns(block: &Block) -> bool {
+        block.statements.iter().any(Self::stmt_returns)
+    }
+
+    fn stmt_returns(stmt: &Stmt) -> bool {
+        match stmt {
+            // The statement that failed to parse may have returned; don't pile on
+            Stmt::Return { .. } | Stmt::Error(_) => true,
+            Stmt::If {
+                then_branch,
+                else_branch: Some(else_branch),
+                ..
+            } => Self::block_returns(then_branch) && Self::block_returns(else_branch),
+            Stmt::Block(block) => Self::block_returns(block),
+            _ => false,
+        }
+    }
+
+    // ===========================
+    // Struct Declaration
+    // ===========================
+
+    /// Registers the struct name; its fields and methods are added once all
+    /// type names are known.
+    pub fn declare_struct(&mut self, decl: &StructDecl) -> Result<(), ParseError> {
+        let (name, span) = (&decl.name, decl.span);
+        if self.symbol_table.struct_exists(name) {
+            return Err(ParseError::at(
+                format!("Struct '{}' already declared", name),
+                span,
+            ));
+        }
+        if self.symbol_table.enum_exists(name) {
+            return Err(ParseError::at(
+                format!("Type '{}' already declared as an enum", name),
+                span,
+            ));
+        }
+        if self.symbol_table.get_type_alias(name).is_some() {
+            return Err(ParseError::at(
+                format!("Type '{}' already declared as an alias", name),
+                span,
+            ));
+        }
+        self.symbol_table
+            .declare_struct(name.clone(), decl.type_params.clone(), decl.visibility.clone(), span.line, span.column)
+            .map_err(|e| ParseError::at(e.to_string(), span))
+    }
+
+    /// Enters the body of a declared struct: its type parameters come into
+    /// scope and its private members become accessible.
+    pub fn enter_struct(&mut self, decl: &StructDecl) -> Result<(), ParseError> {
+        self.enter_type_params(&decl.type_params, decl.span)?;
+        self.current_struct = Some(decl.name.clone());
+        Ok(())
+    }
+
+    pub fn analyze_field_declaration(
+        &mut self,
+        struct_name: &str,
+        field: &FieldDecl,
+    ) -> Result<(), ParseError> {
+        let field_type = self.resolve_type(&field.field_type, field.span)?;
+        self.symbol_table
+            .add_struct_field(
+                struct_name,
+                FieldInfo {
+                    name: field.name.clone(),
+                    field_type,
+                    visibility: field.visibility.clone(),
+                },
+                field.span.line,
+                field.span.column,
+            )
+            .map_err(|_| {
+                ParseError::at(
+                    format!("Member '{}' already declared in struct '{}'", field.name, struct_name),
+                    field.span,
+                )
+            })
+    }
 
-            Expr::Unary { expr, op } => {
-                let expr_type = self.infer_expression_type(expr, line, column)?;
+    /// Like `declare_function`, but registers the method on its struct. Must
+    /// be called inside `enter_struct`.
+    pub fn declare_method(&mut self, struct_name: &str, decl: &FnDecl) -> Result<(), ParseError> {
+        let (params, return_type) = self.enter_signature(decl)?;
+        self.type_params.pop();
+
+        let method = FunctionInfo {
+            type_params: decl.type_params.clone(),
+            parameters: params
+                .into_iter()
+                .map(|p| (p.name, p.param_type))
+                .collect(),
+            return_type,
+            visibility: decl.visibility.clone(),
+            line: decl.line,
+            column: decl.column,
+        };
+        self.symbol_table
+            .add_struct_method(struct_name, decl.name.clone(), method)
+            .map_err(|_| {
+                ParseError::at(
+                    format!("Member '{}' already declared in struct '{}'", decl.name, struct_name),
+                    decl.span,
+                )
+            })
+    }
+
+    /// Like `enter_function`, but also binds `self` inside the method body.
+    pub fn enter_method(&mut self, struct_name: &str, decl: &FnDecl) -> Result<(), ParseError> {
+        let (params, return_type) = self.enter_signature(decl)?;
+        let struct_params = self.declared_type_params(struct_name);
+        let self_type = if struct_params.is_empty() {
+            Type::Custom(struct_name.to_string())
+        } else {
+            Type::Generic {
+                name: struct_name.to_string(),
+                args: struct_params.iter().cloned().map(Type::Param).collect(),
+            }
+        };
+        self.enter_function_scope(
+            &params,
+            return_type,
+            decl.concurrency.clone(),
+            Some((self_type, decl.span)),
+        )
+    }
+
+    /// Leaves the body entered by `enter_struct`.
+    pub fn exit_struct(&mut self) {
+        self.current_struct = None;
+        self.type_params.pop();
+    }
+
+    // ===========================
+    // Enum Declaration
+    // ===========================
+
+    /// Registers the enum with its variants as written; payload types are
+    /// resolved by `resolve_enum_variants` once all type names are known.
+    pub fn declare_enum(&mut self, decl: &EnumDecl) -> Result<(), ParseError> {
+        let (name, span) = (&decl.name, decl.span);
+        if self.symbol_table.enum_exists(name) {
+            return Err(ParseError::at(
+                format!("Enum '{}' already declared", name),
+                span,
+            ));
+        }
+        if self.symbol_table.struct_exists(name) {
+            return Err(ParseError::at(
+                format!("Type '{}' already declared as a struct", name),
+                span,
+            ));
+        }
+        if self.symbol_table.get_type_alias(name).is_some() {
+            return Err(ParseError::at(
+                format!("Type '{}' already declared as an alias", name),
+                span,
+            ));
+        }
+
+        let variants = &decl.variants;
+        for (i, variant) in variants.iter().enumerate() {
+            if variants[..i].iter().any(|prev| prev.name == variant.name) {
+                return Err(ParseError::at(
+                    format!("Variant '{}' already declared in enum '{}'", variant.name, name),
+                    variant.span,
+                ));
+            }
+        }
+
+        self.symbol_table
+            .declare_enum(
+                name.clone(),
+                decl.type_params.clone(),
+                variants
+                    .iter()
+                    .map(|v| (v.name.clone(), v.payload.clone()))
+                    .collect(),
+                decl.visibility.clone(),
+                span.line,
+                span.column,
+            )
+            .map_err(|e| ParseError::at(e.to_string(), span))
+    }
+
+    pub fn resolve_enum_variants(&mut self, decl: &EnumDecl) -> Result<(), ParseError> {
+        self.enter_type_params(&decl.type_params, decl.span)?;
+        let resolved_variants = decl
+            .variants
+            .iter()
+            .map(|variant| {
+                let payload = variant
+                    .payload
+                    .iter()
+                    .map(|payload_type| self.resolve_type(payload_type, variant.span))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok((variant.name.clone(), payload))
+            })
+            .collect::<Result<Vec<_>, ParseError>>();
+        self.type_params.pop();
+        self.symbol_table
+            .update_enum_variants(&decl.name, resolved_variants?)
+            .map_err(|e| ParseError::at(e.to_string(), decl.span))
+    }
+
+    // ===========================
+    // Import Declaration
+    // ===========================
+
+    /// Imports are loaded by the module loader before the file is parsed; here
+    /// we only check that the module was registered under the expected name.
+    pub fn analyze_import(&self, import: &ImportDecl) -> Result<(), ParseError> {
+        let path = import.module_path();
+        match self.symbol_table.get_module(import.binding()) {
+            Some(module) if module.path == path => Ok(()),
+            Some(module) => Err(ParseError::at(
+                format!(
+                    "Cannot import '{}': the name '{}' already refers to module '{}'",
+                    path,
+                    import.binding(),
+                    module.path
+                ),
+                import.span,
+            )),
+            None => Err(ParseError::at(
+                match self.symbol_table.import_failure(&path) {
+                    Some(reason) => reason.to_string(),
+                    None => format!("Module '{}' is not loaded; imports require the module loader", path),
+                },
+                import.span,
+            )),
+        }
+    }
+
+    // ===========================
+    // Type Alias Declaration
+    // ===========================
+
+    /// Aliases are expanded when declared, so an alias may only refer to
+    /// aliases declared before it and can never be recursive.
+    pub fn analyze_type_alias(&mut self, decl: &TypeAliasDecl) -> Result<(), ParseError> {
+        let (name, span) = (&decl.name, decl.span);
+        if self.symbol_table.type_exists(name) || Type::from_type_name(name).is_some() {
+            return Err(ParseError::at(
+                format!("Type '{}' already declared", name),
+                span,
+            ));
+        }
+
+        let target = self.resolve_type(&decl.aliased_type, span)?;
+        self.symbol_table
+            .declare_type_alias(name.clone(), target, decl.visibility.clone(), span.line, span.column)
+            .map_err(|e| ParseError::at(e.to_string(), span))
+    }
+
+    // ===========================
+    // Constant Declaration
+    // ===========================
+    /// Checks a constant declaration, declares the constant and returns its
+    /// value folded at compile time. An initializer using a name that failed
+    /// to check can't be folded; the constant is then declared as failed too
+    /// and `None` is returned.
+    pub fn analyze_const_declaration(&mut self, decl: &ConstDecl) -> Result<Option<Literal>, ParseError> {
+        let (name, span) = (&decl.name, decl.span);
+        let resolved_type = self.resolve_type(&decl.declared_type, span)?;
+        let init_type = self.infer_with_expected(&decl.initializer, Some(&resolved_type))?;
+        if init_type == Type::Error {
+            self.declare_failed(name, Mutability::Let, span);
+            return Ok(None);
+        }
+        if !self.types_compatible(&resolved_type, &init_type) {
+            return Err(ParseError::at(
+                format!("Type mismatch: declared {:?}, but got {:?}", decl.declared_type, init_type),
+                decl.initializer.span,
+            ));
+        }
+
+        let value = ConstEvaluator::new(self.symbol_table)
+            .evaluate(&decl.initializer)
+            .map_err(|err| Self::const_eval_error(decl, err))?;
+        if let (Literal::Float(f, _), true) = (&value, resolved_type.is_integer()) {
+            return Err(ParseError::at(
+                format!("Value {} of constant '{}' is not an integer as required by {:?}", f, name, resolved_type),
+                decl.initializer.span,
+            ));
+        }
+        if let (Literal::Int(n, _), Some((min, max))) = (&value, resolved_type.integer_range()) {
+            if !(min..=max).contains(n) {
+                return Err(ParseError::at(
+                    format!("Value {} of constant '{}' is out of range for {:?}", n, name, resolved_type),
+                    decl.initializer.span,
+                ));
+            }
+        }
+        // Integer values stored in float constants keep the constant's type
+        let value = match (value, &resolved_type) {
+            (Literal::Int(n, _), Type::Float32 | Type::Float64) => Literal::Float(n as f64, None),
+            (value, _) => value,
+        };
+        if let (Literal::Float(f, _), Type::Float32) = (&value, &resolved_type) {
+            Self::check_float32_range(*f, decl.initializer.span)?;
+        }
+
+        self.symbol_table
+            .declare_constant(
+                name.clone(),
+                resolved_type,
+                decl.visibility.clone(),
+                value.clone(),
+                span.line,
+                span.column,
+            )
+            .map_err(|e| ParseError::at(e.to_string(), span))?;
+
+        Ok(Some(value))
+    }
+
+    /// Reports why the initializer of `decl` could not be folded.
+    pub fn const_eval_error(decl: &ConstDecl, err: ConstEvalError) -> ParseError {
+        let message = match err {
+            ConstEvalError::NotConstant(reason) => format!(
+                "Initializer of constant '{}' is not a compile-time constant: {}",
+                decl.name, reason
+            ),
+            ConstEvalError::Arithmetic(reason) => {
+                format!("Cannot evaluate constant '{}': {}", decl.name, reason)
+            }
+            ConstEvalError::Cycle(names) => {
+                format!("Constant '{}' depends on itself: {}", decl.name, names.join(" -> "))
+            }
+        };
+        ParseError::at(message, decl.initializer.span)
+    }
+
+    /// Binds the name of a variable or constant whose declaration failed to
+    /// check to `Type::Error`, so that its uses are neither undeclared nor
+    /// checked again. A name that is already declared keeps its binding.
+    pub fn declare_failed(&mut self, name: &str, mutability: Mutability, span: Span) {
+        let _ = self.symbol_table.declare_variable(
+            name.to_string(),
+            Type::Error,
+            None,
+            mutability,
+            true,
+            span.line,
+            span.column,
+        );
+    }
+
+    /// Whether `expr` refers to a name whose declaration failed to check.
+    fn uses_failed_name(&self, expr: &Expr) -> bool {
+        captures::free_names(expr)
+            .iter()
+            .any(|name| matches!(self.lookup_variable_type(name, expr.span), Ok(Type::Error)))
+    }
+
+    // ===========================
+    // Statements
+    // ===========================
+    pub fn analyze_return(
+        &self,
+        value: Option<&Expr>,
+        span: Span,
+    ) -> Result<(), ParseError> {
+        let expected = match self.return_types.last() {
+            Some(expected) => expected,
+            None => {
+                return Err(ParseError::at(
+                    "'return' outside of a function",
+                    span,
+                ))
+            }
+        };
+
+        match (expected, value) {
+            (None, None) => Ok(()),
+            (None, Some(_)) => Err(ParseError::at(
+                "Cannot return a value from a function without a return type",
+                span,
+            )),
+            (Some(ret_type), None) => Err(ParseError::at(
+                format!("Expected a return value of type {:?}", ret_type),
+                span,
+            )),
+            (Some(ret_type), Some(expr)) => {
+                let value_type = self.infer_with_expected(expr, Some(ret_type))?;
+                if self.types_compatible(ret_type, &value_type) {
+                    Ok(())
+                } else {
+                    Err(ParseError::at(
+                        format!(
+                            "Return type mismatch: expected {:?}, got {:?}",
+                            ret_type, value_type
+                        ),
+                        expr.span,
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Checks an assignment to an l-value: a variable, a field or an array
+    /// element. Fields and elements are writable when the variable they are
+    /// reached through is mutable; inside methods `self` is always writable.
+    pub fn analyze_assignment(&self, target: &Expr, value: &Expr) -> Result<(), ParseError> {
+        let root = Self::lvalue_root(target)
+            .ok_or_else(|| ParseError::at("Invalid assignment target", target.span))?;
+        if self.is_module_name(root) {
+            return Err(ParseError::at(
+                format!("Cannot assign to items of module '{}'", root),
+                target.span,
+            ));
+        }
+        if root != "self" || matches!(target.kind, ExprKind::Identifier(_)) {
+            self.validate_assignment(root, target.span)?;
+        }
+
+        let target_type = self.infer_expression_type(target)?;
+        let value_type = self.infer_with_expected(value, Some(&target_type))?;
+        if !self.types_compatible(&target_type, &value_type) {
+            return Err(ParseError::at(
+                format!(
+                    "Cannot assign {:?} to '{}' of type {:?}",
+                    value_type,
+                    Self::describe_lvalue(target),
+                    target_type
+                ),
+                value.span,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the variable an l-value writes through, or `None` if the
+    /// expression is not assignable.
+    fn lvalue_root(target: &Expr) -> Option<&str> {
+        match &target.kind {
+            ExprKind::Identifier(name) => Some(name),
+            ExprKind::Member { object, .. } | ExprKind::Index { object, .. } => Self::lvalue_root(object),
+            _ => None,
+        }
+    }
+
+    fn describe_lvalue(target: &Expr) -> String {
+        match &target.kind {
+            ExprKind::Identifier(name) => name.clone(),
+            ExprKind::Member { object, member } => format!("{}.{}", Self::describe_lvalue(object), member),
+            ExprKind::Index { object, .. } => format!("{}[..]", Self::describe_lvalue(object)),
+            _ => "expression".to_string(),
+        }
+    }
+
+    pub fn analyze_condition(&self, condition: &Expr, context: &str) -> Result<(), ParseError> {
+        let cond_type = self.infer_expression_type(condition)?;
+        if !matches!(cond_type, Type::Bool | Type::Error) {
+            return Err(ParseError::at(
+                format!("{} condition must be bool, got {:?}", context, cond_type),
+                condition.span,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Opens the scope of a `for` loop and binds the loop variable to the
+    /// element type of the iterable; `span` is the loop's.
+    pub fn enter_for_loop(
+        &mut self,
+        variable: &str,
+        iterable: &Expr,
+        span: Span,
+    ) -> Result<(), ParseError> {
+        let element_type = match self.infer_expression_type(iterable)? {
+            Type::Array(inner) => *inner,
+            Type::Error => Type::Error,
+            other => {
+                return Err(ParseError::at(
+                    format!("Cannot iterate over value of type {:?}", other),
+                    iterable.span,
+                ))
+            }
+        };
+
+        self.symbol_table.enter_scope(ScopeType::Block);
+        self.symbol_table
+            .declare_variable(
+                variable.to_string(),
+                element_type,
+                None,
+                Mutability::Let,
+                true,
+                span.line,
+                span.column,
+            )
+            .map_err(|e| {
+                self.symbol_table.exit_scope();
+                ParseError::at(e.to_string(), span)
+            })
+    }
+
+    pub fn enter_block_scope(&mut self) {
+        self.symbol_table.enter_scope(ScopeType::Block);
+    }
+
+    pub fn exit_block_scope(&mut self) {
+        self.symbol_table.exit_scope();
+    }
+
+    // ===========================
+    // Expression Type Inference
+    // ===========================
+    pub fn infer_expression_type(&self, expr: &Expr) -> Result<Type, ParseError> {
+        let span = expr.span;
+        if self.uses_failed_name(expr) {
+            return Ok(Type::Error);
+        }
+        if let Some((value, suffix)) = Self::int_literal(expr) {
+            let ty = Type::infer_from_literal(&Literal::Int(value, suffix.cloned()));
+            Self::check_int_range(value, &ty, span)?;
+            return Ok(ty);
+        }
+
+        match &expr.kind {
+            ExprKind::Literal(lit @ Literal::Float(f, Some(Type::Float32))) => {
+                Self::check_float32_range(*f, span)?;
+                Ok(Type::infer_from_literal(lit))
+            }
+            ExprKind::Literal(lit) => Ok(Type::infer_from_literal(lit)),
+
+            ExprKind::Interpolated { parts } => {
+                for part in parts {
+                    if let InterpolationPart::Expr(expr) = part {
+                        let part_type = self.infer_expression_type(expr)?;
+                        if !part_type.is_displayable() {
+                            return Err(ParseError::at(
+                                format!("Cannot interpolate a value of type {:?} into a string", part_type),
+                                expr.span,
+                            ));
+                        }
+                    }
+                }
+                Ok(Type::String)
+            }
+
+            ExprKind::Identifier(name) if self.is_module_name(name) => Err(ParseError::at(
+                format!("Module '{}' cannot be used as a value", name),
+                span,
+            )),
+
+            ExprKind::Identifier(name) => match self.lookup_variable_type(name, span) {
+                Ok(ty) => Ok(ty),
+                // A bare function name evaluates to the function itself
+                Err(err) => match self.symbol_table.get_function_info(name) {
+                    Ok(info) => Ok(Self::function_type(info)),
+                    Err(_) => Err(err),
+                },
+            },
+
+            ExprKind::Unary { expr, op } => {
+                let expr_type = self.infer_expression_type(expr)?;
                 match op {
                     UnaryOp::Negate | UnaryOp::Positive => {
                         if expr_type.is_numeric() {
                             Ok(expr_type)
                         } else {
-                            Err(ParseError::new("Unary +/- requires numeric type", line, column))
+                            Err(ParseError::at("Unary +/- requires numeric type", span))
                         }
                     }
                     UnaryOp::Not => {
                         if matches!(expr_type, Type::Bool) {
                             Ok(expr_type)
                         } else {
-                            Err(ParseError::new("Unary ! requires boolean type", line, column))
+                            Err(ParseError::at("Unary ! requires boolean type", span))
                         }
                     }
                 }
             }
 
-            Expr::Binary { left, right, op } => {
-                let left_type = self.infer_expression_type(left, line, column)?;
-                let right_type = self.infer_expression_type(right, line, column)?;
-                self.validate_binary_operation(&left_type, &right_type, op, line, column)?;
+            ExprKind::Binary { left, right, op } => {
+                let left_type = self.infer_expression_type(left)?;
+                let right_type = self.infer_expression_type(right)?;
+                self.validate_binary_operation(&left_type, &right_type, op, span)?;
 
                 match op {
                     BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => {
@@ -208,56 +1093,932 @@ impl<'a> SemanticAnalyzer<'a> {
                 }
             }
 
-            Expr::Assignment { name, value } => {
-                self.validate_assignment(name, line, column)?;
-                self.infer_expression_type(value, line, column)
+            ExprKind::Grouping(inner) => self.infer_expression_type(inner),
+
+            ExprKind::Ternary { condition, then_branch, else_branch } => {
+                self.analyze_condition(condition, "Ternary")?;
+                let then_type = self.infer_expression_type(then_branch)?;
+                let else_type = self.infer_expression_type(else_branch)?;
+                self.unify_types(&then_type, &else_type).ok_or_else(|| {
+                    ParseError::at(
+                        format!(
+                            "Ternary branches have incompatible types: {:?} and {:?}",
+                            then_type, else_type
+                        ),
+                        span,
+                    )
+                })
+            }
+
+            ExprKind::ArrayLiteral(_) | ExprKind::ObjectLiteral(_) => {
+                self.infer_with_expected(expr, None)
             }
 
-            Expr::Grouping(inner) => self.infer_expression_type(inner, line, column),
+            ExprKind::StructLiteral { name, fields } => {
+                self.infer_struct_literal(name, fields, None, span)
+            }
 
-            Expr::Call { func_name, args } => {
-                let func_info = self
-                    .symbol_table
-                    .get_function_info(func_name)
-                    .map_err(|_| ParseError::new(&format!("Undefined function '{}'", func_name), line, column))?;
-
-                if args.len() != func_info.parameters.len() {
-                    return Err(ParseError::new(
-                        &format!("Function '{}' expects {} args, got {}", func_name, func_info.parameters.len(), args.len()),
-                        line,
-                        column,
+            ExprKind::Member { object, member } => {
+                if let ExprKind::Identifier(module) = &object.kind {
+                    if self.is_module_name(module) {
+                        return self.module_member_type(module, member, span);
+                    }
+                }
+                let object_type = self.infer_expression_type(object)?;
+                self.field_type(&object_type, member, span)
+            }
+
+            ExprKind::Index { object, index } => {
+                let object_type = self.infer_expression_type(object)?;
+                let element_type = match object_type {
+                    Type::Array(inner) => *inner,
+                    other => {
+                        return Err(ParseError::at(
+                            format!("Type {:?} cannot be indexed", other),
+                            span,
+                        ))
+                    }
+                };
+                let index_type = self.infer_expression_type(index)?;
+                if !index_type.is_integer() {
+                    return Err(ParseError::at(
+                        format!("Array index must be an integer, got {:?}", index_type),
+                        span,
+                    ));
+                }
+                Ok(element_type)
+            }
+
+            ExprKind::EnumVariant { enum_name, variant, args } => {
+                self.infer_enum_variant(enum_name, variant, args, None, span)
+            }
+
+            ExprKind::Match { scrutinee, arms } => self.infer_match_type(scrutinee, arms, span),
+
+            ExprKind::Spawn(call) => {
+                let result_type = self.infer_expression_type(call)?;
+                Ok(Type::Task(Box::new(result_type)))
+            }
+
+            ExprKind::Await(task) => {
+                let in_async = self.concurrency.last() == Some(&Some(ConcurrencyModifier::Async));
+                if !in_async || self.expr_lambdas.get() > 0 {
+                    return Err(ParseError::at(
+                        "'await' is only allowed inside an async function",
+                        span,
                     ));
                 }
+                match self.infer_expression_type(task)? {
+                    Type::Task(result_type) => Ok(*result_type),
+                    other => Err(ParseError::at(
+                        format!("Cannot await value of type {:?}; expected a Task", other),
+                        span,
+                    )),
+                }
+            }
+
+            ExprKind::Call { callee, args } => self.infer_call(callee, args, None, span),
+
+            ExprKind::Lambda { params, return_type, body, .. } => {
+                self.infer_lambda(params, return_type.as_ref(), body, None, span)
+            }
+        }
+    }
+
+    /// Infers the type of `expr` where the surrounding code expects `expected`.
+    /// The expected type only guides literals whose type cannot be inferred on
+    /// their own (such as `[]`); callers still check compatibility themselves.
+    pub fn infer_with_expected(&self, expr: &Expr, expected: Option<&Type>) -> Result<Type, ParseError> {
+        let span = expr.span;
+        if self.uses_failed_name(expr) {
+            return Ok(Type::Error);
+        }
+        let expected = match expected {
+            Some(Type::Optional(inner)) => Some(inner.as_ref()),
+            other => other,
+        };
 
-                for ((arg_expr, (param_name, param_type))) in args.iter().zip(func_info.parameters.iter()) {
-                    let arg_type = self.infer_expression_type(arg_expr, line, column)?;
-                    if !self.types_compatible(&arg_type, param_type) {
-                        return Err(ParseError::new(
-                            &format!("Argument '{}' expects type {:?}, got {:?}", param_name, param_type, arg_type),
-                            line,
-                            column,
+        // Integer literals take the integer type they are assigned to, so long as
+        // their value fits in it
+        if let (Some((value, suffix)), Some(target)) = (Self::int_literal(expr), expected) {
+            if target.integer_range().is_some() {
+                Self::check_int_range(value, target, span)?;
+                let ty = self.infer_expression_type(expr)?;
+                return Ok(if suffix.is_some() { ty } else { target.clone() });
+            }
+        }
+
+        match &expr.kind {
+            ExprKind::Literal(Literal::Float(f, None)) if expected == Some(&Type::Float32) => {
+                Self::check_float32_range(*f, span)?;
+                Ok(Type::Float32)
+            }
+
+            ExprKind::ArrayLiteral(elements) => {
+                if let Some(Type::Array(element_type)) = expected {
+                    for element in elements {
+                        let actual = self.infer_with_expected(element, Some(element_type))?;
+                        if !self.types_compatible(element_type, &actual) {
+                            return Err(ParseError::at(
+                                format!("Array element expects type {:?}, got {:?}", element_type, actual),
+                                element.span,
+                            ));
+                        }
+                    }
+                    return Ok(Type::Array(element_type.clone()));
+                }
+
+                let mut element_type: Option<Type> = None;
+                for element in elements {
+                    let actual = self.infer_with_expected(element, None)?;
+                    element_type = match element_type {
+                        None => Some(actual),
+                        Some(current) => Some(self.unify_types(&current, &actual).ok_or_else(|| {
+                            ParseError::at(
+                                format!(
+                                    "Array elements have incompatible types: {:?} and {:?}",
+                                    current, actual
+                                ),
+                                element.span,
+                            )
+                        })?),
+                    };
+                }
+                element_type.map(|t| Type::Array(Box::new(t))).ok_or_else(|| {
+                    ParseError::at(
+                        "Cannot infer the element type of an empty array literal; add a type annotation",
+                        span,
+                    )
+                })
+            }
+
+            ExprKind::ObjectLiteral(fields) => {
+                let mut field_types: Vec<(String, Type)> = Vec::with_capacity(fields.len());
+                for (key, value) in fields {
+                    if field_types.iter().any(|(existing, _)| existing == key) {
+                        return Err(ParseError::at(
+                            format!("Duplicate key '{}' in object literal", key),
+                            span,
                         ));
                     }
+                    let expected_field = match expected {
+                        Some(Type::Object(expected_fields)) => expected_fields
+                            .iter()
+                            .find(|(name, _)| name == key)
+                            .map(|(_, ty)| ty),
+                        _ => None,
+                    };
+                    let value_type = self.infer_with_expected(value, expected_field)?;
+                    field_types.push((key.clone(), value_type));
+                }
+                Ok(Type::Object(field_types))
+            }
+
+            ExprKind::Grouping(inner) => self.infer_with_expected(inner, expected),
+
+            ExprKind::Call { callee, args } => self.infer_call(callee, args, expected, span),
+
+            ExprKind::StructLiteral { name, fields } => {
+                self.infer_struct_literal(name, fields, expected, span)
+            }
+
+            ExprKind::EnumVariant { enum_name, variant, args } => {
+                self.infer_enum_variant(enum_name, variant, args, expected, span)
+            }
+
+            ExprKind::Lambda { params, return_type, body, .. } => {
+                self.infer_lambda(params, return_type.as_ref(), body, expected, span)
+            }
+
+            _ => self.infer_expression_type(expr),
+        }
+    }
+
+    fn infer_call(
+        &self,
+        callee: &Expr,
+        args: &[Expr],
+        expected: Option<&Type>,
+        span: Span,
+    ) -> Result<Type, ParseError> {
+        let Signature { name, type_params, params, return_type } =
+            self.callee_signature(callee)?;
+
+        if args.len() != params.len() {
+            return Err(ParseError::at(
+                format!("Function '{}' expects {} args, got {}", name, params.len(), args.len()),
+                span,
+            ));
+        }
+
+        let mut bindings = HashMap::new();
+        for (i, (arg_expr, param_type)) in args.iter().zip(params.iter()).enumerate() {
+            let hint = if matches!(arg_expr.kind, ExprKind::Lambda { .. }) {
+                // A lambda can use whatever is known so far, e.g. its parameter
+                // types in `map(xs, fn(x) => ...)` before the result type is
+                Some(self.partial_instantiation(param_type, &type_params, &bindings))
+            } else {
+                self.instantiation_hint(param_type, &type_params, &bindings)
+            };
+            let arg_type = self.infer_with_expected(arg_expr, hint.as_ref())?;
+            if !self.bind_type_params(param_type, &arg_type, &type_params, &mut bindings) {
+                return Err(ParseError::at(
+                    format!(
+                        "Argument {} of '{}' expects type {:?}, got {:?}",
+                        i + 1,
+                        name,
+                        param_type.substitute(&bindings),
+                        arg_type
+                    ),
+                    arg_expr.span,
+                ));
+            }
+        }
+
+        let return_type = return_type.unwrap_or(Type::Unknown);
+        let unbound: Vec<String> = return_type
+            .type_params()
+            .into_iter()
+            .filter(|param| type_params.contains(param) && !bindings.contains_key(param))
+            .collect();
+        if !unbound.is_empty() {
+            // Parameters only used in the result are taken from the context
+            if let Some(expected) = expected {
+                let mut from_context = bindings.clone();
+                if self.bind_type_params(&return_type, expected, &type_params, &mut from_context) {
+                    bindings = from_context;
                 }
+            }
+            if let Some(param) = unbound.iter().find(|param| !bindings.contains_key(*param)) {
+                return Err(Self::uninferred_param(param, &name, span));
+            }
+        }
+        Ok(return_type.substitute(&bindings))
+    }
+
+    fn infer_struct_literal(
+        &self,
+        name: &str,
+        fields: &[(String, Expr)],
+        expected: Option<&Type>,
+        span: Span,
+    ) -> Result<Type, ParseError> {
+        let info = self
+            .symbol_table
+            .get_struct_info(name)
+            .map_err(|_| ParseError::at(format!("Unknown struct '{}'", name), span))?;
+        let mut bindings = self.context_bindings(name, expected);
+
+        for (i, (field_name, value)) in fields.iter().enumerate() {
+            if fields[..i].iter().any(|(prev, _)| prev == field_name) {
+                return Err(ParseError::at(
+                    format!("Field '{}' specified more than once", field_name),
+                    span,
+                ));
+            }
+            let field = info.get_field(field_name).ok_or_else(|| {
+                ParseError::at(
+                    format!("Struct '{}' has no field '{}'", name, field_name),
+                    span,
+                )
+            })?;
+            let hint = self.instantiation_hint(&field.field_type, &info.type_params, &bindings);
+            let value_type = self.infer_with_expected(value, hint.as_ref())?;
+            if !self.bind_type_params(&field.field_type, &value_type, &info.type_params, &mut bindings) {
+                return Err(ParseError::at(
+                    format!(
+                        "Field '{}' expects type {:?}, got {:?}",
+                        field_name,
+                        field.field_type.substitute(&bindings),
+                        value_type
+                    ),
+                    value.span,
+                ));
+            }
+        }
+
+        let missing: Vec<&str> = info
+            .fields
+            .iter()
+            .filter(|field| !fields.iter().any(|(given, _)| *given == field.name))
+            .map(|field| field.name.as_str())
+            .collect();
+        if !missing.is_empty() {
+            return Err(ParseError::at(
+                format!("Missing fields in '{}' literal: {}", name, missing.join(", ")),
+                span,
+            ));
+        }
+
+        Self::instantiate(name, &info.type_params, &bindings, span)
+    }
 
-                Ok(func_info.return_type.clone().unwrap_or(Type::Unknown))
+    fn infer_enum_variant(
+        &self,
+        enum_name: &str,
+        variant: &str,
+        args: &[Expr],
+        expected: Option<&Type>,
+        span: Span,
+    ) -> Result<Type, ParseError> {
+        let info = self
+            .symbol_table
+            .get_enum_info(enum_name)
+            .map_err(|_| ParseError::at(format!("Unknown enum '{}'", enum_name), span))?;
+        let payload = info.get_variant(variant).ok_or_else(|| {
+            ParseError::at(
+                format!("Enum '{}' has no variant '{}'", enum_name, variant),
+                span,
+            )
+        })?;
+
+        if args.len() != payload.len() {
+            return Err(ParseError::at(
+                format!(
+                    "Variant '{}.{}' expects {} values, got {}",
+                    enum_name,
+                    variant,
+                    payload.len(),
+                    args.len()
+                ),
+                span,
+            ));
+        }
+        let mut bindings = self.context_bindings(enum_name, expected);
+        for (arg, declared) in args.iter().zip(payload) {
+            let hint = self.instantiation_hint(declared, &info.type_params, &bindings);
+            let arg_type = self.infer_with_expected(arg, hint.as_ref())?;
+            if !self.bind_type_params(declared, &arg_type, &info.type_params, &mut bindings) {
+                return Err(ParseError::at(
+                    format!(
+                        "Variant '{}.{}' expects {:?}, got {:?}",
+                        enum_name,
+                        variant,
+                        declared.substitute(&bindings),
+                        arg_type
+                    ),
+                    arg.span,
+                ));
             }
         }
+
+        Self::instantiate(enum_name, &info.type_params, &bindings, span)
+    }
+
+    // ===========================
+    // Generics
+    // ===========================
+
+    /// Matches `declared`, which may mention the type parameters `params`,
+    /// against the `actual` type found at a use site and records what each
+    /// parameter stands for. Returns false when the two cannot agree.
+    fn bind_type_params(
+        &self,
+        declared: &Type,
+        actual: &Type,
+        params: &[String],
+        bindings: &mut TypeBindings,
+    ) -> bool {
+        match (declared, actual) {
+            (Type::Param(name), _) if params.contains(name) => {
+                let bound = match bindings.get(name) {
+                    Some(existing) => self.unify_types(existing, actual),
+                    None => Some(actual.clone()),
+                };
+                match bound {
+                    Some(ty) => {
+                        bindings.insert(name.clone(), ty);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            (Type::Optional(d), Type::Optional(a))
+            | (Type::Array(d), Type::Array(a))
+            | (Type::Task(d), Type::Task(a)) => self.bind_type_params(d, a, params, bindings),
+            (Type::Optional(d), a) => self.bind_type_params(d, a, params, bindings),
+            (
+                Type::Function { params: declared_params, return_type: declared_ret },
+                Type::Function { params: actual_params, return_type: actual_ret },
+            ) if declared_params.len() == actual_params.len() => {
+                declared_params
+                    .iter()
+                    .zip(actual_params)
+                    .all(|(d, a)| self.bind_type_params(d, a, params, bindings))
+                    && match (declared_ret, actual_ret) {
+                        (Some(d), Some(a)) => self.bind_type_params(d, a, params, bindings),
+                        (None, None) => true,
+                        _ => false,
+                    }
+            }
+            (
+                Type::Generic { name: declared_name, args: declared_args },
+                Type::Generic { name: actual_name, args: actual_args },
+            ) if declared_name == actual_name && declared_args.len() == actual_args.len() => {
+                declared_args
+                    .iter()
+                    .zip(actual_args)
+                    .all(|(d, a)| self.bind_type_params(d, a, params, bindings))
+            }
+            _ => self.types_compatible(&declared.substitute(bindings), actual),
+        }
+    }
+
+    /// The type a use site should expect for `declared`, once every type
+    /// parameter in it is known; `None` while some are still unbound.
+    fn instantiation_hint(
+        &self,
+        declared: &Type,
+        params: &[String],
+        bindings: &TypeBindings,
+    ) -> Option<Type> {
+        let unbound = declared
+            .type_params()
+            .iter()
+            .any(|param| params.contains(param) && !bindings.contains_key(param));
+        (!unbound).then(|| declared.substitute(bindings))
+    }
+
+    /// Like `instantiation_hint`, but with the type parameters that are still
+    /// unbound replaced by `Unknown`.
+    fn partial_instantiation(&self, declared: &Type, params: &[String], bindings: &TypeBindings) -> Type {
+        let mut partial = bindings.clone();
+        for param in params {
+            partial.entry(param.clone()).or_insert(Type::Unknown);
+        }
+        declared.substitute(&partial)
+    }
+
+    /// Type arguments of a generic struct or enum taken from the type the
+    /// context expects, e.g. the annotation of `let b: Box<int32> = Box { ... }`.
+    fn context_bindings(&self, name: &str, expected: Option<&Type>) -> TypeBindings {
+        match expected.and_then(|ty| self.nominal_type(ty)) {
+            Some((expected_name, bindings)) if expected_name == name => bindings,
+            _ => HashMap::new(),
+        }
+    }
+
+    fn instantiate(
+        name: &str,
+        params: &[String],
+        bindings: &TypeBindings,
+        span: Span,
+    ) -> Result<Type, ParseError> {
+        if params.is_empty() {
+            return Ok(Type::Custom(name.to_string()));
+        }
+        let args = params
+            .iter()
+            .map(|param| {
+                bindings
+                    .get(param)
+                    .cloned()
+                    .ok_or_else(|| Self::uninferred_param(param, name, span))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Type::Generic { name: name.to_string(), args })
+    }
+
+    fn uninferred_param(param: &str, owner: &str, span: Span) -> ParseError {
+        ParseError::at(
+            format!("Cannot infer type parameter '{}' of '{}'; add a type annotation", param, owner),
+            span,
+        )
+    }
+
+    fn function_type(info: &FunctionInfo) -> Type {
+        Type::Function {
+            params: info.parameters.iter().map(|(_, ty)| ty.clone()).collect(),
+            return_type: info.return_type.clone().map(Box::new),
+        }
+    }
+
+    /// Resolves what a call expression invokes: a declared function, a struct
+    /// method, or any value of function type.
+    fn callee_signature(&self, callee: &Expr) -> Result<Signature, ParseError> {
+        let span = callee.span;
+        let (name, callee_type) = match &callee.kind {
+            ExprKind::Identifier(name) => match self.lookup_variable_type(name, span) {
+                Ok(ty) => (name.clone(), ty),
+                Err(_) => {
+                    let info = self.symbol_table.get_function_info(name).map_err(|_| {
+                        ParseError::at(format!("Undefined function '{}'", name), span)
+                    })?;
+                    return Ok(Signature {
+                        name: name.clone(),
+                        type_params: info.type_params.clone(),
+                        params: info.parameters.iter().map(|(_, ty)| ty.clone()).collect(),
+                        return_type: info.return_type.clone(),
+                    });
+                }
+            },
+            ExprKind::Member { object, member } if matches!(&object.kind, ExprKind::Identifier(m) if self.is_module_name(m)) => {
+                // Module functions are top-level, so every parameter in their type is their own
+                let ty = self.infer_expression_type(callee)?;
+                let type_params = ty.type_params();
+                return match ty {
+                    Type::Function { params, return_type } => Ok(Signature {
+                        name: member.clone(),
+                        type_params,
+                        params,
+                        return_type: return_type.map(|t| *t),
+                    }),
+                    other => Err(ParseError::at(
+                        format!("'{}' is not callable: it has type {:?}", member, other),
+                        span,
+                    )),
+                };
+            }
+            ExprKind::Member { object, member } => {
+                let object_type = self.infer_expression_type(object)?;
+                if let Some((method, bindings)) = self.method_info(&object_type, member, span)? {
+                    return Ok(Signature {
+                        name: member.clone(),
+                        type_params: method.type_params.clone(),
+                        params: method
+                            .parameters
+                            .iter()
+                            .map(|(_, ty)| ty.substitute(&bindings))
+                            .collect(),
+                        return_type: method.return_type.as_ref().map(|ty| ty.substitute(&bindings)),
+                    });
+                }
+                (member.clone(), self.field_type(&object_type, member, span)?)
+            }
+            _ => ("expression".to_string(), self.infer_expression_type(callee)?),
+        };
+
+        match callee_type {
+            Type::Function { params, return_type } => Ok(Signature {
+                name,
+                type_params: Vec::new(),
+                params,
+                return_type: return_type.map(|t| *t),
+            }),
+            other => Err(ParseError::at(
+                format!("'{}' is not callable: it has type {:?}", name, other),
+                span,
+            )),
+        }
+    }
+
+    /// Looks up a method on a struct type, applying the same visibility rules
+    /// as field access.
+    fn method_info(
+        &self,
+        object_type: &Type,
+        member: &str,
+        span: Span,
+    ) -> Result<Option<(&FunctionInfo, TypeBindings)>, ParseError> {
+        let Some((struct_name, bindings)) = self.nominal_type(object_type) else {
+            return Ok(None);
+        };
+        let Some(method) = self
+            .symbol_table
+            .get_struct_info(struct_name)
+            .ok()
+            .and_then(|info| info.methods.get(member))
+        else {
+            return Ok(None);
+        };
+
+        let restricted = matches!(
+            method.visibility,
+            Some(Visibility::Private) | Some(Visibility::Protected)
+        );
+        if restricted && self.current_struct.as_deref() != Some(struct_name.as_str()) {
+            return Err(ParseError::at(
+                format!("Method '{}' of struct '{}' is private", member, struct_name),
+                span,
+            ));
+        }
+        Ok(Some((method, bindings)))
+    }
+
+    fn lookup_variable_type(&self, name: &str, span: Span) -> Result<Type, ParseError> {
+        let bound = self
+            .expr_scopes
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).cloned());
+        match bound {
+            Some(ty) => Ok(ty),
+            None => self
+                .symbol_table
+                .get_variable_type(name)
+                .map_err(|err| ParseError::at(err.to_string(), span)),
+        }
+    }
+
+    /// Runs `f` with `bindings` visible to identifier lookups.
+    fn with_expr_scope<T>(
+        &self,
+        bindings: HashMap<String, Type>,
+        f: impl FnOnce() -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        self.expr_scopes.borrow_mut().push(bindings);
+        let result = f();
+        self.expr_scopes.borrow_mut().pop();
+        result
+    }
+
+    /// True when `name` refers to an imported module rather than a value.
+    pub fn is_module_name(&self, name: &str) -> bool {
+        self.symbol_table.module_exists(name)
+            && !self.symbol_table.variable_exists(name)
+            && !self.expr_scopes.borrow().iter().any(|scope| scope.contains_key(name))
+    }
+
+    pub fn is_type_name(&self, name: &str) -> bool {
+        self.symbol_table.type_exists(name)
+    }
+
+    fn module_member_type(
+        &self,
+        module: &str,
+        member: &str,
+        span: Span,
+    ) -> Result<Type, ParseError> {
+        let info = self
+            .symbol_table
+            .get_module(module)
+            .ok_or_else(|| ParseError::at(format!("Unknown module '{}'", module), span))?;
+        info.values.get(member).cloned().ok_or_else(|| {
+            ParseError::at(
+                format!("Module '{}' has no public item '{}'", info.path, member),
+                span,
+            )
+        })
+    }
+
+    /// True when `name` refers to an enum type rather than a value, so that
+    /// `Name.Variant` names a variant.
+    pub fn is_enum_name(&self, name: &str) -> bool {
+        self.symbol_table.enum_exists(name)
+            && !self.symbol_table.variable_exists(name)
+            && !self.expr_scopes.borrow().iter().any(|scope| scope.contains_key(name))
+    }
+
+    // ===========================
+    // Match Expressions
+    // ===========================
+    fn infer_match_type(
+        &self,
+        scrutinee: &Expr,
+        arms: &[MatchArm],
+        span: Span,
+    ) -> Result<Type, ParseError> {
+        let scrutinee_type = self.infer_expression_type(scrutinee)?;
+        if arms.is_empty() {
+            return Err(ParseError::at("Match expression has no arms", span));
+        }
+
+        let mut result_type: Option<Type> = None;
+        for arm in arms {
+            let mut bindings = HashMap::new();
+            self.check_pattern(&arm.pattern, &scrutinee_type, &mut bindings)?;
+            let arm_type =
+                self.with_expr_scope(bindings, || self.infer_expression_type(&arm.body))?;
+
+            result_type = match result_type {
+                None => Some(arm_type),
+                Some(current) => Some(self.unify_types(&current, &arm_type).ok_or_else(|| {
+                    ParseError::at(
+                        format!(
+                            "Match arms have incompatible types: {:?} and {:?}",
+                            current, arm_type
+                        ),
+                        span,
+                    )
+                })?),
+            };
+        }
+
+        self.check_exhaustive(&scrutinee_type, arms, span)?;
+        Ok(result_type.unwrap_or(Type::Unknown))
+    }
+
+    fn check_pattern(
+        &self,
+        pattern: &Pattern,
+        expected: &Type,
+        bindings: &mut HashMap<String, Type>,
+    ) -> Result<(), ParseError> {
+        let span = pattern.span;
+        match &pattern.kind {
+            PatternKind::Wildcard => Ok(()),
+            PatternKind::Binding(name) => {
+                if bindings.insert(name.clone(), expected.clone()).is_some() {
+                    return Err(ParseError::at(
+                        format!("Identifier '{}' is bound more than once in the same pattern", name),
+                        span,
+                    ));
+                }
+                Ok(())
+            }
+            PatternKind::Literal(lit) => {
+                let lit_type = Type::infer_from_literal(lit);
+                if self.types_compatible(expected, &lit_type) {
+                    Ok(())
+                } else {
+                    Err(ParseError::at(
+                        format!("Pattern of type {:?} cannot match value of type {:?}", lit_type, expected),
+                        span,
+                    ))
+                }
+            }
+            PatternKind::Variant { enum_name, variant, fields } => {
+                let type_args = match self.nominal_type(expected) {
+                    Some((name, bindings)) if name == enum_name => bindings,
+                    _ => {
+                        return Err(ParseError::at(
+                            format!("Pattern '{}.{}' cannot match value of type {:?}", enum_name, variant, expected),
+                            span,
+                        ))
+                    }
+                };
+                let info = self
+                    .symbol_table
+                    .get_enum_info(enum_name)
+                    .map_err(|_| ParseError::at(format!("Unknown enum '{}'", enum_name), span))?;
+                let payload = info.get_variant(variant).ok_or_else(|| {
+                    ParseError::at(
+                        format!("Enum '{}' has no variant '{}'", enum_name, variant),
+                        span,
+                    )
+                })?;
+                if fields.len() != payload.len() {
+                    return Err(ParseError::at(
+                        format!(
+                            "Pattern '{}.{}' expects {} fields, got {}",
+                            enum_name,
+                            variant,
+                            payload.len(),
+                            fields.len()
+                        ),
+                        span,
+                    ));
+                }
+                for (field, field_type) in fields.iter().zip(payload) {
+                    self.check_pattern(field, &field_type.substitute(&type_args), bindings)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn check_exhaustive(
+        &self,
+        scrutinee_type: &Type,
+        arms: &[MatchArm],
+        span: Span,
+    ) -> Result<(), ParseError> {
+        if arms.iter().any(|arm| arm.pattern.is_catch_all()) {
+            return Ok(());
+        }
+
+        let missing: Vec<String> = match scrutinee_type {
+            Type::Custom(name) | Type::Generic { name, .. } if self.symbol_table.enum_exists(name) => {
+                let info = self
+                    .symbol_table
+                    .get_enum_info(name)
+                    .map_err(|e| ParseError::at(e.to_string(), span))?;
+                info.variants
+                    .iter()
+                    .filter(|(variant, payload)| {
+                        !arms.iter().any(|arm| match &arm.pattern.kind {
+                            PatternKind::Variant { variant: v, fields, .. } => {
+                                v == variant
+                                    && fields.len() == payload.len()
+                                    && fields.iter().all(Pattern::is_catch_all)
+                            }
+                            _ => false,
+                        })
+                    })
+                    .map(|(variant, _)| format!("{}.{}", name, variant))
+                    .collect()
+            }
+            Type::Bool => [true, false]
+                .iter()
+                .filter(|value| {
+                    !arms
+                        .iter()
+                        .any(|arm| arm.pattern.kind == PatternKind::Literal(Literal::Boolean(**value)))
+                })
+                .map(|value| value.to_string())
+                .collect(),
+            _ => vec!["_".to_string()],
+        };
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(ParseError::at(
+                format!("Non-exhaustive match: missing {}", missing.join(", ")),
+                span,
+            ))
+        }
+    }
+
+    /// Finds a common type for two values that must agree, e.g. match arms.
+    pub fn unify_types(&self, a: &Type, b: &Type) -> Option<Type> {
+        match (a, b) {
+            (a, b) if a == b => Some(a.clone()),
+            (a, b) if a.is_numeric() && b.is_numeric() => Some(self.promote_numeric_types(a, b)),
+            (Type::Optional(inner), other) | (other, Type::Optional(inner)) => self
+                .unify_types(inner, other)
+                .map(|t| Type::Optional(Box::new(t))),
+            (Type::Array(a), Type::Array(b)) => {
+                self.unify_types(a, b).map(|t| Type::Array(Box::new(t)))
+            }
+            (Type::Object(a), Type::Object(b)) if a.len() == b.len() => a
+                .iter()
+                .map(|(name, ty)| {
+                    let (_, other) = b.iter().find(|(other_name, _)| other_name == name)?;
+                    Some((name.clone(), self.unify_types(ty, other)?))
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(Type::Object),
+            _ => None,
+        }
+    }
+
+    fn field_type(
+        &self,
+        object_type: &Type,
+        member: &str,
+        span: Span,
+    ) -> Result<Type, ParseError> {
+        let Some((struct_name, bindings)) = self.nominal_type(object_type) else {
+            return match object_type {
+                Type::Object(fields) => fields
+                    .iter()
+                    .find(|(name, _)| name == member)
+                    .map(|(_, ty)| ty.clone())
+                    .ok_or_else(|| {
+                        ParseError::at(format!("Object has no field '{}'", member), span)
+                    }),
+                Type::Optional(_) => Err(ParseError::at(
+                    format!("Cannot access '{}' on optional type {:?}", member, object_type),
+                    span,
+                )),
+                other => Err(ParseError::at(
+                    format!("Type {:?} has no fields", other),
+                    span,
+                )),
+            };
+        };
+
+        if self.symbol_table.enum_exists(struct_name) {
+            return Err(ParseError::at(
+                format!("Enum '{}' has no fields; use 'match' to read its payload", struct_name),
+                span,
+            ));
+        }
+
+        let info = self
+            .symbol_table
+            .get_struct_info(struct_name)
+            .map_err(|_| ParseError::at(format!("Unknown struct '{}'", struct_name), span))?;
+
+        match info.get_field(member) {
+            Some(field) => {
+                let restricted = matches!(
+                    field.visibility,
+                    Some(Visibility::Private) | Some(Visibility::Protected)
+                );
+                if restricted && self.current_struct.as_deref() != Some(struct_name.as_str()) {
+                    return Err(ParseError::at(
+                        format!("Field '{}' of struct '{}' is private", member, struct_name),
+                        span,
+                    ));
+                }
+                Ok(field.field_type.substitute(&bindings))
+            }
+            None if info.methods.contains_key(member) => Err(ParseError::at(
+                format!("Method '{}' of struct '{}' must be called", member, struct_name),
+                span,
+            )),
+            None => Err(ParseError::at(
+                format!("Struct '{}' has no field '{}'", struct_name, member),
+                span,
+            )),
+        }
     }
 
     // ===========================
     // Assignment Validation
     // ===========================
-    pub fn validate_assignment(&self, name: &str, line: usize, column: usize) -> Result<(), ParseError> {
-        match self.symbol_table.get_variable_type(name) {
-            Ok(_) => {
-                let symbol = self.symbol_table.get_all_variables().get(name).unwrap();
+    pub fn validate_assignment(&self, name: &str, span: Span) -> Result<(), ParseError> {
+        match self.symbol_table.get_variable(name) {
+            Some(symbol) => {
                 if !symbol.is_mutable() {
-                    return Err(ParseError::new(&format!("Cannot assign to immutable variable '{}'", name), line, column));
+                    return Err(ParseError::at(format!("Cannot assign to immutable variable '{}'", name), span));
                 }
             }
-            Err(_) => {
-                return Err(ParseError::new(&format!("Undefined variable '{}'", name), line, column));
+            None => {
+                return Err(ParseError::at(format!("Undefined variable '{}'", name), span));
             }
         }
         Ok(())
@@ -271,28 +2032,27 @@ impl<'a> SemanticAnalyzer<'a> {
         left: &Type,
         right: &Type,
         op: &BinaryOp,
-        line: usize,
-        column: usize,
+        span: Span,
     ) -> Result<(), ParseError> {
         match op {
             BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => {
                 if !left.is_numeric() || !right.is_numeric() {
-                    return Err(ParseError::new("Arithmetic operations require numeric types", line, column));
+                    return Err(ParseError::at("Arithmetic operations require numeric types", span));
                 }
             }
             BinaryOp::And | BinaryOp::Or => {
                 if !matches!(left, Type::Bool) || !matches!(right, Type::Bool) {
-                    return Err(ParseError::new("Logical operations require boolean types", line, column));
+                    return Err(ParseError::at("Logical operations require boolean types", span));
                 }
             }
             BinaryOp::Equal | BinaryOp::NotEqual => {
                 if !self.types_compatible(left, right) {
-                    return Err(ParseError::new("Equality comparison requires compatible types", line, column));
+                    return Err(ParseError::at("Equality comparison requires compatible types", span));
                 }
             }
             BinaryOp::Less | BinaryOp::LessEqual | BinaryOp::Greater | BinaryOp::GreaterEqual => {
                 if !left.is_numeric() || !right.is_numeric() {
-                    return Err(ParseError::new("Comparison operations require numeric types", line, column));
+                    return Err(ParseError::at("Comparison operations require numeric types", span));
                 }
             }
         }
@@ -312,9 +2072,43 @@ impl<'a> SemanticAnalyzer<'a> {
         if priority(left) >= priority(right) { left.clone() } else { right.clone() }
     }
 
+    /// The value of an integer literal, possibly negated or parenthesized, and
+    /// the type its suffix gives it.
+    fn int_literal(expr: &Expr) -> Option<(i128, Option<&Type>)> {
+        match &expr.kind {
+            ExprKind::Literal(Literal::Int(value, suffix)) => Some((*value, suffix.as_ref())),
+            ExprKind::Unary { op: UnaryOp::Negate, expr } => {
+                Self::int_literal(expr).map(|(value, suffix)| (-value, suffix))
+            }
+            ExprKind::Grouping(inner) => Self::int_literal(inner),
+            _ => None,
+        }
+    }
+
+    fn check_int_range(value: i128, ty: &Type, span: Span) -> Result<(), ParseError> {
+        match ty.integer_range() {
+            Some((min, max)) if value < min || value > max => Err(ParseError::at(
+                format!("Integer literal {} is out of range for {:?}", value, ty),
+                span,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_float32_range(value: f64, span: Span) -> Result<(), ParseError> {
+        if value.abs() > f32::MAX as f64 {
+            return Err(ParseError::at(
+                format!("Float literal {} is out of range for {:?}", value, Type::Float32),
+                span,
+            ));
+        }
+        Ok(())
+    }
+
     fn types_compatible(&self, t1: &Type, t2: &Type) -> bool {
         match (t1, t2) {
             (a, b) if a == b => true,
+            (Type::Error, _) | (_, Type::Error) => true,
             (Type::Optional(inner1), t2) => self.types_compatible(inner1, t2),
             (t1, Type::Optional(inner2)) => self.types_compatible(t1, inner2),
             (a, b) if a.is_numeric() && b.is_numeric() => true, // numeric promotion
@@ -323,6 +2117,6 @@ impl<'a> SemanticAnalyzer<'a> {
     }
 
     pub fn get_symbol_table_mut(&mut self) -> &mut SymbolTable {
-        &mut self.symbol_table
+        self.symbol_table
     }
 }
@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+
+use crate::parser::visit::{visitor, Visitor};
+use crate::parser::{Block, ConstDecl, Expr, ExprKind, LambdaBody, MatchArm, Stmt, VarDecl};
+
+/// Names a lambda body refers to without binding them itself.
+#[derive(Debug, Default)]
+pub struct FreeVariables {
+    /// In order of first use.
+    pub used: Vec<String>,
+    /// The subset of `used` the body assigns to.
+    pub assigned: HashSet<String>,
+}
+
+/// Collects the free variables of a lambda with the given parameters. This is
+/// purely syntactic: whether a name refers to a variable at all is decided by
+/// the caller against the symbol table.
+pub fn free_variables<'a>(params: impl IntoIterator<Item = &'a str>, body: &LambdaBody) -> FreeVariables {
+    let mut collector = Collector {
+        scopes: vec![params.into_iter().map(str::to_string).collect()],
+        free: FreeVariables::default(),
+    };
+    collector.visit_lambda_body(body);
+    collector.free
+}
+
+/// Names `expr` refers to without binding them itself, in order of first use.
+pub fn free_names(expr: &Expr) -> Vec<String> {
+    let mut collector = Collector { scopes: Vec::new(), free: FreeVariables::default() };
+    collector.visit_expr(expr);
+    collector.free.used
+}
+
+struct Collector {
+    scopes: Vec<HashSet<String>>,
+    free: FreeVariables,
+}
+
+impl Collector {
+    fn is_bound(&self, name: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.contains(name))
+    }
+
+    fn bind(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string());
+        }
+    }
+
+    fn use_name(&mut self, name: &str) {
+        if !self.is_bound(name) && !self.free.used.iter().any(|used| used == name) {
+            self.free.used.push(name.to_string());
+        }
+    }
+
+    fn scoped(&mut self, names: Vec<String>, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(names.into_iter().collect());
+        f(self);
+        self.scopes.pop();
+    }
+}
+
+impl Visitor for Collector {
+    fn visit_var_decl(&mut self, decl: &VarDecl) {
+        visitor::walk_var_decl(self, decl);
+        self.bind(&decl.name);
+    }
+
+    fn visit_const_decl(&mut self, decl: &ConstDecl) {
+        visitor::walk_const_decl(self, decl);
+        self.bind(&decl.name);
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        self.scoped(Vec::new(), |this| visitor::walk_block(this, block));
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Assignment { target, value, .. } => {
+                self.visit_expr(value);
+                self.visit_expr(target);
+                let mut root = target;
+                while let ExprKind::Member { object, .. } | ExprKind::Index { object, .. } = &root.kind {
+                    root = object;
+                }
+                if let ExprKind::Identifier(name) = &root.kind {
+                    if !self.is_bound(name) {
+                        self.free.assigned.insert(name.clone());
+                    }
+                }
+            }
+            Stmt::For { variable, iterable, body, .. } => {
+                self.visit_expr(iterable);
+                self.scoped(vec![variable.clone()], |this| this.visit_block(body));
+            }
+            _ => visitor::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Identifier(name) => self.use_name(name),
+            ExprKind::Lambda { params, body, .. } => {
+                let names = params.iter().map(|param| param.name.clone()).collect();
+                self.scoped(names, |this| this.visit_lambda_body(body));
+            }
+            _ => visitor::walk_expr(self, expr),
+        }
+    }
+
+    fn visit_match_arm(&mut self, arm: &MatchArm) {
+        self.scoped(arm.pattern.bindings(), |this| this.visit_expr(&arm.body));
+    }
+}
@@ -0,0 +1,198 @@
+use crate::parser::symbol_table::SymbolTable;
+use crate::parser::{BinaryOp, Expr, ExprKind, InterpolationPart, Literal, UnaryOp};
+
+/// Why a constant initializer could not be folded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstEvalError {
+    /// The expression contains something that is only known at run time.
+    NotConstant(String),
+    /// The expression is constant but its evaluation fails.
+    Arithmetic(String),
+    /// The constant depends on itself; the names of the constants around
+    /// the cycle, starting and ending with the same one.
+    Cycle(Vec<String>),
+}
+
+impl ConstEvalError {
+    /// `name` is bound, but to a variable.
+    pub fn variable(name: &str) -> Self {
+        ConstEvalError::NotConstant(format!("'{}' is a variable, not a constant", name))
+    }
+}
+
+/// Folds `const` initializers at compile time. Only literals, other constants,
+/// unary, binary and ternary operators and string interpolations over them
+/// are accepted.
+pub struct ConstEvaluator<'t> {
+    symbol_table: &'t SymbolTable,
+}
+
+impl<'t> ConstEvaluator<'t> {
+    pub fn new(symbol_table: &'t SymbolTable) -> Self {
+        ConstEvaluator { symbol_table }
+    }
+
+    pub fn evaluate(&self, expr: &Expr) -> Result<Literal, ConstEvalError> {
+        match &expr.kind {
+            ExprKind::Literal(lit) => Ok(lit.clone()),
+            ExprKind::Grouping(inner) => self.evaluate(inner),
+            ExprKind::Identifier(name) => self.lookup_constant(name),
+            ExprKind::Member { object, member } => match &object.kind {
+                ExprKind::Identifier(module) if self.symbol_table.module_exists(module) => self
+                    .symbol_table
+                    .get_module(module)
+                    .and_then(|info| info.constants.get(member))
+                    .cloned()
+                    .ok_or_else(|| {
+                        ConstEvalError::NotConstant(format!("'{}.{}' is not a constant", module, member))
+                    }),
+                _ => Err(Self::not_constant("field access")),
+            },
+            ExprKind::Unary { op, expr } => Self::fold_unary(op, self.evaluate(expr)?),
+            ExprKind::Binary { left, op, right } => {
+                let left = self.evaluate(left)?;
+                let right = self.evaluate(right)?;
+                Self::fold_binary(op, left, right)
+            }
+            ExprKind::Ternary { condition, then_branch, else_branch } => {
+                let then_value = self.evaluate(then_branch)?;
+                let else_value = self.evaluate(else_branch)?;
+                match self.evaluate(condition)? {
+                    Literal::Boolean(true) => Ok(then_value),
+                    Literal::Boolean(false) => Ok(else_value),
+                    other => Err(Self::type_error("ternary condition", &other)),
+                }
+            }
+            ExprKind::Call { callee, .. } => Err(ConstEvalError::NotConstant(match &callee.kind {
+                ExprKind::Identifier(name) => format!("call to '{}' cannot be evaluated at compile time", name),
+                _ => "function calls cannot be evaluated at compile time".to_string(),
+            })),
+            ExprKind::Index { .. } => Err(Self::not_constant("indexing")),
+            ExprKind::StructLiteral { .. } => Err(Self::not_constant("struct literals")),
+            ExprKind::ArrayLiteral(_) => Err(Self::not_constant("array literals")),
+            ExprKind::ObjectLiteral(_) => Err(Self::not_constant("object literals")),
+            ExprKind::EnumVariant { .. } => Err(Self::not_constant("enum values")),
+            ExprKind::Match { .. } => Err(Self::not_constant("match expressions")),
+            ExprKind::Spawn(_) | ExprKind::Await(_) => Err(Self::not_constant("tasks")),
+            ExprKind::Lambda { .. } => Err(Self::not_constant("lambdas")),
+            ExprKind::Interpolated { parts } => {
+                let mut text = String::new();
+                for part in parts {
+                    match part {
+                        InterpolationPart::Literal(literal) => text.push_str(literal),
+                        InterpolationPart::Expr(expr) => text.push_str(&Self::stringify(self.evaluate(expr)?)),
+                    }
+                }
+                Ok(Literal::String(text))
+            }
+        }
+    }
+
+    /// The text a constant contributes to an interpolated string.
+    fn stringify(value: Literal) -> String {
+        match value {
+            Literal::Int(n, _) => n.to_string(),
+            Literal::Float(f, _) => f.to_string(),
+            Literal::String(text) => text,
+            Literal::Rune(c) => c.to_string(),
+            Literal::Byte(b) => b.to_string(),
+            Literal::ByteString(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            Literal::Boolean(b) => b.to_string(),
+        }
+    }
+
+    fn lookup_constant(&self, name: &str) -> Result<Literal, ConstEvalError> {
+        match self.symbol_table.get_variable(name) {
+            Some(info) => info.constant.clone().ok_or_else(|| ConstEvalError::variable(name)),
+            None => Err(ConstEvalError::NotConstant(format!("'{}' is not a constant", name))),
+        }
+    }
+
+    fn not_constant(what: &str) -> ConstEvalError {
+        ConstEvalError::NotConstant(format!("{} are not allowed in constant expressions", what))
+    }
+
+    fn type_error(context: &str, value: &Literal) -> ConstEvalError {
+        ConstEvalError::Arithmetic(format!("unexpected value {:?} in {}", value, context))
+    }
+
+    fn fold_unary(op: &UnaryOp, value: Literal) -> Result<Literal, ConstEvalError> {
+        match (op, value) {
+            (UnaryOp::Positive, value @ (Literal::Int(..) | Literal::Float(..))) => Ok(value),
+            (UnaryOp::Negate, Literal::Int(n, ty)) => Ok(Literal::Int(-n, ty)),
+            (UnaryOp::Negate, Literal::Float(f, ty)) => Ok(Literal::Float(-f, ty)),
+            (UnaryOp::Not, Literal::Boolean(b)) => Ok(Literal::Boolean(!b)),
+            (_, other) => Err(Self::type_error("unary operation", &other)),
+        }
+    }
+
+    fn fold_binary(op: &BinaryOp, left: Literal, right: Literal) -> Result<Literal, ConstEvalError> {
+        use Literal::*;
+
+        match (op, left, right) {
+            (BinaryOp::And, Boolean(a), Boolean(b)) => Ok(Boolean(a && b)),
+            (BinaryOp::Or, Boolean(a), Boolean(b)) => Ok(Boolean(a || b)),
+            (BinaryOp::Equal, a, b) => Ok(Boolean(Self::literals_equal(&a, &b))),
+            (BinaryOp::NotEqual, a, b) => Ok(Boolean(!Self::literals_equal(&a, &b))),
+            (op, Int(a, _), Int(b, _)) => Self::fold_int(op, a, b),
+            (op, Int(a, _), Float(b, _)) => Self::fold_float(op, a as f64, b),
+            (op, Float(a, _), Int(b, _)) => Self::fold_float(op, a, b as f64),
+            (op, Float(a, _), Float(b, _)) => Self::fold_float(op, a, b),
+            (op, a, _) => Err(Self::type_error(&format!("{:?} operation", op), &a)),
+        }
+    }
+
+    /// Folds integer arithmetic in 128 bits, wide enough for every integer
+    /// type; whether the result fits the constant's type is checked by the
+    /// caller.
+    fn fold_int(op: &BinaryOp, a: i128, b: i128) -> Result<Literal, ConstEvalError> {
+        let checked = |result: Option<i128>| {
+            result
+                .map(|n| Literal::Int(n, None))
+                .ok_or_else(|| ConstEvalError::Arithmetic("integer overflow".to_string()))
+        };
+
+        match op {
+            BinaryOp::Add => checked(a.checked_add(b)),
+            BinaryOp::Sub => checked(a.checked_sub(b)),
+            BinaryOp::Mul => checked(a.checked_mul(b)),
+            BinaryOp::Div | BinaryOp::Mod if b == 0 => {
+                Err(ConstEvalError::Arithmetic("division by zero".to_string()))
+            }
+            BinaryOp::Div => checked(a.checked_div(b)),
+            BinaryOp::Mod => checked(a.checked_rem(b)),
+            BinaryOp::Less => Ok(Literal::Boolean(a < b)),
+            BinaryOp::LessEqual => Ok(Literal::Boolean(a <= b)),
+            BinaryOp::Greater => Ok(Literal::Boolean(a > b)),
+            BinaryOp::GreaterEqual => Ok(Literal::Boolean(a >= b)),
+            other => Err(Self::type_error(&format!("{:?} operation", other), &Literal::Int(a, None))),
+        }
+    }
+
+    fn fold_float(op: &BinaryOp, a: f64, b: f64) -> Result<Literal, ConstEvalError> {
+        match op {
+            BinaryOp::Add => Ok(Literal::Float(a + b, None)),
+            BinaryOp::Sub => Ok(Literal::Float(a - b, None)),
+            BinaryOp::Mul => Ok(Literal::Float(a * b, None)),
+            BinaryOp::Div | BinaryOp::Mod if b == 0.0 => {
+                Err(ConstEvalError::Arithmetic("division by zero".to_string()))
+            }
+            BinaryOp::Div => Ok(Literal::Float(a / b, None)),
+            BinaryOp::Mod => Ok(Literal::Float(a % b, None)),
+            BinaryOp::Less => Ok(Literal::Boolean(a < b)),
+            BinaryOp::LessEqual => Ok(Literal::Boolean(a <= b)),
+            BinaryOp::Greater => Ok(Literal::Boolean(a > b)),
+            BinaryOp::GreaterEqual => Ok(Literal::Boolean(a >= b)),
+            other => Err(Self::type_error(&format!("{:?} operation", other), &Literal::Float(a, None))),
+        }
+    }
+
+    fn literals_equal(a: &Literal, b: &Literal) -> bool {
+        match (a, b) {
+            (Literal::Int(x, _), Literal::Float(y, _)) | (Literal::Float(y, _), Literal::Int(x, _)) => *x as f64 == *y,
+            (Literal::Int(x, _), Literal::Int(y, _)) => x == y,
+            (Literal::Float(x, _), Literal::Float(y, _)) => x == y,
+            _ => a == b,
+        }
+    }
+}
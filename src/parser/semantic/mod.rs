@@ -1 +1,23 @@
-pub mod analyzer;
\ No newline at end of file
+pub mod analyzer;
+pub mod captures;
+pub mod const_eval;
+pub mod resolver;
+pub mod type_checker;
+
+use crate::parser::recursive_descent::errors::ParseError;
+use crate::parser::{Program, SymbolTable};
+
+use analyzer::SemanticAnalyzer;
+use resolver::NameResolver;
+use type_checker::TypeChecker;
+
+/// Runs name resolution and then type checking over a parsed program,
+/// declaring its items in `symbol_table`. Returns the errors of both passes
+/// in source order.
+pub fn analyze_program(program: &mut Program, symbol_table: &mut SymbolTable) -> Vec<ParseError> {
+    let mut analyzer = SemanticAnalyzer::new(symbol_table);
+    let (mut errors, undeclared) = NameResolver::new(&mut analyzer).resolve_program(program);
+    errors.extend(TypeChecker::new(&mut analyzer).check_program(program, &undeclared));
+    errors.sort_by_key(|err| (err.line, err.column));
+    errors
+}
@@ -0,0 +1,319 @@
+//! Name resolution, the first semantic pass over a parsed `Program`.
+//!
+//! Every top-level item is declared before any body is looked at, so
+//! functions, types and top-level constants may be used before their
+//! declaration; variables and local constants are only visible after theirs.
+//! Top-level constants are folded here, each after the constants it refers
+//! to, and a constant that depends on itself is reported. The parser cannot
+//! tell what a dotted name refers to, so this pass also rewrites
+//! `Enum.Variant` and `Enum.Variant(args)` into `ExprKind::EnumVariant`, and
+//! `module.Type` into the qualified identifier `module.Type`.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::parser::recursive_descent::errors::ParseError;
+use crate::parser::semantic::analyzer::SemanticAnalyzer;
+use crate::parser::semantic::captures;
+use crate::parser::semantic::const_eval::ConstEvalError;
+use crate::parser::visit::{visitor_mut, VisitorMut};
+use crate::parser::{
+    ASTNode, Block, ConstDecl, EnumDecl, Expr, ExprKind, FnDecl, MatchArm, Mutability, Program, Stmt, StructDecl,
+    VarDecl,
+};
+
+pub struct NameResolver<'s, 'a> {
+    analyzer: &'s mut SemanticAnalyzer<'a>,
+    errors: Vec<ParseError>,
+    /// Indices of the top-level declarations that could not be declared.
+    undeclared: HashSet<usize>,
+    /// Variables bound so far, innermost scope last; the first scope holds
+    /// the program's globals. They shadow enum and module names.
+    scopes: Vec<HashSet<String>>,
+}
+
+/// The progress of `NameResolver::declare_constants`.
+struct ConstFolding {
+    /// Indices of the top-level constants by name.
+    constants: HashMap<String, usize>,
+    /// Names of the top-level variables.
+    variables: HashSet<String>,
+    /// Constants being folded, each waiting on the next one.
+    in_progress: Vec<usize>,
+    /// Constants already folded or reported.
+    done: HashSet<usize>,
+}
+
+impl<'s, 'a> NameResolver<'s, 'a> {
+    pub fn new(analyzer: &'s mut SemanticAnalyzer<'a>) -> Self {
+        NameResolver { analyzer, errors: Vec::new(), undeclared: HashSet::new(), scopes: vec![HashSet::new()] }
+    }
+
+    /// Declares the items of `program` and resolves the dotted names in it.
+    /// Returns the errors together with the indices of the declarations that
+    /// could not be declared; those stay in the tree but are not checked.
+    pub fn resolve_program(mut self, program: &mut Program) -> (Vec<ParseError>, HashSet<usize>) {
+        // Type names first, so that any signature, field or payload may use any
+        // type. Aliases are declared afterwards, but a struct or enum reusing
+        // the name of an earlier alias is still the one reported.
+        let mut aliases = HashSet::new();
+        self.declare_each(program, |analyzer, decl| match decl {
+            ASTNode::StructDecl(StructDecl { name, line, column, .. })
+            | ASTNode::EnumDecl(EnumDecl { name, line, column, .. })
+                if aliases.contains(name) =>
+            {
+                Err(ParseError::new(format!("Type '{}' already declared as an alias", name), *line, *column))
+            }
+            ASTNode::StructDecl(decl) => analyzer.declare_struct(decl),
+            ASTNode::EnumDecl(decl) => analyzer.declare_enum(decl),
+            ASTNode::Import(import) => analyzer.analyze_import(import),
+            ASTNode::TypeAlias(decl) => {
+                aliases.insert(decl.name.clone());
+                Ok(())
+            }
+            _ => Ok(()),
+        });
+        self.declare_each(program, |analyzer, decl| match decl {
+            ASTNode::TypeAlias(decl) => analyzer.analyze_type_alias(decl),
+            _ => Ok(()),
+        });
+        self.declare_each(program, |analyzer, decl| match decl {
+            ASTNode::EnumDecl(decl) => analyzer.resolve_enum_variants(decl),
+            ASTNode::StructDecl(decl) => Self::declare_members(analyzer, decl),
+            ASTNode::FnDecl(decl) => analyzer.declare_function(decl),
+            _ => Ok(()),
+        });
+
+        // Constants may be used anywhere, so they shadow enum and module
+        // names from the start
+        let constants = Self::constant_indices(program);
+        self.scopes[0].extend(constants.keys().cloned());
+        self.visit_program(program);
+        self.declare_constants(program, constants);
+        (self.errors, self.undeclared)
+    }
+
+    /// The top-level constants by name; a name declared twice keeps its
+    /// first declaration.
+    fn constant_indices(program: &Program) -> HashMap<String, usize> {
+        let mut constants = HashMap::new();
+        for (index, declaration) in program.declarations.iter().enumerate() {
+            if let ASTNode::ConstDecl(decl) = declaration {
+                constants.entry(decl.name.clone()).or_insert(index);
+            }
+        }
+        constants
+    }
+
+    /// Folds every top-level constant, each after the constants its
+    /// initializer refers to. Dotted names must be resolved already.
+    fn declare_constants(&mut self, program: &mut Program, constants: HashMap<String, usize>) {
+        let variables = program
+            .declarations
+            .iter()
+            .filter_map(|declaration| match declaration {
+                ASTNode::VarDecl(decl) => Some(decl.name.clone()),
+                _ => None,
+            })
+            .collect();
+        let mut folding = ConstFolding { constants, variables, in_progress: Vec::new(), done: HashSet::new() };
+        for index in 0..program.declarations.len() {
+            if matches!(program.declarations[index], ASTNode::ConstDecl(_)) {
+                self.declare_constant(&mut program.declarations, index, &mut folding);
+            }
+        }
+    }
+
+    fn declare_constant(&mut self, declarations: &mut [ASTNode], index: usize, folding: &mut ConstFolding) {
+        if folding.done.contains(&index) {
+            return;
+        }
+        let ASTNode::ConstDecl(decl) = &declarations[index] else { return };
+        if let Some(start) = folding.in_progress.iter().position(|&i| i == index) {
+            // Every constant around the cycle fails, reported once at this one
+            let cycle = folding.in_progress.split_off(start);
+            let mut names: Vec<String> = cycle.iter().map(|&i| Self::const_name(&declarations[i])).collect();
+            names.push(decl.name.clone());
+            self.errors.push(SemanticAnalyzer::const_eval_error(decl, ConstEvalError::Cycle(names)));
+            for i in cycle {
+                folding.done.insert(i);
+                let ASTNode::ConstDecl(decl) = &declarations[i] else { continue };
+                self.analyzer.declare_failed(&decl.name, Mutability::Let, decl.span);
+            }
+            return;
+        }
+
+        let names = captures::free_names(&decl.initializer);
+        // Global variables are declared later, in source order, so they are
+        // reported here rather than as undeclared
+        if let Some(variable) = names.iter().find(|name| {
+            folding.variables.contains(*name) && !folding.constants.contains_key(*name)
+        }) {
+            folding.done.insert(index);
+            self.errors.push(SemanticAnalyzer::const_eval_error(decl, ConstEvalError::variable(variable)));
+            self.analyzer.declare_failed(&decl.name, Mutability::Let, decl.span);
+            return;
+        }
+
+        folding.in_progress.push(index);
+        for name in names {
+            if let Some(&dependency) = folding.constants.get(&name) {
+                self.declare_constant(declarations, dependency, folding);
+            }
+        }
+        if folding.done.contains(&index) {
+            // Part of a cycle, already reported
+            return;
+        }
+        folding.in_progress.pop();
+        folding.done.insert(index);
+
+        let ASTNode::ConstDecl(decl) = &mut declarations[index] else { return };
+        match self.analyzer.analyze_const_declaration(decl) {
+            Ok(value) => decl.value = value,
+            Err(err) => {
+                self.analyzer.declare_failed(&decl.name, Mutability::Let, decl.span);
+                self.errors.push(err);
+            }
+        }
+    }
+
+    fn const_name(declaration: &ASTNode) -> String {
+        match declaration {
+            ASTNode::ConstDecl(decl) => decl.name.clone(),
+            _ => unreachable!("only constants are folded"),
+        }
+    }
+
+    fn declare_each(
+        &mut self,
+        program: &mut Program,
+        mut declare: impl FnMut(&mut SemanticAnalyzer<'a>, &ASTNode) -> Result<(), ParseError>,
+    ) {
+        for (index, declaration) in program.declarations.iter().enumerate() {
+            if self.undeclared.contains(&index) {
+                continue;
+            }
+            if let Err(err) = declare(self.analyzer, declaration) {
+                self.undeclared.insert(index);
+                self.errors.push(err);
+            }
+        }
+    }
+
+    fn declare_members(analyzer: &mut SemanticAnalyzer<'a>, decl: &StructDecl) -> Result<(), ParseError> {
+        analyzer.enter_struct(decl)?;
+        let declared = decl
+            .fields
+            .iter()
+            .try_for_each(|field| analyzer.analyze_field_declaration(&decl.name, field))
+            .and_then(|()| {
+                decl.methods
+                    .iter()
+                    .try_for_each(|method| analyzer.declare_method(&decl.name, method))
+            });
+        analyzer.exit_struct();
+        declared
+    }
+
+    fn is_bound(&self, name: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.contains(name))
+    }
+
+    fn bind(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string());
+        }
+    }
+
+    fn scoped(&mut self, names: Vec<String>, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(names.into_iter().collect());
+        f(self);
+        self.scopes.pop();
+    }
+
+    /// What `name.member` refers to when it is not a member access.
+    fn resolve_path(&self, name: &str, member: &str) -> Option<ExprKind> {
+        if self.is_bound(name) {
+            return None;
+        }
+        let qualified = format!("{}.{}", name, member);
+        if self.analyzer.is_module_name(name) && self.analyzer.is_type_name(&qualified) {
+            Some(ExprKind::Identifier(qualified))
+        } else if self.analyzer.is_enum_name(name) {
+            Some(ExprKind::EnumVariant {
+                enum_name: name.to_string(),
+                variant: member.to_string(),
+                args: Vec::new(),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl VisitorMut for NameResolver<'_, '_> {
+    fn visit_var_decl(&mut self, decl: &mut VarDecl) {
+        visitor_mut::walk_var_decl(self, decl);
+        self.bind(&decl.name);
+    }
+
+    fn visit_const_decl(&mut self, decl: &mut ConstDecl) {
+        visitor_mut::walk_const_decl(self, decl);
+        self.bind(&decl.name);
+    }
+
+    fn visit_fn_decl(&mut self, decl: &mut FnDecl) {
+        let params = decl.parameters.iter().map(|param| param.name.clone()).collect();
+        self.scoped(params, |this| this.visit_block(&mut decl.body));
+    }
+
+    fn visit_block(&mut self, block: &mut Block) {
+        self.scoped(Vec::new(), |this| visitor_mut::walk_block(this, block));
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        match stmt {
+            Stmt::For { variable, iterable, body, .. } => {
+                self.visit_expr(iterable);
+                self.scoped(vec![variable.clone()], |this| this.visit_block(body));
+            }
+            _ => visitor_mut::walk_stmt(self, stmt),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        match &mut expr.kind {
+            ExprKind::Member { object, member } => {
+                self.visit_expr(object);
+                if let ExprKind::Identifier(name) = &object.kind {
+                    if let Some(resolved) = self.resolve_path(name, member) {
+                        expr.kind = resolved;
+                    }
+                }
+            }
+            ExprKind::Call { callee, .. } => {
+                let calls_member = matches!(callee.kind, ExprKind::Member { .. });
+                visitor_mut::walk_expr(self, expr);
+                // `Enum.Variant(args)` constructs the variant
+                if let ExprKind::Call { callee, args } = &mut expr.kind {
+                    if let (true, ExprKind::EnumVariant { enum_name, variant, .. }) = (calls_member, &mut callee.kind) {
+                        expr.kind = ExprKind::EnumVariant {
+                            enum_name: std::mem::take(enum_name),
+                            variant: std::mem::take(variant),
+                            args: std::mem::take(args),
+                        };
+                    }
+                }
+            }
+            ExprKind::Lambda { params, body, .. } => {
+                let names = params.iter().map(|param| param.name.clone()).collect();
+                self.scoped(names, |this| this.visit_lambda_body(body));
+            }
+            _ => visitor_mut::walk_expr(self, expr),
+        }
+    }
+
+    fn visit_match_arm(&mut self, arm: &mut MatchArm) {
+        self.scoped(arm.pattern.bindings(), |this| this.visit_expr(&mut arm.body));
+    }
+}
@@ -0,0 +1,249 @@
+//! Type checking, the semantic pass that runs after `NameResolver`.
+//!
+//! Declarations and statements are checked in source order, filling in
+//! what the parser leaves unset: inferred variable types, folded values of
+//! local constants and lambda captures. A declaration or statement that fails to
+//! check stays in the tree with those left unset, and checking carries on
+//! with the next one. A variable or constant that fails is still bound, to
+//! `Type::Error`, so its uses don't report further errors.
+
+use std::collections::HashSet;
+
+use crate::parser::recursive_descent::errors::ParseError;
+use crate::parser::semantic::analyzer::SemanticAnalyzer;
+use crate::parser::visit::{visitor_mut, VisitorMut};
+use crate::parser::{
+    ASTNode, Block, ConstDecl, Expr, ExprKind, LambdaBody, LambdaParam, Mutability, NodeId, Parameter, Program,
+    Span, Stmt, StructDecl, Type, VarDecl,
+};
+
+pub struct TypeChecker<'s, 'a> {
+    analyzer: &'s mut SemanticAnalyzer<'a>,
+    errors: Vec<ParseError>,
+    /// The first error met while walking an expression; it ends the walk.
+    failure: Option<ParseError>,
+}
+
+impl<'s, 'a> TypeChecker<'s, 'a> {
+    pub fn new(analyzer: &'s mut SemanticAnalyzer<'a>) -> Self {
+        TypeChecker { analyzer, errors: Vec::new(), failure: None }
+    }
+
+    /// Checks a program whose names have been resolved, skipping the
+    /// declarations at the `undeclared` indices, which name resolution could
+    /// not declare.
+    pub fn check_program(mut self, program: &mut Program, undeclared: &HashSet<usize>) -> Vec<ParseError> {
+        for (index, declaration) in program.declarations.iter_mut().enumerate() {
+            if undeclared.contains(&index) {
+                continue;
+            }
+            if let Err(err) = self.declaration(declaration) {
+                self.errors.push(err);
+            }
+        }
+        self.errors
+    }
+
+    fn declaration(&mut self, declaration: &mut ASTNode) -> Result<(), ParseError> {
+        match declaration {
+            ASTNode::VarDecl(decl) => self.var_decl(decl),
+            // Folded by `NameResolver`, before any use
+            ASTNode::ConstDecl(_) => Ok(()),
+            ASTNode::FnDecl(decl) => {
+                self.analyzer.enter_function(decl)?;
+                self.block(&mut decl.body);
+                self.analyzer.exit_function(decl)
+            }
+            ASTNode::StructDecl(decl) => self.struct_decl(decl),
+            _ => Ok(()),
+        }
+    }
+
+    fn var_decl(&mut self, decl: &mut VarDecl) -> Result<(), ParseError> {
+        let checked = match &mut decl.initializer {
+            Some(initializer) => self.expr(initializer),
+            None => Ok(()),
+        };
+        match checked.and_then(|()| self.analyzer.analyze_var_declaration(decl)) {
+            Ok(var_type) => {
+                decl.inferred_type = Some(var_type);
+                Ok(())
+            }
+            Err(err) => {
+                self.analyzer.declare_failed(&decl.name, decl.mutability.clone(), decl.span);
+                Err(err)
+            }
+        }
+    }
+
+    fn const_decl(&mut self, decl: &mut ConstDecl) -> Result<(), ParseError> {
+        let checked = self.expr(&mut decl.initializer);
+        match checked.and_then(|()| self.analyzer.analyze_const_declaration(decl)) {
+            Ok(value) => {
+                decl.value = value;
+                Ok(())
+            }
+            Err(err) => {
+                self.analyzer.declare_failed(&decl.name, Mutability::Let, decl.span);
+                Err(err)
+            }
+        }
+    }
+
+    fn struct_decl(&mut self, decl: &mut StructDecl) -> Result<(), ParseError> {
+        self.analyzer.enter_struct(decl)?;
+        let StructDecl { name, methods, .. } = decl;
+        let checked = methods.iter_mut().try_for_each(|method| {
+            self.analyzer.enter_method(name, method)?;
+            self.block(&mut method.body);
+            self.analyzer.exit_function(method)
+        });
+        self.analyzer.exit_struct();
+        checked
+    }
+
+    fn block(&mut self, block: &mut Block) {
+        self.analyzer.enter_block_scope();
+        for stmt in &mut block.statements {
+            if let Err(err) = self.stmt(stmt) {
+                self.errors.push(err);
+            }
+        }
+        self.analyzer.exit_block_scope();
+    }
+
+    fn stmt(&mut self, stmt: &mut Stmt) -> Result<(), ParseError> {
+        match stmt {
+            Stmt::VarDecl(decl) => self.var_decl(decl),
+            Stmt::ConstDecl(decl) => self.const_decl(decl),
+            Stmt::Assignment { target, value, .. } => {
+                self.expr(target)?;
+                self.expr(value)?;
+                self.analyzer.analyze_assignment(target, value)
+            }
+            Stmt::Return { value, span, .. } => {
+                if let Some(value) = value {
+                    self.expr(value)?;
+                }
+                self.analyzer.analyze_return(value.as_ref(), *span)
+            }
+            Stmt::If { condition, then_branch, else_branch, .. } => {
+                // A failed condition doesn't keep the body from being checked
+                if let Err(err) = self.condition(condition, "'if'") {
+                    self.errors.push(err);
+                }
+                self.block(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.block(else_branch);
+                }
+                Ok(())
+            }
+            Stmt::While { condition, body, .. } => {
+                // A failed condition doesn't keep the body from being checked
+                if let Err(err) = self.condition(condition, "'while'") {
+                    self.errors.push(err);
+                }
+                self.block(body);
+                Ok(())
+            }
+            Stmt::For { variable, iterable, body, span, .. } => {
+                let entered = self.expr(iterable);
+                if let Err(err) = entered.and_then(|()| self.analyzer.enter_for_loop(variable, iterable, *span)) {
+                    // The body is checked with the loop variable bound to `Type::Error`
+                    self.errors.push(err);
+                    self.analyzer.enter_block_scope();
+                    self.analyzer.declare_failed(variable, Mutability::Let, *span);
+                }
+                self.block(body);
+                self.analyzer.exit_block_scope();
+                Ok(())
+            }
+            Stmt::Expression { expr, .. } => {
+                self.expr(expr)?;
+                self.analyzer.infer_expression_type(expr).map(|_| ())
+            }
+            Stmt::Block(block) => {
+                self.block(block);
+                Ok(())
+            }
+            Stmt::DSLBlock { .. } | Stmt::Error(_) => Ok(()),
+        }
+    }
+
+    fn condition(&mut self, condition: &mut Expr, context: &str) -> Result<(), ParseError> {
+        self.expr(condition)?;
+        self.analyzer.analyze_condition(condition, context)
+    }
+
+    /// Checks the bodies of the block lambdas in `expr` and records what every
+    /// lambda captures; the expression itself is inferred by the statement
+    /// containing it.
+    fn expr(&mut self, expr: &mut Expr) -> Result<(), ParseError> {
+        self.visit_expr(expr);
+        self.failure.take().map_or(Ok(()), Err)
+    }
+
+    /// `span` is the lambda's.
+    fn lambda(
+        &mut self,
+        params: &[LambdaParam],
+        return_type: Option<&Type>,
+        body: &mut LambdaBody,
+        span: Span,
+    ) -> Result<(), ParseError> {
+        match body {
+            LambdaBody::Expr(body) => {
+                self.visit_expr(body);
+                if let Some(err) = self.failure.take() {
+                    return Err(err);
+                }
+            }
+            LambdaBody::Block(block) => {
+                // The body is checked before the enclosing expression is
+                // inferred, so its parameter types must be written out
+                let mut signature = Vec::with_capacity(params.len());
+                for param in params {
+                    let param_type = param.param_type.clone().ok_or_else(|| {
+                        ParseError::at(
+                            format!(
+                                "Parameter '{}' of a lambda with a block body needs a type annotation",
+                                param.name
+                            ),
+                            span,
+                        )
+                    })?;
+                    signature.push(Parameter {
+                        name: param.name.clone(),
+                        param_type,
+                        line: span.line,
+                        column: span.column,
+                        span,
+                        id: NodeId::DUMMY,
+                    });
+                }
+                self.analyzer.enter_lambda_scope(&signature, return_type, span)?;
+                self.block(block);
+                self.analyzer.exit_lambda_scope(block, return_type, span)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl VisitorMut for TypeChecker<'_, '_> {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        if self.failure.is_some() {
+            return;
+        }
+        let span = expr.span;
+        match &mut expr.kind {
+            ExprKind::Lambda { params, return_type, body, captures } => {
+                match self.lambda(params, return_type.as_ref(), body, span) {
+                    Ok(()) => *captures = self.analyzer.lambda_captures(params, body),
+                    Err(err) => self.failure = Some(err),
+                }
+            }
+            _ => visitor_mut::walk_expr(self, expr),
+        }
+    }
+}
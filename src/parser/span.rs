@@ -0,0 +1,168 @@
+//! Where tree nodes come from and how they are told apart.
+
+use crate::lexer::token::Token;
+use crate::parser::visit::{visitor_mut, VisitorMut};
+use crate::parser::{
+    Block, ConstDecl, EnumDecl, EnumVariant, ErrorNode, Expr, FieldDecl, FnDecl, ImportDecl, MatchArm, Parameter,
+    Pattern, Program, Stmt, StructDecl, TypeAliasDecl, VarDecl,
+};
+
+/// A range of source text. Lines and columns are 1-based with the end column
+/// exclusive; `start` and `end` are the byte offsets of the same range.
+/// Positions made from a bare line and column (`Span::point`) have no byte
+/// offsets and leave both at 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn point(line: usize, column: usize) -> Self {
+        Span { line, column, end_line: line, end_column: column, start: 0, end: 0 }
+    }
+
+    pub fn of_token(token: &Token) -> Self {
+        Span {
+            line: token.line,
+            column: token.column,
+            end_line: token.end_line,
+            end_column: token.end_column,
+            start: token.start,
+            end: token.end,
+        }
+    }
+
+    /// The empty range just before `token`.
+    pub fn before(token: &Token) -> Self {
+        Span { start: token.start, end: token.start, ..Span::point(token.line, token.column) }
+    }
+
+    /// From the start of `self` to the end of `other`.
+    pub fn to(self, other: Span) -> Span {
+        Span { end_line: other.end_line, end_column: other.end_column, end: other.end, ..self }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (self.line, self.column) == (self.end_line, self.end_column)
+    }
+}
+
+/// Identifies a node within its program. Parsers leave every id at
+/// `NodeId::DUMMY` and number the finished tree with `number_nodes`, so the
+/// same source always gets the same ids whichever parser built it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub u32);
+
+impl NodeId {
+    pub const DUMMY: NodeId = NodeId(u32::MAX);
+}
+
+impl Default for NodeId {
+    fn default() -> Self {
+        NodeId::DUMMY
+    }
+}
+
+/// Gives every declaration, block, statement, expression, match arm and
+/// pattern of `program` an id, in the order their text starts.
+pub fn number_nodes(program: &mut Program) {
+    NodeNumbering { next: 0 }.visit_program(program);
+}
+
+struct NodeNumbering {
+    next: u32,
+}
+
+impl NodeNumbering {
+    fn assign(&mut self, id: &mut NodeId) {
+        *id = NodeId(self.next);
+        self.next += 1;
+    }
+}
+
+impl VisitorMut for NodeNumbering {
+    fn visit_import(&mut self, import: &mut ImportDecl) {
+        self.assign(&mut import.id);
+    }
+
+    fn visit_var_decl(&mut self, decl: &mut VarDecl) {
+        self.assign(&mut decl.id);
+        visitor_mut::walk_var_decl(self, decl);
+    }
+
+    fn visit_const_decl(&mut self, decl: &mut ConstDecl) {
+        self.assign(&mut decl.id);
+        visitor_mut::walk_const_decl(self, decl);
+    }
+
+    fn visit_fn_decl(&mut self, decl: &mut FnDecl) {
+        self.assign(&mut decl.id);
+        visitor_mut::walk_fn_decl(self, decl);
+    }
+
+    fn visit_parameter(&mut self, param: &mut Parameter) {
+        self.assign(&mut param.id);
+        visitor_mut::walk_parameter(self, param);
+    }
+
+    fn visit_struct_decl(&mut self, decl: &mut StructDecl) {
+        self.assign(&mut decl.id);
+        visitor_mut::walk_struct_decl(self, decl);
+    }
+
+    fn visit_field_decl(&mut self, field: &mut FieldDecl) {
+        self.assign(&mut field.id);
+        visitor_mut::walk_field_decl(self, field);
+    }
+
+    fn visit_enum_decl(&mut self, decl: &mut EnumDecl) {
+        self.assign(&mut decl.id);
+        visitor_mut::walk_enum_decl(self, decl);
+    }
+
+    fn visit_enum_variant(&mut self, variant: &mut EnumVariant) {
+        self.assign(&mut variant.id);
+        visitor_mut::walk_enum_variant(self, variant);
+    }
+
+    fn visit_type_alias(&mut self, decl: &mut TypeAliasDecl) {
+        self.assign(&mut decl.id);
+        visitor_mut::walk_type_alias(self, decl);
+    }
+
+    fn visit_block(&mut self, block: &mut Block) {
+        self.assign(&mut block.id);
+        visitor_mut::walk_block(self, block);
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        if let Some(id) = stmt.own_id_mut() {
+            self.assign(id);
+        }
+        visitor_mut::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        self.assign(&mut expr.id);
+        visitor_mut::walk_expr(self, expr);
+    }
+
+    fn visit_match_arm(&mut self, arm: &mut MatchArm) {
+        self.assign(&mut arm.id);
+        visitor_mut::walk_match_arm(self, arm);
+    }
+
+    fn visit_pattern(&mut self, pattern: &mut Pattern) {
+        self.assign(&mut pattern.id);
+        visitor_mut::walk_pattern(self, pattern);
+    }
+
+    fn visit_error(&mut self, error: &mut ErrorNode) {
+        self.assign(&mut error.id);
+    }
+}
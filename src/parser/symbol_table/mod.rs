@@ -3,4 +3,5 @@ pub mod symbol;
 pub mod table;
 
 pub use error::SymbolError;
-pub use table::SymbolTable;
+pub use symbol::ScopeType;
+pub use table::{FieldInfo, FunctionInfo, ModuleInfo, SymbolTable};
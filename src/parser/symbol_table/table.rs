@@ -1,26 +1,36 @@
-use std::collections::HashMap;
-use crate::parser::{symbol_table::SymbolError, Mutability, Type, Visibility};
+use std::collections::{HashMap, HashSet};
+use crate::parser::{symbol_table::SymbolError, Literal, Mutability, Type, Visibility};
+use super::symbol::ScopeType;
 
 #[derive(Debug, Clone)]
 pub struct SymbolTable {
     variables: HashMap<String, VariableInfo>,
-    functions: HashMap<String, FunctionInfo>, 
+    functions: HashMap<String, FunctionInfo>,
+    structs: HashMap<String, StructInfo>,
+    enums: HashMap<String, EnumInfo>,
+    aliases: HashMap<String, TypeAliasInfo>,
+    modules: HashMap<String, ModuleInfo>,
+    /// Why each module path that failed to load could not be imported.
+    failed_imports: HashMap<String, String>,
+    scopes: Vec<(ScopeType, HashMap<String, VariableInfo>)>,
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct VariableInfo {
     pub var_type: Type,
     pub visibility: Option<Visibility>,
     pub mutability: Mutability,
     pub initialized: bool,
+    /// Compile-time value of a `const`; `None` for variables.
+    pub constant: Option<Literal>,
     pub line: usize,
     pub column: usize,
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub struct FunctionInfo {
+    /// Names of the function's own type parameters; empty unless generic.
+    pub type_params: Vec<String>,
     pub parameters: Vec<(String, Type)>,
     pub return_type: Option<Type>,
     pub visibility: Option<Visibility>,
@@ -28,22 +38,118 @@ pub struct FunctionInfo {
     pub column: usize,
 }
 
+#[derive(Debug, Clone)]
+pub struct StructInfo {
+    pub type_params: Vec<String>,
+    pub fields: Vec<FieldInfo>,
+    pub methods: HashMap<String, FunctionInfo>,
+    pub visibility: Option<Visibility>,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldInfo {
+    pub name: String,
+    pub field_type: Type,
+    pub visibility: Option<Visibility>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnumInfo {
+    pub type_params: Vec<String>,
+    pub variants: Vec<(String, Vec<Type>)>,
+    pub visibility: Option<Visibility>,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeAliasInfo {
+    /// The aliased type with any aliases inside it already expanded.
+    pub target: Type,
+    pub visibility: Option<Visibility>,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// The public items of a module, as seen by modules that import it.
+#[derive(Debug, Clone, Default)]
+pub struct ModuleInfo {
+    /// Dotted module path, e.g. `std.io`.
+    pub path: String,
+    /// Public functions (as function types), variables and constants.
+    pub values: HashMap<String, Type>,
+    /// Compile-time values of the public constants among `values`.
+    pub constants: HashMap<String, Literal>,
+    pub structs: HashMap<String, StructInfo>,
+    pub enums: HashMap<String, EnumInfo>,
+    pub aliases: HashMap<String, TypeAliasInfo>,
+}
+
+impl EnumInfo {
+    pub fn get_variant(&self, name: &str) -> Option<&[Type]> {
+        self.variants
+            .iter()
+            .find(|(variant, _)| variant == name)
+            .map(|(_, payload)| payload.as_slice())
+    }
+}
+
+impl StructInfo {
+    pub fn get_field(&self, name: &str) -> Option<&FieldInfo> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
 impl VariableInfo {
     pub fn is_mutable(&self) -> bool {
         matches!(self.mutability, Mutability::Mut)
     }
 }
 
-#[allow(dead_code)]
+impl Default for SymbolTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SymbolTable {
     pub fn new() -> Self {
         SymbolTable {
             variables: HashMap::new(),
             functions: HashMap::new(),
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            aliases: HashMap::new(),
+            modules: HashMap::new(),
+            failed_imports: HashMap::new(),
+            scopes: Vec::new(),
+        }
+    }
+
+    // ==================== Scope ====================
+    pub fn enter_scope(&mut self, scope_type: ScopeType) {
+        self.scopes.push((scope_type, HashMap::new()));
+    }
+
+    pub fn exit_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    pub fn scope_depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    fn current_variables_mut(&mut self) -> &mut HashMap<String, VariableInfo> {
+        match self.scopes.last_mut() {
+            Some((_, vars)) => vars,
+            None => &mut self.variables,
         }
     }
 
     // ==================== Variabel ====================
+    #[allow(clippy::too_many_arguments)]
     pub fn declare_variable(
         &mut self,
         name: String,
@@ -54,16 +160,17 @@ impl SymbolTable {
         line: usize,
         column: usize,
     ) -> Result<(), SymbolError> {
-        if self.variables.contains_key(&name) {
-            let existing = self.variables.get(&name).unwrap();
+        let variables = self.current_variables_mut();
+        if let Some(existing) = variables.get(&name) {
             return Err(SymbolError::AlreadyDeclared(name, existing.line, existing.column));
         }
 
-        self.variables.insert(name, VariableInfo {
+        variables.insert(name, VariableInfo {
             var_type,
             visibility,
             mutability,
             initialized,
+            constant: None,
             line,
             column,
         });
@@ -71,14 +178,53 @@ impl SymbolTable {
         Ok(())
     }
 
+    pub fn declare_constant(
+        &mut self,
+        name: String,
+        const_type: Type,
+        visibility: Option<Visibility>,
+        value: Literal,
+        line: usize,
+        column: usize,
+    ) -> Result<(), SymbolError> {
+        self.declare_variable(name.clone(), const_type, visibility, Mutability::Let, true, line, column)?;
+        if let Some(info) = self.current_variables_mut().get_mut(&name) {
+            info.constant = Some(value);
+        }
+        Ok(())
+    }
+
+    /// Looks a variable up from the innermost scope outwards, ending at globals.
+    pub fn get_variable(&self, name: &str) -> Option<&VariableInfo> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|(_, vars)| vars.get(name))
+            .or_else(|| self.variables.get(name))
+    }
+
+    /// Like `get_variable`, but ignores globals.
+    pub fn get_local_variable(&self, name: &str) -> Option<&VariableInfo> {
+        self.scopes.iter().rev().find_map(|(_, vars)| vars.get(name))
+    }
+
+    fn get_variable_mut(&mut self, name: &str) -> Option<&mut VariableInfo> {
+        for (_, vars) in self.scopes.iter_mut().rev() {
+            if let Some(info) = vars.get_mut(name) {
+                return Some(info);
+            }
+        }
+        self.variables.get_mut(name)
+    }
+
     pub fn get_variable_type(&self, name: &str) -> Result<Type, SymbolError> {
-        self.variables.get(name)
+        self.get_variable(name)
             .map(|info| info.var_type.clone())
             .ok_or_else(|| SymbolError::Undeclared(name.to_string()))
     }
 
     pub fn update_variable(&mut self, name: &str, new_type: Type) -> Result<(), SymbolError> {
-        if let Some(var_info) = self.variables.get_mut(name) {
+        if let Some(var_info) = self.get_variable_mut(name) {
             if matches!(var_info.mutability, Mutability::Let) {
                 return Err(SymbolError::ImmutableAssignment(name.to_string()));
             }
@@ -91,9 +237,11 @@ impl SymbolTable {
     }
 
     // ==================== Fungsi ====================
+    #[allow(clippy::too_many_arguments)]
     pub fn declare_function(
         &mut self,
         name: String,
+        type_params: Vec<String>,
         parameters: Vec<(String, Type)>,
         return_type: Option<Type>,
         visibility: Option<Visibility>,
@@ -106,6 +254,7 @@ impl SymbolTable {
         }
 
         self.functions.insert(name, FunctionInfo {
+            type_params,
             parameters,
             return_type,
             visibility,
@@ -121,9 +270,293 @@ impl SymbolTable {
             .ok_or_else(|| SymbolError::Undeclared(name.to_string()))
     }
 
+    // ==================== Struct ====================
+    pub fn declare_struct(
+        &mut self,
+        name: String,
+        type_params: Vec<String>,
+        visibility: Option<Visibility>,
+        line: usize,
+        column: usize,
+    ) -> Result<(), SymbolError> {
+        if let Some(existing) = self.structs.get(&name) {
+            return Err(SymbolError::AlreadyDeclared(name, existing.line, existing.column));
+        }
+
+        self.structs.insert(name, StructInfo {
+            type_params,
+            fields: Vec::new(),
+            methods: HashMap::new(),
+            visibility,
+            line,
+            column,
+        });
+
+        Ok(())
+    }
+
+    pub fn add_struct_field(
+        &mut self,
+        struct_name: &str,
+        field: FieldInfo,
+        line: usize,
+        column: usize,
+    ) -> Result<(), SymbolError> {
+        let info = self.structs.get_mut(struct_name)
+            .ok_or_else(|| SymbolError::Undeclared(struct_name.to_string()))?;
+        if info.get_field(&field.name).is_some() || info.methods.contains_key(&field.name) {
+            return Err(SymbolError::AlreadyDeclared(field.name, line, column));
+        }
+        info.fields.push(field);
+        Ok(())
+    }
+
+    pub fn add_struct_method(
+        &mut self,
+        struct_name: &str,
+        name: String,
+        method: FunctionInfo,
+    ) -> Result<(), SymbolError> {
+        let info = self.structs.get_mut(struct_name)
+            .ok_or_else(|| SymbolError::Undeclared(struct_name.to_string()))?;
+        if info.get_field(&name).is_some() || info.methods.contains_key(&name) {
+            return Err(SymbolError::AlreadyDeclared(name, method.line, method.column));
+        }
+        info.methods.insert(name, method);
+        Ok(())
+    }
+
+    pub fn get_struct_info(&self, name: &str) -> Result<&StructInfo, SymbolError> {
+        self.structs.get(name)
+            .ok_or_else(|| SymbolError::Undeclared(name.to_string()))
+    }
+
+    pub fn struct_exists(&self, name: &str) -> bool {
+        self.structs.contains_key(name)
+    }
+
+    // ==================== Enum ====================
+    pub fn declare_enum(
+        &mut self,
+        name: String,
+        type_params: Vec<String>,
+        variants: Vec<(String, Vec<Type>)>,
+        visibility: Option<Visibility>,
+        line: usize,
+        column: usize,
+    ) -> Result<(), SymbolError> {
+        if let Some(existing) = self.enums.get(&name) {
+            return Err(SymbolError::AlreadyDeclared(name, existing.line, existing.column));
+        }
+
+        self.enums.insert(name, EnumInfo {
+            type_params,
+            variants,
+            visibility,
+            line,
+            column,
+        });
+
+        Ok(())
+    }
+
+    pub fn update_enum_variants(
+        &mut self,
+        name: &str,
+        variants: Vec<(String, Vec<Type>)>,
+    ) -> Result<(), SymbolError> {
+        let info = self.enums.get_mut(name)
+            .ok_or_else(|| SymbolError::Undeclared(name.to_string()))?;
+        info.variants = variants;
+        Ok(())
+    }
+
+    pub fn get_enum_info(&self, name: &str) -> Result<&EnumInfo, SymbolError> {
+        self.enums.get(name)
+            .ok_or_else(|| SymbolError::Undeclared(name.to_string()))
+    }
+
+    pub fn enum_exists(&self, name: &str) -> bool {
+        self.enums.contains_key(name)
+    }
+
+    // ==================== Type Alias ====================
+    pub fn declare_type_alias(
+        &mut self,
+        name: String,
+        target: Type,
+        visibility: Option<Visibility>,
+        line: usize,
+        column: usize,
+    ) -> Result<(), SymbolError> {
+        if let Some(existing) = self.aliases.get(&name) {
+            return Err(SymbolError::AlreadyDeclared(name, existing.line, existing.column));
+        }
+
+        self.aliases.insert(name, TypeAliasInfo {
+            target,
+            visibility,
+            line,
+            column,
+        });
+
+        Ok(())
+    }
+
+    pub fn get_type_alias(&self, name: &str) -> Option<&TypeAliasInfo> {
+        self.aliases.get(name)
+    }
+
+    /// Structs, enums and aliases share one namespace of user-defined type names.
+    pub fn type_exists(&self, name: &str) -> bool {
+        self.struct_exists(name) || self.enum_exists(name) || self.aliases.contains_key(name)
+    }
+
+    // ==================== Module ====================
+
+    /// Collects the `public` top-level items declared in this table.
+    pub fn exports(&self, path: &str) -> ModuleInfo {
+        let is_public = |v: &Option<Visibility>| matches!(v, Some(Visibility::Public));
+
+        let mut values: HashMap<String, Type> = self.variables.iter()
+            .filter(|(_, info)| is_public(&info.visibility))
+            .map(|(name, info)| (name.clone(), info.var_type.clone()))
+            .collect();
+        values.extend(self.functions.iter()
+            .filter(|(_, info)| is_public(&info.visibility))
+            .map(|(name, info)| (name.clone(), Type::Function {
+                params: info.parameters.iter().map(|(_, ty)| ty.clone()).collect(),
+                return_type: info.return_type.clone().map(Box::new),
+            })));
+
+        let constants = self.variables.iter()
+            .filter(|(_, info)| is_public(&info.visibility))
+            .filter_map(|(name, info)| Some((name.clone(), info.constant.clone()?)))
+            .collect();
+
+        ModuleInfo {
+            path: path.to_string(),
+            values,
+            constants,
+            structs: self.structs.iter()
+                .filter(|(_, info)| is_public(&info.visibility))
+                .map(|(name, info)| (name.clone(), info.clone()))
+                .collect(),
+            enums: self.enums.iter()
+                .filter(|(_, info)| is_public(&info.visibility))
+                .map(|(name, info)| (name.clone(), info.clone()))
+                .collect(),
+            aliases: self.aliases.iter()
+                .filter(|(_, info)| is_public(&info.visibility))
+                .map(|(name, info)| (name.clone(), info.clone()))
+                .collect(),
+        }
+    }
+
+    /// Makes a module's exports reachable as `name.item`. Exported types are
+    /// registered under their qualified name (`name.Type`) and every reference
+    /// to them inside the exports is rewritten accordingly.
+    pub fn import_module(&mut self, name: &str, module: &ModuleInfo) -> Result<(), SymbolError> {
+        if let Some(existing) = self.modules.get(name) {
+            return Err(SymbolError::AlreadyDeclared(existing.path.clone(), 0, 0));
+        }
+
+        let local_types: HashSet<&String> = module.structs.keys()
+            .chain(module.enums.keys())
+            .chain(module.aliases.keys())
+            .collect();
+        let qualify = |ty: &Type| Self::qualify_type(ty, name, &local_types);
+        let qualified_name = |item: &String| format!("{}.{}", name, item);
+
+        for (struct_name, info) in &module.structs {
+            let mut info = info.clone();
+            for field in &mut info.fields {
+                field.field_type = qualify(&field.field_type);
+            }
+            for method in info.methods.values_mut() {
+                Self::qualify_function(method, &qualify);
+            }
+            self.structs.insert(qualified_name(struct_name), info);
+        }
+        for (enum_name, info) in &module.enums {
+            let mut info = info.clone();
+            for (_, payload) in &mut info.variants {
+                for ty in payload.iter_mut() {
+                    *ty = qualify(ty);
+                }
+            }
+            self.enums.insert(qualified_name(enum_name), info);
+        }
+        for (alias_name, info) in &module.aliases {
+            let mut info = info.clone();
+            info.target = qualify(&info.target);
+            self.aliases.insert(qualified_name(alias_name), info);
+        }
+
+        let mut imported = module.clone();
+        for ty in imported.values.values_mut() {
+            *ty = qualify(ty);
+        }
+        self.modules.insert(name.to_string(), imported);
+        Ok(())
+    }
+
+    fn qualify_function(function: &mut FunctionInfo, qualify: &impl Fn(&Type) -> Type) {
+        for (_, ty) in &mut function.parameters {
+            *ty = qualify(ty);
+        }
+        function.return_type = function.return_type.as_ref().map(qualify);
+    }
+
+    fn qualify_type(ty: &Type, module: &str, local_types: &HashSet<&String>) -> Type {
+        let qualify = |inner: &Type| Self::qualify_type(inner, module, local_types);
+        match ty {
+            Type::Custom(name) if local_types.contains(name) => {
+                Type::Custom(format!("{}.{}", module, name))
+            }
+            Type::Generic { name, args } => Type::Generic {
+                name: if local_types.contains(name) {
+                    format!("{}.{}", module, name)
+                } else {
+                    name.clone()
+                },
+                args: args.iter().map(qualify).collect(),
+            },
+            Type::Optional(inner) => Type::Optional(Box::new(qualify(inner))),
+            Type::Array(inner) => Type::Array(Box::new(qualify(inner))),
+            Type::Task(inner) => Type::Task(Box::new(qualify(inner))),
+            Type::Function { params, return_type } => Type::Function {
+                params: params.iter().map(qualify).collect(),
+                return_type: return_type.as_ref().map(|ret| Box::new(qualify(ret))),
+            },
+            Type::Object(fields) => Type::Object(
+                fields.iter().map(|(name, field)| (name.clone(), qualify(field))).collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    pub fn get_module(&self, name: &str) -> Option<&ModuleInfo> {
+        self.modules.get(name)
+    }
+
+    pub fn module_exists(&self, name: &str) -> bool {
+        self.modules.contains_key(name)
+    }
+
+    /// Records that the module at `path` could not be loaded, so that a
+    /// `use` of it reports `reason`.
+    pub fn fail_import(&mut self, path: &str, reason: String) {
+        self.failed_imports.insert(path.to_string(), reason);
+    }
+
+    pub fn import_failure(&self, path: &str) -> Option<&str> {
+        self.failed_imports.get(path).map(String::as_str)
+    }
+
     // ==================== Getter lainnya ====================
     pub fn variable_exists(&self, name: &str) -> bool {
-        self.variables.contains_key(name)
+        self.get_variable(name).is_some()
     }
 
     pub fn function_exists(&self, name: &str) -> bool {
@@ -145,5 +578,11 @@ impl SymbolTable {
     pub fn clear(&mut self) {
         self.variables.clear();
         self.functions.clear();
+        self.structs.clear();
+        self.enums.clear();
+        self.aliases.clear();
+        self.modules.clear();
+        self.failed_imports.clear();
+        self.scopes.clear();
     }
 }
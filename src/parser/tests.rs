@@ -1,154 +1,2397 @@
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::lexer::Lexer;
+use crate::lexer::Lexer;
+use crate::parser::modules::error::ModuleError;
+use crate::parser::modules::{ModuleGraph, ModuleLoader};
+use crate::lexer::token::TokenType;
+use crate::parser::recursive_descent::errors::{ParseError, Span};
+use crate::parser::recursive_descent::RecursiveDescentParser;
+use crate::parser::*;
 
-    fn parse_program_from_code(code: &str) -> Result<ASTNode, ParseError> {
-        let code_chars: Vec<char> = code.chars().collect();
-        let mut lexer = Lexer::new(&code_chars);
-        let tokens = lexer.tokenize().map_err(|e| ParseError {
-            message: e.message,
-            line: e.line,
-            column: e.column,
-        })?;
+fn parse_program_from_code(code: &str) -> Result<ASTNode, ParseError> {
+    parse_program_with_errors(code).map(|(program, _)| program)
+}
+
+fn parse_program_with_errors(code: &str) -> Result<(ASTNode, Vec<String>), ParseError> {
+    let (program, errors) = parse_program_with_parse_errors(code)?;
+    let messages = errors.into_iter().map(|err| format!("Parse error: {}", err.message)).collect();
+    Ok((program, messages))
+}
+
+/// Parses `code` and runs the semantic passes over it, returning the syntax
+/// and semantic errors together in source order.
+fn parse_program_with_parse_errors(code: &str) -> Result<(ASTNode, Vec<ParseError>), ParseError> {
+    let code_chars: Vec<char> = code.chars().collect();
+    let mut lexer = Lexer::new(&code_chars);
+    let tokens = lexer.tokenize().map_err(|e| ParseError::new(e.message, e.line, e.column))?;
+
+    let mut parser = RecursiveDescentParser::new(tokens);
+    let mut program = match parser.parse_program()? {
+        ASTNode::Program(program) => program,
+        _ => panic!("Expected Program node"),
+    };
+    let mut errors = parser.errors().to_vec();
+    errors.extend(semantic::analyze_program(&mut program, &mut SymbolTable::new()));
+    errors.sort_by_key(|err| (err.line, err.column));
+    Ok((ASTNode::Program(program), errors))
+}
+
+fn parse_single_var_decl_from_code(code: &str) -> Result<VarDecl, ParseError> {
+    let (program, errors) = parse_program_with_parse_errors(code)?;
+    if let Some(err) = errors.into_iter().next() {
+        return Err(err);
+    }
+    match program_declarations(program).into_iter().next() {
+        Some(ASTNode::VarDecl(decl)) => Ok(decl),
+        other => panic!("Expected a variable declaration, got {:?}", other),
+    }
+}
 
-        let mut parser = RecursiveDescentParser::new(tokens);
-        parser.parse_program()
+fn program_declarations(node: ASTNode) -> Vec<ASTNode> {
+    match node {
+        ASTNode::Program(prog) => prog.declarations,
+        _ => panic!("Expected Program node"),
     }
+}
+
+#[test]
+fn test_simple_var_decl() {
+    let var_decl = parse_single_var_decl_from_code("let x: int32 = 42;").unwrap();
+
+    assert_eq!(var_decl.name, "x");
+    assert_eq!(var_decl.mutability, Mutability::Let);
+    assert_eq!(var_decl.declared_type, Some(Type::Int32));
+    assert!(matches!(
+        var_decl.initializer,
+        Some(Expr { kind: ExprKind::Literal(Literal::Int(42, None)), .. })
+    ));
+    assert_eq!(var_decl.line, 1);
+    assert_eq!(var_decl.column, 1);
+}
 
-    fn parse_single_var_decl_from_code(code: &str) -> Result<VarDecl, ParseError> {
-        let code_chars: Vec<char> = code.chars().collect();
-        let mut lexer = Lexer::new(&code_chars);
-        let tokens = lexer.tokenize().map_err(|e| ParseError {
-            message: e.message,
-            line: e.line,
-            column: e.column,
-        })?;
+#[test]
+fn test_mut_var_decl() {
+    let var_decl = parse_single_var_decl_from_code("mut count: int32 = 0;").unwrap();
+
+    assert_eq!(var_decl.name, "count");
+    assert_eq!(var_decl.mutability, Mutability::Mut);
+    assert_eq!(var_decl.declared_type, Some(Type::Int32));
+}
+
+#[test]
+fn test_type_inference() {
+    let var_decl = parse_single_var_decl_from_code("let name = \"Alice\";").unwrap();
+
+    assert_eq!(var_decl.name, "name");
+    assert_eq!(var_decl.declared_type, None);
+    assert_eq!(var_decl.inferred_type, Some(Type::String));
+}
+
+#[test]
+fn test_no_initializer() {
+    let var_decl = parse_single_var_decl_from_code("let age: int32;").unwrap();
+
+    assert_eq!(var_decl.name, "age");
+    assert_eq!(var_decl.declared_type, Some(Type::Int32));
+    assert_eq!(var_decl.initializer, None);
+}
+
+#[test]
+fn test_public_var_decl() {
+    let var_decl =
+        parse_single_var_decl_from_code("public let config: string = \"default\";").unwrap();
+
+    assert_eq!(var_decl.visibility, Some(Visibility::Public));
+    assert_eq!(var_decl.name, "config");
+    assert_eq!(var_decl.declared_type, Some(Type::String));
+}
+
+#[test]
+fn test_optional_type() {
+    let var_decl = parse_single_var_decl_from_code("let age: int32?;").unwrap();
+
+    assert_eq!(
+        var_decl.declared_type,
+        Some(Type::Optional(Box::new(Type::Int32)))
+    );
+    assert_eq!(var_decl.initializer, None);
+}
 
-        let mut parser = RecursiveDescentParser::new(tokens);
-        parser.parse_single_var_decl()
+#[test]
+fn test_multiple_declarations() {
+    let code = r#"
+    let x: int32 = 42;
+    mut y: string = "hello";
+    public let z = true;
+"#;
+
+    let program_node = parse_program_from_code(code).unwrap();
+
+    let declarations = match program_node {
+        ASTNode::Program(prog) => prog.declarations,
+        _ => panic!("Expected Program node"),
+    };
+
+    assert_eq!(declarations.len(), 3);
+
+    // var decl #1
+    match &declarations[0] {
+        ASTNode::VarDecl(decl) => assert_eq!(decl.name, "x"),
+        _ => panic!("Expected VarDecl"),
     }
 
-    #[test]
-    fn test_simple_var_decl() {
-        let var_decl = parse_single_var_decl_from_code("let x: int32 = 42;").unwrap();
+    // var decl #2
+    match &declarations[1] {
+        ASTNode::VarDecl(decl) => {
+            assert_eq!(decl.name, "y");
+            assert_eq!(decl.mutability, Mutability::Mut);
+        }
+        _ => panic!("Expected VarDecl"),
+    }
 
-        assert_eq!(var_decl.name, "x");
-        assert_eq!(var_decl.mutability, Mutability::Let);
-        assert_eq!(var_decl.declared_type, Some(Type::Int32));
-        assert!(matches!(
-            var_decl.initializer,
-            Some(Expr::Literal(Literal::Int(42)))
-        ));
-        assert_eq!(var_decl.line, 1);
-        assert_eq!(var_decl.column, 1);
+    //var decl #3
+    match &declarations[2] {
+        ASTNode::VarDecl(decl) => {
+            assert_eq!(decl.name, "z");
+            assert_eq!(decl.visibility, Some(Visibility::Public));
+            assert_eq!(decl.inferred_type, Some(Type::Bool));
+        }
+        _ => panic!("Expected VarDecl"),
     }
+}
 
-    #[test]
-    fn test_mut_var_decl() {
-        let var_decl = parse_single_var_decl_from_code("mut count: int32 = 0;").unwrap();
+#[test]
+fn test_type_mismatch_error() {
+    let result = parse_single_var_decl_from_code("let x: string = 42;");
+    assert!(result.is_err());
+    assert!(result.unwrap_err().message.contains("Type mismatch"));
+}
 
-        assert_eq!(var_decl.name, "count");
-        assert_eq!(var_decl.mutability, Mutability::Mut);
-        assert_eq!(var_decl.declared_type, Some(Type::Int32));
+#[test]
+fn test_no_type_no_init_error() {
+    let result = parse_single_var_decl_from_code("let x;");
+    assert!(result.is_err());
+    assert!(result
+        .unwrap_err()
+        .message
+        .contains("must have type annotation or initializer"));
+}
+
+#[test]
+fn test_function_decl() {
+    let code = r#"
+    public fn main() -> int32 {
+        let x: int32 = 42;
+        return x;
     }
+"#;
 
-    #[test]
-    fn test_type_inference() {
-        let var_decl = parse_single_var_decl_from_code("let name = \"Alice\";").unwrap();
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
 
-        assert_eq!(var_decl.name, "name");
-        assert_eq!(var_decl.declared_type, None);
-        assert_eq!(var_decl.inferred_type, Some(Type::String));
+    let declarations = program_declarations(program);
+    assert_eq!(declarations.len(), 1);
+    match &declarations[0] {
+        ASTNode::FnDecl(decl) => {
+            assert_eq!(decl.name, "main");
+            assert_eq!(decl.visibility, Some(Visibility::Public));
+            assert_eq!(decl.concurrency, None);
+            assert_eq!(decl.return_type, Some(Type::Int32));
+            assert!(decl.parameters.is_empty());
+            assert_eq!(decl.body.statements.len(), 2);
+            assert!(matches!(decl.body.statements[0], Stmt::VarDecl(_)));
+            assert!(matches!(
+                decl.body.statements[1],
+                Stmt::Return { value: Some(Expr { kind: ExprKind::Identifier(_), .. }), .. }
+            ));
+        }
+        _ => panic!("Expected FnDecl"),
     }
+}
 
-    #[test]
-    fn test_no_initializer() {
-        let var_decl = parse_single_var_decl_from_code("let age: int32;").unwrap();
+#[test]
+fn test_function_modifiers_and_params() {
+    let code = r#"
+    private async fn fetch(id: int64, name: string?) -> bool {
+        return true;
+    }
+    sync fn log(count: int32) {
+        count + 1;
+    }
+"#;
+
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
 
-        assert_eq!(var_decl.name, "age");
-        assert_eq!(var_decl.declared_type, Some(Type::Int32));
-        assert_eq!(var_decl.initializer, None);
+    let declarations = program_declarations(program);
+    match &declarations[0] {
+        ASTNode::FnDecl(decl) => {
+            assert_eq!(decl.visibility, Some(Visibility::Private));
+            assert_eq!(decl.concurrency, Some(ConcurrencyModifier::Async));
+            assert_eq!(decl.parameters.len(), 2);
+            assert_eq!(decl.parameters[0].param_type, Type::Int64);
+            assert_eq!(
+                decl.parameters[1].param_type,
+                Type::Optional(Box::new(Type::String))
+            );
+        }
+        _ => panic!("Expected FnDecl"),
+    }
+    match &declarations[1] {
+        ASTNode::FnDecl(decl) => {
+            assert_eq!(decl.concurrency, Some(ConcurrencyModifier::Sync));
+            assert_eq!(decl.return_type, None);
+            assert!(matches!(decl.body.statements[0], Stmt::Expression { .. }));
+        }
+        _ => panic!("Expected FnDecl"),
     }
+}
 
-    #[test]
-    fn test_public_var_decl() {
-        let var_decl =
-            parse_single_var_decl_from_code("public let config: string = \"default\";").unwrap();
+#[test]
+fn test_variables_and_functions() {
+    let code = r#"
+    let limit: int32 = 10;
+    fn below_limit(value: int32) -> bool {
+        return value < limit;
+    }
+    mut total = 0;
+"#;
 
-        assert_eq!(var_decl.visibility, Some(Visibility::Public));
-        assert_eq!(var_decl.name, "config");
-        assert_eq!(var_decl.declared_type, Some(Type::String));
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+
+    let declarations = program_declarations(program);
+    assert_eq!(declarations.len(), 3);
+    assert!(matches!(declarations[0], ASTNode::VarDecl(_)));
+    assert!(matches!(declarations[1], ASTNode::FnDecl(_)));
+    assert!(matches!(declarations[2], ASTNode::VarDecl(_)));
+}
+
+#[test]
+fn test_function_locals_are_scoped() {
+    let code = r#"
+    fn helper(x: int32) {
+        let y = x;
     }
+    let z = y;
+"#;
 
-    #[test]
-    fn test_optional_type() {
-        let var_decl = parse_single_var_decl_from_code("let age: int32?;").unwrap();
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    let declarations = program_declarations(program);
+    assert_eq!(declarations.len(), 2);
+    assert!(matches!(&declarations[1], ASTNode::VarDecl(decl) if decl.inferred_type.is_none()));
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("Undeclared variable 'y'"));
+}
+
+#[test]
+fn test_failed_declarations_do_not_cascade() {
+    let code = r#"
+    let a: string = 1;
+    let b = a + 1;
+    const C: int32 = B * 2;
+    const B: int32 = true;
+    fn f(flag: bool) -> int32 {
+        mut n: int32 = missing;
+        n = n + C;
+        if a { return b; }
+        for x in a { n = x; }
+        return n;
+    }
+    let s = "${a} and ${C}";
+"#;
+
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    assert!(errors[0].contains("Type mismatch: declared String, but got Int32"));
+    assert!(errors[1].contains("Type mismatch: declared Int32, but got Bool"));
+    assert!(errors[2].contains("Undeclared variable 'missing'"));
+
+    // The failed declarations stay in the tree, unchecked
+    let declarations = program_declarations(program);
+    assert!(matches!(&declarations[0], ASTNode::VarDecl(decl) if decl.inferred_type.is_none()));
+    assert!(matches!(&declarations[1], ASTNode::VarDecl(decl) if decl.inferred_type == Some(Type::Error)));
+    assert!(matches!(&declarations[2], ASTNode::ConstDecl(decl) if decl.value.is_none()));
+    assert!(matches!(&declarations[3], ASTNode::ConstDecl(decl) if decl.value.is_none()));
+
+    // Real errors after a failed declaration are still reported
+    let (_, errors) = parse_program_with_errors("let a: int32 = true;\nlet b: bool = 1;\nlet c = undefined;").unwrap();
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+
+    // A failed condition or iterable doesn't hide the errors of the body
+    let code = r#"
+    fn f() {
+        if 1 { let q: string = 5; } else { let r: bool = 2; }
+        while "s" { let q: string = 5; }
+        for x in 3 { let q: string = x; }
+    }
+"#;
+    let (_, errors) = parse_program_with_errors(code).unwrap();
+    // The loop variable of a failed iterable is an error type, not a String mismatch
+    assert_eq!(errors.len(), 6, "{:?}", errors);
+    assert!(errors[0].contains("'if' condition must be bool, got Int32"));
+    assert!(errors[1].contains("Type mismatch: declared String, but got Int32"));
+    assert!(errors[2].contains("Type mismatch: declared Bool, but got Int32"));
+    assert!(errors[3].contains("'while' condition must be bool, got String"));
+    assert!(errors[4].contains("Type mismatch: declared String, but got Int32"));
+    assert!(errors[5].contains("Cannot iterate over value of type Int32"));
+}
+
+#[test]
+fn test_return_type_mismatch_error() {
+    let (_, errors) =
+        parse_program_with_errors("fn name() -> string { return 42; }").unwrap();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("Return type mismatch"));
+}
+
+#[test]
+fn test_missing_return_error() {
+    let (_, errors) =
+        parse_program_with_errors("fn answer() -> int32 { let x = 42; }").unwrap();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("must return a value"));
+}
+
+#[test]
+fn test_duplicate_function_error() {
+    let code = r#"
+    fn twice() { return; }
+    fn twice() { return; }
+"#;
+
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    let declarations = program_declarations(program);
+    assert_eq!(declarations.len(), 2);
+    assert!(matches!(declarations[1], ASTNode::FnDecl(_)));
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("Function 'twice' already declared"));
+}
+
+fn parse_function_body(code: &str) -> Vec<Stmt> {
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+    match program_declarations(program).pop() {
+        Some(ASTNode::FnDecl(decl)) => decl.body.statements,
+        _ => panic!("Expected FnDecl"),
+    }
+}
 
-        assert_eq!(
-            var_decl.declared_type,
-            Some(Type::Optional(Box::new(Type::Int32)))
+#[test]
+fn test_if_else_statements() {
+    let code = r#"
+    fn sign(n: int32) -> int32 {
+        if n > 0 {
+            return 1;
+        } else if n < 0 {
+            return -1;
+        } else {
+            return 0;
+        }
+    }
+"#;
+
+    let body = parse_function_body(code);
+    assert_eq!(body.len(), 1);
+    match &body[0] {
+        Stmt::If { condition, else_branch: Some(else_branch), .. } => {
+            assert!(matches!(condition.kind, ExprKind::Binary { op: BinaryOp::Greater, .. }));
+            assert!(matches!(else_branch.statements[0], Stmt::If { .. }));
+        }
+        other => panic!("Expected if statement, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_while_and_assignment_statements() {
+    let code = r#"
+    fn countdown(start: int32) {
+        mut n = start;
+        par while n > 0 {
+            n = n - 1;
+        }
+        {
+            let inner = n;
+        }
+        sql { SELECT * FROM jobs }
+    }
+"#;
+
+    let body = parse_function_body(code);
+    assert_eq!(body.len(), 4);
+    match &body[1] {
+        Stmt::While { modifier, body, .. } => {
+            assert_eq!(*modifier, Some(LoopModifier::Par));
+            assert!(matches!(
+                &body.statements[0],
+                Stmt::Assignment { target: Expr { kind: ExprKind::Identifier(name), .. }, .. } if name == "n"
+            ));
+        }
+        other => panic!("Expected while statement, got {:?}", other),
+    }
+    assert!(matches!(body[2], Stmt::Block(_)));
+    assert!(matches!(&body[3], Stmt::DSLBlock { dsl_type, .. } if dsl_type == "sql"));
+}
+
+#[test]
+fn test_const_statement() {
+    let body = parse_function_body("fn f() { const LIMIT: int64 = 100; return; }");
+    match &body[0] {
+        Stmt::ConstDecl(decl) => {
+            assert_eq!(decl.name, "LIMIT");
+            assert_eq!(decl.declared_type, Type::Int64);
+        }
+        other => panic!("Expected const declaration, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_for_loop_modifier_parsing() {
+    let (_, errors) = parse_program_with_errors(
+        "fn f(count: int32) { async for item in count { item; } }",
+    )
+    .unwrap();
+    assert!(errors[0].contains("Cannot iterate over value of type Int32"));
+}
+
+#[test]
+fn test_statement_semantic_errors() {
+    let cases = [
+        ("fn f() { let x = 1; x = 2; }", "Cannot assign to immutable variable 'x'"),
+        ("fn f() { mut x = 1; x = \"s\"; }", "Cannot assign String"),
+        ("fn f() { if 1 { return; } }", "'if' condition must be bool"),
+        ("fn f() { while 1.5 { return; } }", "'while' condition must be bool"),
+        ("fn f() { { let a = 1; } a; }", "Undeclared variable 'a'"),
+        ("fn f() -> int32 { if true { return 1; } }", "must return a value"),
+    ];
+
+    for (code, expected) in cases {
+        let (_, errors) = parse_program_with_errors(code).unwrap();
+        assert!(
+            errors.iter().any(|e| e.contains(expected)),
+            "{}: expected error containing {:?}, got {:?}",
+            code,
+            expected,
+            errors
         );
-        assert_eq!(var_decl.initializer, None);
     }
+}
 
-    #[test]
-    fn test_multiple_declarations() {
-        let code = r#"
-        let x: int32 = 42;
-        mut y: string = "hello";
-        public let z = true;
-    "#;
+#[test]
+fn test_struct_declaration() {
+    let code = r#"
+    public struct User {
+        private id: int64;
+        public name: string;
+        email: string?;
+
+        public fn display_name() -> string {
+            return self.name;
+        }
+
+        async fn key() -> int64 {
+            return self.id;
+        }
+    }
+"#;
+
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+
+    match &program_declarations(program)[0] {
+        ASTNode::StructDecl(decl) => {
+            assert_eq!(decl.name, "User");
+            assert_eq!(decl.visibility, Some(Visibility::Public));
+            assert_eq!(decl.fields.len(), 3);
+            assert_eq!(decl.fields[0].visibility, Some(Visibility::Private));
+            assert_eq!(decl.fields[2].field_type, Type::Optional(Box::new(Type::String)));
+            assert_eq!(decl.methods.len(), 2);
+            assert_eq!(decl.methods[1].concurrency, Some(ConcurrencyModifier::Async));
+        }
+        _ => panic!("Expected StructDecl"),
+    }
+}
+
+#[test]
+fn test_struct_literal_and_field_access() {
+    let code = r#"
+    struct Point {
+        x: int32;
+        y: int32;
+    }
+    struct Line {
+        start: Point;
+        end: Point;
+    }
+    let origin = Point { x: 0, y: 0 };
+    let line: Line = Line { start: origin, end: Point { x: 3, y: 4 } };
+    let dx: int32 = line.end.x - line.start.x;
+    fn is_origin(p: Point) -> bool {
+        if p.x == 0 && p.y == 0 {
+            return true;
+        }
+        return false;
+    }
+"#;
+
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+
+    let declarations = program_declarations(program);
+    match &declarations[2] {
+        ASTNode::VarDecl(decl) => {
+            assert_eq!(decl.inferred_type, Some(Type::Custom("Point".to_string())));
+            assert!(matches!(
+                &decl.initializer,
+                Some(Expr { kind: ExprKind::StructLiteral { name, fields }, .. }) if name == "Point" && fields.len() == 2
+            ));
+        }
+        _ => panic!("Expected VarDecl"),
+    }
+    match &declarations[4] {
+        ASTNode::VarDecl(decl) => assert!(matches!(
+            &decl.initializer,
+            Some(Expr { kind: ExprKind::Binary { left, .. }, .. }) if matches!(left.kind, ExprKind::Member { .. })
+        )),
+        _ => panic!("Expected VarDecl"),
+    }
+}
 
-        let program_node = parse_program_from_code(code).unwrap();
+#[test]
+fn test_struct_semantic_errors() {
+    let prelude = "struct Account { private balance: float64; owner: string; }";
+    let cases = [
+        ("let a: Missing;", "Unknown type 'Missing'"),
+        ("fn f(p: Ghost) { return; }", "Unknown type 'Ghost'"),
+        ("let a = Account { owner: \"x\" };", "Missing fields in 'Account' literal: balance"),
+        ("let a = Account { owner: \"x\", balance: 1.0, extra: 1 };", "has no field 'extra'"),
+        ("let a = Account { owner: 1, balance: 1.0 };", "Field 'owner' expects type String"),
+        ("let a = Account { owner: \"x\", balance: 1.0 }; let b = a.balance;", "is private"),
+        ("let a = Account { owner: \"x\", balance: 1.0 }; let b = a.nope;", "has no field 'nope'"),
+        ("let n = 5; let b = n.field;", "Type Int32 has no fields"),
+        ("struct Account { id: int32; }", "Struct 'Account' already declared"),
+        ("struct Dup { a: int32; a: string; }", "Member 'a' already declared"),
+    ];
 
-        let declarations = match program_node {
-            ASTNode::Program(prog) => prog.declarations,
-            _ => panic!("Expected Program node"),
+    for (code, expected) in cases {
+        let source = format!("{}\n{}", prelude, code);
+        let (_, errors) = parse_program_with_errors(&source).unwrap();
+        assert!(
+            errors.iter().any(|e| e.contains(expected)),
+            "{}: expected error containing {:?}, got {:?}",
+            code,
+            expected,
+            errors
+        );
+    }
+}
+
+#[test]
+fn test_struct_literal_not_parsed_in_condition() {
+    let code = r#"
+    struct Flag { on: bool; }
+    fn check(flag: bool) {
+        if flag {}
+        while flag {}
+    }
+"#;
+
+    let (_, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+}
+
+#[test]
+fn test_enum_declaration_and_variants() {
+    let code = r#"
+    public enum Shape {
+        Circle(float64),
+        Rect(float64, float64),
+        Empty
+    }
+    let s = Shape.Rect(2.0, 3.0);
+    let e = Shape.Empty;
+"#;
+
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+
+    let declarations = program_declarations(program);
+    match &declarations[0] {
+        ASTNode::EnumDecl(decl) => {
+            assert_eq!(decl.name, "Shape");
+            assert_eq!(decl.visibility, Some(Visibility::Public));
+            let names: Vec<_> = decl.variants.iter().map(|v| v.name.as_str()).collect();
+            assert_eq!(names, ["Circle", "Rect", "Empty"]);
+            assert_eq!(decl.variants[1].payload, vec![Type::Float64, Type::Float64]);
+            assert!(decl.variants[2].payload.is_empty());
+        }
+        _ => panic!("Expected EnumDecl"),
+    }
+    match &declarations[1] {
+        ASTNode::VarDecl(decl) => {
+            assert_eq!(decl.inferred_type, Some(Type::Custom("Shape".to_string())));
+            assert!(matches!(
+                &decl.initializer,
+                Some(Expr { kind: ExprKind::EnumVariant { enum_name, variant, args }, .. })
+                    if enum_name == "Shape" && variant == "Rect" && args.len() == 2
+            ));
+        }
+        _ => panic!("Expected VarDecl"),
+    }
+}
+
+#[test]
+fn test_match_expression() {
+    let code = r#"
+    enum Shape { Circle(float64), Rect(float64, float64), Empty }
+    fn area(s: Shape) -> float64 {
+        return match s {
+            Shape.Circle(r) => r * r * 3.14,
+            Shape.Rect(w, h) => w * h,
+            Shape.Empty => 0.0,
         };
+    }
+    let label = match 3 { 0 => "zero", -1 => "negative", _ => "other" };
+"#;
 
-        assert_eq!(declarations.len(), 3);
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
 
-        // var decl #1
-        match &declarations[0] {
-            ASTNode::VarDecl(decl) => assert_eq!(decl.name, "x"),
-            _ => panic!("Expected VarDecl"),
+    let declarations = program_declarations(program);
+    match &declarations[2] {
+        ASTNode::VarDecl(decl) => {
+            assert_eq!(decl.inferred_type, Some(Type::String));
+            match &decl.initializer {
+                Some(Expr { kind: ExprKind::Match { arms, .. }, .. }) => {
+                    assert_eq!(arms.len(), 3);
+                    assert_eq!(arms[1].pattern.kind, PatternKind::Literal(Literal::Int(-1, None)));
+                    assert_eq!(arms[2].pattern.kind, PatternKind::Wildcard);
+                }
+                other => panic!("Expected match expression, got {:?}", other),
+            }
         }
+        _ => panic!("Expected VarDecl"),
+    }
+}
+
+#[test]
+fn test_enum_semantic_errors() {
+    let prelude = "enum Shape { Circle(float64), Empty }\nlet s = Shape.Empty;";
+    let cases = [
+        ("let a = match s { Shape.Circle(r) => r };", "Non-exhaustive match: missing Shape.Empty"),
+        ("let a = match 1 { 0 => true };", "Non-exhaustive match"),
+        ("let a = match s { Shape.Circle(r) => r, Shape.Empty => \"none\" };", "Match arms have incompatible types"),
+        ("let a = Shape.Circle(1.0, 2.0);", "expects 1 values, got 2"),
+        ("let a = Shape.Square;", "Enum 'Shape' has no variant 'Square'"),
+        ("let a = match s { 1 => 0, _ => 1 };", "cannot match value of type"),
+        ("enum Shape { A }", "Enum 'Shape' already declared"),
+        ("enum Dup { A, A }", "Variant 'A' already declared"),
+    ];
 
-        // var decl #2
-        match &declarations[1] {
-            ASTNode::VarDecl(decl) => {
-                assert_eq!(decl.name, "y");
-                assert_eq!(decl.mutability, Mutability::Mut);
+    for (code, expected) in cases {
+        let source = format!("{}\n{}", prelude, code);
+        let (_, errors) = parse_program_with_errors(&source).unwrap();
+        assert!(
+            errors.iter().any(|e| e.contains(expected)),
+            "{}: expected error containing {:?}, got {:?}",
+            code,
+            expected,
+            errors
+        );
+    }
+}
+
+#[test]
+fn test_compound_type_annotations() {
+    let code = r#"
+    fn first(xs: [int32]) -> string? {
+        return "first";
+    }
+    fn apply(f: fn(int32, int32) -> bool, x: int32) -> bool {
+        return true;
+    }
+    let maybe: int32?? = 5;
+    let grid: [[float64]?];
+    let callback: fn(string);
+"#;
+
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+
+    let declarations = program_declarations(program);
+    match &declarations[0] {
+        ASTNode::FnDecl(decl) => {
+            assert_eq!(decl.parameters[0].param_type, Type::Array(Box::new(Type::Int32)));
+            assert_eq!(decl.return_type, Some(Type::Optional(Box::new(Type::String))));
+        }
+        _ => panic!("Expected FnDecl"),
+    }
+    match &declarations[1] {
+        ASTNode::FnDecl(decl) => assert_eq!(
+            decl.parameters[0].param_type,
+            Type::Function {
+                params: vec![Type::Int32, Type::Int32],
+                return_type: Some(Box::new(Type::Bool)),
             }
+        ),
+        _ => panic!("Expected FnDecl"),
+    }
+
+    let declared: Vec<_> = declarations[2..]
+        .iter()
+        .map(|node| match node {
+            ASTNode::VarDecl(decl) => decl.declared_type.clone(),
             _ => panic!("Expected VarDecl"),
+        })
+        .collect();
+    assert_eq!(
+        declared,
+        vec![
+            Some(Type::Optional(Box::new(Type::Optional(Box::new(Type::Int32))))),
+            Some(Type::Array(Box::new(Type::Optional(Box::new(Type::Array(Box::new(
+                Type::Float64
+            ))))))),
+            Some(Type::Function { params: vec![Type::String], return_type: None }),
+        ]
+    );
+}
+
+#[test]
+fn test_type_aliases() {
+    let code = r#"
+    type Id = int64;
+    public type Ids = [Id];
+    type Handler = fn(Id) -> bool;
+    fn count(ids: Ids) -> int32 {
+        mut n = 0;
+        for id in ids {
+            n = n + 1;
+        }
+        return n;
+    }
+    let user: Id = 42;
+"#;
+
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+
+    let declarations = program_declarations(program);
+    match &declarations[1] {
+        ASTNode::TypeAlias(decl) => {
+            assert_eq!(decl.name, "Ids");
+            assert_eq!(decl.visibility, Some(Visibility::Public));
+            assert_eq!(decl.aliased_type, Type::Array(Box::new(Type::Custom("Id".to_string()))));
         }
+        _ => panic!("Expected TypeAlias"),
+    }
+    match &declarations[4] {
+        ASTNode::VarDecl(decl) => {
+            assert_eq!(decl.declared_type, Some(Type::Custom("Id".to_string())));
+            assert_eq!(decl.inferred_type, Some(Type::Int64));
+        }
+        _ => panic!("Expected VarDecl"),
+    }
+}
+
+#[test]
+fn test_type_alias_errors() {
+    let prelude = "struct Point { x: int32; }\ntype Name = string;";
+    let cases = [
+        ("type Broken = Missing;", "Unknown type 'Missing'"),
+        ("type Loop = [Loop];", "Unknown type 'Loop'"),
+        ("type Name = int32;", "Type 'Name' already declared"),
+        ("type Point = int32;", "Type 'Point' already declared"),
+        ("type int32 = string;", "Type 'int32' already declared"),
+        ("struct Name { a: int32; }", "already declared as an alias"),
+        ("let n: Name = 5;", "Type mismatch: declared String, but got Int32"),
+        ("let f: fn(Ghost) -> bool;", "Unknown type 'Ghost'"),
+    ];
+
+    for (code, expected) in cases {
+        let source = format!("{}\n{}", prelude, code);
+        let (_, errors) = parse_program_with_errors(&source).unwrap();
+        assert!(
+            errors.iter().any(|e| e.contains(expected)),
+            "{}: expected error containing {:?}, got {:?}",
+            code,
+            expected,
+            errors
+        );
+    }
+}
+
+#[test]
+fn test_postfix_chains() {
+    let code = r#"
+    struct Io {
+        fn println(msg: string) -> bool {
+            return true;
+        }
+    }
+    struct Item { name: string; }
+    let io = Io {};
+    fn show(items: [Item], i: int32) -> bool {
+        let msg = items[i].name;
+        return io.println(msg);
+    }
+    fn twice(f: fn(int32) -> int32, x: int32) -> int32 {
+        return f(f(x));
+    }
+    fn inc(x: int32) -> int32 {
+        return x + 1;
+    }
+    let n = twice(inc, 1);
+"#;
+
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
 
-        //var decl #3
-        match &declarations[2] {
-            ASTNode::VarDecl(decl) => {
-                assert_eq!(decl.name, "z");
-                assert_eq!(decl.visibility, Some(Visibility::Public));
-                assert_eq!(decl.inferred_type, Some(Type::Bool));
+    let declarations = program_declarations(program);
+    match &declarations[3] {
+        ASTNode::FnDecl(decl) => {
+            match &decl.body.statements[0] {
+                Stmt::VarDecl(var) => {
+                    assert_eq!(var.inferred_type, Some(Type::String));
+                    match &var.initializer {
+                        Some(Expr { kind: ExprKind::Member { object, member }, .. }) => {
+                            assert_eq!(member, "name");
+                            assert!(matches!(object.kind, ExprKind::Index { .. }));
+                        }
+                        other => panic!("Expected member access, got {:?}", other),
+                    }
+                }
+                other => panic!("Expected VarDecl, got {:?}", other),
             }
+            match &decl.body.statements[1] {
+                Stmt::Return { value: Some(Expr { kind: ExprKind::Call { callee, args }, .. }), .. } => {
+                    assert_eq!(args.len(), 1);
+                    assert!(matches!(
+                        &callee.kind,
+                        ExprKind::Member { object, member }
+                            if member == "println" && object.kind == ExprKind::Identifier("io".to_string())
+                    ));
+                }
+                other => panic!("Expected return of a call, got {:?}", other),
+            }
+        }
+        _ => panic!("Expected FnDecl"),
+    }
+    match &declarations[6] {
+        ASTNode::VarDecl(decl) => assert_eq!(decl.inferred_type, Some(Type::Int32)),
+        _ => panic!("Expected VarDecl"),
+    }
+}
+
+#[test]
+fn test_assignment_to_members_and_elements() {
+    let code = r#"
+    struct Counter {
+        count: int32;
+        fn bump() {
+            self.count = self.count + 1;
+        }
+    }
+    fn reset(counters: [Counter]) {
+        mut all = counters;
+        all[0].count = 0;
+        mut c = Counter { count: 1 };
+        c.count = 2;
+    }
+"#;
+
+    let body = parse_function_body(code);
+    assert!(matches!(
+        &body[1],
+        Stmt::Assignment { target: Expr { kind: ExprKind::Member { object, .. }, .. }, .. }
+            if matches!(object.kind, ExprKind::Index { .. })
+    ));
+}
+
+#[test]
+fn test_postfix_semantic_errors() {
+    let prelude = r#"
+    struct Point { x: int32; private fn secret() -> int32 { return 1; } }
+    fn add(a: int32, b: int32) -> int32 { return a + b; }
+    let p = Point { x: 1 };
+"#;
+    let cases = [
+        ("let a = add(1);", "Function 'add' expects 2 args, got 1"),
+        ("let a = add(1, \"two\");", "Argument 2 of 'add' expects type Int32"),
+        ("let a = missing(1);", "Undefined function 'missing'"),
+        ("let a = p.x(1);", "'x' is not callable"),
+        ("let a = p.secret();", "Method 'secret' of struct 'Point' is private"),
+        ("let a = p[0];", "cannot be indexed"),
+        ("fn f(xs: [Point]) { let a = xs[true]; }", "Array index must be an integer"),
+        ("fn f() { p.x = 2; }", "Cannot assign to immutable variable 'p'"),
+        ("fn f() { mut q = p; q.x = \"s\"; }", "Cannot assign String to 'q.x' of type Int32"),
+        ("fn f() { add(1, 2) = 3; }", "Invalid assignment target"),
+    ];
+
+    for (code, expected) in cases {
+        let source = format!("{}\n{}", prelude, code);
+        let (_, errors) = parse_program_with_errors(&source).unwrap();
+        assert!(
+            errors.iter().any(|e| e.contains(expected)),
+            "{}: expected error containing {:?}, got {:?}",
+            code,
+            expected,
+            errors
+        );
+    }
+}
+
+#[test]
+fn test_array_and_object_literals() {
+    let code = r#"
+    let mixed = [1, 2.5];
+    let nested = [[1], [2, 3]];
+    let empty: [string] = [];
+    let maybe: [int32]? = [];
+    let widened: [float64] = [1, 2];
+    let config = { host: "localhost", "port": 8080, tags: ["a", "b"] };
+    let port = config.port;
+    let servers = [{ weight: 1 }, { weight: 0.5 }];
+"#;
+
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+
+    let inferred: Vec<_> = program_declarations(program)
+        .into_iter()
+        .map(|node| match node {
+            ASTNode::VarDecl(decl) => decl.inferred_type.unwrap(),
             _ => panic!("Expected VarDecl"),
+        })
+        .collect();
+    let array = |t: Type| Type::Array(Box::new(t));
+    assert_eq!(inferred[0], array(Type::Float64));
+    assert_eq!(inferred[1], array(array(Type::Int32)));
+    assert_eq!(inferred[2], array(Type::String));
+    assert_eq!(inferred[4], array(Type::Float64));
+    assert_eq!(
+        inferred[5],
+        Type::Object(vec![
+            ("host".to_string(), Type::String),
+            ("port".to_string(), Type::Int32),
+            ("tags".to_string(), array(Type::String)),
+        ])
+    );
+    assert_eq!(inferred[6], Type::Int32);
+    assert_eq!(
+        inferred[7],
+        array(Type::Object(vec![("weight".to_string(), Type::Float64)]))
+    );
+}
+
+#[test]
+fn test_literal_semantic_errors() {
+    let cases = [
+        ("let a = [];", "Cannot infer the element type of an empty array literal"),
+        ("let a = [1, \"two\"];", "Array elements have incompatible types: Int32 and String"),
+        ("let a = [[1], [true]];", "Array elements have incompatible types"),
+        ("let a: [bool] = [1];", "Array element expects type Bool, got Int32"),
+        ("let a = { k: 1, k: 2 };", "Duplicate key 'k' in object literal"),
+        ("let a = { k: 1 }; let b = a.missing;", "Object has no field 'missing'"),
+        ("let a = [{ x: 1 }, { y: 1 }];", "Array elements have incompatible types"),
+    ];
+
+    for (code, expected) in cases {
+        let (_, errors) = parse_program_with_errors(code).unwrap();
+        assert!(
+            errors.iter().any(|e| e.contains(expected)),
+            "{}: expected error containing {:?}, got {:?}",
+            code,
+            expected,
+            errors
+        );
+    }
+}
+
+#[test]
+fn test_ternary_and_comparison_precedence() {
+    let code = r#"
+    let a = 1;
+    let b = 2;
+    let label = a > b ? "greater" : a == b ? "equal" : "less";
+    let widened = a < b ? 1 : 2.5;
+    let same = a < b == b > a;
+"#;
+
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+
+    let declarations = program_declarations(program);
+    match &declarations[2] {
+        ASTNode::VarDecl(decl) => {
+            assert_eq!(decl.inferred_type, Some(Type::String));
+            match &decl.initializer {
+                Some(Expr { kind: ExprKind::Ternary { condition, else_branch, .. }, .. }) => {
+                    assert!(matches!(condition.kind, ExprKind::Binary { op: BinaryOp::Greater, .. }));
+                    assert!(matches!(else_branch.kind, ExprKind::Ternary { .. }));
+                }
+                other => panic!("Expected ternary, got {:?}", other),
+            }
+        }
+        _ => panic!("Expected VarDecl"),
+    }
+    match &declarations[3] {
+        ASTNode::VarDecl(decl) => assert_eq!(decl.inferred_type, Some(Type::Float64)),
+        _ => panic!("Expected VarDecl"),
+    }
+    match &declarations[4] {
+        ASTNode::VarDecl(decl) => assert!(matches!(
+            &decl.initializer,
+            Some(Expr { kind: ExprKind::Binary { op: BinaryOp::Equal, left, right }, .. })
+                if matches!(left.kind, ExprKind::Binary { op: BinaryOp::Less, .. })
+                    && matches!(right.kind, ExprKind::Binary { op: BinaryOp::Greater, .. })
+        )),
+        _ => panic!("Expected VarDecl"),
+    }
+}
+
+#[test]
+fn test_structured_parse_errors() {
+    // The missing ';' is inserted at the line break and both declarations survive
+    let (program, errors) = parse_program_with_parse_errors("let a = 1\nlet b = a + 2;").unwrap();
+    let declarations = program_declarations(program);
+    assert_eq!(declarations.len(), 2);
+    assert!(declarations.iter().all(|d| matches!(d, ASTNode::VarDecl(_))));
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].message.contains("Expected ';' after variable declaration"));
+    assert_eq!(errors[0].expected, vec![TokenType::Semicolon]);
+    assert_eq!(errors[0].found.as_deref(), Some(&TokenType::Let));
+    assert_eq!((errors[0].line, errors[0].column), (1, 10));
+    assert_eq!(
+        errors[0].expectation().as_deref(),
+        Some("expected Semicolon, found Let")
+    );
+
+    let (_, errors) = parse_program_with_parse_errors("fn f(: int32) {}").unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].expected, vec![TokenType::Identifier(String::new())]);
+    assert_eq!(errors[0].found.as_deref(), Some(&TokenType::Colon));
+    assert_eq!(errors[0].span, Span { line: 1, column: 6, end_line: 1, end_column: 7, start: 5, end: 6 });
+
+    // Semantic errors carry no expected set
+    let (_, errors) = parse_program_with_parse_errors("let x: string = 42;").unwrap();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].expected.is_empty());
+    assert_eq!(errors[0].expectation(), None);
+}
+
+#[test]
+fn test_recovery_keeps_partial_ast() {
+    let code = r#"
+fn f() -> int32 {
+    let x = (1 + ;
+    while x > { x = 1; }
+    let z = 3;
+    return z;
+}
+struct P { x int32; y: int32; }
+let q = 1 2
+fn g() -> int32 { return 1; }
+"#;
+
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    let expected_errors = [
+        "Expected expression",
+        "Expected expression",
+        "Expected ':' after field name",
+        "Expected ';' after variable declaration",
+    ];
+    assert_eq!(errors.len(), expected_errors.len(), "{:?}", errors);
+    for (error, expected) in errors.iter().zip(expected_errors) {
+        assert!(error.contains(expected), "expected {:?}, got {:?}", expected, error);
+    }
+
+    let declarations = program_declarations(program);
+    assert_eq!(declarations.len(), 4);
+    match &declarations[0] {
+        ASTNode::FnDecl(decl) => {
+            let body = &decl.body.statements;
+            assert_eq!(body.len(), 4);
+            assert!(matches!(&body[0], Stmt::Error(node) if node.line == 3));
+            assert!(matches!(&body[1], Stmt::Error(node) if node.line == 4));
+            assert!(matches!(&body[2], Stmt::VarDecl(v) if v.name == "z"));
+            assert!(matches!(&body[3], Stmt::Return { .. }));
+        }
+        other => panic!("Expected FnDecl, got {:?}", other),
+    }
+    assert!(matches!(&declarations[1], ASTNode::Error(node) if node.line == 8));
+    assert!(matches!(&declarations[2], ASTNode::Error(node) if node.line == 9));
+    assert!(matches!(&declarations[3], ASTNode::FnDecl(decl) if decl.name == "g"));
+}
+
+#[test]
+fn test_ternary_and_comparison_errors() {
+    let prelude = "let a = 1;\nlet b = 2;";
+    let cases = [
+        ("let c = a < b < 3;", "Comparison operators cannot be chained; found '<'"),
+        ("let c = a == b != true;", "Comparison operators cannot be chained; found '!='"),
+        ("let c = a ? 1 : 2;", "Ternary condition must be bool, got Int32"),
+        ("let c = a < b ? 1 : \"one\";", "Ternary branches have incompatible types"),
+        ("let c = a < b ? 1;", "Expected ':' in ternary expression"),
+    ];
+
+    for (code, expected) in cases {
+        let source = format!("{}\n{}", prelude, code);
+        let (_, errors) = parse_program_with_errors(&source).unwrap();
+        assert!(
+            errors.iter().any(|e| e.contains(expected)),
+            "{}: expected error containing {:?}, got {:?}",
+            code,
+            expected,
+            errors
+        );
+    }
+}
+
+#[test]
+fn test_pipeline_facade() {
+    let source = crate::SourceFile::new("main.kai", "let a: string = 1;\nlet b = a +;");
+
+    let checked = crate::check(&source);
+    let stages: Vec<crate::Stage> = checked.diagnostics.iter().map(|d| d.stage).collect();
+    assert_eq!(stages, vec![crate::Stage::Semantic, crate::Stage::Syntax]);
+    assert_eq!(checked.program.declarations.len(), 2);
+    assert_eq!(checked.diagnostics[1].line(), 2);
+    assert_eq!(
+        checked.diagnostics[1].to_string(),
+        "Parse error at line 2, column 12: Expected expression (expected identifier or number or string or rune \
+         or byte or byte string or boolean or LeftParen or LeftBracket or LeftBrace or Not or Minus or Plus or \
+         Await or Spawn or Match or Fn, found Semicolon)"
+    );
+    let parsed = crate::parse(&crate::SourceFile::new("main.kai", "let x = ;"));
+    assert_eq!(parsed.diagnostics[0].note, checked.diagnostics[1].note);
+    // A condition can't start with an object literal
+    let parsed = crate::parse(&crate::SourceFile::new("main.kai", "fn f() { if ) {} }"));
+    let note = parsed.diagnostics[0].note.as_deref().unwrap();
+    assert!(note.starts_with("expected identifier or number"), "{}", note);
+    assert!(note.ends_with("LeftBracket or Not or Minus or Plus or Await or Spawn or Match or Fn, found RightParen"), "{}", note);
+    // The found token is named once, in the note
+    let parsed = crate::parse(&crate::SourceFile::new("main.kai", "let x = 1 let y = 2;"));
+    assert_eq!(
+        parsed.diagnostics[0].to_string(),
+        "Parse error at line 1, column 11: Expected ';' after variable declaration (expected Semicolon, found Let)"
+    );
+
+    // `parse` only builds the tree: nothing is inferred or reported beyond syntax
+    let parsed = crate::parse(&source);
+    assert_eq!(parsed.diagnostics.len(), 1);
+    assert_eq!(parsed.diagnostics[0].stage, crate::Stage::Syntax);
+    assert!(matches!(&parsed.program.declarations[0], ASTNode::VarDecl(decl) if decl.inferred_type.is_none()));
+
+    let lexed = crate::lex(&crate::SourceFile::new("bad.kai", "let s = \"open;"));
+    let kinds: Vec<&TokenType> = lexed.tokens.iter().skip(3).map(|t| &t.token_type).collect();
+    assert!(matches!(kinds[..], [TokenType::Error(_), TokenType::Eof]), "{:?}", kinds);
+    assert_eq!(lexed.diagnostics[0].stage, crate::Stage::Lex);
+    assert!(lexed.diagnostics[0].message.contains("Unterminated string"));
+
+    // Declarations persist across sources checked against one symbol table
+    let mut table = SymbolTable::new();
+    let first = crate::check_with(&crate::SourceFile::new("1", "let x = 1;"), &mut table);
+    let second = crate::check_with(&crate::SourceFile::new("2", "let y = x + 1;"), &mut table);
+    assert!(!first.has_errors() && !second.has_errors(), "{:?}", second.diagnostics);
+}
+
+#[test]
+fn test_parsing_continues_past_lexer_errors() {
+    let code = "let a = 1 & 2;\nlet b: int32 = 'ab';\nfn f() -> int32 {\n    let d = #;\n    return 1;\n}\nlet e: string = 5;";
+    let checked = crate::check(&crate::SourceFile::new("broken.kai", code));
+
+    // Each invalid token is reported once, by the lexer; checking still
+    // reaches the declarations after them
+    let reported: Vec<(crate::Stage, usize)> = checked.diagnostics.iter().map(|d| (d.stage, d.line())).collect();
+    assert_eq!(
+        reported,
+        vec![(crate::Stage::Lex, 1), (crate::Stage::Lex, 2), (crate::Stage::Lex, 4), (crate::Stage::Semantic, 7)],
+        "{:?}",
+        checked.diagnostics
+    );
+    assert_eq!(checked.program.declarations.len(), 4);
+    assert!(matches!(&checked.program.declarations[2], ASTNode::FnDecl(f) if f.name == "f"));
+}
+
+#[test]
+fn test_forward_references() {
+    let code = r#"
+fn main() -> int32 {
+    let p = make(2);
+    let c = Color.Green;
+    return area(p);
+}
+fn make(n: int32) -> Point {
+    return Point { x: n, y: n };
+}
+fn area(p: Point) -> int32 {
+    return p.x * p.y;
+}
+struct Point { x: int32; y: int32; }
+enum Color { Red, Green }
+"#;
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "{:?}", errors);
+    match &program_declarations(program)[0] {
+        ASTNode::FnDecl(decl) => match &decl.body.statements[1] {
+            Stmt::VarDecl(var) => {
+                assert_eq!(var.inferred_type, Some(Type::Custom("Color".to_string())));
+                assert!(matches!(&var.initializer, Some(Expr { kind: ExprKind::EnumVariant { variant, .. }, .. }) if variant == "Green"));
+            }
+            other => panic!("Expected variable declaration, got {:?}", other),
+        },
+        other => panic!("Expected function, got {:?}", other),
+    }
+
+    // Variables and constants are still only visible after their declaration
+    let (_, errors) = parse_program_with_errors("let a = b + 1;\nlet b = 2;").unwrap();
+    assert!(errors.iter().any(|e| e.contains("'b'")), "{:?}", errors);
+}
+
+#[test]
+fn test_visitor_traits() {
+    use crate::parser::visit::{fold, visitor, visitor_mut, Fold, Visitor, VisitorMut};
+
+    #[derive(Default)]
+    struct Names(Vec<String>);
+    impl Visitor for Names {
+        fn visit_expr(&mut self, expr: &Expr) {
+            if let ExprKind::Identifier(name) = &expr.kind {
+                self.0.push(name.clone());
+            }
+            visitor::walk_expr(self, expr);
+        }
+    }
+
+    struct Rename;
+    impl VisitorMut for Rename {
+        fn visit_expr(&mut self, expr: &mut Expr) {
+            if let ExprKind::Identifier(name) = &mut expr.kind {
+                *name = name.to_uppercase();
+            }
+            visitor_mut::walk_expr(self, expr);
+        }
+    }
+
+    /// Folds additions of integer literals, innermost first.
+    struct AddLiterals;
+    impl Fold for AddLiterals {
+        fn fold_expr(&mut self, expr: Expr) -> Expr {
+            let expr = fold::walk_expr(self, expr);
+            match (&expr.kind, expr.span) {
+                (ExprKind::Binary { left, op: BinaryOp::Add, right }, span) => match (&left.kind, &right.kind) {
+                    (ExprKind::Literal(Literal::Int(a, _)), ExprKind::Literal(Literal::Int(b, _))) => {
+                        Expr { kind: ExprKind::Literal(Literal::Int(a + b, None)), span, id: expr.id }
+                    }
+                    _ => expr,
+                },
+                _ => expr,
+            }
+        }
+    }
+
+    let code = r#"
+fn f(a: int32) -> int32 {
+    let g = fn(x: int32) -> int32 { return x + a; };
+    if a > 0 { return g(1 + 2 + 3); }
+    return match a { 0 => b, _ => [a][0] };
+}
+"#;
+    let parsed = crate::parse(&crate::SourceFile::new("visit.kai", code));
+    assert!(!parsed.has_errors(), "{:?}", parsed.diagnostics);
+
+    let mut names = Names::default();
+    names.visit_program(&parsed.program);
+    assert_eq!(names.0, ["x", "a", "a", "g", "a", "b", "a"]);
+
+    let mut renamed = parsed.program.clone();
+    Rename.visit_program(&mut renamed);
+    let mut names = Names::default();
+    names.visit_program(&renamed);
+    assert_eq!(names.0, ["X", "A", "A", "G", "A", "B", "A"]);
+
+    let folded = AddLiterals.fold_program(parsed.program);
+    let ASTNode::FnDecl(decl) = &folded.declarations[0] else { panic!("Expected function") };
+    let Stmt::If { then_branch, .. } = &decl.body.statements[1] else { panic!("Expected if") };
+    assert!(matches!(
+        &then_branch.statements[0],
+        Stmt::Return { value: Some(Expr { kind: ExprKind::Call { args, .. }, .. }), .. }
+            if args.len() == 1 && args[0].kind == ExprKind::Literal(Literal::Int(6, None))
+    ));
+}
+
+/// Writes `files` (module path, source) below a fresh temporary project root.
+fn write_project(name: &str, files: &[(&str, &str)]) -> ModuleLoader {
+    let root = std::env::temp_dir().join(format!("kai-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let loader = ModuleLoader::new(&root);
+    for (module, source) in files {
+        let path = loader.module_file(module);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, source).unwrap();
+    }
+    loader
+}
+
+#[test]
+fn test_module_imports() {
+    let loader = write_project(
+        "imports",
+        &[
+            (
+                "geo.shapes",
+                r#"
+                public struct Point { x: float64; y: float64; }
+                public enum Shape { Dot(Point), Empty }
+                public fn origin() -> Point { return Point { x: 0.0, y: 0.0 }; }
+                public const UNIT: float64 = 1.0;
+                fn helper() -> int32 { return 1; }
+                "#,
+            ),
+            (
+                "std.io",
+                "public fn println(msg: string) -> bool { return true; }",
+            ),
+            (
+                "main",
+                r#"
+                use std.io;
+                use geo.shapes;
+                const DOUBLE: float64 = shapes.UNIT * 2.0;
+                fn main() -> float64 {
+                    let msg = "hello";
+                    io.println(msg);
+                    let p: shapes.Point = shapes.origin();
+                    let q = shapes.Point { x: shapes.UNIT, y: p.y };
+                    let s = shapes.Shape.Dot(q);
+                    return match s {
+                        shapes.Shape.Dot(pt) => pt.x,
+                        shapes.Shape.Empty => 0.0,
+                    };
+                }
+                "#,
+            ),
+        ],
+    );
+
+    let graph = ModuleGraph::load(&loader, "main").unwrap();
+    for module in graph.modules() {
+        assert!(!module.parsed.has_errors(), "{}: {:?}", module.name, module.parsed.diagnostics);
+    }
+
+    let order: Vec<_> = graph.modules().iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(order, ["std.io", "geo.shapes", "main"]);
+    assert_eq!(graph.entry().imports, ["std.io", "geo.shapes"]);
+    assert!(graph.get("geo.shapes").unwrap().exports.values.contains_key("origin"));
+    assert!(!graph.get("geo.shapes").unwrap().exports.values.contains_key("helper"));
+    assert_eq!(
+        graph.get("geo.shapes").unwrap().exports.constants.get("UNIT"),
+        Some(&Literal::Float(1.0, None))
+    );
+
+    assert!(matches!(
+        &graph.entry().parsed.program.declarations[0],
+        ASTNode::Import(import) if import.path == ["std", "io"] && import.binding() == "io"
+    ));
+
+    // The facade loads the same graph from the entry file, while checking
+    // the entry on its own can't resolve its imports
+    let main_file = loader.module_file("main");
+    let checked = crate::check_modules(&main_file).unwrap();
+    let names: Vec<_> = checked.iter().map(|(source, _)| source.name.clone()).collect();
+    assert_eq!(
+        names,
+        ["std.io", "geo.shapes", "main"].map(|m| loader.module_file(m).display().to_string())
+    );
+    assert!(checked.iter().all(|(_, parsed)| !parsed.has_errors()));
+
+    let (entry, _) = checked.last().unwrap();
+    assert!(crate::check(entry).diagnostics[0].message.contains("Module 'std.io' is not loaded"));
+
+    match crate::check_modules(&loader.root().join("main.txt")) {
+        Err(ModuleError::NotASourceFile(path)) => assert!(path.ends_with("main.txt")),
+        other => panic!("Expected a non-source file error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_module_errors() {
+    // An import cycle is reported at the `use` closing it, and every module
+    // is still checked
+    let loader = write_project(
+        "cycle",
+        &[
+            ("a", "use b;"),
+            ("b", "use c;"),
+            ("c", "let x: int32 = 1;\nuse a;\nlet y: int32 = \"s\";"),
+        ],
+    );
+    let graph = ModuleGraph::load(&loader, "a").unwrap();
+    let order: Vec<_> = graph.modules().iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(order, ["c", "b", "a"]);
+    let diagnostics = &graph.get("c").unwrap().parsed.diagnostics;
+    assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+    assert_eq!(diagnostics[0].message, "Import cycle detected: a -> b -> c -> a");
+    assert_eq!((diagnostics[0].line(), diagnostics[0].column()), (2, 1));
+    assert!(diagnostics[1].message.contains("Type mismatch"));
+    assert!(graph.get("c").unwrap().imports.is_empty());
+    assert!(!graph.get("b").unwrap().parsed.has_errors());
+
+    // So is a missing module, while the imports that loaded stay usable
+    let loader = write_project(
+        "missing",
+        &[
+            ("util", "public fn one() -> int32 { return 1; }"),
+            ("main", "use util;\nuse net.http;\nlet a: int32 = util.one();"),
+        ],
+    );
+    let graph = ModuleGraph::load(&loader, "main").unwrap();
+    let diagnostics = &graph.entry().parsed.diagnostics;
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert!(diagnostics[0].message.starts_with("Module 'net.http' not found"), "{:?}", diagnostics);
+    assert!(diagnostics[0].message.contains("http.kai"));
+    assert_eq!(diagnostics[0].line(), 2);
+    assert_eq!(graph.entry().imports, ["util"]);
+
+    let checked = crate::check_modules(&loader.module_file("main")).unwrap();
+    assert_eq!(checked.len(), 2);
+    assert!(matches!(
+        crate::check_modules(&loader.module_file("absent")),
+        Err(ModuleError::NotFound { .. })
+    ));
+
+    let loader = write_project(
+        "private",
+        &[
+            ("lib", "fn hidden() -> int32 { return 1; }\nstruct Secret { a: int32; }"),
+            ("other.lib", "public fn f() -> int32 { return 1; }"),
+            (
+                "main",
+                "use lib;\nuse other.lib;\nlet a = lib.hidden();\nlet b: lib.Secret;\nlet c = lib;",
+            ),
+        ],
+    );
+    let graph = ModuleGraph::load(&loader, "main").unwrap();
+    let diagnostics = &graph.entry().parsed.diagnostics;
+    for (expected, line) in [
+        ("the name 'lib' already refers to module 'lib'", 2),
+        ("Module 'lib' has no public item 'hidden'", 3),
+        ("Unknown type 'lib.Secret'", 4),
+        ("Module 'lib' cannot be used as a value", 5),
+    ] {
+        assert!(
+            diagnostics.iter().any(|d| d.stage == crate::Stage::Semantic
+                && d.line() == line
+                && d.message.contains(expected)),
+            "expected error containing {:?} on line {}, got {:?}",
+            expected,
+            line,
+            diagnostics
+        );
+    }
+
+    let (_, errors) = parse_program_with_errors("use std.io;").unwrap();
+    assert!(errors[0].contains("Module 'std.io' is not loaded"));
+}
+
+#[test]
+fn test_module_lex_errors() {
+    let loader = write_project(
+        "lex-errors",
+        &[
+            ("util", "public fn one() -> int32 { return 1; }\nlet bad = 2 # 3;"),
+            (
+                "main",
+                "use util;\nlet a = 1 @ 2;\nlet b: int32 = util.one();\nlet d: string = b;\nlet c = \"open;",
+            ),
+        ],
+    );
+
+    // Invalid tokens don't stop the graph from loading, and the `use` after
+    // them is still resolved
+    let graph = ModuleGraph::load(&loader, "main").unwrap();
+    assert!(!graph.is_ok());
+
+    let reported = |module: &str| -> Vec<(crate::Stage, usize, String)> {
+        graph
+            .get(module)
+            .unwrap()
+            .parsed
+            .diagnostics
+            .iter()
+            .map(|d| (d.stage, d.line(), d.message.clone()))
+            .collect()
+    };
+    assert_eq!(
+        reported("util"),
+        [(crate::Stage::Lex, 2, "Unexpected character '#'".to_string())]
+    );
+    assert_eq!(
+        reported("main"),
+        [
+            (crate::Stage::Lex, 2, "Unexpected character '@'".to_string()),
+            (crate::Stage::Semantic, 4, "Type mismatch: declared String, but got Int32".to_string()),
+            (crate::Stage::Lex, 5, "Unterminated string".to_string()),
+        ]
+    );
+}
+
+fn const_values(code: &str) -> Vec<(String, Literal)> {
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+    program_declarations(program)
+        .into_iter()
+        .filter_map(|node| match node {
+            ASTNode::ConstDecl(decl) => Some((decl.name, decl.value?)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_const_folding() {
+    let values = const_values(
+        r#"
+    const WIDTH: int32 = 80;
+    public const AREA: int64 = WIDTH * (WIDTH - 20) % 7;
+    const HALF: float64 = WIDTH / 2;
+    const RATIO: float64 = 1.5 * -WIDTH;
+    const WIDE: bool = WIDTH > 60 && !(AREA == 0);
+    const LABEL: string = WIDE ? "wide" : "narrow";
+    const TOP: uint64 = 18446744073709551615 - 1;
+    fn f() -> int32 {
+        const LOCAL: int32 = WIDTH + 1;
+        return LOCAL;
+    }
+"#,
+    );
+
+    let expected = [
+        ("WIDTH", Literal::Int(80, None)),
+        ("AREA", Literal::Int(80 * 60 % 7, None)),
+        ("HALF", Literal::Float(40.0, None)),
+        ("RATIO", Literal::Float(-120.0, None)),
+        ("WIDE", Literal::Boolean(true)),
+        ("LABEL", Literal::String("wide".to_string())),
+        ("TOP", Literal::Int(u64::MAX as i128 - 1, None)),
+    ];
+    let expected: Vec<_> = expected.into_iter().map(|(n, v)| (n.to_string(), v)).collect();
+    assert_eq!(values, expected);
+
+    let body = parse_function_body("const BASE: int32 = 2;\nfn f() { const SQUARE: int32 = BASE * BASE; }");
+    assert!(matches!(&body[0], Stmt::ConstDecl(decl) if decl.value == Some(Literal::Int(4, None))));
+
+    // Top-level constants may be used before their declaration
+    let values = const_values("fn f() -> int32 { return LIMIT; }\nconst LIMIT: int32 = BASE + 1;\nconst BASE: int32 = 2;");
+    assert_eq!(values, [("LIMIT".to_string(), Literal::Int(3, None)), ("BASE".to_string(), Literal::Int(2, None))]);
+}
+
+#[test]
+fn test_const_errors() {
+    let prelude = r#"
+    fn compute() -> int32 { return 1; }
+    let variable = 3;
+    struct P { x: int32; }
+"#;
+    let cases = [
+        ("const A: int32;", "Constant declarations require an initializer"),
+        ("const A = 1;", "Constant declarations require a type annotation"),
+        ("const A: int32 = compute();", "Initializer of constant 'A' is not a compile-time constant: call to 'compute'"),
+        ("const A: int32 = variable + 1;", "'variable' is a variable, not a constant"),
+        ("const A: int32 = P { x: 1 }.x;", "field access are not allowed in constant expressions"),
+        ("const A: int32 = 10 / (5 - 5);", "Cannot evaluate constant 'A': division by zero"),
+        ("const A: int64 = 9223372036854775807 + 1;", "Value 9223372036854775808 of constant 'A' is out of range for Int64"),
+        ("const A: string = 1;", "Type mismatch: declared String, but got Int32"),
+        ("const A: int8 = 100 + 100;", "Value 200 of constant 'A' is out of range for Int8"),
+        ("const A: uint8 = 256;", "Integer literal 256 is out of range for Uint8"),
+        ("const C: float32 = 3.0e38 * 10.0;", "out of range for Float32"),
+        ("const A: int32 = B;\nconst B: int32 = A;", "Constant 'A' depends on itself: A -> B -> A"),
+        ("const A: int32 = A + 1;", "Constant 'A' depends on itself: A -> A"),
+        ("const A: int32 = 1.5;", "Value 1.5 of constant 'A' is not an integer as required by Int32"),
+        ("const A: uint8 = 2 * 0.5;", "Value 1 of constant 'A' is not an integer as required by Uint8"),
+    ];
+
+    for (code, expected) in cases {
+        let source = format!("{}\n{}", prelude, code);
+        let (_, errors) = parse_program_with_errors(&source).unwrap();
+        assert!(
+            errors.iter().any(|e| e.contains(expected)),
+            "{}: expected error containing {:?}, got {:?}",
+            code,
+            expected,
+            errors
+        );
+    }
+}
+
+#[test]
+fn test_numeric_literal_types_and_ranges() {
+    let (_, errors) = parse_program_with_errors(
+        r#"
+    fn takes_byte(b: uint8) -> uint8 { return b; }
+    let mask: uint32 = 0xFFFF_FFFF;
+    let max: uint64 = 18446744073709551615;
+    let min: int8 = -128;
+    let low = -128i8;
+    let big = 3_000_000_000;
+    let small: float32 = 1.5e3;
+    let flags = takes_byte(0b1111_0000);
+"#,
+    )
+    .unwrap();
+    assert!(errors.is_empty(), "{:?}", errors);
+
+    let cases = [
+        ("let b: int8 = 300;", "Integer literal 300 is out of range for Int8"),
+        ("let b: int8 = -129;", "Integer literal -129 is out of range for Int8"),
+        ("let b: uint8 = -1;", "Integer literal -1 is out of range for Uint8"),
+        ("let b = 300u8;", "Integer literal 300 is out of range for Uint8"),
+        ("let b: int8 = 200u8;", "Integer literal 200 is out of range for Int8"),
+        ("let b: int32 = 0x8000_0000;", "Integer literal 2147483648 is out of range for Int32"),
+        ("let b: int64 = -9223372036854775809;", "Integer literal -9223372036854775809 is out of range for Int64"),
+        ("let f: float32 = 1e39;", "out of range for Float32"),
+        ("let f = 1e39f32;", "out of range for Float32"),
+        ("fn g(b: uint8) {}\nfn h() { g(256); }", "Integer literal 256 is out of range for Uint8"),
+    ];
+    for (code, expected) in cases {
+        let (_, errors) = parse_program_with_errors(code).unwrap();
+        assert!(
+            errors.iter().any(|e| e.contains(expected)),
+            "{}: expected error containing {:?}, got {:?}",
+            code,
+            expected,
+            errors
+        );
+    }
+
+    // Suffixes and magnitude decide the type of an unannotated literal
+    let literal_type = |text: &str| {
+        parse_single_var_decl_from_code(&format!("let x = {};", text)).unwrap().inferred_type.unwrap()
+    };
+    assert_eq!(literal_type("1"), Type::Int32);
+    assert_eq!(literal_type("3_000_000_000"), Type::Int64);
+    assert_eq!(literal_type("0xFFFF_FFFF_FFFF_FFFF"), Type::Uint64);
+    assert_eq!(literal_type("255u8"), Type::Uint8);
+    assert_eq!(literal_type("-128i8"), Type::Int8);
+    assert_eq!(literal_type("1f32"), Type::Float32);
+    assert_eq!(literal_type("2.5"), Type::Float64);
+}
+
+#[test]
+fn test_spawn_await_and_loop_modifiers() {
+    let body = parse_function_body(
+        r#"
+    fn fetch(id: int32) -> string { return "item"; }
+    async fn load(ids: [int32]) -> string {
+        let task: Task<string> = spawn fetch(1);
+        let first = await task;
+        par for id in ids {
+            let local = fetch(id);
+        }
+        async for id in ids {
+            let item = await spawn fetch(id);
+        }
+        sync while first == "" {
+            mut retry = 1;
+            retry = 2;
+        }
+        return first;
+    }
+"#,
+    );
+
+    match &body[0] {
+        Stmt::VarDecl(decl) => {
+            assert_eq!(decl.declared_type, Some(Type::Task(Box::new(Type::String))));
+            assert!(matches!(
+                decl.initializer.as_ref(),
+                Some(Expr { kind: ExprKind::Spawn(call), .. }) if matches!(call.kind, ExprKind::Call { .. })
+            ));
+        }
+        other => panic!("Expected VarDecl, got {:?}", other),
+    }
+    assert!(matches!(&body[1], Stmt::VarDecl(decl) if matches!(decl.initializer, Some(Expr { kind: ExprKind::Await(_), .. }))));
+    assert!(matches!(&body[2], Stmt::For { modifier: Some(LoopModifier::Par), .. }));
+    assert!(matches!(&body[3], Stmt::For { modifier: Some(LoopModifier::Async), .. }));
+    assert!(matches!(&body[4], Stmt::While { modifier: Some(LoopModifier::Sync), .. }));
+}
+
+#[test]
+fn test_concurrency_errors() {
+    let prelude = r#"
+    fn work() -> int32 { return 1; }
+"#;
+    let cases = [
+        ("fn f() { let t = spawn 42; }", "'spawn' must be followed by a function call"),
+        ("fn f() { let v = await spawn work(); }", "'await' is only allowed inside an async function"),
+        ("async fn f() { let v = await work(); }", "Cannot await value of type Int32; expected a Task"),
+        ("sync fn f() { let v = await spawn work(); }", "'await' is only allowed inside an async function"),
+        ("fn f() { let t: Task<int32> = spawn work(); let n: int32 = t; }", "Type mismatch"),
+    ];
+
+    for (code, expected) in cases {
+        let source = format!("{}\n{}", prelude, code);
+        let (_, errors) = parse_program_with_errors(&source).unwrap();
+        assert!(
+            errors.iter().any(|e| e.contains(expected)),
+            "{}: expected error containing {:?}, got {:?}",
+            code,
+            expected,
+            errors
+        );
+    }
+}
+
+#[test]
+fn test_generic_declarations_and_inference() {
+    let code = r#"
+    struct Box<T> {
+        value: T;
+        fn get() -> T { return self.value; }
+    }
+    enum Option<T> { Some(T), None }
+    fn map<T, U>(xs: [T], f: fn(T) -> U) -> [U] {
+        let first: U = f(xs[0]);
+        return [first];
+    }
+    fn double(x: int32) -> int64 { return x * 2; }
+    fn unwrap_or<T>(opt: Option<T>, fallback: T) -> T {
+        return match opt { Option.Some(v) => v, Option.None => fallback };
+    }
+    fn empty<T>() -> [T] { return []; }
+    let b = Box { value: "hi" };
+    let s: string = b.get();
+    let v: string = b.value;
+    let doubled = map([1, 2], double);
+    let none: Option<int32> = Option.None;
+    let n = unwrap_or(Option.Some(3), 0);
+    let wide: Box<int64> = Box { value: 1 };
+    let nothing: [bool] = empty();
+"#;
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+    let declarations = program_declarations(program);
+
+    assert!(matches!(&declarations[0], ASTNode::StructDecl(decl) if decl.type_params == ["T"]));
+    assert!(matches!(&declarations[1], ASTNode::EnumDecl(decl) if decl.type_params == ["T"]));
+    assert!(matches!(&declarations[2], ASTNode::FnDecl(decl) if decl.type_params == ["T", "U"]));
+
+    let generic = |name: &str, args: Vec<Type>| Type::Generic { name: name.to_string(), args };
+    let expected = [
+        ("b", generic("Box", vec![Type::String])),
+        ("doubled", Type::Array(Box::new(Type::Int64))),
+        ("none", generic("Option", vec![Type::Int32])),
+        ("n", Type::Int32),
+        ("wide", generic("Box", vec![Type::Int64])),
+        ("nothing", Type::Array(Box::new(Type::Bool))),
+    ];
+    for (name, ty) in expected {
+        let decl = declarations.iter().find_map(|node| match node {
+            ASTNode::VarDecl(decl) if decl.name == name => Some(decl),
+            _ => None,
+        });
+        assert_eq!(decl.and_then(|d| d.inferred_type.clone()), Some(ty), "type of '{}'", name);
+    }
+}
+
+#[test]
+fn test_generic_errors() {
+    let prelude = r#"
+    struct Box<T> { value: T; }
+    enum Option<T> { Some(T), None }
+    fn pair<T>(a: T, b: T) -> T { return a; }
+    fn make<T>() -> [T] { return []; }
+"#;
+    let cases = [
+        ("let b: Box = Box { value: 1 };", "Type 'Box' expects 1 type arguments, got 0"),
+        ("let b: Box<int32, string> = Box { value: 1 };", "Type 'Box' expects 1 type arguments, got 2"),
+        ("let none = Option.None;", "Cannot infer type parameter 'T' of 'Option'; add a type annotation"),
+        ("let xs = make();", "Cannot infer type parameter 'T' of 'make'"),
+        ("let b: Box<string> = Box { value: 1 };", "Field 'value' expects type String, got Int32"),
+        ("let p = pair(1, \"s\");", "Argument 2 of 'pair' expects type Int32, got String"),
+        ("fn f<T, T>(x: T) -> T { return x; }", "Type parameter 'T' declared more than once"),
+        ("fn f<T>(x: T) -> int32 { return x; }", "Return type mismatch: expected Int32, got Param(\"T\")"),
+        ("let n: int32 = Box { value: true }.value;", "Type mismatch: declared Int32, but got Bool"),
+    ];
+
+    for (code, expected) in cases {
+        let source = format!("{}\n{}", prelude, code);
+        let (_, errors) = parse_program_with_errors(&source).unwrap();
+        assert!(
+            errors.iter().any(|e| e.contains(expected)),
+            "{}: expected error containing {:?}, got {:?}",
+            code,
+            expected,
+            errors
+        );
+    }
+}
+
+#[test]
+fn test_lambdas_and_captures() {
+    let code = r#"
+    fn map<T, U>(xs: [T], f: fn(T) -> U) -> [U] {
+        let first: U = f(xs[0]);
+        return [first];
+    }
+    fn run() -> [string] {
+        let scale = 3;
+        mut total = 0;
+        let inc: fn(int32) -> int32 = fn(x) => x + scale;
+        let add = fn(a: int32, b: int32) -> int64 => a + b;
+        let record = fn(x: int32) {
+            total = total + x * scale;
+        };
+        let squares = map([1, 2], fn(x) => x * x);
+        return map(squares, fn(n) => n > 2 ? "big" : "small");
+    }
+"#;
+    let body = parse_function_body(code);
+
+    let lambda = |stmt: &Stmt| match stmt {
+        Stmt::VarDecl(decl) => match &decl.initializer {
+            Some(Expr { kind: ExprKind::Lambda { params, captures, .. }, .. }) => (params.clone(), captures.clone(), decl.inferred_type.clone()),
+            other => panic!("Expected lambda, got {:?}", other),
+        },
+        other => panic!("Expected VarDecl, got {:?}", other),
+    };
+    let function = |params: Vec<Type>, ret: Option<Type>| Some(Type::Function { params, return_type: ret.map(Box::new) });
+
+    let (params, captures, ty) = lambda(&body[2]);
+    assert_eq!(params, vec![LambdaParam { name: "x".to_string(), param_type: None }]);
+    assert_eq!(captures, vec![Capture { name: "scale".to_string(), mutable: false, mutated: false }]);
+    assert_eq!(ty, function(vec![Type::Int32], Some(Type::Int32)));
+
+    let (_, captures, ty) = lambda(&body[3]);
+    assert!(captures.is_empty());
+    assert_eq!(ty, function(vec![Type::Int32, Type::Int32], Some(Type::Int64)));
+
+    let (_, captures, ty) = lambda(&body[4]);
+    assert_eq!(
+        captures,
+        vec![
+            Capture { name: "total".to_string(), mutable: true, mutated: true },
+            Capture { name: "scale".to_string(), mutable: false, mutated: false },
+        ]
+    );
+    assert_eq!(ty, function(vec![Type::Int32], None));
+
+    match &body[5] {
+        Stmt::VarDecl(decl) => assert_eq!(decl.inferred_type, Some(Type::Array(Box::new(Type::Int32)))),
+        other => panic!("Expected VarDecl, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_lambda_errors() {
+    let prelude = r#"
+    fn apply(f: fn(int32) -> int32) -> int32 { return f(1); }
+"#;
+    let cases = [
+        ("let f = fn(x) => x;", "Cannot infer the type of lambda parameter 'x'; add a type annotation"),
+        ("let f = fn(x) { return; };", "Parameter 'x' of a lambda with a block body needs a type annotation"),
+        ("let n = apply(fn(a, b) => a);", "Lambda takes 2 parameters, but 1 were expected"),
+        ("let n = apply(fn(x) => x > 0);", "Argument 1 of 'apply' expects type"),
+        ("let f = fn(x: int32) -> string => x;", "Lambda body has type Int32, but its return type is String"),
+        ("let f = fn(x: int32) -> int32 { let y = x; };", "Lambda must return a value of type Int32 on every path"),
+        ("fn g() { let n = 1; let f = fn() { n = 2; }; }", "Cannot assign to immutable variable 'n'"),
+        ("async fn g() { let f = fn() => await spawn apply(fn(x) => x); }", "'await' is only allowed inside an async function"),
+    ];
+
+    for (code, expected) in cases {
+        let source = format!("{}\n{}", prelude, code);
+        let (_, errors) = parse_program_with_errors(&source).unwrap();
+        assert!(
+            errors.iter().any(|e| e.contains(expected)),
+            "{}: expected error containing {:?}, got {:?}",
+            code,
+            expected,
+            errors
+        );
+    }
+}
+
+fn tokens_of(code: &str) -> Vec<crate::lexer::token::Token> {
+    let chars: Vec<char> = code.chars().collect();
+    Lexer::new(&chars).tokenize().unwrap()
+}
+
+#[test]
+fn test_lalr_grammar_is_conflict_free() {
+    let table = lalr::LALRParser::table();
+    assert!(table.states.len() > 100);
+    assert!(table.states[0].transitions.contains_key(&grammar::Symbol::NonTerminal(grammar::NonTerminal::Program)));
+}
+
+#[test]
+fn test_lalr_conflict_reports() {
+    use grammar::{ExprMode, NonTerminal as N, Production, Reduction as R, Symbol, SymbolToken as T};
+    use lalr::{ConflictKind, LALRTable};
+
+    let expr = N::Expr(ExprMode::Full);
+    let ambiguous = vec![
+        Production::new(N::Start, vec![Symbol::from(expr)], R::Pass(0)),
+        Production::new(expr, vec![expr.into(), T::Plus.into(), expr.into()], R::Binary),
+        Production::new(expr, vec![T::Identifier.into()], R::Identifier),
+    ];
+    let conflicts = LALRTable::build(ambiguous).unwrap_err();
+    assert_eq!(conflicts.len(), 1);
+    let conflict = &conflicts[0];
+    assert_eq!((conflict.kind, conflict.lookahead), (ConflictKind::ShiftReduce, T::Plus));
+    assert_eq!(conflict.path, vec![Symbol::from(expr), T::Plus.into(), expr.into()]);
+    assert_eq!(
+        conflict.items,
+        vec![
+            "Expr(Full) → Expr(Full) • '+' Expr(Full)   [shift]",
+            "Expr(Full) → Expr(Full) '+' Expr(Full) •   [reduce on '+']",
+        ]
+    );
+    assert!(conflict.to_string().starts_with("shift/reduce conflict in state"));
+
+    let overlapping = vec![
+        Production::new(N::Start, vec![N::Program.into()], R::Pass(0)),
+        Production::new(N::Program, vec![N::Type.into()], R::Pass(0)),
+        Production::new(N::Program, vec![N::Block.into()], R::Pass(0)),
+        Production::new(N::Type, vec![T::Identifier.into()], R::TypeName),
+        Production::new(N::Block, vec![T::Identifier.into()], R::Identifier),
+    ];
+    let conflicts = LALRTable::build(overlapping).unwrap_err();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!((conflicts[0].kind, conflicts[0].lookahead), (ConflictKind::ReduceReduce, T::Eof));
+    assert_eq!(conflicts[0].path, vec![Symbol::from(T::Identifier)]);
+}
+
+#[test]
+fn test_lalr_matches_recursive_descent() {
+    let program = r#"
+    public const LIMIT: int32 = 2 * (3 + 4);
+    type Id = int64;
+    let names: [string?] = ["a", "b",];
+    mut total = -LIMIT + 1;
+
+    public struct Pair<A, B> {
+        first: A;
+        private second: B;
+        fn swap() -> Pair<B, A> { return Pair { first: self.second, second: self.first }; }
+    }
+
+    enum Shape<T> { Circle(T), Rect(T, T), Empty }
+
+    fn area(s: Shape<float64>) -> float64 {
+        return match s {
+            Shape.Circle(r) => r * r * 3.14,
+            Shape.Rect(w, h) => w * h,
+            _ => 0.0,
+        };
+    }
+
+    fn apply(f: fn(int32) -> int32, x: int32,) -> int32 { return f(x); }
+
+    async fn work(n: int32) -> int32 { return n; }
+
+    async fn run(flags: [bool]) -> int32 {
+        let t: Task<int32> = spawn work(1);
+        let v = await t;
+        let shape = Shape.Rect(1.0, 2.0);
+        let dot: Shape<float64> = Shape.Empty;
+        let p = Pair { first: 1, second: "x" };
+        let o = { size: 1, "quoted key": 2 };
+        let double: fn(int32) -> int32 = fn(x) => x * 2;
+        let block = fn(x: int32) -> int32 { return x + v; };
+        let n = apply(fn(x) => x + 1, 2) + apply(double, 3);
+        let pick = v > 0 && !flags[0] || v == 2 ? n : -n;
+        mut i = 0;
+        if i < 10 { i = i + 1; } else if i > 20 { return 0; } else { i = 0; }
+        par for f in flags { if f { total = total + 1; } }
+        sync while i >= 0 { i = i - 1; }
+        { let inner = (i + 1) % 3; }
+        let m = match i { 0 => "zero", -1 => "neg", _ => "many" };
+        block(p.first);
+        return pick + o.size;
+    }
+"#;
+    let (_, errors) = parse_program_with_errors(program).unwrap();
+    assert!(errors.is_empty(), "the program must be valid to be compared: {:?}", errors);
+    if let Err(disagreement) = lalr::compare_parsers(&tokens_of(program)) {
+        panic!("{}", disagreement);
+    }
+
+    // Both parsers must reject these
+    let rejected = [
+        "let x = 1",
+        "let x = a < b < c;",
+        "let f = 1 + fn(x) => x;",
+        "fn f() { if p { } x = 1 }",
+        "enum E { A(), }",
+        "fn f() { let p = Point { x: 1 } }",
+        "let t: Pair<int32,> = 1;",
+    ];
+    for code in rejected {
+        let tokens = tokens_of(code);
+        assert!(
+            lalr::LALRParser::new(tokens.clone()).parse_program().is_err(),
+            "{} should not parse",
+            code
+        );
+        if let Err(disagreement) = lalr::compare_parsers(&tokens) {
+            panic!("{}: {}", code, disagreement);
+        }
+    }
+}
+
+#[test]
+fn test_recovery_after_block_lambda_errors() {
+    let code = r#"
+let f = fn(x) { return 1; } == fn(y) { return 2; };
+let g = fn() -> int32 { } + 1;
+let h: int32 = match fn() -> int32 { } { _ => 1 };
+let ok: int32 = 1;
+"#;
+    let (program, errors) = parse_program_with_parse_errors(code).unwrap();
+    assert!(errors.iter().all(|e| e.found.is_none()), "only semantic errors expected: {:?}", errors);
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    assert!(errors[0].message.contains("Parameter 'x' of a lambda with a block body needs a type annotation"));
+    assert!(errors[1].message.contains("Lambda must return a value"));
+    assert!(errors[2].message.contains("Lambda must return a value"));
+
+    let declarations = program_declarations(program);
+    assert_eq!(declarations.len(), 4);
+    assert!(declarations[..3].iter().all(|d| matches!(d, ASTNode::VarDecl(decl) if decl.inferred_type.is_none())));
+    assert!(matches!(&declarations[3], ASTNode::VarDecl(decl) if decl.name == "ok"));
+}
+
+#[test]
+fn test_string_interpolation_ends_at_closing_quote() {
+    let declarations = program_declarations(parse_program_from_code(r#"let s = "a\${b}"; let t = 1;"#).unwrap());
+    assert_eq!(declarations.len(), 2);
+    match &declarations[0] {
+        ASTNode::VarDecl(decl) => {
+            assert_eq!(decl.initializer.as_ref().map(|expr| &expr.kind), Some(&ExprKind::Literal(Literal::String("a${b}".to_string()))));
+        }
+        other => panic!("Expected VarDecl, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_ebnf_reader() {
+    use fuzz::Expr as E;
+
+    let grammar = fuzz::Grammar::parse(
+        r#"
+        (* a comment *)
+        S = "a" { B } [ "c" | D ] ;
+          // a note
+        B = ( "x" | "y" ) "z"{2} ;
+        D = "0"…"9" | ? anything ? ;
+        "#,
+    )
+    .unwrap();
+    assert_eq!(grammar.start().name, "S");
+    assert_eq!(
+        grammar.rule("S").unwrap().body,
+        E::Sequence(vec![
+            E::Terminal("a".to_string()),
+            E::Repeat(Box::new(E::NonTerminal("B".to_string()))),
+            E::Optional(Box::new(E::Choice(vec![E::Terminal("c".to_string()), E::NonTerminal("D".to_string())]))),
+        ])
+    );
+    assert_eq!(
+        grammar.rule("B").unwrap().body,
+        E::Sequence(vec![
+            E::Choice(vec![E::Terminal("x".to_string()), E::Terminal("y".to_string())]),
+            E::Times(Box::new(E::Terminal("z".to_string())), 2),
+        ])
+    );
+    assert_eq!(
+        grammar.rule("D").unwrap().body,
+        E::Choice(vec![E::Range('0', '9'), E::Special("anything".to_string())])
+    );
+    assert_eq!(grammar.terminals(), vec!["a", "c", "x", "y", "z"]);
+
+    let errors = [
+        ("S = T ;", "Rule 'S' refers to undefined rule 'T'", 1),
+        ("S = \"a\" ;\nS = \"b\" ;", "Rule 'S' is defined twice", 2),
+        ("S = \"a\" ;\n(* open", "Unterminated comment", 2),
+        ("S = ( \"a\" ;", "Expected ')'", 1),
+        ("S = \"b\"…\"a\" ;", "A range needs two single characters in order", 1),
+    ];
+    for (source, message, line) in errors {
+        let err = fuzz::Grammar::parse(source).unwrap_err();
+        assert_eq!((err.message.as_str(), err.line), (message, line), "{}", source);
+    }
+}
+
+#[test]
+fn test_generator_is_deterministic() {
+    let grammar = fuzz::Grammar::kai().unwrap();
+    let generator = fuzz::Generator::new(&grammar).unwrap();
+    assert_eq!(grammar.start().name, "Program");
+    assert_eq!(generator.program(42), generator.program(42));
+    assert_eq!(generator.mutate(&generator.program(3), 9), generator.mutate(&generator.program(3), 9));
+
+    let programs: std::collections::HashSet<String> = (0..20).map(|seed| generator.program(seed).source()).collect();
+    assert!(programs.len() > 15);
+
+    // Identifiers are single tokens that never spell a keyword
+    for seed in 0..200 {
+        let sample = generator.sentence("Identifier", seed);
+        assert_eq!(sample.tokens.len(), 1);
+        assert!(matches!(tokens_of(&sample.source())[0].token_type, TokenType::Identifier(_)), "{:?}", sample);
+    }
+
+    // Interpolated strings are single tokens whose expressions parse
+    for seed in 0..100 {
+        let source = generator.sentence("InterpolatedString", seed).source();
+        let tokens = tokens_of(&source);
+        assert!(matches!(tokens[0].token_type, TokenType::InterpolatedString(_)), "{}", source);
+        assert_eq!(tokens.len(), 2, "{}", source);
+    }
+
+    // The token budget bounds program size
+    let small = fuzz::Generator::new(&grammar).unwrap().with_max_tokens(20);
+    assert!((0..50).all(|seed| small.program(seed).tokens.len() < 200));
+}
+
+#[test]
+fn test_rune_and_byte_literals() {
+    let code = r#"
+const NEWLINE: rune = '\n';
+let smile = '\u{1F600}';
+let first: byte = b'A';
+let request = b"GET /\r\n";
+let label = "rune ${'x'}, byte ${b'y'}";
+fn kind(c: rune) -> string {
+    return match c { 'a' => "a", '\'' => "quote", _ => "other" };
+}
+fn is_space(b: byte) -> bool {
+    return match b { b' ' => true, _ => false };
+}
+"#;
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "{:?}", errors);
+    let declarations = program_declarations(program);
+
+    let ASTNode::ConstDecl(newline) = &declarations[0] else { panic!("Expected const") };
+    assert_eq!(newline.value, Some(Literal::Rune('\n')));
+    let inferred: Vec<Option<Type>> = declarations[1..4]
+        .iter()
+        .map(|decl| match decl {
+            ASTNode::VarDecl(decl) => decl.inferred_type.clone(),
+            other => panic!("Expected variable, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(inferred, vec![Some(Type::Rune), Some(Type::Byte), Some(Type::Array(Box::new(Type::Byte)))]);
+    let ASTNode::VarDecl(request) = &declarations[3] else { panic!("Expected variable") };
+    assert_eq!(
+        request.initializer.as_ref().map(|e| &e.kind),
+        Some(&ExprKind::Literal(Literal::ByteString(b"GET /\r\n".to_vec())))
+    );
+
+    // Both parsers agree, patterns included
+    assert!(lalr::compare_parsers(&tokens_of(code)).is_ok());
+
+    let cases = [
+        ("let r: rune = b'a';", "Type mismatch"),
+        ("let b: byte = 'a';", "Type mismatch"),
+        ("let s: string = 'a';", "Type mismatch"),
+        ("fn f(c: rune) -> int32 { return match c { b'a' => 1, _ => 0 }; }", "Pattern of type Byte cannot match value of type Rune"),
+    ];
+    for (code, expected) in cases {
+        let (_, errors) = parse_program_with_errors(code).unwrap();
+        assert!(
+            errors.iter().any(|e| e.contains(expected)),
+            "{}: expected error containing {:?}, got {:?}",
+            code,
+            expected,
+            errors
+        );
+    }
+}
+
+#[test]
+fn test_raw_and_multiline_strings() {
+    let code = r####"
+const PATTERN: string = r"\d+\.\d+";
+const TEMPLATE: string = r#"<a href="${url}">"#;
+fn page(title: string, count: int32) -> string {
+    return """
+        <h1>${title}</h1>
+          <p>${count + 1} items</p>
+        """;
+}
+"####;
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "{:?}", errors);
+    let declarations = program_declarations(program);
+
+    let ASTNode::ConstDecl(pattern) = &declarations[0] else { panic!("Expected const") };
+    assert_eq!(pattern.value, Some(Literal::String(r"\d+\.\d+".to_string())));
+    let ASTNode::ConstDecl(template) = &declarations[1] else { panic!("Expected const") };
+    assert_eq!(template.value, Some(Literal::String(r#"<a href="${url}">"#.to_string())));
+
+    let ASTNode::FnDecl(page) = &declarations[2] else { panic!("Expected function") };
+    let Stmt::Return { value: Some(Expr { kind: ExprKind::Interpolated { parts }, .. }), .. } = &page.body.statements[0] else {
+        panic!("Expected interpolated return value")
+    };
+    assert_eq!(parts[0], InterpolationPart::Literal("<h1>".to_string()));
+    assert_eq!(parts[2], InterpolationPart::Literal("</h1>\n  <p>".to_string()));
+    assert_eq!(parts[4], InterpolationPart::Literal(" items</p>".to_string()));
+    let InterpolationPart::Expr(count) = &parts[3] else { panic!("Expected expression") };
+    assert_eq!(&code[count.span.start..count.span.end], "count + 1");
+    assert_eq!((count.span.line, count.span.column), (7, 16));
+
+    assert!(lalr::compare_parsers(&tokens_of(code)).is_ok());
+}
+
+#[test]
+fn test_interpolated_strings() {
+    let code = r#"
+struct User { name: string; age: int32; }
+const VERSION: int32 = 2;
+const BANNER: string = "v${VERSION}.${VERSION * 5}";
+fn greet(user: User, count: int32?) -> string {
+    return "Hello ${user.name} (${user.age + 1}), ${count} new, ${"nested ${user.name}"}";
+}
+"#;
+    let (program, errors) = parse_program_with_errors(code).unwrap();
+    assert!(errors.is_empty(), "{:?}", errors);
+    let declarations = program_declarations(program);
+
+    let ASTNode::ConstDecl(banner) = &declarations[2] else { panic!("Expected const") };
+    assert_eq!(banner.value, Some(Literal::String("v2.10".to_string())));
+
+    let ASTNode::FnDecl(greet) = &declarations[3] else { panic!("Expected function") };
+    let Stmt::Return { value: Some(Expr { kind: ExprKind::Interpolated { parts }, .. }), .. } = &greet.body.statements[0] else {
+        panic!("Expected interpolated return value")
+    };
+    assert_eq!(parts.len(), 8);
+    assert_eq!(parts[0], InterpolationPart::Literal("Hello ".to_string()));
+    let InterpolationPart::Expr(age) = &parts[3] else { panic!("Expected expression") };
+    assert!(matches!(age.kind, ExprKind::Binary { op: BinaryOp::Add, .. }));
+    assert_eq!(&code[age.span.start..age.span.end], "user.age + 1");
+
+    // Both parsers build the same tree, embedded expressions included
+    assert!(lalr::compare_parsers(&tokens_of(code)).is_ok());
+
+    let errors = parse_program_with_parse_errors(
+        "fn f() {}\nlet a = \"${f}\";\nlet b = \"${[1]}\";\nlet c = \"${missing}\";\nlet d = \"${1 +}\";",
+    )
+    .unwrap()
+    .1;
+    let messages: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(errors.len(), 4, "{:?}", messages);
+    assert!(messages[0].contains("Cannot interpolate a value of type Function"), "{:?}", messages);
+    assert_eq!((errors[0].line, errors[0].column), (2, 12));
+    assert!(messages[1].contains("Cannot interpolate a value of type Array"));
+    assert!(messages[2].contains("'missing'"));
+    // Syntax errors inside `${...}` point into the string
+    assert!(messages[3].contains("Expected expression"));
+    assert_eq!((errors[3].line, errors[3].column), (5, 15));
+}
+
+#[test]
+fn test_token_offsets() {
+    let code = "let café = \"é\";";
+    let lexed = crate::lex(&crate::SourceFile::new("offsets.kai", code));
+    let extents: Vec<(usize, usize)> = lexed.tokens.iter().map(|t| (t.start, t.end)).collect();
+    assert_eq!(extents, [(0, 3), (4, 9), (10, 11), (12, 16), (16, 17), (17, 17)]);
+
+    // Columns count characters, offsets count bytes
+    let string = &lexed.tokens[3];
+    assert_eq!((string.column, string.end_column), (12, 15));
+    assert_eq!(&code[string.start..string.end], "\"é\"");
+}
+
+#[test]
+fn test_expression_spans() {
+    let code = "fn add(a: int32, b: int32) -> int32 { return a + b; }\nlet total = add(1, \"two\");\nlet flag: int32 = 1 + -true;\n";
+    let checked = crate::check(&crate::SourceFile::new("spans.kai", code));
+    let texts: Vec<&str> = checked.diagnostics.iter().map(|d| &code[d.span.start..d.span.end]).collect();
+    assert_eq!(texts, ["\"two\"", "-true"], "{:?}", checked.diagnostics);
+    assert_eq!((checked.diagnostics[1].line(), checked.diagnostics[1].column()), (3, 23));
+
+    // A rejected declaration stays in place, reported at its initializer
+    let code = "fn f() { let x: int32 = \"s\"; }";
+    let checked = crate::check(&crate::SourceFile::new("keep.kai", code));
+    let ASTNode::FnDecl(decl) = &checked.program.declarations[0] else { panic!("Expected function") };
+    let Stmt::VarDecl(local) = &decl.body.statements[0] else { panic!("Expected variable declaration") };
+    assert_eq!(&code[local.span.start..local.span.end], "let x: int32 = \"s\";");
+    assert_eq!(local.inferred_type, None);
+    assert_eq!(&code[checked.diagnostics[0].span.start..checked.diagnostics[0].span.end], "\"s\"");
+
+    // Pattern errors point at the pattern, nested ones at the field
+    let code = "enum Shape { Circle(float64), Empty }\nfn f(s: Shape, c: rune) -> int32 {\n    let a = match c { b'a' => 1, _ => 0 };\n    let b = match s { Shape.Circle(r, x) => 1, _ => 0 };\n    return match s { Shape.Circle(\"r\") => 1, _ => 0 };\n}";
+    let checked = crate::check(&crate::SourceFile::new("patterns.kai", code));
+    let texts: Vec<&str> = checked.diagnostics.iter().map(|d| &code[d.span.start..d.span.end]).collect();
+    assert_eq!(texts, ["b'a'", "Shape.Circle(r, x)", "\"r\""], "{:?}", checked.diagnostics);
+
+    // Both parsers report a misused `spawn` over the whole expression
+    let code = "fn f() { let t = spawn 42; }";
+    let parsed = crate::parse(&crate::SourceFile::new("spawn.kai", code));
+    let span = parsed.diagnostics[0].span;
+    assert_eq!(&code[span.start..span.end], "spawn 42");
+    let lalr = lalr::LALRParser::new(tokens_of(code)).parse_program().unwrap_err();
+    assert_eq!(lalr.span, span);
+}
+
+#[test]
+fn test_node_ids() {
+    use crate::parser::visit::{visitor, Visitor};
+
+    #[derive(Default)]
+    struct Ids(Vec<NodeId>);
+    impl Visitor for Ids {
+        fn visit_stmt(&mut self, stmt: &Stmt) {
+            self.0.push(stmt.id());
+            visitor::walk_stmt(self, stmt);
+        }
+        fn visit_expr(&mut self, expr: &Expr) {
+            self.0.push(expr.id);
+            visitor::walk_expr(self, expr);
+        }
+        fn visit_match_arm(&mut self, arm: &MatchArm) {
+            self.0.push(arm.id);
+            visitor::walk_match_arm(self, arm);
+        }
+        fn visit_pattern(&mut self, pattern: &Pattern) {
+            self.0.push(pattern.id);
+            visitor::walk_pattern(self, pattern);
         }
     }
 
+    let code = "fn f(a: int32) -> int32 {\n    let b = a * 2;\n    if b > 1 { return b; } else if a < 0 { return -a; }\n    let c = match b { 0 => a, -1 => g(a, [b][0]), n => n };\n    return g(a, c);\n}\nfn g(x: int32, y: int32) -> int32 { return x + y; }\n";
+    let source = crate::SourceFile::new("ids.kai", code);
+    let parsed = crate::parse(&source);
+    assert!(!parsed.has_errors(), "{:?}", parsed.diagnostics);
+
+    // Preorder numbering: visiting order is increasing and no node is left unnumbered
+    let mut ids = Ids::default();
+    ids.visit_program(&parsed.program);
+    assert!(ids.0.len() > 20);
+    assert!(ids.0.windows(2).all(|w| w[0] < w[1]), "{:?}", ids.0);
+    assert!(!ids.0.contains(&NodeId::DUMMY));
+
+    // Numbering is deterministic, and the table-driven parser agrees
+    assert_eq!(crate::parse(&source).program, parsed.program);
+    let lalr = lalr::LALRParser::new(tokens_of(code)).parse_program().unwrap();
+    assert_eq!(lalr, parsed.program);
+}
+
+proptest::proptest! {
+    #![proptest_config(proptest::prelude::ProptestConfig::with_cases(48))]
+
     #[test]
-    fn test_type_mismatch_error() {
-        let result = parse_single_var_decl_from_code("let x: string = 42;");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().message.contains("Type mismatch"));
+    fn test_generated_programs_parse(seed: u64) {
+        let grammar = fuzz::Grammar::kai().unwrap();
+        let source = fuzz::Generator::new(&grammar).unwrap().program(seed).source();
+        let result = fuzz::check_valid(&source);
+        proptest::prop_assert!(result.is_ok(), "{}\n{}", result.unwrap_err(), source);
     }
 
     #[test]
-    fn test_no_type_no_init_error() {
-        let result = parse_single_var_decl_from_code("let x;");
-        assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .message
-            .contains("must have either a type annotation or an initializer"));
+    fn test_mutated_programs_dont_panic(seed: u64) {
+        let grammar = fuzz::Grammar::kai().unwrap();
+        let generator = fuzz::Generator::new(&grammar).unwrap();
+        let (source, mutation) = generator.mutate(&generator.program(seed), seed.rotate_left(32));
+        let result = fuzz::check_no_panic(&source);
+        proptest::prop_assert!(result.is_ok(), "{:?}: {}\n{}", mutation, result.unwrap_err(), source);
     }
 }
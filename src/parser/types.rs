@@ -1,4 +1,6 @@
+use crate::lexer::token::NumberSuffix;
 use crate::parser::expr::Literal;
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use std::mem::discriminant;
 
@@ -6,6 +8,9 @@ use std::mem::discriminant;
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     Unknown,
+    /// The type of a name whose declaration failed to check. It is compatible
+    /// with every type, so the failure isn't reported again at each use.
+    Error,
     Int8, Int16, Int32, Int64,
     Uint8, Uint16, Uint32, Uint64,
     Float32, Float64,
@@ -13,6 +18,21 @@ pub enum Type {
     Custom(String),
     Optional(Box<Type>),
     Array(Box<Type>),
+    Function {
+        params: Vec<Type>,
+        return_type: Option<Box<Type>>,
+    },
+    /// Anonymous record type of an object literal, fields in source order.
+    Object(Vec<(String, Type)>),
+    /// Handle of a spawned call producing `T`, written `Task<T>`.
+    Task(Box<Type>),
+    /// A type parameter of the enclosing generic declaration, e.g. `T`.
+    Param(String),
+    /// A generic struct or enum applied to type arguments, e.g. `Pair<int32, T>`.
+    Generic {
+        name: String,
+        args: Vec<Type>,
+    },
 }
 
 impl Type {
@@ -36,11 +56,25 @@ impl Type {
         }
     }
 
+    /// The type a suffix such as `u8` stands for.
+    pub fn from_suffix(suffix: NumberSuffix) -> Type {
+        Type::from_type_name(suffix.type_name()).expect("number suffixes name primitive types")
+    }
+
+    /// A literal's own type: its suffix's, or else `int32` for integers that
+    /// fit, widening to `int64` and `uint64`, and `float64` for floats.
     pub fn infer_from_literal(literal: &Literal) -> Type {
         match literal {
-            Literal::Int(_) => Type::Int32,
-            Literal::Float(_) => Type::Float64,
+            Literal::Int(_, Some(ty)) | Literal::Float(_, Some(ty)) => ty.clone(),
+            Literal::Int(n, None) => [Type::Int32, Type::Int64]
+                .into_iter()
+                .find(|ty| ty.integer_range().is_some_and(|(min, max)| (min..=max).contains(n)))
+                .unwrap_or(Type::Uint64),
+            Literal::Float(_, None) => Type::Float64,
             Literal::String(_) => Type::String,
+            Literal::Rune(_) => Type::Rune,
+            Literal::Byte(_) => Type::Byte,
+            Literal::ByteString(_) => Type::Array(Box::new(Type::Byte)),
             Literal::Boolean(_) => Type::Bool,
         }
     }
@@ -58,6 +92,30 @@ impl Type {
         }
     }
 
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            Type::Int8 | Type::Int16 | Type::Int32 | Type::Int64 |
+            Type::Uint8 | Type::Uint16 | Type::Uint32 | Type::Uint64
+        )
+    }
+
+    /// The smallest and largest values of an integer type.
+    pub fn integer_range(&self) -> Option<(i128, i128)> {
+        let range = match self {
+            Type::Int8 => (i8::MIN as i128, i8::MAX as i128),
+            Type::Int16 => (i16::MIN as i128, i16::MAX as i128),
+            Type::Int32 => (i32::MIN as i128, i32::MAX as i128),
+            Type::Int64 => (i64::MIN as i128, i64::MAX as i128),
+            Type::Uint8 => (0, u8::MAX as i128),
+            Type::Uint16 => (0, u16::MAX as i128),
+            Type::Uint32 => (0, u32::MAX as i128),
+            Type::Uint64 => (0, u64::MAX as i128),
+            _ => return None,
+        };
+        Some(range)
+    }
+
     pub fn is_numeric(&self) -> bool {
         matches!(
             self,
@@ -66,6 +124,61 @@ impl Type {
             Type::Float32 | Type::Float64
         )
     }
+
+    /// Whether a value of this type can be written into an interpolated
+    /// string. `Unknown` and `Error` are accepted so one error isn't reported twice.
+    pub fn is_displayable(&self) -> bool {
+        match self {
+            Type::Optional(inner) => inner.is_displayable(),
+            Type::Unknown | Type::Error | Type::Bool | Type::String | Type::Byte | Type::Rune => true,
+            other => other.is_numeric(),
+        }
+    }
+
+    /// Replaces the type parameters bound in `bindings`, leaving others as-is.
+    pub fn substitute(&self, bindings: &HashMap<String, Type>) -> Type {
+        let sub = |ty: &Type| ty.substitute(bindings);
+        match self {
+            Type::Param(name) => bindings.get(name).cloned().unwrap_or_else(|| self.clone()),
+            Type::Optional(inner) => Type::Optional(Box::new(sub(inner))),
+            Type::Array(inner) => Type::Array(Box::new(sub(inner))),
+            Type::Task(inner) => Type::Task(Box::new(sub(inner))),
+            Type::Function { params, return_type } => Type::Function {
+                params: params.iter().map(sub).collect(),
+                return_type: return_type.as_ref().map(|ret| Box::new(sub(ret))),
+            },
+            Type::Object(fields) => {
+                Type::Object(fields.iter().map(|(name, ty)| (name.clone(), sub(ty))).collect())
+            }
+            Type::Generic { name, args } => Type::Generic {
+                name: name.clone(),
+                args: args.iter().map(sub).collect(),
+            },
+            other => other.clone(),
+        }
+    }
+
+    /// Names of the type parameters occurring in this type, in order of first use.
+    pub fn type_params(&self) -> Vec<String> {
+        fn collect(ty: &Type, names: &mut Vec<String>) {
+            match ty {
+                Type::Param(name) if !names.contains(name) => names.push(name.clone()),
+                Type::Optional(inner) | Type::Array(inner) | Type::Task(inner) => collect(inner, names),
+                Type::Function { params, return_type } => {
+                    params.iter().for_each(|p| collect(p, names));
+                    if let Some(ret) = return_type {
+                        collect(ret, names);
+                    }
+                }
+                Type::Object(fields) => fields.iter().for_each(|(_, ty)| collect(ty, names)),
+                Type::Generic { args, .. } => args.iter().for_each(|a| collect(a, names)),
+                _ => {}
+            }
+        }
+        let mut names = Vec::new();
+        collect(self, &mut names);
+        names
+    }
 }
 
 
@@ -76,10 +189,19 @@ impl Hash for Type {
         discriminant(self).hash(state);
 
         match self {
-            Type::Custom(name) => name.hash(state),
+            Type::Custom(name) | Type::Param(name) => name.hash(state),
+            Type::Generic { name, args } => {
+                name.hash(state);
+                args.hash(state);
+            }
             Type::Optional(inner) => inner.hash(state),
-            Type::Array(inner) => inner.hash(state),
-            _ => {} 
+            Type::Array(inner) | Type::Task(inner) => inner.hash(state),
+            Type::Function { params, return_type } => {
+                params.hash(state);
+                return_type.hash(state);
+            }
+            Type::Object(fields) => fields.hash(state),
+            _ => {}
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,360 @@
+use crate::parser::{
+    ASTNode, Block, ConstDecl, EnumDecl, EnumVariant, ErrorNode, Expr, ExprKind, FieldDecl, FnDecl, ImportDecl,
+    InterpolationPart, LambdaBody, LambdaParam, Literal, MatchArm, Parameter, Pattern, PatternKind, Program, Stmt,
+    StructDecl, Type, TypeAliasDecl, VarDecl,
+};
+
+/// Rebuilds the tree by value. Every method defaults to the matching `walk_*`
+/// function, which folds the node's children and reassembles the node;
+/// override a method to replace that kind of node with something else.
+pub trait Fold {
+    fn fold_program(&mut self, program: Program) -> Program {
+        walk_program(self, program)
+    }
+
+    fn fold_declaration(&mut self, declaration: ASTNode) -> ASTNode {
+        walk_declaration(self, declaration)
+    }
+
+    fn fold_import(&mut self, import: ImportDecl) -> ImportDecl {
+        import
+    }
+
+    fn fold_var_decl(&mut self, decl: VarDecl) -> VarDecl {
+        walk_var_decl(self, decl)
+    }
+
+    fn fold_const_decl(&mut self, decl: ConstDecl) -> ConstDecl {
+        walk_const_decl(self, decl)
+    }
+
+    fn fold_fn_decl(&mut self, decl: FnDecl) -> FnDecl {
+        walk_fn_decl(self, decl)
+    }
+
+    fn fold_parameter(&mut self, param: Parameter) -> Parameter {
+        walk_parameter(self, param)
+    }
+
+    fn fold_struct_decl(&mut self, decl: StructDecl) -> StructDecl {
+        walk_struct_decl(self, decl)
+    }
+
+    fn fold_field_decl(&mut self, field: FieldDecl) -> FieldDecl {
+        walk_field_decl(self, field)
+    }
+
+    fn fold_enum_decl(&mut self, decl: EnumDecl) -> EnumDecl {
+        walk_enum_decl(self, decl)
+    }
+
+    fn fold_enum_variant(&mut self, variant: EnumVariant) -> EnumVariant {
+        walk_enum_variant(self, variant)
+    }
+
+    fn fold_type_alias(&mut self, decl: TypeAliasDecl) -> TypeAliasDecl {
+        walk_type_alias(self, decl)
+    }
+
+    fn fold_block(&mut self, block: Block) -> Block {
+        walk_block(self, block)
+    }
+
+    fn fold_stmt(&mut self, stmt: Stmt) -> Stmt {
+        walk_stmt(self, stmt)
+    }
+
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        walk_expr(self, expr)
+    }
+
+    fn fold_lambda_param(&mut self, param: LambdaParam) -> LambdaParam {
+        walk_lambda_param(self, param)
+    }
+
+    fn fold_lambda_body(&mut self, body: LambdaBody) -> LambdaBody {
+        walk_lambda_body(self, body)
+    }
+
+    fn fold_match_arm(&mut self, arm: MatchArm) -> MatchArm {
+        walk_match_arm(self, arm)
+    }
+
+    fn fold_pattern(&mut self, pattern: Pattern) -> Pattern {
+        walk_pattern(self, pattern)
+    }
+
+    fn fold_literal(&mut self, literal: Literal) -> Literal {
+        literal
+    }
+
+    fn fold_type(&mut self, ty: Type) -> Type {
+        walk_type(self, ty)
+    }
+
+    fn fold_error(&mut self, error: ErrorNode) -> ErrorNode {
+        error
+    }
+}
+
+/// Folds the expression in place, reusing its allocation.
+fn fold_boxed<F: Fold + ?Sized>(folder: &mut F, mut expr: Box<Expr>) -> Box<Expr> {
+    *expr = folder.fold_expr(*expr);
+    expr
+}
+
+fn fold_exprs<F: Fold + ?Sized>(folder: &mut F, exprs: Vec<Expr>) -> Vec<Expr> {
+    exprs.into_iter().map(|expr| folder.fold_expr(expr)).collect()
+}
+
+fn fold_types<F: Fold + ?Sized>(folder: &mut F, types: Vec<Type>) -> Vec<Type> {
+    types.into_iter().map(|ty| folder.fold_type(ty)).collect()
+}
+
+pub fn walk_program<F: Fold + ?Sized>(folder: &mut F, program: Program) -> Program {
+    Program {
+        declarations: program
+            .declarations
+            .into_iter()
+            .map(|declaration| folder.fold_declaration(declaration))
+            .collect(),
+    }
+}
+
+pub fn walk_declaration<F: Fold + ?Sized>(folder: &mut F, declaration: ASTNode) -> ASTNode {
+    match declaration {
+        ASTNode::Program(program) => ASTNode::Program(folder.fold_program(program)),
+        ASTNode::Import(import) => ASTNode::Import(folder.fold_import(import)),
+        ASTNode::VarDecl(decl) => ASTNode::VarDecl(folder.fold_var_decl(decl)),
+        ASTNode::FnDecl(decl) => ASTNode::FnDecl(folder.fold_fn_decl(decl)),
+        ASTNode::StructDecl(decl) => ASTNode::StructDecl(folder.fold_struct_decl(decl)),
+        ASTNode::EnumDecl(decl) => ASTNode::EnumDecl(folder.fold_enum_decl(decl)),
+        ASTNode::TypeAlias(decl) => ASTNode::TypeAlias(folder.fold_type_alias(decl)),
+        ASTNode::ConstDecl(decl) => ASTNode::ConstDecl(folder.fold_const_decl(decl)),
+        ASTNode::Type(ty) => ASTNode::Type(folder.fold_type(ty)),
+        ASTNode::Expr(expr) => ASTNode::Expr(folder.fold_expr(expr)),
+        ASTNode::Literal(literal) => ASTNode::Literal(folder.fold_literal(literal)),
+        ASTNode::Error(error) => ASTNode::Error(folder.fold_error(error)),
+        node @ (ASTNode::Visibility(_) | ASTNode::Mutability(_) | ASTNode::Identifier(_)) => node,
+    }
+}
+
+pub fn walk_var_decl<F: Fold + ?Sized>(folder: &mut F, decl: VarDecl) -> VarDecl {
+    VarDecl {
+        declared_type: decl.declared_type.map(|ty| folder.fold_type(ty)),
+        initializer: decl.initializer.map(|expr| folder.fold_expr(expr)),
+        ..decl
+    }
+}
+
+pub fn walk_const_decl<F: Fold + ?Sized>(folder: &mut F, decl: ConstDecl) -> ConstDecl {
+    ConstDecl {
+        declared_type: folder.fold_type(decl.declared_type),
+        initializer: folder.fold_expr(decl.initializer),
+        ..decl
+    }
+}
+
+pub fn walk_fn_decl<F: Fold + ?Sized>(folder: &mut F, decl: FnDecl) -> FnDecl {
+    FnDecl {
+        parameters: decl.parameters.into_iter().map(|param| folder.fold_parameter(param)).collect(),
+        return_type: decl.return_type.map(|ty| folder.fold_type(ty)),
+        body: folder.fold_block(decl.body),
+        ..decl
+    }
+}
+
+pub fn walk_parameter<F: Fold + ?Sized>(folder: &mut F, param: Parameter) -> Parameter {
+    Parameter { param_type: folder.fold_type(param.param_type), ..param }
+}
+
+pub fn walk_struct_decl<F: Fold + ?Sized>(folder: &mut F, decl: StructDecl) -> StructDecl {
+    StructDecl {
+        fields: decl.fields.into_iter().map(|field| folder.fold_field_decl(field)).collect(),
+        methods: decl.methods.into_iter().map(|method| folder.fold_fn_decl(method)).collect(),
+        ..decl
+    }
+}
+
+pub fn walk_field_decl<F: Fold + ?Sized>(folder: &mut F, field: FieldDecl) -> FieldDecl {
+    FieldDecl { field_type: folder.fold_type(field.field_type), ..field }
+}
+
+pub fn walk_enum_decl<F: Fold + ?Sized>(folder: &mut F, decl: EnumDecl) -> EnumDecl {
+    EnumDecl {
+        variants: decl.variants.into_iter().map(|variant| folder.fold_enum_variant(variant)).collect(),
+        ..decl
+    }
+}
+
+pub fn walk_enum_variant<F: Fold + ?Sized>(folder: &mut F, variant: EnumVariant) -> EnumVariant {
+    EnumVariant { payload: fold_types(folder, variant.payload), ..variant }
+}
+
+pub fn walk_type_alias<F: Fold + ?Sized>(folder: &mut F, decl: TypeAliasDecl) -> TypeAliasDecl {
+    TypeAliasDecl { aliased_type: folder.fold_type(decl.aliased_type), ..decl }
+}
+
+pub fn walk_block<F: Fold + ?Sized>(folder: &mut F, block: Block) -> Block {
+    Block { statements: block.statements.into_iter().map(|stmt| folder.fold_stmt(stmt)).collect(), ..block }
+}
+
+pub fn walk_stmt<F: Fold + ?Sized>(folder: &mut F, stmt: Stmt) -> Stmt {
+    match stmt {
+        Stmt::VarDecl(decl) => Stmt::VarDecl(folder.fold_var_decl(decl)),
+        Stmt::ConstDecl(decl) => Stmt::ConstDecl(folder.fold_const_decl(decl)),
+        Stmt::Assignment { target, value, line, column, span, id } => Stmt::Assignment {
+            target: folder.fold_expr(target),
+            value: folder.fold_expr(value),
+            line,
+            column,
+            span,
+            id,
+        },
+        Stmt::Return { value, line, column, span, id } => Stmt::Return {
+            value: value.map(|value| folder.fold_expr(value)),
+            line,
+            column,
+            span,
+            id,
+        },
+        Stmt::If { condition, then_branch, else_branch, line, column, span, id } => Stmt::If {
+            condition: folder.fold_expr(condition),
+            then_branch: folder.fold_block(then_branch),
+            else_branch: else_branch.map(|block| folder.fold_block(block)),
+            line,
+            column,
+            span,
+            id,
+        },
+        Stmt::While { modifier, condition, body, line, column, span, id } => Stmt::While {
+            modifier,
+            condition: folder.fold_expr(condition),
+            body: folder.fold_block(body),
+            line,
+            column,
+            span,
+            id,
+        },
+        Stmt::For { modifier, variable, iterable, body, line, column, span, id } => Stmt::For {
+            modifier,
+            variable,
+            iterable: folder.fold_expr(iterable),
+            body: folder.fold_block(body),
+            line,
+            column,
+            span,
+            id,
+        },
+        Stmt::Expression { expr, line, column, span, id } => Stmt::Expression { expr: folder.fold_expr(expr), line, column, span, id },
+        Stmt::Block(block) => Stmt::Block(folder.fold_block(block)),
+        Stmt::Error(error) => Stmt::Error(folder.fold_error(error)),
+        stmt @ Stmt::DSLBlock { .. } => stmt,
+    }
+}
+
+pub fn walk_expr<F: Fold + ?Sized>(folder: &mut F, expr: Expr) -> Expr {
+    let kind = match expr.kind {
+        ExprKind::Literal(literal) => ExprKind::Literal(folder.fold_literal(literal)),
+        kind @ ExprKind::Identifier(_) => kind,
+        ExprKind::Unary { op, expr } => ExprKind::Unary { op, expr: fold_boxed(folder, expr) },
+        ExprKind::Binary { left, op, right } => ExprKind::Binary {
+            left: fold_boxed(folder, left),
+            op,
+            right: fold_boxed(folder, right),
+        },
+        ExprKind::Grouping(expr) => ExprKind::Grouping(fold_boxed(folder, expr)),
+        ExprKind::Ternary { condition, then_branch, else_branch } => ExprKind::Ternary {
+            condition: fold_boxed(folder, condition),
+            then_branch: fold_boxed(folder, then_branch),
+            else_branch: fold_boxed(folder, else_branch),
+        },
+        ExprKind::Call { callee, args } => ExprKind::Call {
+            callee: fold_boxed(folder, callee),
+            args: fold_exprs(folder, args),
+        },
+        ExprKind::StructLiteral { name, fields } => ExprKind::StructLiteral {
+            name,
+            fields: fields.into_iter().map(|(field, value)| (field, folder.fold_expr(value))).collect(),
+        },
+        ExprKind::ArrayLiteral(elements) => ExprKind::ArrayLiteral(fold_exprs(folder, elements)),
+        ExprKind::ObjectLiteral(fields) => ExprKind::ObjectLiteral(
+            fields.into_iter().map(|(field, value)| (field, folder.fold_expr(value))).collect(),
+        ),
+        ExprKind::Member { object, member } => ExprKind::Member { object: fold_boxed(folder, object), member },
+        ExprKind::Interpolated { parts } => ExprKind::Interpolated {
+            parts: parts
+                .into_iter()
+                .map(|part| match part {
+                    InterpolationPart::Expr(expr) => InterpolationPart::Expr(fold_boxed(folder, expr)),
+                    literal @ InterpolationPart::Literal(_) => literal,
+                })
+                .collect(),
+        },
+        ExprKind::Index { object, index } => ExprKind::Index {
+            object: fold_boxed(folder, object),
+            index: fold_boxed(folder, index),
+        },
+        ExprKind::EnumVariant { enum_name, variant, args } => ExprKind::EnumVariant {
+            enum_name,
+            variant,
+            args: fold_exprs(folder, args),
+        },
+        ExprKind::Match { scrutinee, arms } => ExprKind::Match {
+            scrutinee: fold_boxed(folder, scrutinee),
+            arms: arms.into_iter().map(|arm| folder.fold_match_arm(arm)).collect(),
+        },
+        ExprKind::Spawn(expr) => ExprKind::Spawn(fold_boxed(folder, expr)),
+        ExprKind::Await(expr) => ExprKind::Await(fold_boxed(folder, expr)),
+        ExprKind::Lambda { params, return_type, body, captures } => ExprKind::Lambda {
+            params: params.into_iter().map(|param| folder.fold_lambda_param(param)).collect(),
+            return_type: return_type.map(|ty| folder.fold_type(ty)),
+            body: folder.fold_lambda_body(body),
+            captures,
+        },
+    };
+    Expr { kind, ..expr }
+}
+
+pub fn walk_lambda_param<F: Fold + ?Sized>(folder: &mut F, param: LambdaParam) -> LambdaParam {
+    LambdaParam { param_type: param.param_type.map(|ty| folder.fold_type(ty)), ..param }
+}
+
+pub fn walk_lambda_body<F: Fold + ?Sized>(folder: &mut F, body: LambdaBody) -> LambdaBody {
+    match body {
+        LambdaBody::Expr(expr) => LambdaBody::Expr(fold_boxed(folder, expr)),
+        LambdaBody::Block(block) => LambdaBody::Block(folder.fold_block(block)),
+    }
+}
+
+pub fn walk_match_arm<F: Fold + ?Sized>(folder: &mut F, arm: MatchArm) -> MatchArm {
+    MatchArm { pattern: folder.fold_pattern(arm.pattern), body: folder.fold_expr(arm.body), ..arm }
+}
+
+pub fn walk_pattern<F: Fold + ?Sized>(folder: &mut F, pattern: Pattern) -> Pattern {
+    let kind = match pattern.kind {
+        PatternKind::Literal(literal) => PatternKind::Literal(folder.fold_literal(literal)),
+        PatternKind::Variant { enum_name, variant, fields } => PatternKind::Variant {
+            enum_name,
+            variant,
+            fields: fields.into_iter().map(|field| folder.fold_pattern(field)).collect(),
+        },
+        kind @ (PatternKind::Wildcard | PatternKind::Binding(_)) => kind,
+    };
+    Pattern { kind, ..pattern }
+}
+
+pub fn walk_type<F: Fold + ?Sized>(folder: &mut F, ty: Type) -> Type {
+    match ty {
+        Type::Optional(inner) => Type::Optional(Box::new(folder.fold_type(*inner))),
+        Type::Array(inner) => Type::Array(Box::new(folder.fold_type(*inner))),
+        Type::Task(inner) => Type::Task(Box::new(folder.fold_type(*inner))),
+        Type::Function { params, return_type } => Type::Function {
+            params: fold_types(folder, params),
+            return_type: return_type.map(|ty| Box::new(folder.fold_type(*ty))),
+        },
+        Type::Object(fields) => Type::Object(fields.into_iter().map(|(name, ty)| (name, folder.fold_type(ty))).collect()),
+        Type::Generic { name, args } => Type::Generic { name, args: fold_types(folder, args) },
+        ty => ty,
+    }
+}
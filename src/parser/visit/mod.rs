@@ -0,0 +1,15 @@
+//! Generic traversals of the tree.
+//!
+//! `Visitor` reads the tree, `VisitorMut` edits it in place and `Fold`
+//! rebuilds it by value. Each trait has one method per kind of node, and each
+//! method defaults to the `walk_*` function of the same node in the trait's
+//! module, so a pass overrides only the nodes it cares about. Leaves
+//! (imports, literals, error nodes) have nothing to walk.
+
+pub mod fold;
+pub mod visitor;
+pub mod visitor_mut;
+
+pub use fold::Fold;
+pub use visitor::Visitor;
+pub use visitor_mut::VisitorMut;
@@ -0,0 +1,304 @@
+use crate::parser::{
+    ASTNode, Block, ConstDecl, EnumDecl, EnumVariant, ErrorNode, Expr, ExprKind, FieldDecl, FnDecl, ImportDecl,
+    InterpolationPart, LambdaBody, LambdaParam, Literal, MatchArm, Parameter, Pattern, PatternKind, Program, Stmt,
+    StructDecl, Type, TypeAliasDecl, VarDecl,
+};
+
+/// Read-only traversal of the tree. Every method defaults to the matching
+/// `walk_*` function, which visits the node's children; override a method to
+/// act on that kind of node and call the `walk_*` function from it to keep
+/// descending.
+pub trait Visitor {
+    fn visit_program(&mut self, program: &Program) {
+        walk_program(self, program)
+    }
+
+    fn visit_declaration(&mut self, declaration: &ASTNode) {
+        walk_declaration(self, declaration)
+    }
+
+    fn visit_import(&mut self, _import: &ImportDecl) {}
+
+    fn visit_var_decl(&mut self, decl: &VarDecl) {
+        walk_var_decl(self, decl)
+    }
+
+    fn visit_const_decl(&mut self, decl: &ConstDecl) {
+        walk_const_decl(self, decl)
+    }
+
+    fn visit_fn_decl(&mut self, decl: &FnDecl) {
+        walk_fn_decl(self, decl)
+    }
+
+    fn visit_parameter(&mut self, param: &Parameter) {
+        walk_parameter(self, param)
+    }
+
+    fn visit_struct_decl(&mut self, decl: &StructDecl) {
+        walk_struct_decl(self, decl)
+    }
+
+    fn visit_field_decl(&mut self, field: &FieldDecl) {
+        walk_field_decl(self, field)
+    }
+
+    fn visit_enum_decl(&mut self, decl: &EnumDecl) {
+        walk_enum_decl(self, decl)
+    }
+
+    fn visit_enum_variant(&mut self, variant: &EnumVariant) {
+        walk_enum_variant(self, variant)
+    }
+
+    fn visit_type_alias(&mut self, decl: &TypeAliasDecl) {
+        walk_type_alias(self, decl)
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        walk_block(self, block)
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        walk_stmt(self, stmt)
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr)
+    }
+
+    fn visit_lambda_param(&mut self, param: &LambdaParam) {
+        walk_lambda_param(self, param)
+    }
+
+    fn visit_lambda_body(&mut self, body: &LambdaBody) {
+        walk_lambda_body(self, body)
+    }
+
+    fn visit_match_arm(&mut self, arm: &MatchArm) {
+        walk_match_arm(self, arm)
+    }
+
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        walk_pattern(self, pattern)
+    }
+
+    fn visit_literal(&mut self, _literal: &Literal) {}
+
+    fn visit_type(&mut self, ty: &Type) {
+        walk_type(self, ty)
+    }
+
+    fn visit_error(&mut self, _error: &ErrorNode) {}
+}
+
+pub fn walk_program<V: Visitor + ?Sized>(visitor: &mut V, program: &Program) {
+    for declaration in &program.declarations {
+        visitor.visit_declaration(declaration);
+    }
+}
+
+pub fn walk_declaration<V: Visitor + ?Sized>(visitor: &mut V, declaration: &ASTNode) {
+    match declaration {
+        ASTNode::Program(program) => visitor.visit_program(program),
+        ASTNode::Import(import) => visitor.visit_import(import),
+        ASTNode::VarDecl(decl) => visitor.visit_var_decl(decl),
+        ASTNode::FnDecl(decl) => visitor.visit_fn_decl(decl),
+        ASTNode::StructDecl(decl) => visitor.visit_struct_decl(decl),
+        ASTNode::EnumDecl(decl) => visitor.visit_enum_decl(decl),
+        ASTNode::TypeAlias(decl) => visitor.visit_type_alias(decl),
+        ASTNode::ConstDecl(decl) => visitor.visit_const_decl(decl),
+        ASTNode::Type(ty) => visitor.visit_type(ty),
+        ASTNode::Expr(expr) => visitor.visit_expr(expr),
+        ASTNode::Literal(literal) => visitor.visit_literal(literal),
+        ASTNode::Error(error) => visitor.visit_error(error),
+        ASTNode::Visibility(_) | ASTNode::Mutability(_) | ASTNode::Identifier(_) => {}
+    }
+}
+
+pub fn walk_var_decl<V: Visitor + ?Sized>(visitor: &mut V, decl: &VarDecl) {
+    if let Some(declared_type) = &decl.declared_type {
+        visitor.visit_type(declared_type);
+    }
+    if let Some(initializer) = &decl.initializer {
+        visitor.visit_expr(initializer);
+    }
+}
+
+pub fn walk_const_decl<V: Visitor + ?Sized>(visitor: &mut V, decl: &ConstDecl) {
+    visitor.visit_type(&decl.declared_type);
+    visitor.visit_expr(&decl.initializer);
+}
+
+pub fn walk_fn_decl<V: Visitor + ?Sized>(visitor: &mut V, decl: &FnDecl) {
+    for param in &decl.parameters {
+        visitor.visit_parameter(param);
+    }
+    if let Some(return_type) = &decl.return_type {
+        visitor.visit_type(return_type);
+    }
+    visitor.visit_block(&decl.body);
+}
+
+pub fn walk_parameter<V: Visitor + ?Sized>(visitor: &mut V, param: &Parameter) {
+    visitor.visit_type(&param.param_type);
+}
+
+pub fn walk_struct_decl<V: Visitor + ?Sized>(visitor: &mut V, decl: &StructDecl) {
+    for field in &decl.fields {
+        visitor.visit_field_decl(field);
+    }
+    for method in &decl.methods {
+        visitor.visit_fn_decl(method);
+    }
+}
+
+pub fn walk_field_decl<V: Visitor + ?Sized>(visitor: &mut V, field: &FieldDecl) {
+    visitor.visit_type(&field.field_type);
+}
+
+pub fn walk_enum_decl<V: Visitor + ?Sized>(visitor: &mut V, decl: &EnumDecl) {
+    for variant in &decl.variants {
+        visitor.visit_enum_variant(variant);
+    }
+}
+
+pub fn walk_enum_variant<V: Visitor + ?Sized>(visitor: &mut V, variant: &EnumVariant) {
+    for ty in &variant.payload {
+        visitor.visit_type(ty);
+    }
+}
+
+pub fn walk_type_alias<V: Visitor + ?Sized>(visitor: &mut V, decl: &TypeAliasDecl) {
+    visitor.visit_type(&decl.aliased_type);
+}
+
+pub fn walk_block<V: Visitor + ?Sized>(visitor: &mut V, block: &Block) {
+    for stmt in &block.statements {
+        visitor.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_stmt<V: Visitor + ?Sized>(visitor: &mut V, stmt: &Stmt) {
+    match stmt {
+        Stmt::VarDecl(decl) => visitor.visit_var_decl(decl),
+        Stmt::ConstDecl(decl) => visitor.visit_const_decl(decl),
+        Stmt::Assignment { target, value, .. } => {
+            visitor.visit_expr(target);
+            visitor.visit_expr(value);
+        }
+        Stmt::Return { value, .. } => {
+            if let Some(value) = value {
+                visitor.visit_expr(value);
+            }
+        }
+        Stmt::If { condition, then_branch, else_branch, .. } => {
+            visitor.visit_expr(condition);
+            visitor.visit_block(then_branch);
+            if let Some(else_branch) = else_branch {
+                visitor.visit_block(else_branch);
+            }
+        }
+        Stmt::While { condition, body, .. } => {
+            visitor.visit_expr(condition);
+            visitor.visit_block(body);
+        }
+        Stmt::For { iterable, body, .. } => {
+            visitor.visit_expr(iterable);
+            visitor.visit_block(body);
+        }
+        Stmt::Expression { expr, .. } => visitor.visit_expr(expr),
+        Stmt::Block(block) => visitor.visit_block(block),
+        Stmt::Error(error) => visitor.visit_error(error),
+        Stmt::DSLBlock { .. } => {}
+    }
+}
+
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match &expr.kind {
+        ExprKind::Literal(literal) => visitor.visit_literal(literal),
+        ExprKind::Identifier(_) => {}
+        ExprKind::Unary { expr, .. } | ExprKind::Grouping(expr) | ExprKind::Spawn(expr) | ExprKind::Await(expr) => {
+            visitor.visit_expr(expr)
+        }
+        ExprKind::Binary { left, right, .. } | ExprKind::Index { object: left, index: right } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        }
+        ExprKind::Ternary { condition, then_branch, else_branch } => {
+            visitor.visit_expr(condition);
+            visitor.visit_expr(then_branch);
+            visitor.visit_expr(else_branch);
+        }
+        ExprKind::Call { callee, args } => {
+            visitor.visit_expr(callee);
+            args.iter().for_each(|arg| visitor.visit_expr(arg));
+        }
+        ExprKind::StructLiteral { fields, .. } | ExprKind::ObjectLiteral(fields) => {
+            fields.iter().for_each(|(_, value)| visitor.visit_expr(value));
+        }
+        ExprKind::ArrayLiteral(elements) | ExprKind::EnumVariant { args: elements, .. } => {
+            elements.iter().for_each(|element| visitor.visit_expr(element));
+        }
+        ExprKind::Member { object, .. } => visitor.visit_expr(object),
+        ExprKind::Interpolated { parts } => {
+            for part in parts {
+                if let InterpolationPart::Expr(expr) = part {
+                    visitor.visit_expr(expr);
+                }
+            }
+        }
+        ExprKind::Match { scrutinee, arms } => {
+            visitor.visit_expr(scrutinee);
+            arms.iter().for_each(|arm| visitor.visit_match_arm(arm));
+        }
+        ExprKind::Lambda { params, return_type, body, .. } => {
+            params.iter().for_each(|param| visitor.visit_lambda_param(param));
+            if let Some(return_type) = return_type {
+                visitor.visit_type(return_type);
+            }
+            visitor.visit_lambda_body(body);
+        }
+    }
+}
+
+pub fn walk_lambda_param<V: Visitor + ?Sized>(visitor: &mut V, param: &LambdaParam) {
+    if let Some(param_type) = &param.param_type {
+        visitor.visit_type(param_type);
+    }
+}
+
+pub fn walk_lambda_body<V: Visitor + ?Sized>(visitor: &mut V, body: &LambdaBody) {
+    match body {
+        LambdaBody::Expr(expr) => visitor.visit_expr(expr),
+        LambdaBody::Block(block) => visitor.visit_block(block),
+    }
+}
+
+pub fn walk_match_arm<V: Visitor + ?Sized>(visitor: &mut V, arm: &MatchArm) {
+    visitor.visit_pattern(&arm.pattern);
+    visitor.visit_expr(&arm.body);
+}
+
+pub fn walk_pattern<V: Visitor + ?Sized>(visitor: &mut V, pattern: &Pattern) {
+    match &pattern.kind {
+        PatternKind::Literal(literal) => visitor.visit_literal(literal),
+        PatternKind::Variant { fields, .. } => fields.iter().for_each(|field| visitor.visit_pattern(field)),
+        PatternKind::Wildcard | PatternKind::Binding(_) => {}
+    }
+}
+
+pub fn walk_type<V: Visitor + ?Sized>(visitor: &mut V, ty: &Type) {
+    match ty {
+        Type::Optional(inner) | Type::Array(inner) | Type::Task(inner) => visitor.visit_type(inner),
+        Type::Function { params, return_type } => {
+            params.iter().for_each(|param| visitor.visit_type(param));
+            if let Some(return_type) = return_type {
+                visitor.visit_type(return_type);
+            }
+        }
+        Type::Object(fields) => fields.iter().for_each(|(_, field)| visitor.visit_type(field)),
+        Type::Generic { args, .. } => args.iter().for_each(|arg| visitor.visit_type(arg)),
+        _ => {}
+    }
+}
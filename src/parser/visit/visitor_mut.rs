@@ -0,0 +1,302 @@
+use crate::parser::{
+    ASTNode, Block, ConstDecl, EnumDecl, EnumVariant, ErrorNode, Expr, ExprKind, FieldDecl, FnDecl, ImportDecl,
+    InterpolationPart, LambdaBody, LambdaParam, Literal, MatchArm, Parameter, Pattern, PatternKind, Program, Stmt,
+    StructDecl, Type, TypeAliasDecl, VarDecl,
+};
+
+/// Like `Visitor`, but with mutable access, for passes that rewrite the tree
+/// in place.
+pub trait VisitorMut {
+    fn visit_program(&mut self, program: &mut Program) {
+        walk_program(self, program)
+    }
+
+    fn visit_declaration(&mut self, declaration: &mut ASTNode) {
+        walk_declaration(self, declaration)
+    }
+
+    fn visit_import(&mut self, _import: &mut ImportDecl) {}
+
+    fn visit_var_decl(&mut self, decl: &mut VarDecl) {
+        walk_var_decl(self, decl)
+    }
+
+    fn visit_const_decl(&mut self, decl: &mut ConstDecl) {
+        walk_const_decl(self, decl)
+    }
+
+    fn visit_fn_decl(&mut self, decl: &mut FnDecl) {
+        walk_fn_decl(self, decl)
+    }
+
+    fn visit_parameter(&mut self, param: &mut Parameter) {
+        walk_parameter(self, param)
+    }
+
+    fn visit_struct_decl(&mut self, decl: &mut StructDecl) {
+        walk_struct_decl(self, decl)
+    }
+
+    fn visit_field_decl(&mut self, field: &mut FieldDecl) {
+        walk_field_decl(self, field)
+    }
+
+    fn visit_enum_decl(&mut self, decl: &mut EnumDecl) {
+        walk_enum_decl(self, decl)
+    }
+
+    fn visit_enum_variant(&mut self, variant: &mut EnumVariant) {
+        walk_enum_variant(self, variant)
+    }
+
+    fn visit_type_alias(&mut self, decl: &mut TypeAliasDecl) {
+        walk_type_alias(self, decl)
+    }
+
+    fn visit_block(&mut self, block: &mut Block) {
+        walk_block(self, block)
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        walk_stmt(self, stmt)
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr(self, expr)
+    }
+
+    fn visit_lambda_param(&mut self, param: &mut LambdaParam) {
+        walk_lambda_param(self, param)
+    }
+
+    fn visit_lambda_body(&mut self, body: &mut LambdaBody) {
+        walk_lambda_body(self, body)
+    }
+
+    fn visit_match_arm(&mut self, arm: &mut MatchArm) {
+        walk_match_arm(self, arm)
+    }
+
+    fn visit_pattern(&mut self, pattern: &mut Pattern) {
+        walk_pattern(self, pattern)
+    }
+
+    fn visit_literal(&mut self, _literal: &mut Literal) {}
+
+    fn visit_type(&mut self, ty: &mut Type) {
+        walk_type(self, ty)
+    }
+
+    fn visit_error(&mut self, _error: &mut ErrorNode) {}
+}
+
+pub fn walk_program<V: VisitorMut + ?Sized>(visitor: &mut V, program: &mut Program) {
+    for declaration in &mut program.declarations {
+        visitor.visit_declaration(declaration);
+    }
+}
+
+pub fn walk_declaration<V: VisitorMut + ?Sized>(visitor: &mut V, declaration: &mut ASTNode) {
+    match declaration {
+        ASTNode::Program(program) => visitor.visit_program(program),
+        ASTNode::Import(import) => visitor.visit_import(import),
+        ASTNode::VarDecl(decl) => visitor.visit_var_decl(decl),
+        ASTNode::FnDecl(decl) => visitor.visit_fn_decl(decl),
+        ASTNode::StructDecl(decl) => visitor.visit_struct_decl(decl),
+        ASTNode::EnumDecl(decl) => visitor.visit_enum_decl(decl),
+        ASTNode::TypeAlias(decl) => visitor.visit_type_alias(decl),
+        ASTNode::ConstDecl(decl) => visitor.visit_const_decl(decl),
+        ASTNode::Type(ty) => visitor.visit_type(ty),
+        ASTNode::Expr(expr) => visitor.visit_expr(expr),
+        ASTNode::Literal(literal) => visitor.visit_literal(literal),
+        ASTNode::Error(error) => visitor.visit_error(error),
+        ASTNode::Visibility(_) | ASTNode::Mutability(_) | ASTNode::Identifier(_) => {}
+    }
+}
+
+pub fn walk_var_decl<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut VarDecl) {
+    if let Some(declared_type) = &mut decl.declared_type {
+        visitor.visit_type(declared_type);
+    }
+    if let Some(initializer) = &mut decl.initializer {
+        visitor.visit_expr(initializer);
+    }
+}
+
+pub fn walk_const_decl<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut ConstDecl) {
+    visitor.visit_type(&mut decl.declared_type);
+    visitor.visit_expr(&mut decl.initializer);
+}
+
+pub fn walk_fn_decl<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut FnDecl) {
+    for param in &mut decl.parameters {
+        visitor.visit_parameter(param);
+    }
+    if let Some(return_type) = &mut decl.return_type {
+        visitor.visit_type(return_type);
+    }
+    visitor.visit_block(&mut decl.body);
+}
+
+pub fn walk_parameter<V: VisitorMut + ?Sized>(visitor: &mut V, param: &mut Parameter) {
+    visitor.visit_type(&mut param.param_type);
+}
+
+pub fn walk_struct_decl<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut StructDecl) {
+    for field in &mut decl.fields {
+        visitor.visit_field_decl(field);
+    }
+    for method in &mut decl.methods {
+        visitor.visit_fn_decl(method);
+    }
+}
+
+pub fn walk_field_decl<V: VisitorMut + ?Sized>(visitor: &mut V, field: &mut FieldDecl) {
+    visitor.visit_type(&mut field.field_type);
+}
+
+pub fn walk_enum_decl<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut EnumDecl) {
+    for variant in &mut decl.variants {
+        visitor.visit_enum_variant(variant);
+    }
+}
+
+pub fn walk_enum_variant<V: VisitorMut + ?Sized>(visitor: &mut V, variant: &mut EnumVariant) {
+    for ty in &mut variant.payload {
+        visitor.visit_type(ty);
+    }
+}
+
+pub fn walk_type_alias<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut TypeAliasDecl) {
+    visitor.visit_type(&mut decl.aliased_type);
+}
+
+pub fn walk_block<V: VisitorMut + ?Sized>(visitor: &mut V, block: &mut Block) {
+    for stmt in &mut block.statements {
+        visitor.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_stmt<V: VisitorMut + ?Sized>(visitor: &mut V, stmt: &mut Stmt) {
+    match stmt {
+        Stmt::VarDecl(decl) => visitor.visit_var_decl(decl),
+        Stmt::ConstDecl(decl) => visitor.visit_const_decl(decl),
+        Stmt::Assignment { target, value, .. } => {
+            visitor.visit_expr(target);
+            visitor.visit_expr(value);
+        }
+        Stmt::Return { value, .. } => {
+            if let Some(value) = value {
+                visitor.visit_expr(value);
+            }
+        }
+        Stmt::If { condition, then_branch, else_branch, .. } => {
+            visitor.visit_expr(condition);
+            visitor.visit_block(then_branch);
+            if let Some(else_branch) = else_branch {
+                visitor.visit_block(else_branch);
+            }
+        }
+        Stmt::While { condition, body, .. } => {
+            visitor.visit_expr(condition);
+            visitor.visit_block(body);
+        }
+        Stmt::For { iterable, body, .. } => {
+            visitor.visit_expr(iterable);
+            visitor.visit_block(body);
+        }
+        Stmt::Expression { expr, .. } => visitor.visit_expr(expr),
+        Stmt::Block(block) => visitor.visit_block(block),
+        Stmt::Error(error) => visitor.visit_error(error),
+        Stmt::DSLBlock { .. } => {}
+    }
+}
+
+pub fn walk_expr<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+    match &mut expr.kind {
+        ExprKind::Literal(literal) => visitor.visit_literal(literal),
+        ExprKind::Identifier(_) => {}
+        ExprKind::Unary { expr, .. } | ExprKind::Grouping(expr) | ExprKind::Spawn(expr) | ExprKind::Await(expr) => {
+            visitor.visit_expr(expr)
+        }
+        ExprKind::Binary { left, right, .. } | ExprKind::Index { object: left, index: right } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        }
+        ExprKind::Ternary { condition, then_branch, else_branch } => {
+            visitor.visit_expr(condition);
+            visitor.visit_expr(then_branch);
+            visitor.visit_expr(else_branch);
+        }
+        ExprKind::Call { callee, args } => {
+            visitor.visit_expr(callee);
+            args.iter_mut().for_each(|arg| visitor.visit_expr(arg));
+        }
+        ExprKind::StructLiteral { fields, .. } | ExprKind::ObjectLiteral(fields) => {
+            fields.iter_mut().for_each(|(_, value)| visitor.visit_expr(value));
+        }
+        ExprKind::ArrayLiteral(elements) | ExprKind::EnumVariant { args: elements, .. } => {
+            elements.iter_mut().for_each(|element| visitor.visit_expr(element));
+        }
+        ExprKind::Member { object, .. } => visitor.visit_expr(object),
+        ExprKind::Interpolated { parts } => {
+            for part in parts {
+                if let InterpolationPart::Expr(expr) = part {
+                    visitor.visit_expr(expr);
+                }
+            }
+        }
+        ExprKind::Match { scrutinee, arms } => {
+            visitor.visit_expr(scrutinee);
+            arms.iter_mut().for_each(|arm| visitor.visit_match_arm(arm));
+        }
+        ExprKind::Lambda { params, return_type, body, .. } => {
+            params.iter_mut().for_each(|param| visitor.visit_lambda_param(param));
+            if let Some(return_type) = return_type {
+                visitor.visit_type(return_type);
+            }
+            visitor.visit_lambda_body(body);
+        }
+    }
+}
+
+pub fn walk_lambda_param<V: VisitorMut + ?Sized>(visitor: &mut V, param: &mut LambdaParam) {
+    if let Some(param_type) = &mut param.param_type {
+        visitor.visit_type(param_type);
+    }
+}
+
+pub fn walk_lambda_body<V: VisitorMut + ?Sized>(visitor: &mut V, body: &mut LambdaBody) {
+    match body {
+        LambdaBody::Expr(expr) => visitor.visit_expr(expr),
+        LambdaBody::Block(block) => visitor.visit_block(block),
+    }
+}
+
+pub fn walk_match_arm<V: VisitorMut + ?Sized>(visitor: &mut V, arm: &mut MatchArm) {
+    visitor.visit_pattern(&mut arm.pattern);
+    visitor.visit_expr(&mut arm.body);
+}
+
+pub fn walk_pattern<V: VisitorMut + ?Sized>(visitor: &mut V, pattern: &mut Pattern) {
+    match &mut pattern.kind {
+        PatternKind::Literal(literal) => visitor.visit_literal(literal),
+        PatternKind::Variant { fields, .. } => fields.iter_mut().for_each(|field| visitor.visit_pattern(field)),
+        PatternKind::Wildcard | PatternKind::Binding(_) => {}
+    }
+}
+
+pub fn walk_type<V: VisitorMut + ?Sized>(visitor: &mut V, ty: &mut Type) {
+    match ty {
+        Type::Optional(inner) | Type::Array(inner) | Type::Task(inner) => visitor.visit_type(inner),
+        Type::Function { params, return_type } => {
+            params.iter_mut().for_each(|param| visitor.visit_type(param));
+            if let Some(return_type) = return_type {
+                visitor.visit_type(return_type);
+            }
+        }
+        Type::Object(fields) => fields.iter_mut().for_each(|(_, field)| visitor.visit_type(field)),
+        Type::Generic { args, .. } => args.iter_mut().for_each(|arg| visitor.visit_type(arg)),
+        _ => {}
+    }
+}
@@ -0,0 +1,193 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::lexer::lexer::LexError;
+use crate::lexer::token::Token;
+use crate::lexer::Lexer;
+use crate::parser::modules::error::ModuleError;
+use crate::parser::modules::{ModuleGraph, ModuleLoader};
+use crate::parser::recursive_descent::errors::{ParseError, Span};
+use crate::parser::recursive_descent::RecursiveDescentParser;
+use crate::parser::semantic;
+use crate::parser::{ASTNode, Program, SymbolTable};
+
+/// A named piece of Flux source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+    /// Shown in diagnostics; usually the path the text was read from.
+    pub name: String,
+    pub text: String,
+}
+
+impl SourceFile {
+    pub fn new(name: impl Into<String>, text: impl Into<String>) -> Self {
+        SourceFile { name: name.into(), text: text.into() }
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        Ok(SourceFile::new(path.display().to_string(), text))
+    }
+}
+
+/// The front-end phase that reported a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Lex,
+    Syntax,
+    Semantic,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub stage: Stage,
+    pub message: String,
+    pub span: Span,
+    /// Expected and found tokens of a syntax error, e.g. `expected Semicolon, found Let`.
+    pub note: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn line(&self) -> usize {
+        self.span.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.span.column
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stage = match self.stage {
+            Stage::Lex => "Lexer",
+            Stage::Syntax => "Parse",
+            Stage::Semantic => "Semantic",
+        };
+        write!(f, "{} error at line {}, column {}: {}", stage, self.line(), self.column(), self.message)?;
+        if let Some(note) = &self.note {
+            write!(f, " ({})", note)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<LexError> for Diagnostic {
+    fn from(err: LexError) -> Self {
+        Diagnostic {
+            stage: Stage::Lex,
+            message: err.message,
+            span: Span::point(err.line, err.column),
+            note: None,
+        }
+    }
+}
+
+impl Diagnostic {
+    /// Wraps an error reported by the parser (`Stage::Syntax`) or by the
+    /// semantic passes (`Stage::Semantic`).
+    pub fn from_parse_error(stage: Stage, err: ParseError) -> Self {
+        Diagnostic {
+            stage,
+            note: err.expectation(),
+            message: err.message,
+            span: err.span,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Lexed {
+    pub tokens: Vec<Token>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// A program tree, possibly with `Error` nodes where declarations or
+/// statements failed, plus everything reported while building it.
+#[derive(Debug, Clone)]
+pub struct Parsed {
+    pub program: Program,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Parsed {
+    pub fn has_errors(&self) -> bool {
+        !self.diagnostics.is_empty()
+    }
+}
+
+/// The tokens of `source`, ending in `Eof`. Text that doesn't lex becomes
+/// an `Error` token, with a diagnostic for each.
+pub fn lex(source: &SourceFile) -> Lexed {
+    let chars: Vec<char> = source.text.chars().collect();
+    let (tokens, errors) = Lexer::new(&chars).tokenize_recovering();
+    Lexed { tokens, diagnostics: errors.into_iter().map(Diagnostic::from).collect() }
+}
+
+/// Parses `source` into an untyped tree, reporting lexical and syntax
+/// errors only. Invalid tokens are parsed around like other syntax errors,
+/// but only the lexer reports them.
+pub fn parse(source: &SourceFile) -> Parsed {
+    let Lexed { tokens, diagnostics: lex_diagnostics } = lex(source);
+
+    let mut parser = RecursiveDescentParser::new(tokens);
+    let result = parser.parse_program();
+    let mut errors = parser.errors().to_vec();
+    let program = match result {
+        Ok(ASTNode::Program(program)) => program,
+        Ok(_) => unreachable!("parse_program always returns a Program node"),
+        Err(err) => {
+            errors.push(err);
+            Program { declarations: Vec::new() }
+        }
+    };
+    let mut diagnostics = lex_diagnostics;
+    diagnostics.extend(errors.into_iter().map(|err| Diagnostic::from_parse_error(Stage::Syntax, err)));
+    diagnostics.sort_by_key(|d| (d.span.line, d.span.column));
+    Parsed { program, diagnostics }
+}
+
+/// Parses and analyses `source` as a standalone module.
+pub fn check(source: &SourceFile) -> Parsed {
+    check_with(source, &mut SymbolTable::new())
+}
+
+/// Like `check`, but declarations go into `symbol_table`, so a sequence of
+/// sources (e.g. REPL lines) can build on each other.
+///
+/// Name resolution and type checking run over the tree `parse` returns, so
+/// semantic errors are reported even where the tree has syntax errors.
+/// Diagnostics are in source order.
+pub fn check_with(source: &SourceFile, symbol_table: &mut SymbolTable) -> Parsed {
+    let mut parsed = parse(source);
+    analyze(&mut parsed, symbol_table);
+    parsed
+}
+
+/// Parses and analyses the module at `entry` and every module it imports.
+/// `use` paths are resolved against the directory of `entry`, and each
+/// module is analysed with the public items of its imports in scope.
+///
+/// Modules come after the modules they import, so the entry module is
+/// last. Missing modules and import cycles are reported at the `use` that
+/// names them; only an entry that cannot be read fails the whole load.
+pub fn check_modules(entry: &Path) -> Result<Vec<(SourceFile, Parsed)>, ModuleError> {
+    let root = entry.parent().unwrap_or_else(|| Path::new("."));
+    let loader = ModuleLoader::new(root);
+    let name = loader
+        .module_name(entry)
+        .ok_or_else(|| ModuleError::NotASourceFile(entry.to_path_buf()))?;
+    let graph = ModuleGraph::load(&loader, &name)?;
+    Ok(graph.into_modules().into_iter().map(|module| (module.source, module.parsed)).collect())
+}
+
+/// Runs the semantic passes over a parsed tree, adding their diagnostics.
+pub(crate) fn analyze(parsed: &mut Parsed, symbol_table: &mut SymbolTable) {
+    let errors = semantic::analyze_program(&mut parsed.program, symbol_table);
+    parsed
+        .diagnostics
+        .extend(errors.into_iter().map(|err| Diagnostic::from_parse_error(Stage::Semantic, err)));
+    parsed.diagnostics.sort_by_key(|d| (d.span.line, d.span.column));
+}
@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Writes `files` (file name, source) into a fresh temporary directory.
+fn write_files(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("kai-cli-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for (file, source) in files {
+        std::fs::write(dir.join(file), source).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn parse_reports_every_lexer_error() {
+    let dir = write_files(
+        "lex-errors",
+        &[
+            ("util.kai", "public fn one() -> int32 { return 1; }\nlet bad = 2 # 3;"),
+            (
+                "main.kai",
+                "use util;\nlet a = 1 @ 2;\nlet b: int32 = util.one();\nlet c = \"open;",
+            ),
+        ],
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_flux"))
+        .arg("parse")
+        .arg(dir.join("main.kai"))
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "flux parse failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    for expected in [
+        "util.kai': 2 declarations.",
+        "Lexer error at line 2, column 13: Unexpected character '#'",
+        "main.kai': 4 declarations.",
+        "Lexer error at line 2, column 11: Unexpected character '@'",
+        "3. let b : Int32 (initialized)",
+        "Lexer error at line 4, column 9: Unterminated string",
+    ] {
+        assert!(stdout.contains(expected), "expected {:?} in output:\n{}", expected, stdout);
+    }
+}
+
+#[test]
+fn lex_reports_every_lexer_error() {
+    let dir = write_files("lex", &[("main.kai", "let a = 1 @ 2;\nlet bad = 2 # 3;\nlet c = \"open;")]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_flux"))
+        .arg("lex")
+        .arg(dir.join("main.kai"))
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "flux lex failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    for expected in [
+        "Tokenization finished with errors.",
+        "Lexer error at line 1, column 11: Unexpected character '@'",
+        "Lexer error at line 2, column 13: Unexpected character '#'",
+        "Lexer error at line 3, column 9: Unterminated string",
+    ] {
+        assert!(stdout.contains(expected), "expected {:?} in output:\n{}", expected, stdout);
+    }
+}
+
+#[test]
+fn parse_prints_constants_that_failed_to_fold() {
+    let dir = write_files(
+        "consts",
+        &[("main.kai", "const A: int32 = 6 * 7;\nconst B: int32 = 1 / 0;")],
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_flux"))
+        .arg("parse")
+        .arg(dir.join("main.kai"))
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "flux parse failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("1. const A : Int32 = Int(42, None)"), "{}", stdout);
+    assert!(stdout.contains("2. const B : Int32 = <error>"), "{}", stdout);
+}